@@ -125,6 +125,7 @@ pub enum InvocationState {
     Running,
     Suspended,
     BackingOff,
+    Completed,
 }
 
 impl FromStr for InvocationState {
@@ -136,6 +137,7 @@ impl FromStr for InvocationState {
             "running" => Self::Running,
             "suspended" => Self::Suspended,
             "backing-off" => Self::BackingOff,
+            "completed" => Self::Completed,
             _ => Self::Unknown,
         })
     }
@@ -150,6 +152,7 @@ impl Display for InvocationState {
             InvocationState::Running => write!(f, "running"),
             InvocationState::Suspended => write!(f, "suspended"),
             InvocationState::BackingOff => write!(f, "backing-off"),
+            InvocationState::Completed => write!(f, "completed"),
         }
     }
 }
@@ -272,6 +275,10 @@ pub struct Invocation {
     pub last_attempt_started_at: Option<DateTime<Local>>,
     // Last attempt failed?
     pub last_failure_message: Option<String>,
+
+    // If completed, either 'success' or 'failure'
+    pub completion_result: Option<String>,
+    pub completion_failure: Option<String>,
 }
 
 pub async fn count_deployment_active_inv(
@@ -482,7 +489,7 @@ pub async fn get_services_status(
                 ss.created_at
             FROM sys_invocation_status ss
             LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
-            WHERE ss.service IN {}
+            WHERE ss.service IN {} AND ss.status != 'completed'
             )
             SELECT service, method, combined_status, COUNT(id), MIN(created_at), FIRST_VALUE(id ORDER BY created_at ASC)
             FROM enriched_invokes GROUP BY service, method, combined_status ORDER BY method",
@@ -629,7 +636,7 @@ pub async fn get_locked_keys_status(
                 sis.last_start_at
             FROM sys_invocation_status ss
             LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
-            WHERE ss.service IN {}
+            WHERE ss.service IN {} AND ss.status != 'completed'
             )
             SELECT
                 service,
@@ -719,6 +726,7 @@ pub async fn find_active_invocations(
             ss.service_key,
             CASE
              WHEN ss.status = 'suspended' THEN 'suspended'
+             WHEN ss.status = 'completed' THEN 'completed'
              WHEN sis.in_flight THEN 'running'
              WHEN ss.status = 'invoked' AND retry_count > 0 THEN 'backing-off'
              ELSE 'ready'
@@ -736,7 +744,9 @@ pub async fn find_active_invocations(
             svc.instance_type,
             svc.deployment_id as svc_latest_deployment,
             dp.id as known_deployment_id,
-            ss.trace_id
+            ss.trace_id,
+            ss.completion_result,
+            ss.completion_failure
         FROM sys_invocation_status ss
         LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
         LEFT JOIN sys_service svc ON svc.name = ss.service
@@ -783,6 +793,8 @@ pub async fn find_active_invocations(
 
             let existing_pinned_deployment_id = value_as_string_opt(&batch, 17, i);
             let trace_id = value_as_string_opt(&batch, 18, i);
+            let completion_result = value_as_string_opt(&batch, 19, i);
+            let completion_failure = value_as_string_opt(&batch, 20, i);
 
             let key = if instance_type == InstanceType::Keyed {
                 service_key
@@ -808,6 +820,8 @@ pub async fn find_active_invocations(
                 last_failure_message,
                 last_attempt_deployment_id,
                 trace_id,
+                completion_result,
+                completion_failure,
                 ..Default::default()
             };
 
@@ -87,6 +87,13 @@ pub fn invocation_status_note(invocation: &Invocation) -> String {
 
             msg.push(')');
         }
+        InvocationState::Completed => {
+            if let Some(failure) = &invocation.completion_failure {
+                msg.push_str(&format!(" ({} {})", style("failed:").red(), failure));
+            } else if invocation.completion_result.is_some() {
+                msg.push_str(&format!(" ({})", style("succeeded").green()));
+            }
+        }
         _ => {}
     }
 
@@ -131,6 +138,7 @@ pub fn invocation_status(status: InvocationState) -> StyledObject<InvocationStat
         InvocationState::Running => DStyle::new().green(),
         InvocationState::Suspended => DStyle::new().dim(),
         InvocationState::BackingOff => DStyle::new().red(),
+        InvocationState::Completed => DStyle::new().cyan(),
     };
     status_style.apply_to(status)
 }
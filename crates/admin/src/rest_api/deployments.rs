@@ -14,6 +14,7 @@ use super::error::*;
 
 use restate_meta::{ApplyMode, Force};
 use restate_meta_rest_model::deployments::*;
+use restate_schema_api::concurrency_limit::ConcurrencyLimitScope;
 use restate_schema_api::deployment::DeploymentResolver;
 use restate_service_client::Endpoint;
 use restate_service_protocol::old_discovery::DiscoverEndpoint;
@@ -251,6 +252,48 @@ pub async fn delete_deployment<W>(
     }
 }
 
+/// Modify a deployment
+#[openapi(
+    summary = "Modify deployment",
+    description = "Modify the invoker settings of a registered deployment.",
+    operation_id = "modify_deployment",
+    tags = "deployment",
+    parameters(path(
+        name = "deployment",
+        description = "Deployment identifier",
+        schema = "std::string::String"
+    ))
+)]
+pub async fn modify_deployment<W>(
+    State(state): State<AdminServiceState<W>>,
+    Path(deployment_id): Path<DeploymentId>,
+    #[request_body(required = true)] Json(ModifyDeploymentRequest { concurrency_limit }): Json<
+        ModifyDeploymentRequest,
+    >,
+) -> Result<Json<DetailedDeploymentResponse>, MetaApiError> {
+    state
+        .meta_handle()
+        .modify_concurrency_limits(vec![(
+            ConcurrencyLimitScope::Deployment(deployment_id),
+            concurrency_limit,
+        )])
+        .await?;
+
+    notify_worker_about_schema_changes(state.schema_reader(), state.node_svc_client()).await?;
+
+    let (deployment, services) = state
+        .schemas()
+        .get_deployment_and_services(&deployment_id)
+        .ok_or_else(|| MetaApiError::DeploymentNotFound(deployment_id))?;
+
+    Ok(DetailedDeploymentResponse {
+        id: deployment.id,
+        deployment: deployment.metadata.into(),
+        services,
+    }
+    .into())
+}
+
 pub struct ProtoBytes(Bytes);
 
 impl IntoResponse for ProtoBytes {
@@ -81,6 +81,9 @@ impl IntoResponse for MetaApiError {
             MetaApiError::Meta(MetaError::SchemaRegistry(
                 SchemasUpdateError::UnknownDeployment(_),
             )) => StatusCode::NOT_FOUND,
+            MetaApiError::Meta(MetaError::SchemaRegistry(SchemasUpdateError::UnknownHandler {
+                ..
+            })) => StatusCode::NOT_FOUND,
             MetaApiError::Meta(MetaError::SchemaRegistry(
                 SchemasUpdateError::ModifyInternalService(_),
             )) => StatusCode::FORBIDDEN,
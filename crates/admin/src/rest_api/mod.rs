@@ -54,6 +54,10 @@ pub fn create_router<W: restate_worker_api::Handle + Clone + Send + Sync + 'stat
             "/deployments/:deployment",
             delete(openapi_handler!(deployments::delete_deployment)),
         )
+        .route(
+            "/deployments/:deployment",
+            patch(openapi_handler!(deployments::modify_deployment)),
+        )
         .route("/services", get(openapi_handler!(services::list_services)))
         .route(
             "/services/:service",
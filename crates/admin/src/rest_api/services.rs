@@ -13,6 +13,8 @@ use crate::state::AdminServiceState;
 
 use restate_meta_rest_model::services::*;
 use restate_pb::grpc::reflection::v1::FileDescriptorResponse;
+use restate_schema_api::concurrency_limit::ConcurrencyLimitScope;
+use restate_schema_api::retry_policy::RetryPolicyScope;
 use restate_schema_api::service::ServiceMetadataResolver;
 
 use crate::rest_api::notify_worker_about_schema_changes;
@@ -83,14 +85,78 @@ pub async fn get_service<W>(
 pub async fn modify_service<W>(
     State(state): State<AdminServiceState<W>>,
     Path(service_name): Path<String>,
-    #[request_body(required = true)] Json(ModifyServiceRequest { public }): Json<
-        ModifyServiceRequest,
-    >,
+    #[request_body(required = true)] Json(ModifyServiceRequest {
+        public,
+        concurrency_limit,
+        handler_concurrency_limits,
+        retry_policy,
+        handler_retry_policies,
+    }): Json<ModifyServiceRequest>,
 ) -> Result<Json<ServiceMetadata>, MetaApiError> {
-    state
-        .meta_handle()
-        .modify_service(service_name.clone(), public)
-        .await?;
+    if let Some(public) = public {
+        state
+            .meta_handle()
+            .modify_service(service_name.clone(), public)
+            .await?;
+    }
+
+    let limits: Vec<_> = concurrency_limit
+        .map(|limit| {
+            (
+                ConcurrencyLimitScope::Component(service_name.clone()),
+                limit,
+            )
+        })
+        .into_iter()
+        .chain(
+            handler_concurrency_limits
+                .into_iter()
+                .map(|(handler, limit)| {
+                    (
+                        ConcurrencyLimitScope::Handler {
+                            component: service_name.clone(),
+                            handler,
+                        },
+                        limit,
+                    )
+                }),
+        )
+        .collect();
+    if !limits.is_empty() {
+        state
+            .meta_handle()
+            .modify_concurrency_limits(limits)
+            .await?;
+    }
+
+    let retry_policies: Vec<_> = retry_policy
+        .map(|retry_policy| {
+            (
+                RetryPolicyScope::Component(service_name.clone()),
+                retry_policy,
+            )
+        })
+        .into_iter()
+        .chain(
+            handler_retry_policies
+                .into_iter()
+                .map(|(handler, retry_policy)| {
+                    (
+                        RetryPolicyScope::Handler {
+                            component: service_name.clone(),
+                            handler,
+                        },
+                        retry_policy,
+                    )
+                }),
+        )
+        .collect();
+    if !retry_policies.is_empty() {
+        state
+            .meta_handle()
+            .modify_retry_policies(retry_policies)
+            .await?;
+    }
 
     notify_worker_about_schema_changes(state.schema_reader(), state.node_svc_client()).await?;
 
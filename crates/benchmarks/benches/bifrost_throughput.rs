@@ -0,0 +1,140 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Compares the throughput of single and batched appends and reads of Bifrost, backed by the
+//! local loglet provider.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use futures::StreamExt;
+use pprof::criterion::{Output, PProfProfiler};
+use restate_bifrost::{Bifrost, Options};
+use restate_core::TestCoreEnv;
+use restate_types::logs::metadata::ProviderKind;
+use restate_types::logs::{LogId, Lsn, Payload, SequenceNumber};
+use tokio::runtime::Builder;
+
+const NUM_RECORDS: u64 = 1000;
+const BATCH_SIZE: usize = 100;
+const PAYLOAD_SIZE: usize = 128;
+
+const APPEND_LOG: u64 = 0;
+const READ_LOG: u64 = 1;
+
+fn bifrost_throughput(criterion: &mut Criterion) {
+    let rt = Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("runtime must build");
+    let tmp = tempfile::tempdir().expect("tempdir failed");
+
+    let (tc, bifrost) = rt.block_on(async {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let mut opts = Options {
+            default_provider: ProviderKind::Local,
+            ..Options::default()
+        };
+        opts.providers_config[ProviderKind::Local] = serde_json::json!({ "path": tmp.path() });
+        let bifrost_svc = opts.build(2, node_env.metadata.clone(), node_env.metadata_writer);
+        let mut bifrost = bifrost_svc.handle();
+        node_env
+            .tc
+            .run_in_scope("bifrost-init", None, bifrost_svc.start())
+            .await
+            .expect("bifrost must start");
+
+        bifrost
+            .append_batch(LogId::from(READ_LOG), payloads(NUM_RECORDS as usize))
+            .await
+            .expect("prefilling the read log must succeed");
+        (node_env.tc, bifrost)
+    });
+
+    let mut group = criterion.benchmark_group("bifrost");
+    group
+        .throughput(Throughput::Elements(NUM_RECORDS))
+        .bench_function("append", |bencher| {
+            bencher.to_async(&rt).iter(|| append(bifrost.clone()))
+        })
+        .bench_function("append_batch", |bencher| {
+            bencher.to_async(&rt).iter(|| append_batch(bifrost.clone()))
+        })
+        .bench_function("read_next_single", |bencher| {
+            bencher.to_async(&rt).iter(|| read_next_single(&bifrost))
+        })
+        .bench_function("read_next_n", |bencher| {
+            bencher.to_async(&rt).iter(|| read_next_n(&bifrost))
+        })
+        .bench_function("read_stream", |bencher| {
+            bencher.to_async(&rt).iter(|| read_stream(&bifrost))
+        });
+    group.finish();
+
+    rt.block_on(tc.shutdown_node("completed", 0));
+}
+
+fn payloads(num_records: usize) -> Vec<Payload> {
+    vec![Payload::from("x".repeat(PAYLOAD_SIZE)); num_records]
+}
+
+async fn append(mut bifrost: Bifrost) {
+    for payload in payloads(NUM_RECORDS as usize) {
+        bifrost
+            .append(LogId::from(APPEND_LOG), payload)
+            .await
+            .expect("append should not fail");
+    }
+}
+
+async fn append_batch(mut bifrost: Bifrost) {
+    for _ in 0..NUM_RECORDS as usize / BATCH_SIZE {
+        bifrost
+            .append_batch(LogId::from(APPEND_LOG), payloads(BATCH_SIZE))
+            .await
+            .expect("append_batch should not fail");
+    }
+}
+
+async fn read_next_single(bifrost: &Bifrost) {
+    let mut reader = bifrost.create_reader(LogId::from(READ_LOG), Lsn::INVALID);
+    for _ in 0..NUM_RECORDS {
+        reader.read_next().await.expect("read should not fail");
+    }
+}
+
+async fn read_next_n(bifrost: &Bifrost) {
+    let mut reader = bifrost.create_reader(LogId::from(READ_LOG), Lsn::INVALID);
+    let mut num_read = 0;
+    while num_read < NUM_RECORDS {
+        num_read += reader
+            .read_next_n(BATCH_SIZE)
+            .await
+            .expect("read should not fail")
+            .len() as u64;
+    }
+}
+
+async fn read_stream(bifrost: &Bifrost) {
+    let reader = bifrost
+        .create_reader(LogId::from(READ_LOG), Lsn::INVALID)
+        .with_prefetch(BATCH_SIZE)
+        .with_until(Lsn::from(NUM_RECORDS));
+    let num_read = reader
+        .map(|record| record.expect("read should not fail"))
+        .count()
+        .await;
+    assert_eq!(NUM_RECORDS as usize, num_read);
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().with_profiler(PProfProfiler::new(997, Output::Flamegraph(Some(restate_benchmarks::flamegraph_options()))));
+    targets = bifrost_throughput
+);
+criterion_main!(benches);
@@ -11,21 +11,27 @@
 // TODO: Remove after fleshing the code out.
 #![allow(dead_code)]
 
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 use enum_map::EnumMap;
 use once_cell::sync::OnceCell;
+use tracing::{debug, info};
 
+use restate_core::metadata_store::WriteError;
+use restate_core::{Metadata, MetadataUpdateError, MetadataWriter};
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::logs::metadata::{LogletConfig, LogletParams, Logs, ProviderKind, Segment};
 use restate_types::logs::{LogId, Lsn, Payload, SequenceNumber};
-use restate_types::Version;
+use restate_types::{Version, Versioned};
 
-use crate::loglet::{LogletBase, LogletProvider, LogletWrapper, ProviderKind};
-use crate::metadata::Logs;
+use crate::loglet::{LogletBase, LogletProvider, LogletWrapper};
 use crate::options::Options;
 use crate::watchdog::{WatchdogCommand, WatchdogSender};
-use crate::{create_static_metadata, Error, FindTailAttributes, LogReadStream, LogRecord};
+use crate::{
+    create_static_metadata, Error, FindTailAttributes, LogReadStream, LogRecord, Record, SealReason,
+};
 
 /// Bifrost is Restate's durable interconnect system
 ///
@@ -43,8 +49,12 @@ impl Bifrost {
     }
 
     #[cfg(any(test, feature = "memory_loglet"))]
-    pub async fn new_in_memory(num_logs: u64) -> Self {
-        let bifrost_svc = Options::memory().build(num_logs);
+    pub async fn new_in_memory(
+        metadata: Metadata,
+        metadata_writer: MetadataWriter,
+        num_logs: u64,
+    ) -> Self {
+        let bifrost_svc = Options::memory().build(num_logs, metadata, metadata_writer);
         let bifrost = bifrost_svc.handle();
 
         // start bifrost service in the background
@@ -56,15 +66,30 @@ impl Bifrost {
     }
 
     /// Appends a single record to a log. The log id must exist, otherwise the
-    /// operation fails with [`Error::UnknownLogId`]
+    /// operation fails with [`Error::UnknownLogId`]. Appends to a sealed log fail with
+    /// [`Error::LogSealed`] until the log is continued in a new segment.
     pub async fn append(&mut self, log_id: LogId, payload: Payload) -> Result<Lsn, Error> {
         self.inner.append(log_id, payload).await
     }
 
+    /// Appends a batch of records to a log, the records get consecutive LSNs in the order of
+    /// the batch. Either all or none of the records are appended. Returns the range of LSNs of
+    /// the appended records. Appending an empty batch fails with [`Error::EmptyBatch`].
+    pub async fn append_batch(
+        &mut self,
+        log_id: LogId,
+        payloads: Vec<Payload>,
+    ) -> Result<Range<Lsn>, Error> {
+        self.inner.append_batch(log_id, payloads).await
+    }
+
     /// Read the next record after the LSN provided. The `start` indicates the LSN where we will
     /// read after. This means that the record returned will have a LSN strictly greater than
     /// `after`. If no records are committed yet after this LSN, this read operation will "wait"
     /// for such records to appear.
+    ///
+    /// Reads cross segment boundaries of the log transparently. A [`Record::Seal`] is only
+    /// returned if the log is sealed and not continued in a new segment (yet).
     pub async fn read_next_single(&self, log_id: LogId, after: Lsn) -> Result<LogRecord, Error> {
         self.inner.read_next_single(log_id, after).await
     }
@@ -80,6 +105,59 @@ impl Bifrost {
         self.inner.read_next_single_opt(log_id, after).await
     }
 
+    /// Like [`Self::read_next_single`], but also returns the records that are committed
+    /// right after the next record, up to `max_records` records in total. A trim gap or a seal
+    /// is always returned as the only record of the batch.
+    pub async fn read_next_n(
+        &self,
+        log_id: LogId,
+        after: Lsn,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord>, Error> {
+        self.inner.read_next_n(log_id, after, max_records).await
+    }
+
+    /// Trims the log up to and including the `trim_point`. Readers that read before the trim
+    /// point receive a [`crate::Record::TrimGap`] instead of the trimmed records. Trim points
+    /// beyond the tail of the log are clamped to the tail.
+    pub async fn trim(&self, log_id: LogId, trim_point: Lsn) -> Result<(), Error> {
+        self.inner.trim(log_id, trim_point).await
+    }
+
+    /// The LSN of the last trimmed record of a log, or [`Lsn::INVALID`] if the log has never
+    /// been trimmed.
+    pub async fn get_trim_point(&self, log_id: LogId) -> Result<Lsn, Error> {
+        self.inner.get_trim_point(log_id).await
+    }
+
+    /// Seals the log, appends fail with [`Error::LogSealed`] afterwards and readers that reach
+    /// the end of the log receive a [`Record::Seal`]. Returns the LSN of the last record of the
+    /// sealed log. Sealing an already sealed log keeps the original reason.
+    pub async fn seal(&self, log_id: LogId, reason: SealReason) -> Result<Lsn, Error> {
+        self.inner.seal(log_id, reason).await
+    }
+
+    /// Continues the log in a new segment that's backed by the loglet of the given provider
+    /// `kind` and `params`. The current tail segment is sealed first, the new segment starts
+    /// right after its last record. Returns the base LSN of the new segment.
+    ///
+    /// The new version of the log metadata is published through the metadata manager, from
+    /// where it reaches the other nodes.
+    ///
+    /// The `params` must identify a loglet that's not used by any other segment. Appends and
+    /// reads move on to the new segment without interruption, this can be used to migrate a
+    /// log to another loglet provider.
+    pub async fn extend_chain(
+        &self,
+        log_id: LogId,
+        kind: ProviderKind,
+        params: LogletParams,
+    ) -> Result<Lsn, Error> {
+        self.inner.extend_chain(log_id, kind, params).await
+    }
+
+    /// Creates a stream of the records of the log after the LSN `after`. See [`LogReadStream`]
+    /// for configuring the read-ahead and an upper bound of the stream.
     pub fn create_reader(&self, log_id: LogId, after: Lsn) -> LogReadStream {
         LogReadStream::new(self.inner.clone(), log_id, after)
     }
@@ -99,6 +177,12 @@ impl Bifrost {
         self.inner.log_metadata.lock().unwrap().version
     }
 
+    /// Waits until a newer version than `version` of the logs metadata is loaded, e.g. to retry
+    /// an append to a sealed log once the log has been continued in a new segment.
+    pub async fn wait_for_version_after(&self, version: Version) -> Result<(), Error> {
+        self.inner.wait_for_logs_version_after(version).await
+    }
+
     #[cfg(test)]
     pub fn inner(&self) -> Arc<BifrostInner> {
         self.inner.clone()
@@ -114,18 +198,30 @@ pub struct BifrostInner {
     opts: Options,
     num_partitions: u64,
     watchdog: WatchdogSender,
-    log_metadata: Mutex<Logs>,
+    metadata: Metadata,
+    metadata_writer: MetadataWriter,
+    // The logs metadata as last loaded from the metadata manager, which is its only source. It's
+    // never modified locally, changes are published through the metadata manager first.
+    log_metadata: Mutex<Arc<Logs>>,
     providers: EnumMap<ProviderKind, OnceCell<Arc<dyn LogletProvider>>>,
     shutting_down: AtomicBool,
 }
 
 impl BifrostInner {
-    pub fn new(opts: Options, watchdog: WatchdogSender, num_partitions: u64) -> Self {
+    pub fn new(
+        opts: Options,
+        watchdog: WatchdogSender,
+        num_partitions: u64,
+        metadata: Metadata,
+        metadata_writer: MetadataWriter,
+    ) -> Self {
         Self {
             opts,
             num_partitions,
             watchdog,
-            log_metadata: Mutex::new(Logs::empty()),
+            metadata,
+            metadata_writer,
+            log_metadata: Mutex::new(Arc::new(Logs::empty())),
             providers: Default::default(),
             shutting_down: AtomicBool::new(false),
         }
@@ -142,15 +238,62 @@ impl BifrostInner {
     /// operation fails with [`Error::UnknownLogId`]
     pub async fn append(&self, log_id: LogId, payload: Payload) -> Result<Lsn, Error> {
         self.fail_if_shutting_down()?;
-        let loglet = self.writeable_loglet(log_id).await?;
-        loglet.append(payload).await
+        loop {
+            let segment = self.tail_segment(log_id)?;
+            let loglet = self.loglet_for_segment(&segment).await?;
+            match loglet.append(payload.clone()).await {
+                Err(Error::LogletSealed(reason)) => {
+                    // The chain might have been extended since we looked up the tail segment,
+                    // the append moves on to the new tail segment in this case.
+                    if self.is_tail_segment(log_id, &segment)? {
+                        return Err(Error::LogSealed(log_id, reason));
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn append_batch(
+        &self,
+        log_id: LogId,
+        payloads: Vec<Payload>,
+    ) -> Result<Range<Lsn>, Error> {
+        self.fail_if_shutting_down()?;
+        if payloads.is_empty() {
+            return Err(Error::EmptyBatch);
+        }
+        loop {
+            let segment = self.tail_segment(log_id)?;
+            let loglet = self.loglet_for_segment(&segment).await?;
+            match loglet.append_batch(&payloads).await {
+                Ok(last_lsn) => {
+                    let last_lsn_raw: u64 = last_lsn.into();
+                    let first_lsn = Lsn::from(last_lsn_raw - (payloads.len() as u64 - 1));
+                    return Ok(first_lsn..last_lsn.next());
+                }
+                Err(Error::LogletSealed(reason)) => {
+                    if self.is_tail_segment(log_id, &segment)? {
+                        return Err(Error::LogSealed(log_id, reason));
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
     }
 
     pub async fn read_next_single(&self, log_id: LogId, after: Lsn) -> Result<LogRecord, Error> {
         self.fail_if_shutting_down()?;
 
-        let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
-        loglet.read_next_single(after).await
+        let mut segment = self.segment_for_lsn(log_id, after.next())?;
+        loop {
+            let loglet = self.loglet_for_segment(&segment).await?;
+            let record = loglet.read_next_single(after).await?;
+            match self.next_segment_after_seal(log_id, &segment, &record)? {
+                Some(next_segment) => segment = next_segment,
+                None => return Ok(record),
+            }
+        }
     }
 
     pub async fn read_next_single_opt(
@@ -160,8 +303,40 @@ impl BifrostInner {
     ) -> Result<Option<LogRecord>, Error> {
         self.fail_if_shutting_down()?;
 
-        let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
-        loglet.read_next_single_opt(after).await
+        let mut segment = self.segment_for_lsn(log_id, after.next())?;
+        loop {
+            let loglet = self.loglet_for_segment(&segment).await?;
+            let Some(record) = loglet.read_next_single_opt(after).await? else {
+                return Ok(None);
+            };
+            match self.next_segment_after_seal(log_id, &segment, &record)? {
+                Some(next_segment) => segment = next_segment,
+                None => return Ok(Some(record)),
+            }
+        }
+    }
+
+    pub async fn read_next_n(
+        &self,
+        log_id: LogId,
+        after: Lsn,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord>, Error> {
+        self.fail_if_shutting_down()?;
+
+        let mut segment = self.segment_for_lsn(log_id, after.next())?;
+        loop {
+            let loglet = self.loglet_for_segment(&segment).await?;
+            let records = loglet.read_next_n(after, max_records).await?;
+            let Some(first_record) = records.first() else {
+                return Ok(records);
+            };
+            // A seal is always the only record of a batch.
+            match self.next_segment_after_seal(log_id, &segment, first_record)? {
+                Some(next_segment) => segment = next_segment,
+                None => return Ok(records),
+            }
+        }
     }
 
     pub async fn find_tail(
@@ -169,9 +344,131 @@ impl BifrostInner {
         log_id: LogId,
         _attributes: FindTailAttributes,
     ) -> Result<Option<Lsn>, Error> {
+        self.fail_if_shutting_down()?;
+        // The tail segment might not hold any readable records yet, the tail of the log is
+        // then in one of the previous segments.
+        for segment in self.segments(log_id)?.iter().rev() {
+            let loglet = self.loglet_for_segment(segment).await?;
+            if let Some(tail) = loglet.find_tail().await? {
+                return Ok(Some(tail));
+            }
+        }
+        Ok(None)
+    }
+
+    pub async fn trim(&self, log_id: LogId, trim_point: Lsn) -> Result<(), Error> {
+        self.fail_if_shutting_down()?;
+        for segment in self.segments(log_id)? {
+            if segment.base_lsn > trim_point {
+                break;
+            }
+            let loglet = self.loglet_for_segment(&segment).await?;
+            loglet.trim(trim_point).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_trim_point(&self, log_id: LogId) -> Result<Lsn, Error> {
+        self.fail_if_shutting_down()?;
+        let segments = self.segments(log_id)?;
+        let mut segments = segments.iter().peekable();
+        let mut trim_point = Lsn::INVALID;
+        while let Some(segment) = segments.next() {
+            let loglet = self.loglet_for_segment(segment).await?;
+            trim_point = loglet.get_trim_point().await?;
+            // The trim point is only in one of the next segments if this one is trimmed
+            // entirely.
+            match segments.peek() {
+                Some(next_segment) if trim_point >= next_segment.base_lsn.prev() => {}
+                _ => break,
+            }
+        }
+        Ok(trim_point)
+    }
+
+    pub async fn seal(&self, log_id: LogId, reason: SealReason) -> Result<Lsn, Error> {
         self.fail_if_shutting_down()?;
         let loglet = self.writeable_loglet(log_id).await?;
-        loglet.find_tail().await
+        loglet.seal(reason).await?;
+        Self::last_lsn(&loglet).await
+    }
+
+    pub async fn extend_chain(
+        &self,
+        log_id: LogId,
+        kind: ProviderKind,
+        params: LogletParams,
+    ) -> Result<Lsn, Error> {
+        self.fail_if_shutting_down()?;
+        let segment = self.tail_segment(log_id)?;
+        let loglet = self.loglet_for_segment(&segment).await?;
+        loglet.seal(SealReason::Reconfiguration).await?;
+        let base_lsn = Self::last_lsn(&loglet).await?.next();
+
+        // Make sure that the loglet of the new segment is usable before it's published.
+        self.provider_for(kind)?.get_loglet(&params).await?;
+
+        // The extended chain is built on a copy, the local logs metadata is only ever replaced
+        // by versions that the metadata manager has accepted.
+        let logs = {
+            let guard = self.log_metadata.lock().unwrap();
+            let tail_segment = guard
+                .tail_segment(log_id)
+                .ok_or(Error::UnknownLogId(log_id))?;
+            if !Arc::ptr_eq(&tail_segment.config, &segment.config) {
+                return Err(Error::ConcurrentReconfiguration(log_id));
+            }
+            let mut logs = guard.as_ref().clone();
+            logs.logs
+                .get_mut(&log_id)
+                .expect("log exists")
+                .append_segment(base_lsn, LogletConfig::new(kind, params));
+            logs.increment_version();
+            logs
+        };
+        let version = logs.version;
+        let result = self.metadata_writer.update(logs).await;
+        // Pick up the new version, or the version of a concurrent reconfiguration which the
+        // metadata manager has reloaded after the failed write.
+        self.sync_metadata().await?;
+        result.map_err(|err| match err {
+            // Another node has changed the logs metadata in the meantime
+            MetadataUpdateError::Write(WriteError::FailedPrecondition(_)) => {
+                Error::ConcurrentReconfiguration(log_id)
+            }
+            MetadataUpdateError::Write(err) => Error::MetadataWrite(err),
+            MetadataUpdateError::Shutdown(_) => Error::Shutdown,
+        })?;
+        info!(
+            %log_id,
+            %base_lsn,
+            ?kind,
+            "Extended the chain of the log with a new segment, logs metadata is at {}",
+            version
+        );
+        Ok(base_lsn)
+    }
+
+    /// Version of the loaded logs metadata.
+    pub(crate) fn logs_version(&self) -> Version {
+        self.log_metadata.lock().unwrap().version
+    }
+
+    /// Waits until a newer version than `version` of the logs metadata is loaded, e.g. once the
+    /// chain of a sealed log has been extended.
+    pub(crate) async fn wait_for_logs_version_after(&self, version: Version) -> Result<(), Error> {
+        let mut logs_watch = self.metadata.watch(MetadataKind::Logs);
+        loop {
+            self.fail_if_shutting_down()?;
+            if self.logs_version() > version {
+                return Ok(());
+            }
+            if *logs_watch.borrow_and_update() > version {
+                self.sync_metadata().await?;
+                continue;
+            }
+            logs_watch.changed().await.map_err(|_| Error::Shutdown)?;
+        }
     }
 
     #[inline]
@@ -183,15 +480,39 @@ impl BifrostInner {
         }
     }
 
-    /// Immediately fetch new metadata from metadata store and update the local copy
-    ///
-    /// NOTE: The current version assumes static metadata map!
+    /// Publishes the static log metadata if no node has provided the logs metadata yet.
+    pub async fn bootstrap_metadata(&self) -> Result<(), Error> {
+        if self.metadata.logs_version() != Version::INVALID {
+            return Ok(());
+        }
+        let logs = create_static_metadata(&self.opts, self.num_partitions);
+        info!(
+            "Bootstrapping logs metadata {} with {} logs",
+            logs.version,
+            logs.logs.len()
+        );
+        match self.metadata_writer.update(logs).await {
+            // Another node has bootstrapped the logs metadata in the meantime
+            Ok(()) | Err(MetadataUpdateError::Write(WriteError::FailedPrecondition(_))) => Ok(()),
+            Err(MetadataUpdateError::Write(err)) => Err(Error::MetadataWrite(err)),
+            Err(MetadataUpdateError::Shutdown(_)) => Err(Error::Shutdown),
+        }
+    }
+
+    /// Immediately loads the logs metadata from the metadata manager if it holds a newer version.
     pub async fn sync_metadata(&self) -> Result<(), Error> {
         self.fail_if_shutting_down()?;
 
-        let logs = create_static_metadata(&self.opts, self.num_partitions);
+        if self.metadata.logs_version() <= self.log_metadata.lock().unwrap().version {
+            return Ok(());
+        }
+        let logs = self.metadata.logs();
         let mut guard = self.log_metadata.lock().unwrap();
         if logs.version > guard.version {
+            debug!(
+                "Updating logs metadata from {} to {}",
+                guard.version, logs.version
+            );
             *guard = logs;
         }
         Ok(())
@@ -201,17 +522,16 @@ impl BifrostInner {
 
     /// Get the provider for a given kind. If the provider is not yet initialized, it will be
     /// lazily initialized by the watchdog.
-    fn provider_for(&self, kind: ProviderKind) -> &dyn LogletProvider {
-        self.providers[kind]
-            .get_or_init(|| {
-                let provider = crate::loglet::create_provider(kind, &self.opts);
-                // tell watchdog about it.
-                let _ = self
-                    .watchdog
-                    .send(WatchdogCommand::StartProvider(provider.clone()));
-                provider
-            })
-            .deref()
+    fn provider_for(&self, kind: ProviderKind) -> Result<&dyn LogletProvider, Error> {
+        let provider = self.providers[kind].get_or_try_init(|| {
+            let provider = crate::loglet::create_provider(kind, &self.opts)?;
+            // tell watchdog about it.
+            let _ = self
+                .watchdog
+                .send(WatchdogCommand::StartProvider(provider.clone()));
+            Ok::<_, Error>(provider)
+        })?;
+        Ok(provider.deref())
     }
 
     /// Injects a provider for testing purposes. The call is responsible for starting the provider
@@ -224,34 +544,78 @@ impl BifrostInner {
     }
 
     async fn writeable_loglet(&self, log_id: LogId) -> Result<LogletWrapper, Error> {
-        // Locks the logs mutex.
-        let tail_segment = self
-            .log_metadata
+        let tail_segment = self.tail_segment(log_id)?;
+        self.loglet_for_segment(&tail_segment).await
+    }
+
+    async fn find_loglet_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Result<LogletWrapper, Error> {
+        let segment = self.segment_for_lsn(log_id, lsn)?;
+        self.loglet_for_segment(&segment).await
+    }
+
+    async fn loglet_for_segment(&self, segment: &Segment) -> Result<LogletWrapper, Error> {
+        let provider = self.provider_for(segment.config.kind)?;
+        let loglet = provider.get_loglet(&segment.config.params).await?;
+
+        Ok(LogletWrapper::new(segment.base_lsn, loglet))
+    }
+
+    fn tail_segment(&self, log_id: LogId) -> Result<Segment, Error> {
+        self.log_metadata
             .lock()
             .unwrap()
             .tail_segment(log_id)
-            .ok_or(Error::UnknownLogId(log_id))?;
-        // Logs lock released here.
-        let provider = self.provider_for(tail_segment.config.kind);
-        let loglet = provider.get_loglet(&tail_segment.config.params).await?;
-
-        Ok(LogletWrapper::new(tail_segment.base_lsn, loglet))
+            .ok_or(Error::UnknownLogId(log_id))
     }
 
-    async fn find_loglet_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Result<LogletWrapper, Error> {
-        // Locks the logs mutex.
-        let segment = self
-            .log_metadata
+    fn segment_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Result<Segment, Error> {
+        self.log_metadata
             .lock()
             .unwrap()
             .find_segment_for_lsn(log_id, lsn)
-            .ok_or(Error::UnknownLogId(log_id))?;
+            .ok_or(Error::UnknownLogId(log_id))
+    }
 
-        // Logs lock released here.
-        let provider = self.provider_for(segment.config.kind);
-        let loglet = provider.get_loglet(&segment.config.params).await?;
+    fn segments(&self, log_id: LogId) -> Result<Vec<Segment>, Error> {
+        self.log_metadata
+            .lock()
+            .unwrap()
+            .segments(log_id)
+            .ok_or(Error::UnknownLogId(log_id))
+    }
 
-        Ok(LogletWrapper::new(segment.base_lsn, loglet))
+    fn is_tail_segment(&self, log_id: LogId, segment: &Segment) -> Result<bool, Error> {
+        let tail_segment = self.tail_segment(log_id)?;
+        Ok(Arc::ptr_eq(&tail_segment.config, &segment.config))
+    }
+
+    /// Returns the segment that continues the log if `record` marks the end of a sealed
+    /// `segment` and the chain has been extended already.
+    fn next_segment_after_seal(
+        &self,
+        log_id: LogId,
+        segment: &Segment,
+        record: &LogRecord,
+    ) -> Result<Option<Segment>, Error> {
+        if !matches!(record.record, Record::Seal(_)) {
+            return Ok(None);
+        }
+        // The seal record sits at the base LSN of the next segment.
+        let next_segment = self.segment_for_lsn(log_id, record.offset)?;
+        if Arc::ptr_eq(&next_segment.config, &segment.config) {
+            Ok(None)
+        } else {
+            Ok(Some(next_segment))
+        }
+    }
+
+    /// LSN of the last record of a segment, or the LSN before the base LSN if it's empty.
+    async fn last_lsn(loglet: &LogletWrapper) -> Result<Lsn, Error> {
+        match loglet.find_tail().await? {
+            Some(tail) => Ok(tail),
+            // The trim point never moves beyond the tail.
+            None => loglet.get_trim_point().await,
+        }
     }
 }
 
@@ -261,10 +625,11 @@ mod tests {
 
     use super::*;
 
-    use crate::loglet::ProviderKind;
     use crate::loglets::memory_loglet::MemoryLogletProvider;
     use googletest::prelude::*;
+    use restate_test_util::let_assert;
 
+    use restate_core::metadata_store::Precondition;
     use restate_core::task_center;
     use restate_core::TestCoreEnv;
     use restate_types::logs::SequenceNumber;
@@ -279,7 +644,12 @@ mod tests {
         tc.run_in_scope("test", None, async {
             // start a simple bifrost service with 5 logs.
             let num_partitions = 5;
-            let mut bifrost = Bifrost::new_in_memory(num_partitions).await;
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                num_partitions,
+            )
+            .await;
 
             let mut clean_bifrost_clone = bifrost.clone();
 
@@ -344,6 +714,332 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_trim() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+            assert_eq!(Lsn::INVALID, bifrost.get_trim_point(log_id).await?);
+
+            for _ in 1..=10 {
+                bifrost.append(log_id, Payload::default()).await?;
+            }
+
+            bifrost.trim(log_id, Lsn::from(5)).await?;
+            assert_eq!(Lsn::from(5), bifrost.get_trim_point(log_id).await?);
+            assert_eq!(
+                Some(Lsn::from(10)),
+                bifrost
+                    .find_tail(log_id, FindTailAttributes::default())
+                    .await?
+            );
+
+            // trimming to an older LSN doesn't move the trim point back
+            bifrost.trim(log_id, Lsn::from(3)).await?;
+            assert_eq!(Lsn::from(5), bifrost.get_trim_point(log_id).await?);
+
+            // trim points beyond the tail are clamped to the tail
+            bifrost.trim(log_id, Lsn::from(20)).await?;
+            assert_eq!(Lsn::from(10), bifrost.get_trim_point(log_id).await?);
+            assert_eq!(
+                Lsn::from(11),
+                bifrost.append(log_id, Payload::default()).await?
+            );
+
+            let invalid_log = LogId::from(1);
+            assert_that!(
+                bifrost.trim(invalid_log, Lsn::from(1)).await,
+                pat!(Err(pat!(Error::UnknownLogId(eq(invalid_log)))))
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_seal_and_extend_chain() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+            for _ in 1..=5 {
+                bifrost.append(log_id, Payload::default()).await?;
+            }
+            let version = bifrost.version();
+
+            // sealing returns the LSN of the last record
+            assert_eq!(
+                Lsn::from(5),
+                bifrost.seal(log_id, SealReason::Resharding).await?
+            );
+            assert!(matches!(
+                bifrost.append(log_id, Payload::default()).await,
+                Err(Error::LogSealed(id, SealReason::Resharding)) if id == log_id
+            ));
+
+            // readers at the end of a sealed log receive the seal record
+            let mut reader = bifrost.create_reader(log_id, Lsn::from(4));
+            let_assert!(Some(record) = reader.read_next().await?);
+            assert_eq!(Lsn::from(5), record.offset);
+            let_assert!(Some(record) = reader.read_next().await?);
+            assert_eq!(Lsn::from(6), record.offset);
+            let_assert!(Record::Seal(reason) = record.record);
+            assert_eq!(SealReason::Resharding, reason);
+            assert_eq!(Lsn::from(5), reader.current_read_pointer());
+
+            // a reader that waits at the end of the log moves on to the new segment once the
+            // extended chain has been published
+            let waiting_reader = bifrost.clone();
+            let waiting_read = tokio::spawn(async move {
+                loop {
+                    let version = waiting_reader.version();
+                    let record = waiting_reader
+                        .read_next_single(log_id, Lsn::from(5))
+                        .await?;
+                    if !matches!(record.record, Record::Seal(_)) {
+                        return Ok::<_, Error>(record);
+                    }
+                    waiting_reader.wait_for_version_after(version).await?;
+                }
+            });
+
+            // the log continues in a new segment, sealing again keeps the original reason
+            let base_lsn = bifrost
+                .extend_chain(
+                    log_id,
+                    ProviderKind::Memory,
+                    LogletParams::from("0-1".to_owned()),
+                )
+                .await?;
+            assert_eq!(Lsn::from(6), base_lsn);
+            assert!(bifrost.version() > version);
+            // the extended chain is published through the metadata manager
+            assert_eq!(bifrost.version(), node_env.metadata.logs_version());
+            assert_eq!(
+                Lsn::from(6),
+                bifrost.append(log_id, Payload::from("6")).await?
+            );
+            assert_eq!(
+                Lsn::from(7),
+                bifrost.append(log_id, Payload::from("7")).await?
+            );
+            assert_eq!(
+                Some(Lsn::from(7)),
+                bifrost
+                    .find_tail(log_id, FindTailAttributes::default())
+                    .await?
+            );
+
+            let record = waiting_read.await.unwrap()?;
+            assert_eq!(Lsn::from(6), record.offset);
+            assert_eq!(Payload::from("6"), record.record.into_payload_unchecked());
+
+            // readers cross the segment boundary transparently
+            for lsn in 6..=7 {
+                let_assert!(Some(record) = reader.read_next().await?);
+                assert_eq!(Lsn::from(lsn), record.offset);
+                assert_eq!(
+                    Payload::from(lsn.to_string()),
+                    record.record.into_payload_unchecked()
+                );
+            }
+            assert!(reader.read_next_opt().await?.is_none());
+
+            // trimming spans segments
+            bifrost.trim(log_id, Lsn::from(3)).await?;
+            assert_eq!(Lsn::from(3), bifrost.get_trim_point(log_id).await?);
+            bifrost.trim(log_id, Lsn::from(6)).await?;
+            assert_eq!(Lsn::from(6), bifrost.get_trim_point(log_id).await?);
+            let_assert!(Some(record) = bifrost.read_next_single_opt(log_id, Lsn::from(5)).await?);
+            assert!(record.record.is_trim_gap());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_extend_chain_concurrent_reconfiguration() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+            for _ in 1..=2 {
+                bifrost.append(log_id, Payload::default()).await?;
+            }
+            let version = bifrost.version();
+
+            // another node extends the chain, this node doesn't know about it yet
+            let mut logs = node_env.metadata.logs().as_ref().clone();
+            logs.logs.get_mut(&log_id).unwrap().append_segment(
+                Lsn::from(3),
+                LogletConfig::new(ProviderKind::Memory, LogletParams::from("0-2".to_owned())),
+            );
+            logs.increment_version();
+            let winning_version = logs.version;
+            node_env
+                .metadata_store_client
+                .put("logs", &logs, Precondition::MatchesVersion(version))
+                .await?;
+
+            // the write at the same version fails and the winning version is picked up instead
+            assert!(matches!(
+                bifrost
+                    .extend_chain(
+                        log_id,
+                        ProviderKind::Memory,
+                        LogletParams::from("0-1".to_owned()),
+                    )
+                    .await,
+                Err(Error::ConcurrentReconfiguration(id)) if id == log_id
+            ));
+            assert_eq!(winning_version, bifrost.version());
+            assert_eq!(winning_version, node_env.metadata.logs_version());
+
+            // appends continue in the segment of the winning version
+            assert_eq!(
+                Lsn::from(3),
+                bifrost.append(log_id, Payload::default()).await?
+            );
+            let segment = bifrost.inner().tail_segment(log_id)?;
+            assert_eq!(LogletParams::from("0-2".to_owned()), segment.config.params);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_append_batch() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+            let payloads = |range: std::ops::RangeInclusive<u64>| -> Vec<Payload> {
+                range.map(|i| Payload::from(i.to_string())).collect()
+            };
+
+            assert_eq!(
+                Lsn::from(1)..Lsn::from(4),
+                bifrost.append_batch(log_id, payloads(1..=3)).await?
+            );
+            assert_eq!(
+                Lsn::from(4)..Lsn::from(5),
+                bifrost.append_batch(log_id, payloads(4..=4)).await?
+            );
+            assert!(matches!(
+                bifrost.append_batch(log_id, vec![]).await,
+                Err(Error::EmptyBatch)
+            ));
+
+            // batches move on to the next segment once the chain is extended
+            bifrost
+                .extend_chain(
+                    log_id,
+                    ProviderKind::Memory,
+                    LogletParams::from("0-1".to_owned()),
+                )
+                .await?;
+            assert_eq!(
+                Lsn::from(5)..Lsn::from(7),
+                bifrost.append_batch(log_id, payloads(5..=6)).await?
+            );
+
+            // a batch read stops at the end of a segment, the next read continues in the
+            // next segment
+            let records = bifrost.read_next_n(log_id, Lsn::from(1), 10).await?;
+            assert_eq!(3, records.len());
+            for (lsn, record) in (2..=4).zip(records) {
+                assert_eq!(Lsn::from(lsn), record.offset);
+                assert_eq!(
+                    Payload::from(lsn.to_string()),
+                    record.record.into_payload_unchecked()
+                );
+            }
+            let records = bifrost.read_next_n(log_id, Lsn::from(4), 10).await?;
+            let lsns: Vec<_> = records.iter().map(|record| record.offset).collect();
+            assert_eq!(vec![Lsn::from(5), Lsn::from(6)], lsns);
+
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            assert_eq!(2, reader.read_next_n(2).await?.len());
+            assert_eq!(Lsn::from(2), reader.current_read_pointer());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_logs_metadata_updates() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+            // the static metadata was bootstrapped through the metadata manager
+            assert_eq!(bifrost.version(), node_env.metadata.logs_version());
+            for _ in 1..=3 {
+                bifrost.append(log_id, Payload::default()).await?;
+            }
+
+            // another node continues the log in a new segment
+            let mut logs = node_env.metadata.logs().as_ref().clone();
+            logs.logs.get_mut(&log_id).unwrap().append_segment(
+                Lsn::from(10),
+                LogletConfig::new(ProviderKind::Memory, LogletParams::from("hot".to_owned())),
+            );
+            logs.increment_version();
+            let new_version = logs.version;
+            node_env.metadata_writer.update(logs).await?;
+
+            // bifrost picks up the new version in the background
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while bifrost.version() < new_version {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await?;
+
+            // appends go to the new tail segment
+            assert_eq!(
+                Lsn::from(10),
+                bifrost.append(log_id, Payload::default()).await?
+            );
+            Ok(())
+        })
+        .await
+    }
+
     #[tokio::test(start_paused = true)]
     async fn test_lazy_initialization() -> Result<()> {
         let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
@@ -359,7 +1055,11 @@ mod tests {
                 default_provider: ProviderKind::Memory,
                 ..Options::default()
             };
-            let bifrost_svc = bifrost_opts.build(num_partitions);
+            let bifrost_svc = bifrost_opts.build(
+                num_partitions,
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+            );
             let mut bifrost = bifrost_svc.handle();
 
             // Inject out preconfigured memory provider
@@ -10,6 +10,8 @@
 
 use thiserror::Error;
 
+use restate_core::metadata_store::WriteError;
+use restate_types::logs::metadata::ProviderKind;
 use restate_types::logs::{LogId, Lsn};
 
 use crate::types::SealReason;
@@ -18,12 +20,28 @@ use crate::types::SealReason;
 pub enum Error {
     #[error("log '{0}' is sealed")]
     LogSealed(LogId, SealReason),
+    #[error("loglet is sealed")]
+    LogletSealed(SealReason),
+    #[error("log '{0}' was reconfigured concurrently")]
+    ConcurrentReconfiguration(LogId),
     #[error("unknown log '{0}")]
     UnknownLogId(LogId),
     #[error("invalid log sequence number '{0}")]
     InvalidLsn(Lsn),
+    #[error("cannot append an empty batch")]
+    EmptyBatch,
     #[error("cannot fetch log metadata")]
     MetadataSync,
+    #[error("cannot write log metadata: {0}")]
+    MetadataWrite(WriteError),
     #[error("operation failed due to an ongoing shutdown")]
     Shutdown,
+    #[error("loglet provider '{0:?}' is not enabled in this build")]
+    DisabledProvider(ProviderKind),
+    #[error("invalid configuration for loglet provider '{0:?}': {1}")]
+    InvalidProviderConfig(ProviderKind, serde_json::Error),
+    #[error("loglet I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("local loglet store error: {0}")]
+    LogStore(#[from] rocksdb::Error),
 }
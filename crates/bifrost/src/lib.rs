@@ -12,7 +12,6 @@ mod bifrost;
 mod error;
 mod loglet;
 mod loglets;
-mod metadata;
 mod options;
 mod read_stream;
 mod service;
@@ -25,13 +24,12 @@ pub use bifrost::Bifrost;
 pub use error::Error;
 pub use options::Options;
 pub use read_stream::LogReadStream;
+use restate_types::logs::metadata::{Chain, LogletParams, Logs};
 use restate_types::logs::LogId;
 use restate_types::Version;
 pub use service::BifrostService;
 pub use types::*;
 
-use self::metadata::{Chain, LogletParams, Logs};
-
 /// Initializes the bifrost metadata with static log metadata, it creates a log for every partition
 /// with a chain of the default loglet provider kind.
 pub(crate) fn create_static_metadata(opts: &Options, num_partitions: u64) -> Logs {
@@ -11,48 +11,40 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use enum_map::Enum;
 
+use restate_types::logs::metadata::{LogletParams, ProviderKind};
 use restate_types::logs::{Lsn, Payload, SequenceNumber};
 
-use crate::metadata::LogletParams;
-use crate::{Error, LogRecord, LsnExt, Options};
-
-/// An enum with the list of supported loglet providers.
-/// For each variant we must have a corresponding implementation of the
-/// [`crate::loglet::Loglet`] trait
-#[derive(
-    Debug,
-    Clone,
-    Hash,
-    Eq,
-    PartialEq,
-    Copy,
-    serde::Serialize,
-    serde::Deserialize,
-    Enum,
-    strum_macros::EnumIter,
-)]
-pub enum ProviderKind {
-    /// A file-backed loglet.
-    File,
-    #[cfg(any(test, feature = "memory_loglet"))]
-    Memory,
-}
+use crate::{Error, LogRecord, LsnExt, Options, SealReason};
 
 pub fn provider_default_config(kind: ProviderKind) -> serde_json::Value {
     match kind {
         ProviderKind::File => crate::loglets::file_loglet::default_config(),
+        ProviderKind::Local => crate::loglets::local_loglet::default_config(),
         #[cfg(any(test, feature = "memory_loglet"))]
         ProviderKind::Memory => crate::loglets::memory_loglet::default_config(),
+        #[cfg(not(any(test, feature = "memory_loglet")))]
+        ProviderKind::Memory => serde_json::Value::Null,
     }
 }
 
-pub fn create_provider(kind: ProviderKind, options: &Options) -> Arc<dyn LogletProvider> {
+pub fn create_provider(
+    kind: ProviderKind,
+    options: &Options,
+) -> Result<Arc<dyn LogletProvider>, Error> {
     match kind {
-        ProviderKind::File => crate::loglets::file_loglet::FileLogletProvider::new(options),
+        ProviderKind::File => {
+            let provider = crate::loglets::file_loglet::FileLogletProvider::new(options)?;
+            Ok(provider)
+        }
+        ProviderKind::Local => {
+            let provider = crate::loglets::local_loglet::LocalLogletProvider::new(options)?;
+            Ok(provider)
+        }
         #[cfg(any(test, feature = "memory_loglet"))]
-        ProviderKind::Memory => crate::loglets::memory_loglet::MemoryLogletProvider::new(),
+        ProviderKind::Memory => Ok(crate::loglets::memory_loglet::MemoryLogletProvider::new()),
+        #[cfg(not(any(test, feature = "memory_loglet")))]
+        ProviderKind::Memory => Err(Error::DisabledProvider(kind)),
     }
 }
 
@@ -136,6 +128,10 @@ pub trait LogletBase: Send + Sync {
     /// Append a record to the loglet.
     async fn append(&self, payload: Payload) -> Result<Self::Offset, Error>;
 
+    /// Append the records of a non-empty batch atomically at consecutive offsets, in the order
+    /// of the batch. Returns the offset of the last record.
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Self::Offset, Error>;
+
     /// Find the tail of the loglet. If the loglet is empty or have been trimmed, the loglet should
     /// return `None`.
     async fn find_tail(&self) -> Result<Option<Self::Offset>, Error>;
@@ -144,6 +140,20 @@ pub trait LogletBase: Send + Sync {
     /// before the next slot that will be written to.
     async fn get_trim_point(&self) -> Result<Self::Offset, Error>;
 
+    /// Trims the loglet up to and including the `trim_point`. Trim points beyond the tail of the
+    /// loglet are clamped to the tail, trimming to a point at or before the current trim point
+    /// is a no-op.
+    ///
+    /// Reads before the trim point return a trim gap, the trimmed records might be removed
+    /// from storage asynchronously.
+    async fn trim(&self, trim_point: Self::Offset) -> Result<(), Error>;
+
+    /// Seals the loglet, appends that are not committed yet fail with
+    /// [`Error::LogletSealed`]. Once sealed, the tail of the loglet never changes and reads
+    /// after the tail return a [`crate::Record::Seal`] record. Sealing a sealed loglet is a
+    /// no-op that keeps the original reason.
+    async fn seal(&self, reason: SealReason) -> Result<(), Error>;
+
     /// Read or wait for the record at `from` offset, or the next available record if `from` isn't
     /// defined for the loglet.
     async fn read_next_single(&self, after: Self::Offset)
@@ -154,6 +164,15 @@ pub trait LogletBase: Send + Sync {
         &self,
         after: Self::Offset,
     ) -> Result<Option<LogRecord<Self::Offset>>, Error>;
+
+    /// Read or wait for the next record after `after`, like `read_next_single`, together with
+    /// the records that are committed right after it. At least one and at most `max_records`
+    /// records are returned. Trim gaps and seals are returned as the only record of a batch.
+    async fn read_next_n(
+        &self,
+        after: Self::Offset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<Self::Offset>>, Error>;
 }
 
 #[async_trait]
@@ -166,6 +185,11 @@ impl LogletBase for LogletWrapper {
         Ok(self.base_lsn.offset_by(offset))
     }
 
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Lsn, Error> {
+        let offset = self.loglet.append_batch(payloads).await?;
+        Ok(self.base_lsn.offset_by(offset))
+    }
+
     async fn find_tail(&self) -> Result<Option<Lsn>, Error> {
         let offset = self.loglet.find_tail().await?;
         Ok(offset.map(|o| self.base_lsn.offset_by(o)))
@@ -173,7 +197,26 @@ impl LogletBase for LogletWrapper {
 
     async fn get_trim_point(&self) -> Result<Self::Offset, Error> {
         let offset = self.loglet.get_trim_point().await?;
-        Ok(self.base_lsn.offset_by(offset))
+        if offset == LogletOffset::INVALID {
+            // Nothing is trimmed, the trim point is the slot before the base LSN.
+            let base_lsn_raw: u64 = self.base_lsn.into();
+            Ok(Lsn::from(base_lsn_raw - 1))
+        } else {
+            Ok(self.base_lsn.offset_by(offset))
+        }
+    }
+
+    async fn trim(&self, trim_point: Self::Offset) -> Result<(), Error> {
+        if trim_point < self.base_lsn {
+            // nothing of this segment is covered by the trim point
+            return Ok(());
+        }
+        let offset = trim_point.into_offset(self.base_lsn);
+        self.loglet.trim(offset).await
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<(), Error> {
+        self.loglet.seal(reason).await
     }
 
     async fn read_next_single(&self, after: Lsn) -> Result<LogRecord<Lsn>, Error> {
@@ -195,6 +238,19 @@ impl LogletBase for LogletWrapper {
             .await
             .map(|maybe_record| maybe_record.map(|record| record.with_base_lsn(self.base_lsn)))
     }
+
+    async fn read_next_n(
+        &self,
+        after: Self::Offset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<Self::Offset>>, Error> {
+        let offset = after.into_offset(self.base_lsn);
+        let records = self.loglet.read_next_n(offset, max_records).await?;
+        Ok(records
+            .into_iter()
+            .map(|record| record.with_base_lsn(self.base_lsn))
+            .collect())
+    }
 }
 
 static_assertions::assert_impl_all!(LogletWrapper: Send, Sync, Clone);
@@ -0,0 +1,831 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod segment;
+
+use std::collections::{hash_map, BTreeMap, HashMap};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use async_trait::async_trait;
+use tokio::sync::{watch, Mutex as AsyncMutex};
+use tracing::{debug, info, warn};
+
+use restate_types::logs::metadata::{LogletParams, ProviderKind};
+use restate_types::logs::{Payload, SequenceNumber};
+
+use self::segment::{ActiveSegment, RecoveredSegment, SegmentIndex};
+use crate::loglet::{Loglet, LogletBase, LogletOffset, LogletProvider};
+use crate::{Error, LogRecord, Options, SealReason};
+
+/// Configuration of the file loglet provider, read from the provider's entry in
+/// [`Options::providers_config`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FileLogletOptions {
+    /// Root directory of the file loglets. Every loglet stores its segments in a sub-directory
+    /// named after its params.
+    pub path: PathBuf,
+    /// Size in bytes after which a loglet starts writing to a new segment file.
+    pub segment_size: u64,
+}
+
+impl Default for FileLogletOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("target/logs/"),
+            segment_size: 64 * 1024 * 1024, // 64 MiB
+        }
+    }
+}
+
+pub fn default_config() -> serde_json::Value {
+    serde_json::to_value(FileLogletOptions::default()).expect("valid file loglet options")
+}
+
+pub struct FileLogletProvider {
+    opts: FileLogletOptions,
+    loglets: AsyncMutex<HashMap<LogletParams, Arc<FileLoglet>>>,
+}
+
+impl FileLogletProvider {
+    pub fn new(options: &Options) -> Result<Arc<Self>, Error> {
+        let opts = match &options.providers_config[ProviderKind::File] {
+            serde_json::Value::Null => FileLogletOptions::default(),
+            config => serde_json::from_value(config.clone())
+                .map_err(|e| Error::InvalidProviderConfig(ProviderKind::File, e))?,
+        };
+        Ok(Self::with_options(opts))
+    }
+
+    pub fn with_options(opts: FileLogletOptions) -> Arc<Self> {
+        Arc::new(Self {
+            opts,
+            loglets: Default::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl LogletProvider for FileLogletProvider {
+    async fn get_loglet(&self, params: &LogletParams) -> Result<Arc<dyn Loglet>, Error> {
+        let mut guard = self.loglets.lock().await;
+
+        let loglet = match guard.entry(params.clone()) {
+            hash_map::Entry::Vacant(entry) => {
+                let loglet = FileLoglet::open(params.clone(), self.opts.clone()).await?;
+                Arc::clone(entry.insert(Arc::new(loglet)))
+            }
+            hash_map::Entry::Occupied(entry) => Arc::clone(entry.get()),
+        };
+
+        Ok(loglet as Arc<dyn Loglet>)
+    }
+
+    async fn start(&self) -> Result<(), Error> {
+        info!(
+            "Starting file loglet provider at {}",
+            self.opts.path.display()
+        );
+        Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<(), Error> {
+        // Appends are synced to disk before they are acknowledged, nothing to flush here.
+        info!("Shutting down file loglet provider");
+        Ok(())
+    }
+}
+
+/// State shared between the loglet and its in-flight blocking I/O operations.
+struct LogletState {
+    /// Index of the segment files, keyed by the base offset of the segment.
+    segments: RwLock<BTreeMap<LogletOffset, SegmentIndex>>,
+    /// Offset of the last durably committed record, or `INVALID` if the loglet is empty.
+    tail: watch::Sender<LogletOffset>,
+    /// Offset of the last trimmed record, or `INVALID` if the loglet was never trimmed.
+    trim_point: AtomicU64,
+    /// Set under the writer lock once the seal is durable.
+    sealed: OnceLock<SealReason>,
+}
+
+/// A loglet that stores its records in a directory of append-only segment files.
+///
+/// Every append is synced to disk before its offset is returned. On open, the segments are
+/// scanned to rebuild the in-memory index and incomplete records left behind by a crash are
+/// discarded. Trimming removes the segments that only hold trimmed records, the segment that
+/// receives appends is always kept. A sealed loglet rejects all further appends.
+pub struct FileLoglet {
+    params: LogletParams,
+    dir: PathBuf,
+    segment_size: u64,
+    state: Arc<LogletState>,
+    // Serializes appends. The lock is held until the record is durable.
+    writer: Arc<AsyncMutex<ActiveSegment>>,
+}
+
+impl FileLoglet {
+    pub async fn open(params: LogletParams, opts: FileLogletOptions) -> Result<Self, Error> {
+        let dir = loglet_dir(&opts.path, &params)?;
+        let ((segments, active, trim_point), seal) = tokio::task::spawn_blocking({
+            let dir = dir.clone();
+            move || -> io::Result<_> { Ok((recover(&dir)?, segment::read_seal(&dir)?)) }
+        })
+        .await
+        .map_err(|_| Error::Shutdown)??;
+
+        let tail = segments
+            .values()
+            .next_back()
+            .map(|segment| LogletOffset(segment.next_offset().0 - 1))
+            .unwrap_or(LogletOffset::INVALID);
+        debug!(
+            "Opened file loglet {:?} with {} segments, trim point {} and tail {} (sealed: {:?})",
+            params,
+            segments.len(),
+            trim_point,
+            tail,
+            seal
+        );
+
+        let sealed = OnceLock::new();
+        if let Some(reason) = seal {
+            let _ = sealed.set(reason);
+        }
+        let (tail, _) = watch::channel(tail);
+        Ok(Self {
+            params,
+            dir,
+            segment_size: opts.segment_size,
+            state: Arc::new(LogletState {
+                segments: RwLock::new(segments),
+                tail,
+                trim_point: AtomicU64::new(trim_point.0),
+                sealed,
+            }),
+            writer: Arc::new(AsyncMutex::new(active)),
+        })
+    }
+
+    fn last_committed_offset(&self) -> LogletOffset {
+        *self.state.tail.borrow()
+    }
+
+    fn trim_point(&self) -> LogletOffset {
+        LogletOffset(self.state.trim_point.load(Ordering::Acquire))
+    }
+
+    /// Appends the records to the active segment with a single sync, a new segment is started
+    /// first if the records don't fit into the active one anymore.
+    async fn append_records(&self, payloads: Vec<Payload>) -> Result<LogletOffset, Error> {
+        let mut writer = Arc::clone(&self.writer).lock_owned().await;
+        if let Some(reason) = self.state.sealed.get() {
+            return Err(Error::LogletSealed(reason.clone()));
+        }
+        let state = Arc::clone(&self.state);
+        let dir = self.dir.clone();
+        let segment_size = self.segment_size;
+        let num_records = payloads.len();
+
+        // The blocking task runs to completion even if this future is dropped, the index and
+        // the tail are updated there to never lose track of a durable record.
+        let offset = tokio::task::spawn_blocking(move || -> io::Result<LogletOffset> {
+            let first_offset = state.tail.borrow().next();
+            if writer.is_full(&payloads, segment_size) {
+                let (index, active) = segment::create_segment(&dir, first_offset)?;
+                state.segments.write().unwrap().insert(first_offset, index);
+                *writer = active;
+            }
+
+            let positions = writer.append(&payloads)?;
+            {
+                let mut segments = state.segments.write().unwrap();
+                let index = segments
+                    .get_mut(&writer.base_offset())
+                    .expect("active segment is indexed");
+                for position in positions {
+                    index.push(position);
+                }
+            }
+            let offset = LogletOffset(first_offset.0 + num_records as u64 - 1);
+            state.tail.send_replace(offset);
+            Ok(offset)
+        })
+        .await
+        .map_err(|_| Error::Shutdown)??;
+
+        debug!(
+            "Appended {} records to file loglet {:?} up to offset {}",
+            num_records, self.params, offset
+        );
+        Ok(offset)
+    }
+
+    /// Reads up to `max_records` consecutive records after `after`. Returns no records if
+    /// nothing is committed after `after` yet. Trim gaps and seals are returned on their own.
+    async fn read_after(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<LogletOffset>>, Error> {
+        let trim_point = self.trim_point();
+        if after < trim_point {
+            return Ok(vec![LogRecord::new_trim_gap(after.next(), trim_point)]);
+        }
+
+        // The seal is observed before the tail, a sealed loglet's tail doesn't move anymore.
+        let sealed = self.state.sealed.get();
+        let from_offset = after.next();
+        let tail = self.last_committed_offset();
+        if from_offset > tail {
+            return Ok(sealed
+                .map(|reason| LogRecord::new_seal(tail.next(), reason.clone()))
+                .into_iter()
+                .collect());
+        }
+
+        let to_offset = tail.min(LogletOffset(from_offset.0 + max_records.max(1) as u64 - 1));
+        let locations: Vec<_> = {
+            let segments = self.state.segments.read().unwrap();
+            (from_offset.0..=to_offset.0)
+                .map(|offset| {
+                    let offset = LogletOffset(offset);
+                    segments
+                        .range(..=offset)
+                        .next_back()
+                        .and_then(|(_, segment)| segment.locate(offset))
+                        .map(|(file, position)| (offset, file, position))
+                        .expect("committed records are indexed")
+                })
+                .collect()
+        };
+
+        tokio::task::spawn_blocking(move || {
+            locations
+                .into_iter()
+                .map(|(offset, file, position)| {
+                    let payload = segment::read_record(&file, position)?;
+                    Ok(LogRecord::new_data(offset, payload))
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .await
+        .map_err(|_| Error::Shutdown)?
+    }
+}
+
+/// Resolves the directory of a loglet. The params are used as a directory name, so they
+/// must not be able to escape the root path of the provider.
+fn loglet_dir(root: &Path, params: &LogletParams) -> Result<PathBuf, Error> {
+    let mut components = Path::new(params.as_str()).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(root.join(params.as_str())),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("loglet params {:?} are not a valid directory name", params),
+        )
+        .into()),
+    }
+}
+
+/// Rebuilds the segment index of the loglet in `dir` and opens the last segment for appends.
+/// Returns the index, the active segment and the trim point of the loglet.
+fn recover(
+    dir: &Path,
+) -> io::Result<(
+    BTreeMap<LogletOffset, SegmentIndex>,
+    ActiveSegment,
+    LogletOffset,
+)> {
+    std::fs::create_dir_all(dir)?;
+
+    let listed = segment::list_segments(dir)?;
+    let mut segments = BTreeMap::new();
+    let mut expected_base_offset = None;
+    for (i, (base_offset, path)) in listed.iter().enumerate() {
+        if expected_base_offset.is_some_and(|expected| expected != *base_offset) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "segment {} does not continue the previous segment at offset {}",
+                    path.display(),
+                    expected_base_offset.unwrap()
+                ),
+            ));
+        }
+
+        let is_last = i == listed.len() - 1;
+        match segment::recover_segment(path, *base_offset, is_last)? {
+            RecoveredSegment::Valid(index) => {
+                expected_base_offset = Some(index.next_offset());
+                segments.insert(*base_offset, index);
+            }
+            RecoveredSegment::Incomplete => {
+                warn!("Removing incomplete segment {}", path.display());
+                std::fs::remove_file(path)?;
+                segment::sync_dir(dir)?;
+            }
+        }
+    }
+
+    let active = match segments.values().next_back() {
+        Some(index) => segment::open_active_segment(index)?,
+        None => {
+            let (index, active) = segment::create_segment(dir, LogletOffset::OLDEST)?;
+            segments.insert(index.base_offset(), index);
+            active
+        }
+    };
+
+    // Segments are removed after the trim point is persisted, the records before the first
+    // segment are always trimmed.
+    let first_offset = *segments
+        .keys()
+        .next()
+        .expect("loglet has an active segment");
+    let trim_point = segment::read_trim_point(dir)?.max(LogletOffset(first_offset.0 - 1));
+    Ok((segments, active, trim_point))
+}
+
+/// Persists the trim point and removes the segments that only hold trimmed records.
+fn trim(state: &LogletState, dir: &Path, trim_point: LogletOffset) -> io::Result<()> {
+    segment::write_trim_point(dir, trim_point)?;
+    state.trim_point.store(trim_point.0, Ordering::Release);
+
+    let trimmed_segments: Vec<SegmentIndex> = {
+        let mut segments = state.segments.write().unwrap();
+        let active_base_offset = *segments.keys().next_back().expect("active segment");
+        let trimmed: Vec<LogletOffset> = segments
+            .values()
+            .take_while(|segment| {
+                segment.base_offset() != active_base_offset
+                    && segment.next_offset() <= trim_point.next()
+            })
+            .map(|segment| segment.base_offset())
+            .collect();
+        trimmed
+            .iter()
+            .filter_map(|base_offset| segments.remove(base_offset))
+            .collect()
+    };
+
+    if !trimmed_segments.is_empty() {
+        // Readers might still hold the file of a removed segment open, but they'll never
+        // read trimmed records from it.
+        for segment in &trimmed_segments {
+            std::fs::remove_file(segment.path())?;
+        }
+        segment::sync_dir(dir)?;
+        debug!(
+            "Removed {} trimmed segments of {}",
+            trimmed_segments.len(),
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl LogletBase for FileLoglet {
+    type Offset = LogletOffset;
+
+    async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
+        self.append_records(vec![payload]).await
+    }
+
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<LogletOffset, Error> {
+        self.append_records(payloads.to_vec()).await
+    }
+
+    async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
+        let tail = self.last_committed_offset();
+        // The trim point never moves beyond the tail, this also covers empty loglets.
+        if tail <= self.trim_point() {
+            Ok(None)
+        } else {
+            Ok(Some(tail))
+        }
+    }
+
+    async fn get_trim_point(&self) -> Result<LogletOffset, Error> {
+        Ok(self.trim_point())
+    }
+
+    async fn trim(&self, trim_point: LogletOffset) -> Result<(), Error> {
+        // Serializes trims with appends, so that the active segment can't change underneath.
+        let writer = Arc::clone(&self.writer).lock_owned().await;
+        let trim_point = trim_point.min(self.last_committed_offset());
+        if trim_point <= self.trim_point() {
+            return Ok(());
+        }
+
+        let state = Arc::clone(&self.state);
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let _writer = writer;
+            trim(&state, &dir, trim_point)
+        })
+        .await
+        .map_err(|_| Error::Shutdown)??;
+
+        debug!(
+            "Trimmed file loglet {:?} to offset {}",
+            self.params, trim_point
+        );
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<(), Error> {
+        // Serializes the seal with appends, no append can complete after the seal.
+        let writer = Arc::clone(&self.writer).lock_owned().await;
+        if self.state.sealed.get().is_some() {
+            return Ok(());
+        }
+
+        let state = Arc::clone(&self.state);
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || -> io::Result<()> {
+            let _writer = writer;
+            segment::write_seal(&dir, &reason)?;
+            let _ = state.sealed.set(reason);
+            // wake up readers that are waiting for the next record
+            state.tail.send_modify(|_| {});
+            Ok(())
+        })
+        .await
+        .map_err(|_| Error::Shutdown)??;
+
+        debug!("Sealed file loglet {:?}", self.params);
+        Ok(())
+    }
+
+    async fn read_next_single(
+        &self,
+        after: LogletOffset,
+    ) -> Result<LogRecord<Self::Offset>, Error> {
+        let mut records = self.read_next_n(after, 1).await?;
+        Ok(records.pop().expect("at least one record"))
+    }
+
+    async fn read_next_single_opt(
+        &self,
+        after: Self::Offset,
+    ) -> Result<Option<LogRecord<Self::Offset>>, Error> {
+        Ok(self.read_after(after, 1).await?.pop())
+    }
+
+    async fn read_next_n(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<Self::Offset>>, Error> {
+        loop {
+            let records = self.read_after(after, max_records).await?;
+            if !records.is_empty() {
+                break Ok(records);
+            }
+            // Wait until the tail moves beyond `after` or the loglet is sealed. The sender lives
+            // as long as the loglet, so this can't fail.
+            let mut tail = self.state.tail.subscribe();
+            let _ = tail
+                .wait_for(|tail| *tail > after || self.state.sealed.get().is_some())
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::time::Duration;
+
+    use googletest::prelude::*;
+    use restate_test_util::let_assert;
+    use tokio::task::JoinHandle;
+
+    use crate::{Record, TrimGap};
+
+    fn test_options(path: &Path) -> FileLogletOptions {
+        FileLogletOptions {
+            path: path.to_path_buf(),
+            ..FileLogletOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("1".to_string());
+        let loglet = Arc::new(FileLoglet::open(params, test_options(tmp.path())).await?);
+
+        assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        assert!(loglet
+            .read_next_single_opt(LogletOffset::INVALID)
+            .await?
+            .is_none());
+
+        for i in 1..=3 {
+            let offset = loglet.append(Payload::from(format!("record{}", i))).await?;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(Some(LogletOffset(i)), loglet.find_tail().await?);
+        }
+        assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        assert_eq!(Payload::from("record1"), record.into_payload_unchecked());
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert_eq!(Payload::from("record3"), record.into_payload_unchecked());
+
+        assert!(loglet
+            .read_next_single_opt(LogletOffset(3))
+            .await?
+            .is_none());
+
+        // a reader waiting for a future record
+        let handle: JoinHandle<Result<()>> = tokio::spawn({
+            let loglet = Arc::clone(&loglet);
+            async move {
+                let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(3)).await?;
+                assert_eq!(LogletOffset(4), offset);
+                assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+                Ok(())
+            }
+        });
+
+        tokio::task::yield_now().await;
+        assert!(!handle.is_finished());
+
+        let offset = loglet.append(Payload::from("record4")).await?;
+        assert_eq!(LogletOffset(4), offset);
+        tokio::time::timeout(Duration::from_secs(5), handle).await???;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet_recovery() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("2".to_string());
+        let opts = test_options(tmp.path());
+
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        for i in 1..=3 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+        drop(loglet);
+
+        // Simulate a crash in the middle of an append by leaving a torn frame behind.
+        let segment_path = segment::segment_path(&tmp.path().join("2"), LogletOffset::OLDEST);
+        let clean_len = std::fs::metadata(&segment_path)?.len();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&segment_path)?;
+        file.write_all(&[100, 0, 0, 0, 1, 2, 3, 4, b'r', b'e'])?;
+        drop(file);
+
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        assert_eq!(clean_len, std::fs::metadata(&segment_path)?.len());
+        assert_eq!(Some(LogletOffset(3)), loglet.find_tail().await?);
+
+        let mut after = LogletOffset::INVALID;
+        for i in 1..=3 {
+            let LogRecord { offset, record } = loglet.read_next_single(after).await?;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(
+                Payload::from(format!("record{}", i)),
+                record.into_payload_unchecked()
+            );
+            after = offset;
+        }
+
+        // appends continue after the recovered tail
+        assert_eq!(
+            LogletOffset(4),
+            loglet.append(Payload::from("record4")).await?
+        );
+        drop(loglet);
+
+        let loglet = FileLoglet::open(params, opts).await?;
+        assert_eq!(Some(LogletOffset(4)), loglet.find_tail().await?);
+        let LogRecord { record, .. } = loglet.read_next_single(LogletOffset(3)).await?;
+        assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet_segments() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("3".to_string());
+        let opts = FileLogletOptions {
+            // fits a single record per segment
+            segment_size: 40,
+            ..test_options(tmp.path())
+        };
+
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        for i in 1..=5 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+        drop(loglet);
+
+        assert_eq!(5, segment::list_segments(&tmp.path().join("3"))?.len());
+
+        let loglet = FileLoglet::open(params, opts).await?;
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+        assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+        let mut after = LogletOffset::INVALID;
+        for i in 1..=5 {
+            let LogRecord { offset, record } = loglet.read_next_single(after).await?;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(
+                Payload::from(format!("record{}", i)),
+                record.into_payload_unchecked()
+            );
+            after = offset;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet_batches() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("5".to_string());
+        let opts = FileLogletOptions {
+            // fits a single record per segment, batches are never split
+            segment_size: 40,
+            ..test_options(tmp.path())
+        };
+        let payloads = |range: std::ops::RangeInclusive<u64>| -> Vec<Payload> {
+            range
+                .map(|i| Payload::from(format!("record{}", i)))
+                .collect()
+        };
+
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        assert_eq!(
+            LogletOffset(3),
+            loglet.append_batch(&payloads(1..=3)).await?
+        );
+        assert_eq!(
+            LogletOffset(5),
+            loglet.append_batch(&payloads(4..=5)).await?
+        );
+        assert_eq!(2, segment::list_segments(&tmp.path().join("5"))?.len());
+        drop(loglet);
+
+        let loglet = FileLoglet::open(params, opts).await?;
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+        // batches span segments and stop at the tail
+        let records = loglet.read_next_n(LogletOffset::INVALID, 10).await?;
+        assert_eq!(5, records.len());
+        for (i, LogRecord { offset, record }) in records.into_iter().enumerate() {
+            let i = i as u64 + 1;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(
+                Payload::from(format!("record{}", i)),
+                record.into_payload_unchecked()
+            );
+        }
+
+        let records = loglet.read_next_n(LogletOffset(1), 2).await?;
+        let offsets: Vec<_> = records.iter().map(|record| record.offset).collect();
+        assert_eq!(vec![LogletOffset(2), LogletOffset(3)], offsets);
+
+        // trim gaps are returned on their own
+        loglet.trim(LogletOffset(2)).await?;
+        let records = loglet.read_next_n(LogletOffset::INVALID, 10).await?;
+        assert_eq!(1, records.len());
+        let_assert!(Record::TrimGap(TrimGap { until }) = &records[0].record);
+        assert_eq!(LogletOffset(2), *until);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet_trim() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("4".to_string());
+        let opts = FileLogletOptions {
+            // fits a single record per segment
+            segment_size: 40,
+            ..test_options(tmp.path())
+        };
+        let dir = tmp.path().join("4");
+
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        for i in 1..=5 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+
+        loglet.trim(LogletOffset(3)).await?;
+        assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+        assert_eq!(2, segment::list_segments(&dir)?.len());
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        let_assert!(Record::TrimGap(TrimGap { until }) = record);
+        assert_eq!(LogletOffset(3), until);
+        let LogRecord { offset, record } = loglet.read_next_single(until).await?;
+        assert_eq!(LogletOffset(4), offset);
+        assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+        drop(loglet);
+
+        // the trim point survives a restart
+        let loglet = FileLoglet::open(params.clone(), opts.clone()).await?;
+        assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+        // trim points beyond the tail are clamped, the active segment is kept
+        loglet.trim(LogletOffset(10)).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        assert_eq!(1, segment::list_segments(&dir)?.len());
+        drop(loglet);
+
+        let loglet = FileLoglet::open(params, opts).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        assert_eq!(
+            LogletOffset(6),
+            loglet.append(Payload::from("record6")).await?
+        );
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert!(record.is_trim_gap());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_loglet_seal() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let params = LogletParams::from("5".to_string());
+        let opts = test_options(tmp.path());
+
+        let loglet = Arc::new(FileLoglet::open(params.clone(), opts.clone()).await?);
+        loglet.append(Payload::from("record1")).await?;
+        loglet.append(Payload::from("record2")).await?;
+
+        // a reader waiting for the next record is woken up by the seal
+        let waiting_reader: JoinHandle<Result<LogRecord<LogletOffset>>> = tokio::spawn({
+            let loglet = Arc::clone(&loglet);
+            async move { Ok(loglet.read_next_single(LogletOffset(2)).await?) }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiting_reader.is_finished());
+
+        loglet.seal(SealReason::Resharding).await?;
+        let record = waiting_reader.await.unwrap()?;
+        assert_eq!(LogletOffset(3), record.offset);
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+        assert!(matches!(
+            loglet.append(Payload::from("record3")).await,
+            Err(Error::LogletSealed(SealReason::Resharding))
+        ));
+        drop(loglet);
+
+        // the seal survives a restart and sealing again keeps the original reason
+        let loglet = FileLoglet::open(params, opts).await?;
+        loglet.seal(SealReason::Reconfiguration).await?;
+        assert_eq!(Some(LogletOffset(2)), loglet.find_tail().await?);
+        let_assert!(Some(record) = loglet.read_next_single_opt(LogletOffset(2)).await?);
+        assert_eq!(LogletOffset(3), record.offset);
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+        assert!(loglet.append(Payload::from("record3")).await.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_loglet_dir_rejects_paths() {
+        let root = Path::new("/logs");
+        assert_eq!(
+            PathBuf::from("/logs/7"),
+            loglet_dir(root, &LogletParams::from("7".to_string())).unwrap()
+        );
+        assert!(loglet_dir(root, &LogletParams::from("..".to_string())).is_err());
+        assert!(loglet_dir(root, &LogletParams::from("a/b".to_string())).is_err());
+        assert!(loglet_dir(root, &LogletParams::from("".to_string())).is_err());
+    }
+}
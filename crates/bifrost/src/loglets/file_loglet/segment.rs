@@ -0,0 +1,398 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! On-disk format of the file loglet segments.
+//!
+//! A segment file starts with a fixed-size header followed by a sequence of record frames:
+//!
+//! ```text
+//! header: | magic (4) | format version (u32 LE) | base offset (u64 LE) |
+//! frame:  | payload length (u32 LE) | crc32 of payload (u32 LE) | payload |
+//! ```
+//!
+//! The offset of a record is implied by its position in the segment, the first frame of a
+//! segment holds the record at the segment's base offset.
+//!
+//! The trim point of a loglet is stored next to its segments in a `trim_point` file that holds
+//! the offset as u64 LE. Once the loglet is sealed, a `sealed` file holds the JSON encoded
+//! reason of the seal.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use tracing::warn;
+
+use restate_types::logs::{Payload, SequenceNumber};
+
+use crate::loglet::LogletOffset;
+use crate::SealReason;
+
+const SEGMENT_MAGIC: &[u8; 4] = b"RSBF";
+const SEGMENT_FORMAT_VERSION: u32 = 1;
+const SEGMENT_FILE_EXTENSION: &str = "segment";
+
+const TRIM_POINT_FILE_NAME: &str = "trim_point";
+const SEAL_FILE_NAME: &str = "sealed";
+
+const SEGMENT_HEADER_LEN: u64 = 16;
+const FRAME_HEADER_LEN: u64 = 8;
+
+pub(super) fn segment_path(dir: &Path, base_offset: LogletOffset) -> PathBuf {
+    dir.join(format!("{:020}.{}", base_offset.0, SEGMENT_FILE_EXTENSION))
+}
+
+fn parse_segment_base_offset(path: &Path) -> Option<LogletOffset> {
+    if path.extension()? != SEGMENT_FILE_EXTENSION {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()?
+        .parse::<u64>()
+        .ok()
+        .map(LogletOffset::from)
+}
+
+/// Lists the segment files in `dir` ordered by their base offset.
+pub(super) fn list_segments(dir: &Path) -> io::Result<Vec<(LogletOffset, PathBuf)>> {
+    let mut segments = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(base_offset) = parse_segment_base_offset(&path) {
+            segments.push((base_offset, path));
+        }
+    }
+    segments.sort_by_key(|(base_offset, _)| *base_offset);
+    Ok(segments)
+}
+
+/// Makes creation and removal of files in `dir` durable.
+pub(super) fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Reads the trim point of the loglet in `dir`, `INVALID` if the loglet was never trimmed.
+pub(super) fn read_trim_point(dir: &Path) -> io::Result<LogletOffset> {
+    let path = dir.join(TRIM_POINT_FILE_NAME);
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(LogletOffset::INVALID),
+        Err(err) => return Err(err),
+    };
+    let trim_point: [u8; 8] = content
+        .as_slice()
+        .try_into()
+        .map_err(|_| invalid_data(format!("trim point file {} is corrupted", path.display())))?;
+    Ok(LogletOffset(u64::from_le_bytes(trim_point)))
+}
+
+/// Durably replaces the trim point of the loglet in `dir`.
+pub(super) fn write_trim_point(dir: &Path, trim_point: LogletOffset) -> io::Result<()> {
+    let tmp_path = dir.join(format!("{}.tmp", TRIM_POINT_FILE_NAME));
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&trim_point.0.to_le_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, dir.join(TRIM_POINT_FILE_NAME))?;
+    sync_dir(dir)
+}
+
+/// Reads the seal of the loglet in `dir`, `None` if the loglet is not sealed.
+pub(super) fn read_seal(dir: &Path) -> io::Result<Option<SealReason>> {
+    let path = dir.join(SEAL_FILE_NAME);
+    let content = match std::fs::read(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|_| invalid_data(format!("seal file {} is corrupted", path.display())))
+}
+
+/// Durably seals the loglet in `dir`.
+pub(super) fn write_seal(dir: &Path, reason: &SealReason) -> io::Result<()> {
+    let tmp_path = dir.join(format!("{}.tmp", SEAL_FILE_NAME));
+    let mut file = File::create(&tmp_path)?;
+    serde_json::to_writer(&mut file, reason)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, dir.join(SEAL_FILE_NAME))?;
+    sync_dir(dir)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Read-side index of a single segment file. It maps every record in the segment to the
+/// position of its frame in the file.
+pub(super) struct SegmentIndex {
+    base_offset: LogletOffset,
+    path: PathBuf,
+    file: Arc<File>,
+    positions: Vec<u64>,
+}
+
+impl SegmentIndex {
+    pub fn base_offset(&self) -> LogletOffset {
+        self.base_offset
+    }
+
+    /// The offset that the next record appended to this segment will get.
+    pub fn next_offset(&self) -> LogletOffset {
+        LogletOffset(self.base_offset.0 + self.positions.len() as u64)
+    }
+
+    /// Returns a handle to the segment file and the frame position of the record at `offset`
+    pub fn locate(&self, offset: LogletOffset) -> Option<(Arc<File>, u64)> {
+        let index = offset.0.checked_sub(self.base_offset.0)?;
+        self.positions
+            .get(index as usize)
+            .map(|position| (Arc::clone(&self.file), *position))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(super) fn push(&mut self, position: u64) {
+        self.positions.push(position);
+    }
+}
+
+/// Write-side of the segment that receives appends.
+pub(super) struct ActiveSegment {
+    base_offset: LogletOffset,
+    file: File,
+    size: u64,
+}
+
+impl ActiveSegment {
+    pub fn base_offset(&self) -> LogletOffset {
+        self.base_offset
+    }
+
+    /// Whether appending the records of `payloads` would grow the segment beyond
+    /// `segment_size`. A segment always accepts at least one batch of records.
+    pub fn is_full(&self, payloads: &[Payload], segment_size: u64) -> bool {
+        let batch_size: u64 = payloads
+            .iter()
+            .map(|payload| FRAME_HEADER_LEN + payload.len() as u64)
+            .sum();
+        self.size > SEGMENT_HEADER_LEN && self.size + batch_size > segment_size
+    }
+
+    /// Writes the frames of all records and syncs them to disk at once. Either all or none of
+    /// the records are appended. Returns the positions of the frames.
+    pub fn append(&mut self, payloads: &[Payload]) -> io::Result<Vec<u64>> {
+        let mut frames = Vec::new();
+        let mut positions = Vec::with_capacity(payloads.len());
+        for payload in payloads {
+            let len = u32::try_from(payload.len()).map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("record of {} bytes is too large", payload.len()),
+                )
+            })?;
+            positions.push(self.size + frames.len() as u64);
+            frames.extend_from_slice(&len.to_le_bytes());
+            frames.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+            frames.extend_from_slice(payload);
+        }
+
+        let position = self.size;
+        if let Err(err) = self
+            .file
+            .write_all(&frames)
+            .and_then(|_| self.file.sync_data())
+        {
+            // Drop whatever might have been partially written so that the next append starts
+            // from a clean frame boundary.
+            let _ = self
+                .file
+                .set_len(position)
+                .and_then(|_| self.file.seek(SeekFrom::Start(position)));
+            return Err(err);
+        }
+        self.size += frames.len() as u64;
+        Ok(positions)
+    }
+}
+
+/// Creates a new, empty segment file that starts at `base_offset`. A left-over file of a failed
+/// segment creation is overwritten, it can never contain any committed records.
+pub(super) fn create_segment(
+    dir: &Path,
+    base_offset: LogletOffset,
+) -> io::Result<(SegmentIndex, ActiveSegment)> {
+    let path = segment_path(dir, base_offset);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(&path)?;
+
+    let mut header = Vec::with_capacity(SEGMENT_HEADER_LEN as usize);
+    header.extend_from_slice(SEGMENT_MAGIC);
+    header.extend_from_slice(&SEGMENT_FORMAT_VERSION.to_le_bytes());
+    header.extend_from_slice(&base_offset.0.to_le_bytes());
+    file.write_all(&header)?;
+    file.sync_all()?;
+    sync_dir(dir)?;
+
+    let index = SegmentIndex {
+        base_offset,
+        file: Arc::new(File::open(&path)?),
+        path,
+        positions: Vec::new(),
+    };
+    let active = ActiveSegment {
+        base_offset,
+        file,
+        size: SEGMENT_HEADER_LEN,
+    };
+    Ok((index, active))
+}
+
+/// Result of scanning a segment file on startup.
+pub(super) enum RecoveredSegment {
+    Valid(SegmentIndex),
+    /// The segment file was created but its header never made it to disk. This can only happen
+    /// to the last segment of a loglet and such a segment never contained any records.
+    Incomplete,
+}
+
+/// Scans the segment at `path` and builds its index.
+///
+/// A partially written frame at the end of the last segment is the result of a crash during
+/// append. Such appends were never acknowledged, so the torn tail is truncated. The same
+/// condition in any other segment indicates data corruption and fails the recovery.
+pub(super) fn recover_segment(
+    path: &Path,
+    base_offset: LogletOffset,
+    is_last: bool,
+) -> io::Result<RecoveredSegment> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(&file);
+
+    let mut header = [0u8; SEGMENT_HEADER_LEN as usize];
+    if file_len < SEGMENT_HEADER_LEN || reader.read_exact(&mut header).is_err() {
+        return if is_last {
+            Ok(RecoveredSegment::Incomplete)
+        } else {
+            Err(invalid_data(format!(
+                "segment {} has an incomplete header",
+                path.display()
+            )))
+        };
+    }
+    if &header[0..4] != SEGMENT_MAGIC {
+        return Err(invalid_data(format!(
+            "segment {} is not a loglet segment file",
+            path.display()
+        )));
+    }
+    let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    if version != SEGMENT_FORMAT_VERSION {
+        return Err(invalid_data(format!(
+            "segment {} has unsupported format version {}",
+            path.display(),
+            version
+        )));
+    }
+    let header_base_offset = u64::from_le_bytes(header[8..16].try_into().unwrap());
+    if header_base_offset != base_offset.0 {
+        return Err(invalid_data(format!(
+            "segment {} has base offset {} in its header",
+            path.display(),
+            header_base_offset
+        )));
+    }
+
+    let mut positions = Vec::new();
+    let mut position = SEGMENT_HEADER_LEN;
+    let mut payload = Vec::new();
+    while position < file_len {
+        let mut frame_header = [0u8; FRAME_HEADER_LEN as usize];
+        if file_len - position < FRAME_HEADER_LEN {
+            break;
+        }
+        reader.read_exact(&mut frame_header)?;
+        let len = u32::from_le_bytes(frame_header[0..4].try_into().unwrap()) as u64;
+        let checksum = u32::from_le_bytes(frame_header[4..8].try_into().unwrap());
+        if file_len - position - FRAME_HEADER_LEN < len {
+            break;
+        }
+        payload.resize(len as usize, 0);
+        reader.read_exact(&mut payload)?;
+        if crc32fast::hash(&payload) != checksum {
+            break;
+        }
+        positions.push(position);
+        position += FRAME_HEADER_LEN + len;
+    }
+    drop(reader);
+
+    if position < file_len {
+        if !is_last {
+            return Err(invalid_data(format!(
+                "segment {} is corrupted at position {}",
+                path.display(),
+                position
+            )));
+        }
+        warn!(
+            "Truncating {} bytes of incomplete records at the end of segment {}",
+            file_len - position,
+            path.display()
+        );
+        file.set_len(position)?;
+        file.sync_all()?;
+    }
+
+    Ok(RecoveredSegment::Valid(SegmentIndex {
+        base_offset,
+        path: path.to_path_buf(),
+        file: Arc::new(file),
+        positions,
+    }))
+}
+
+/// Re-opens the last segment of a loglet for appends.
+pub(super) fn open_active_segment(index: &SegmentIndex) -> io::Result<ActiveSegment> {
+    let mut file = OpenOptions::new().write(true).open(&index.path)?;
+    let size = file.seek(SeekFrom::End(0))?;
+    Ok(ActiveSegment {
+        base_offset: index.base_offset,
+        file,
+        size,
+    })
+}
+
+/// Reads and validates the record frame at `position`.
+pub(super) fn read_record(file: &File, position: u64) -> io::Result<Payload> {
+    let mut frame_header = [0u8; FRAME_HEADER_LEN as usize];
+    file.read_exact_at(&mut frame_header, position)?;
+    let len = u32::from_le_bytes(frame_header[0..4].try_into().unwrap());
+    let checksum = u32::from_le_bytes(frame_header[4..8].try_into().unwrap());
+
+    let mut payload = vec![0u8; len as usize];
+    file.read_exact_at(&mut payload, position + FRAME_HEADER_LEN)?;
+    if crc32fast::hash(&payload) != checksum {
+        return Err(invalid_data(format!(
+            "checksum mismatch of record at position {}",
+            position
+        )));
+    }
+    Ok(Payload::from(Bytes::from(payload)))
+}
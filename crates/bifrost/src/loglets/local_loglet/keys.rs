@@ -0,0 +1,125 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Key layout of the local loglet store.
+//!
+//! All loglets share the same column family. Records are keyed by the id of their loglet
+//! followed by their offset, both big-endian encoded so that the records of a loglet are
+//! stored next to each other and in offset order:
+//!
+//! ```text
+//! | b'd' | loglet id (u64 BE) | offset (u64 BE) |
+//! ```
+//!
+//! Per-loglet metadata, like the trim point or the seal, lives in a separate column family:
+//!
+//! ```text
+//! | b'm' | loglet id (u64 BE) | metadata kind (u8) |
+//! ```
+
+use crate::loglet::LogletOffset;
+
+const DATA_KEY_PREFIX: u8 = b'd';
+const METADATA_KEY_PREFIX: u8 = b'm';
+
+pub(super) const RECORD_KEY_LEN: usize = 17;
+pub(super) const LOGLET_PREFIX_LEN: usize = 9;
+pub(super) const METADATA_KEY_LEN: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RecordKey {
+    pub loglet_id: u64,
+    pub offset: LogletOffset,
+}
+
+impl RecordKey {
+    pub fn new(loglet_id: u64, offset: LogletOffset) -> Self {
+        Self { loglet_id, offset }
+    }
+
+    /// The prefix that is shared by all records of a loglet.
+    pub fn loglet_prefix(loglet_id: u64) -> [u8; LOGLET_PREFIX_LEN] {
+        let mut prefix = [0u8; LOGLET_PREFIX_LEN];
+        prefix[0] = DATA_KEY_PREFIX;
+        prefix[1..].copy_from_slice(&loglet_id.to_be_bytes());
+        prefix
+    }
+
+    pub fn to_bytes(self) -> [u8; RECORD_KEY_LEN] {
+        let mut key = [0u8; RECORD_KEY_LEN];
+        key[..LOGLET_PREFIX_LEN].copy_from_slice(&Self::loglet_prefix(self.loglet_id));
+        key[LOGLET_PREFIX_LEN..].copy_from_slice(&self.offset.0.to_be_bytes());
+        key
+    }
+
+    pub fn from_slice(key: &[u8]) -> Option<Self> {
+        if key.len() != RECORD_KEY_LEN || key[0] != DATA_KEY_PREFIX {
+            return None;
+        }
+        let loglet_id = u64::from_be_bytes(key[1..LOGLET_PREFIX_LEN].try_into().unwrap());
+        let offset = u64::from_be_bytes(key[LOGLET_PREFIX_LEN..].try_into().unwrap());
+        Some(Self::new(loglet_id, LogletOffset(offset)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub(super) enum MetadataKind {
+    TrimPoint = b't',
+    Seal = b's',
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct MetadataKey {
+    pub loglet_id: u64,
+    pub kind: MetadataKind,
+}
+
+impl MetadataKey {
+    pub fn new(loglet_id: u64, kind: MetadataKind) -> Self {
+        Self { loglet_id, kind }
+    }
+
+    pub fn to_bytes(self) -> [u8; METADATA_KEY_LEN] {
+        let mut key = [0u8; METADATA_KEY_LEN];
+        key[0] = METADATA_KEY_PREFIX;
+        key[1..LOGLET_PREFIX_LEN].copy_from_slice(&self.loglet_id.to_be_bytes());
+        key[LOGLET_PREFIX_LEN] = self.kind as u8;
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_key_roundtrip() {
+        let key = RecordKey::new(7, LogletOffset(42));
+        let bytes = key.to_bytes();
+        assert_eq!(&RecordKey::loglet_prefix(7), &bytes[..LOGLET_PREFIX_LEN]);
+        assert_eq!(Some(key), RecordKey::from_slice(&bytes));
+        assert_eq!(None, RecordKey::from_slice(&bytes[1..]));
+    }
+
+    #[test]
+    fn test_record_keys_are_ordered() {
+        // records of one loglet sort by offset and never interleave with the next loglet
+        let keys = [
+            RecordKey::new(1, LogletOffset(1)),
+            RecordKey::new(1, LogletOffset(2)),
+            RecordKey::new(1, LogletOffset(256)),
+            RecordKey::new(2, LogletOffset(1)),
+        ];
+        for pair in keys.windows(2) {
+            assert!(pair[0].to_bytes() < pair[1].to_bytes());
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::io;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, PrefixRange, ReadOptions, DB};
+
+use restate_types::logs::{Payload, SequenceNumber};
+
+use super::keys::{MetadataKey, MetadataKind, RecordKey};
+use super::LocalLogletOptions;
+use crate::loglet::LogletOffset;
+use crate::{Error, SealReason};
+
+pub(super) const DATA_CF: &str = "logstore_data";
+pub(super) const METADATA_CF: &str = "logstore_metadata";
+
+/// The RocksDB instance shared by all local loglets of a provider.
+#[derive(Clone)]
+pub(super) struct LogStore {
+    db: Arc<DB>,
+}
+
+impl LogStore {
+    pub fn open(opts: &LocalLogletOptions) -> Result<Self, rocksdb::Error> {
+        let mut db_options = rocksdb::Options::default();
+        db_options.create_if_missing(true);
+        db_options.create_missing_column_families(true);
+        //
+        // Records are only acknowledged once the WAL is synced, so we can keep the memtables
+        // around for longer before flushing.
+        //
+        db_options.set_max_total_wal_size(opts.max_total_wal_size);
+        db_options.set_keep_log_file_num(1);
+
+        let mut data_cf_options = rocksdb::Options::default();
+        if opts.write_buffer_size > 0 {
+            data_cf_options.set_write_buffer_size(opts.write_buffer_size);
+        }
+
+        let db = DB::open_cf_descriptors(
+            &db_options,
+            &opts.path,
+            vec![
+                ColumnFamilyDescriptor::new(DATA_CF, data_cf_options),
+                ColumnFamilyDescriptor::new(METADATA_CF, rocksdb::Options::default()),
+            ],
+        )?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    pub fn db(&self) -> &Arc<DB> {
+        &self.db
+    }
+
+    pub fn data_cf(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(DATA_CF)
+            .expect("data column family exists")
+    }
+
+    pub fn metadata_cf(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(METADATA_CF)
+            .expect("metadata column family exists")
+    }
+
+    /// Offset of the last trimmed record of the loglet, or `INVALID` if it was never trimmed.
+    pub fn get_trim_point(&self, loglet_id: u64) -> Result<LogletOffset, Error> {
+        let key = MetadataKey::new(loglet_id, MetadataKind::TrimPoint).to_bytes();
+        let Some(value) = self.db.get_pinned_cf(self.metadata_cf(), key)? else {
+            return Ok(LogletOffset::INVALID);
+        };
+        let value: [u8; 8] = value
+            .as_ref()
+            .try_into()
+            .map_err(|_| invalid_data(format!("trim point of loglet {loglet_id} is corrupted")))?;
+        Ok(LogletOffset(u64::from_be_bytes(value)))
+    }
+
+    /// Reason the loglet was sealed with, or `None` if it's not sealed.
+    pub fn get_seal(&self, loglet_id: u64) -> Result<Option<SealReason>, Error> {
+        let key = MetadataKey::new(loglet_id, MetadataKind::Seal).to_bytes();
+        self.db
+            .get_pinned_cf(self.metadata_cf(), key)?
+            .map(|value| {
+                serde_json::from_slice(&value).map_err(|_| {
+                    invalid_data(format!("seal reason of loglet {loglet_id} is corrupted"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Offset of the last record of the loglet, or `INVALID` if the loglet has no records.
+    pub fn find_last_offset(&self, loglet_id: u64) -> Result<LogletOffset, Error> {
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_iterate_range(PrefixRange(RecordKey::loglet_prefix(loglet_id)));
+        let mut iterator = self.db.raw_iterator_cf_opt(self.data_cf(), read_opts);
+        iterator.seek_to_last();
+        iterator.status()?;
+
+        Ok(iterator
+            .key()
+            .and_then(RecordKey::from_slice)
+            .map(|key| key.offset)
+            .unwrap_or(LogletOffset::INVALID))
+    }
+
+    /// Records of the loglet from `from` up to and including `to` that haven't been removed.
+    pub fn get_records(
+        &self,
+        loglet_id: u64,
+        from: LogletOffset,
+        to: LogletOffset,
+    ) -> Result<Vec<(LogletOffset, Payload)>, Error> {
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_iterate_range(
+            RecordKey::new(loglet_id, from).to_bytes()
+                ..RecordKey::new(loglet_id, to.next()).to_bytes(),
+        );
+        let mut iterator = self.db.raw_iterator_cf_opt(self.data_cf(), read_opts);
+        iterator.seek_to_first();
+
+        let mut records = Vec::new();
+        while let (Some(key), Some(value)) = (iterator.key(), iterator.value()) {
+            let key = RecordKey::from_slice(key).ok_or_else(|| {
+                invalid_data(format!("record key of loglet {loglet_id} is corrupted"))
+            })?;
+            records.push((key.offset, Payload::from(Bytes::copy_from_slice(value))));
+            iterator.next();
+        }
+        iterator.status()?;
+        Ok(records)
+    }
+}
+
+fn invalid_data(msg: String) -> Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg).into()
+}
@@ -0,0 +1,365 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use rocksdb::{WriteBatch, WriteOptions};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tracing::{debug, trace, warn};
+
+use restate_types::logs::{Payload, SequenceNumber};
+
+use super::keys::{MetadataKey, MetadataKind, RecordKey};
+use super::log_store::LogStore;
+use super::LogletState;
+use crate::loglet::LogletOffset;
+use crate::{Error, SealReason};
+
+enum WriteCommand {
+    /// Appends the records at consecutive offsets, acknowledged with the offset of the last.
+    Append {
+        loglet: Arc<LogletState>,
+        payloads: Vec<Payload>,
+        ack: oneshot::Sender<Result<LogletOffset, Error>>,
+    },
+    /// Persists the trim point of the loglet and removes the trimmed records. The trim point is
+    /// published to readers once it's durable.
+    Trim {
+        loglet: Arc<LogletState>,
+        trim_point: LogletOffset,
+        ack: oneshot::Sender<Result<(), Error>>,
+    },
+    /// Persists the seal of the loglet, appends that are enqueued after it are rejected.
+    Seal {
+        loglet: Arc<LogletState>,
+        reason: SealReason,
+        ack: oneshot::Sender<Result<(), Error>>,
+    },
+}
+
+impl WriteCommand {
+    fn fail(self, error: Error) {
+        match self {
+            WriteCommand::Append { ack, .. } => {
+                let _ = ack.send(Err(error));
+            }
+            WriteCommand::Trim { ack, .. } | WriteCommand::Seal { ack, .. } => {
+                let _ = ack.send(Err(error));
+            }
+        }
+    }
+}
+
+enum WriterCommand {
+    Write(WriteCommand),
+    /// Commits the writes that were enqueued before and stops the writer.
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// Single writer of the log store.
+///
+/// Appends of all loglets are funneled through this task. Commands that queue up while a
+/// commit is in progress are written together as one batch with a single WAL sync (group
+/// commit). The writer is the only one assigning offsets, an offset is only handed out once
+/// the record is durable, so a failed commit never leaves a gap in a loglet.
+pub(super) struct LogStoreWriter {
+    store: LogStore,
+    max_batch_size: usize,
+    rx: UnboundedReceiver<WriterCommand>,
+}
+
+impl LogStoreWriter {
+    /// Spawns the writer task. The task stops on shutdown or once all handles are dropped.
+    pub fn start(store: LogStore, max_batch_size: usize) -> LogStoreWriterHandle {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let writer = Self {
+            store,
+            max_batch_size: max_batch_size.max(1),
+            rx,
+        };
+        tokio::spawn(async move {
+            debug!("Local loglet writer started");
+            // The writer, and with it its reference to the store, is gone at this point.
+            if let Some(shutdown_ack) = writer.run().await {
+                let _ = shutdown_ack.send(());
+            }
+            debug!("Local loglet writer stopped");
+        });
+        LogStoreWriterHandle { tx }
+    }
+
+    async fn run(mut self) -> Option<oneshot::Sender<()>> {
+        let mut writes = Vec::with_capacity(self.max_batch_size);
+        let mut shutdown = None;
+
+        while shutdown.is_none() {
+            let Some(command) = self.rx.recv().await else {
+                break;
+            };
+            Self::enqueue(command, &mut writes, &mut shutdown);
+            //
+            // take whatever has queued up since the last commit
+            //
+            while shutdown.is_none() && writes.len() < self.max_batch_size {
+                match self.rx.try_recv() {
+                    Ok(command) => Self::enqueue(command, &mut writes, &mut shutdown),
+                    Err(_) => break,
+                }
+            }
+
+            if !writes.is_empty() && !self.commit(&mut writes).await {
+                break;
+            }
+        }
+        shutdown
+    }
+
+    fn enqueue(
+        command: WriterCommand,
+        writes: &mut Vec<WriteCommand>,
+        shutdown: &mut Option<oneshot::Sender<()>>,
+    ) {
+        match command {
+            WriterCommand::Write(write) => writes.push(write),
+            WriterCommand::Shutdown(ack) => *shutdown = Some(ack),
+        }
+    }
+
+    /// Writes and syncs `writes` in a single batch and acknowledges them. Returns `false` if
+    /// the outcome of the write is unknown and the writer must not continue.
+    async fn commit(&self, writes: &mut Vec<WriteCommand>) -> bool {
+        Self::reject_sealed(writes);
+        if writes.is_empty() {
+            return true;
+        }
+
+        let mut batch = WriteBatch::default();
+        let mut next_offsets: HashMap<u64, LogletOffset> = HashMap::new();
+        let mut offsets = Vec::with_capacity(writes.len());
+        let mut trim_points: HashMap<u64, LogletOffset> = HashMap::new();
+        for write in writes.iter() {
+            match write {
+                WriteCommand::Append {
+                    loglet, payloads, ..
+                } => {
+                    let offset = next_offsets
+                        .entry(loglet.loglet_id)
+                        .or_insert_with(|| *loglet.tail.borrow());
+                    for payload in payloads {
+                        *offset = offset.next();
+                        batch.put_cf(
+                            self.store.data_cf(),
+                            RecordKey::new(loglet.loglet_id, *offset).to_bytes(),
+                            &payload[..],
+                        );
+                    }
+                    offsets.push(*offset);
+                }
+                WriteCommand::Trim {
+                    loglet,
+                    trim_point: requested,
+                    ..
+                } => {
+                    // Trim points only move forward, also across the trims of this batch.
+                    let trim_point = trim_points
+                        .entry(loglet.loglet_id)
+                        .or_insert_with(|| loglet.trim_point());
+                    *trim_point = (*trim_point).max(*requested);
+                    let trim_point = *trim_point;
+                    batch.put_cf(
+                        self.store.metadata_cf(),
+                        MetadataKey::new(loglet.loglet_id, MetadataKind::TrimPoint).to_bytes(),
+                        trim_point.0.to_be_bytes(),
+                    );
+                    batch.delete_range_cf(
+                        self.store.data_cf(),
+                        RecordKey::new(loglet.loglet_id, LogletOffset::OLDEST).to_bytes(),
+                        RecordKey::new(loglet.loglet_id, trim_point.next()).to_bytes(),
+                    );
+                }
+                WriteCommand::Seal { loglet, reason, .. } => {
+                    batch.put_cf(
+                        self.store.metadata_cf(),
+                        MetadataKey::new(loglet.loglet_id, MetadataKind::Seal).to_bytes(),
+                        serde_json::to_vec(reason).expect("serializable seal reason"),
+                    );
+                }
+            }
+        }
+
+        let store = self.store.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut write_opts = WriteOptions::default();
+            write_opts.set_sync(true);
+            store.db().write_opt(batch, &write_opts)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {
+                trace!("Committed a batch of {} writes", writes.len());
+                let mut offsets = offsets.into_iter();
+                for write in writes.drain(..) {
+                    match write {
+                        WriteCommand::Append { loglet, ack, .. } => {
+                            let offset = offsets.next().expect("offset of append");
+                            loglet.tail.send_replace(offset);
+                            let _ = ack.send(Ok(offset));
+                        }
+                        WriteCommand::Trim { loglet, ack, .. } => {
+                            // the records are gone, readers observe the durable trim point
+                            if let Some(trim_point) = trim_points.get(&loglet.loglet_id) {
+                                loglet.trim_point.fetch_max(trim_point.0, Ordering::AcqRel);
+                            }
+                            let _ = ack.send(Ok(()));
+                        }
+                        WriteCommand::Seal {
+                            loglet,
+                            reason,
+                            ack,
+                        } => {
+                            let _ = loglet.sealed.set(reason);
+                            // wake up readers that are waiting for the next record
+                            loglet.tail.send_modify(|_| {});
+                            let _ = ack.send(Ok(()));
+                        }
+                    }
+                }
+                true
+            }
+            Ok(Err(err)) => {
+                warn!(
+                    "Failed to commit a batch of {} writes: {}",
+                    writes.len(),
+                    err
+                );
+                for write in writes.drain(..) {
+                    write.fail(Error::LogStore(err.clone()));
+                }
+                true
+            }
+            Err(_) => {
+                for write in writes.drain(..) {
+                    write.fail(Error::Shutdown);
+                }
+                false
+            }
+        }
+    }
+
+    /// Rejects appends to sealed loglets, including loglets that are sealed by an earlier
+    /// command of the same batch, and acknowledges seals of loglets that are already sealed.
+    fn reject_sealed(writes: &mut Vec<WriteCommand>) {
+        let mut seals: HashMap<u64, SealReason> = HashMap::new();
+        for write in std::mem::take(writes) {
+            match write {
+                WriteCommand::Append { ref loglet, .. } => {
+                    let sealed = loglet
+                        .sealed
+                        .get()
+                        .or_else(|| seals.get(&loglet.loglet_id))
+                        .cloned();
+                    match sealed {
+                        Some(reason) => write.fail(Error::LogletSealed(reason)),
+                        None => writes.push(write),
+                    }
+                }
+                WriteCommand::Seal {
+                    ref loglet,
+                    ref reason,
+                    ..
+                } => {
+                    let already_sealed = loglet.sealed.get().is_some();
+                    match seals.entry(loglet.loglet_id) {
+                        Entry::Vacant(entry) if !already_sealed => {
+                            entry.insert(reason.clone());
+                            writes.push(write);
+                        }
+                        // sealing again keeps the original reason
+                        _ => {
+                            if let WriteCommand::Seal { ack, .. } = write {
+                                let _ = ack.send(Ok(()));
+                            }
+                        }
+                    }
+                }
+                WriteCommand::Trim { .. } => writes.push(write),
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct LogStoreWriterHandle {
+    tx: UnboundedSender<WriterCommand>,
+}
+
+impl LogStoreWriterHandle {
+    /// Enqueues the records and waits until they're durably committed. Returns the offset of
+    /// the last record.
+    pub async fn append(
+        &self,
+        loglet: &Arc<LogletState>,
+        payloads: Vec<Payload>,
+    ) -> Result<LogletOffset, Error> {
+        let (ack, ack_rx) = oneshot::channel();
+        self.send(WriteCommand::Append {
+            loglet: Arc::clone(loglet),
+            payloads,
+            ack,
+        })?;
+        ack_rx.await.map_err(|_| Error::Shutdown)?
+    }
+
+    /// Persists the trim point of the loglet and removes its trimmed records. The trim point of
+    /// the loglet is moved once this is durable.
+    pub async fn trim(
+        &self,
+        loglet: &Arc<LogletState>,
+        trim_point: LogletOffset,
+    ) -> Result<(), Error> {
+        let (ack, ack_rx) = oneshot::channel();
+        self.send(WriteCommand::Trim {
+            loglet: Arc::clone(loglet),
+            trim_point,
+            ack,
+        })?;
+        ack_rx.await.map_err(|_| Error::Shutdown)?
+    }
+
+    /// Persists the seal of the loglet. Sealing an already sealed loglet is a no-op.
+    pub async fn seal(&self, loglet: &Arc<LogletState>, reason: SealReason) -> Result<(), Error> {
+        let (ack, ack_rx) = oneshot::channel();
+        self.send(WriteCommand::Seal {
+            loglet: Arc::clone(loglet),
+            reason,
+            ack,
+        })?;
+        ack_rx.await.map_err(|_| Error::Shutdown)?
+    }
+
+    /// Stops the writer once the writes enqueued so far are committed.
+    pub async fn shutdown(&self) {
+        let (ack, ack_rx) = oneshot::channel();
+        if self.tx.send(WriterCommand::Shutdown(ack)).is_ok() {
+            let _ = ack_rx.await;
+        }
+    }
+
+    fn send(&self, command: WriteCommand) -> Result<(), Error> {
+        self.tx
+            .send(WriterCommand::Write(command))
+            .map_err(|_| Error::Shutdown)
+    }
+}
@@ -0,0 +1,742 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod keys;
+mod log_store;
+mod log_store_writer;
+
+use std::collections::{hash_map, HashMap};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use async_trait::async_trait;
+use tokio::sync::{watch, Mutex as AsyncMutex, OnceCell};
+use tracing::{debug, info};
+
+use restate_types::logs::metadata::{LogletParams, ProviderKind};
+use restate_types::logs::{Payload, SequenceNumber};
+
+use self::log_store::LogStore;
+use self::log_store_writer::{LogStoreWriter, LogStoreWriterHandle};
+use crate::loglet::{Loglet, LogletBase, LogletOffset, LogletProvider};
+use crate::{Error, LogRecord, Options, SealReason};
+
+/// Configuration of the local loglet provider, read from the provider's entry in
+/// [`Options::providers_config`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LocalLogletOptions {
+    /// Path of the RocksDB instance that stores the records of all local loglets.
+    pub path: PathBuf,
+    /// Size of a single memtable, `0` uses the RocksDB default.
+    pub write_buffer_size: usize,
+    /// Size of the WAL after which RocksDB starts flushing memtables to disk.
+    pub max_total_wal_size: u64,
+    /// Maximum number of appends that are committed together with a single WAL sync.
+    pub writer_batch_commit_count: usize,
+}
+
+impl Default for LocalLogletOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("target/local-loglet/"),
+            write_buffer_size: 0,
+            max_total_wal_size: 2 * (1 << 30), // 2 GiB
+            writer_batch_commit_count: 500,
+        }
+    }
+}
+
+pub fn default_config() -> serde_json::Value {
+    serde_json::to_value(LocalLogletOptions::default()).expect("valid local loglet options")
+}
+
+/// Store and writer are created on first use, they are shared by all loglets of the provider.
+struct LogStoreHandles {
+    store: LogStore,
+    writer: LogStoreWriterHandle,
+}
+
+pub struct LocalLogletProvider {
+    opts: LocalLogletOptions,
+    log_store: OnceCell<LogStoreHandles>,
+    loglets: AsyncMutex<HashMap<u64, Arc<LocalLoglet>>>,
+}
+
+impl LocalLogletProvider {
+    pub fn new(options: &Options) -> Result<Arc<Self>, Error> {
+        let opts = match &options.providers_config[ProviderKind::Local] {
+            serde_json::Value::Null => LocalLogletOptions::default(),
+            config => serde_json::from_value(config.clone())
+                .map_err(|e| Error::InvalidProviderConfig(ProviderKind::Local, e))?,
+        };
+        Ok(Self::with_options(opts))
+    }
+
+    pub fn with_options(opts: LocalLogletOptions) -> Arc<Self> {
+        Arc::new(Self {
+            opts,
+            log_store: OnceCell::new(),
+            loglets: Default::default(),
+        })
+    }
+
+    async fn log_store(&self) -> Result<&LogStoreHandles, Error> {
+        self.log_store
+            .get_or_try_init(|| async {
+                let opts = self.opts.clone();
+                let store = tokio::task::spawn_blocking(move || LogStore::open(&opts))
+                    .await
+                    .map_err(|_| Error::Shutdown)??;
+                let writer =
+                    LogStoreWriter::start(store.clone(), self.opts.writer_batch_commit_count);
+                Ok(LogStoreHandles { store, writer })
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl LogletProvider for LocalLogletProvider {
+    async fn get_loglet(&self, params: &LogletParams) -> Result<Arc<dyn Loglet>, Error> {
+        let loglet_id = parse_loglet_id(params)?;
+        let handles = self.log_store().await?;
+        let mut guard = self.loglets.lock().await;
+
+        let loglet = match guard.entry(loglet_id) {
+            hash_map::Entry::Vacant(entry) => {
+                let loglet =
+                    LocalLoglet::open(loglet_id, handles.store.clone(), handles.writer.clone())?;
+                Arc::clone(entry.insert(Arc::new(loglet)))
+            }
+            hash_map::Entry::Occupied(entry) => Arc::clone(entry.get()),
+        };
+
+        Ok(loglet as Arc<dyn Loglet>)
+    }
+
+    async fn start(&self) -> Result<(), Error> {
+        info!(
+            "Starting local loglet provider at {}",
+            self.opts.path.display()
+        );
+        self.log_store().await?;
+        Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<(), Error> {
+        info!("Shutting down local loglet provider");
+        if let Some(handles) = self.log_store.get() {
+            handles.writer.shutdown().await;
+        }
+        Ok(())
+    }
+}
+
+/// The params of a local loglet hold the id under which its records are stored.
+fn parse_loglet_id(params: &LogletParams) -> Result<u64, Error> {
+    params.as_str().parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("loglet params {:?} are not a valid loglet id", params),
+        )
+        .into()
+    })
+}
+
+/// State of a loglet that is shared with the log store writer.
+struct LogletState {
+    loglet_id: u64,
+    /// Offset of the last durably committed record, or `INVALID` if the loglet is empty. Only
+    /// the log store writer moves the tail.
+    tail: watch::Sender<LogletOffset>,
+    /// Offset of the last trimmed record, or `INVALID` if the loglet was never trimmed. Only
+    /// the log store writer moves it, once the trim point is durable and the trimmed records
+    /// are removed.
+    trim_point: AtomicU64,
+    /// Set by the log store writer once the seal is durable, after the tail reached its final
+    /// position.
+    sealed: OnceLock<SealReason>,
+}
+
+impl LogletState {
+    fn trim_point(&self) -> LogletOffset {
+        LogletOffset(self.trim_point.load(Ordering::Acquire))
+    }
+}
+
+/// A loglet that stores its records in the RocksDB instance shared by all local loglets.
+///
+/// Appends are handed over to the log store writer which commits the records of concurrent
+/// appends, across all loglets, in a single write batch.
+pub struct LocalLoglet {
+    store: LogStore,
+    writer: LogStoreWriterHandle,
+    state: Arc<LogletState>,
+}
+
+impl LocalLoglet {
+    fn open(loglet_id: u64, store: LogStore, writer: LogStoreWriterHandle) -> Result<Self, Error> {
+        let trim_point = store.get_trim_point(loglet_id)?;
+        // All records up to the tail might have been trimmed and removed.
+        let tail = store.find_last_offset(loglet_id)?.max(trim_point);
+        let sealed = OnceLock::new();
+        if let Some(reason) = store.get_seal(loglet_id)? {
+            let _ = sealed.set(reason);
+        }
+        debug!(
+            "Opened local loglet {} with trim point {} and tail {} (sealed: {:?})",
+            loglet_id,
+            trim_point,
+            tail,
+            sealed.get()
+        );
+
+        let (tail, _) = watch::channel(tail);
+        Ok(Self {
+            store,
+            writer,
+            state: Arc::new(LogletState {
+                loglet_id,
+                tail,
+                trim_point: AtomicU64::new(trim_point.0),
+                sealed,
+            }),
+        })
+    }
+
+    fn last_committed_offset(&self) -> LogletOffset {
+        *self.state.tail.borrow()
+    }
+
+    /// Reads up to `max_records` consecutive records after `after`. Returns no records if
+    /// nothing is committed after `after` yet. Trim gaps and seals are returned on their own.
+    fn read_after(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<LogletOffset>>, Error> {
+        let trim_point = self.state.trim_point();
+        if after < trim_point {
+            return Ok(vec![LogRecord::new_trim_gap(after.next(), trim_point)]);
+        }
+
+        // The seal is observed before the tail, a sealed loglet's tail doesn't move anymore.
+        let sealed = self.state.sealed.get();
+        let from_offset = after.next();
+        let tail = self.last_committed_offset();
+        if from_offset > tail {
+            return Ok(sealed
+                .map(|reason| LogRecord::new_seal(tail.next(), reason.clone()))
+                .into_iter()
+                .collect());
+        }
+
+        let to_offset = tail.min(LogletOffset(from_offset.0 + max_records.max(1) as u64 - 1));
+        let records = self
+            .store
+            .get_records(self.state.loglet_id, from_offset, to_offset)?;
+        if records.first().map(|(offset, _)| *offset) != Some(from_offset) {
+            // The records were trimmed after we checked the trim point. The trim point is
+            // persisted together with the removal of the records, but it might not be
+            // published yet.
+            let trim_point = self
+                .state
+                .trim_point()
+                .max(self.store.get_trim_point(self.state.loglet_id)?);
+            assert!(from_offset <= trim_point, "committed records are stored");
+            return Ok(vec![LogRecord::new_trim_gap(from_offset, trim_point)]);
+        }
+        Ok(records
+            .into_iter()
+            .map(|(offset, payload)| LogRecord::new_data(offset, payload))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl LogletBase for LocalLoglet {
+    type Offset = LogletOffset;
+
+    async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
+        let offset = self.writer.append(&self.state, vec![payload]).await?;
+        debug!(
+            "Appended record to local loglet {} at offset {}",
+            self.state.loglet_id, offset
+        );
+        Ok(offset)
+    }
+
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<LogletOffset, Error> {
+        let offset = self.writer.append(&self.state, payloads.to_vec()).await?;
+        debug!(
+            "Appended {} records to local loglet {} up to offset {}",
+            payloads.len(),
+            self.state.loglet_id,
+            offset
+        );
+        Ok(offset)
+    }
+
+    async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
+        let tail = self.last_committed_offset();
+        // The trim point never moves beyond the tail, this also covers empty loglets.
+        if tail <= self.state.trim_point() {
+            Ok(None)
+        } else {
+            Ok(Some(tail))
+        }
+    }
+
+    async fn get_trim_point(&self) -> Result<LogletOffset, Error> {
+        Ok(self.state.trim_point())
+    }
+
+    async fn trim(&self, trim_point: LogletOffset) -> Result<(), Error> {
+        let trim_point = trim_point.min(self.last_committed_offset());
+        if trim_point <= self.state.trim_point() {
+            return Ok(());
+        }
+
+        self.writer.trim(&self.state, trim_point).await?;
+        debug!(
+            "Trimmed local loglet {} to offset {}",
+            self.state.loglet_id, trim_point
+        );
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<(), Error> {
+        self.writer.seal(&self.state, reason).await?;
+        debug!("Sealed local loglet {}", self.state.loglet_id);
+        Ok(())
+    }
+
+    async fn read_next_single(
+        &self,
+        after: LogletOffset,
+    ) -> Result<LogRecord<Self::Offset>, Error> {
+        let mut records = self.read_next_n(after, 1).await?;
+        Ok(records.pop().expect("at least one record"))
+    }
+
+    async fn read_next_single_opt(
+        &self,
+        after: Self::Offset,
+    ) -> Result<Option<LogRecord<Self::Offset>>, Error> {
+        Ok(self.read_after(after, 1)?.pop())
+    }
+
+    async fn read_next_n(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<Self::Offset>>, Error> {
+        // Subscribe before reading to not miss a commit that happens in between.
+        let mut tail = self.state.tail.subscribe();
+        loop {
+            let records = self.read_after(after, max_records)?;
+            if !records.is_empty() {
+                break Ok(records);
+            }
+            // The sender lives as long as the loglet, so this can't fail.
+            let _ = tail
+                .wait_for(|tail| *tail > after || self.state.sealed.get().is_some())
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+    use std::time::Duration;
+
+    use googletest::prelude::*;
+    use restate_test_util::let_assert;
+    use tokio::task::JoinHandle;
+
+    use super::keys::{MetadataKey, MetadataKind};
+    use crate::{Record, TrimGap};
+
+    fn test_options(path: &Path) -> LocalLogletOptions {
+        LocalLogletOptions {
+            path: path.to_path_buf(),
+            ..LocalLogletOptions::default()
+        }
+    }
+
+    fn params(loglet_id: u64) -> LogletParams {
+        LogletParams::from(loglet_id.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let provider = LocalLogletProvider::with_options(test_options(tmp.path()));
+        let loglet = provider.get_loglet(&params(1)).await?;
+
+        assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        assert!(loglet
+            .read_next_single_opt(LogletOffset::INVALID)
+            .await?
+            .is_none());
+
+        for i in 1..=3 {
+            let offset = loglet.append(Payload::from(format!("record{}", i))).await?;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(Some(LogletOffset(i)), loglet.find_tail().await?);
+        }
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        assert_eq!(Payload::from("record1"), record.into_payload_unchecked());
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert_eq!(Payload::from("record3"), record.into_payload_unchecked());
+
+        assert!(loglet
+            .read_next_single_opt(LogletOffset(3))
+            .await?
+            .is_none());
+
+        // a reader waiting for a future record is woken up by the append
+        let handle: JoinHandle<Result<()>> = tokio::spawn({
+            let loglet = Arc::clone(&loglet);
+            async move {
+                let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(3)).await?;
+                assert_eq!(LogletOffset(4), offset);
+                assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+                Ok(())
+            }
+        });
+
+        tokio::task::yield_now().await;
+        assert!(!handle.is_finished());
+
+        let offset = loglet.append(Payload::from("record4")).await?;
+        assert_eq!(LogletOffset(4), offset);
+        tokio::time::timeout(Duration::from_secs(5), handle).await???;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglets_are_isolated() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let provider = LocalLogletProvider::with_options(test_options(tmp.path()));
+        let loglet_1 = provider.get_loglet(&params(1)).await?;
+        let loglet_2 = provider.get_loglet(&params(2)).await?;
+
+        // concurrent appends to both loglets end up in shared write batches
+        let appends: Vec<JoinHandle<Result<()>>> = (1..=20)
+            .map(|i| {
+                let loglet = if i % 2 == 0 {
+                    Arc::clone(&loglet_2)
+                } else {
+                    Arc::clone(&loglet_1)
+                };
+                tokio::spawn(async move {
+                    loglet.append(Payload::from(format!("record{}", i))).await?;
+                    Ok(())
+                })
+            })
+            .collect();
+        for append in appends {
+            append.await??;
+        }
+
+        for loglet in [&loglet_1, &loglet_2] {
+            assert_eq!(Some(LogletOffset(10)), loglet.find_tail().await?);
+            let mut after = LogletOffset::INVALID;
+            while let Some(LogRecord { offset, .. }) = loglet.read_next_single_opt(after).await? {
+                assert_eq!(after.next(), offset);
+                after = offset;
+            }
+            assert_eq!(LogletOffset(10), after);
+        }
+
+        // the same loglet is handed out for the same params
+        let loglet = provider.get_loglet(&params(1)).await?;
+        assert_eq!(Some(LogletOffset(10)), loglet.find_tail().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_recovery() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let opts = test_options(tmp.path());
+
+        let provider = LocalLogletProvider::with_options(opts.clone());
+        let loglet = provider.get_loglet(&params(1)).await?;
+        for i in 1..=3 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+        provider
+            .get_loglet(&params(2))
+            .await?
+            .append(Payload::from("other"))
+            .await?;
+        provider.shutdown().await?;
+        assert!(matches!(
+            loglet.append(Payload::from("record4")).await,
+            Err(Error::Shutdown)
+        ));
+        drop(loglet);
+        drop(provider);
+
+        let provider = LocalLogletProvider::with_options(opts);
+        let loglet = provider.get_loglet(&params(1)).await?;
+        assert_eq!(Some(LogletOffset(3)), loglet.find_tail().await?);
+
+        let mut after = LogletOffset::INVALID;
+        for i in 1..=3 {
+            let LogRecord { offset, record } = loglet.read_next_single(after).await?;
+            assert_eq!(LogletOffset(i), offset);
+            assert_eq!(
+                Payload::from(format!("record{}", i)),
+                record.into_payload_unchecked()
+            );
+            after = offset;
+        }
+
+        // appends continue after the recovered tail
+        assert_eq!(
+            LogletOffset(4),
+            loglet.append(Payload::from("record4")).await?
+        );
+        assert_eq!(
+            Some(LogletOffset(1)),
+            provider.get_loglet(&params(2)).await?.find_tail().await?
+        );
+        assert_eq!(
+            None,
+            provider.get_loglet(&params(3)).await?.find_tail().await?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_batches() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let provider = LocalLogletProvider::with_options(test_options(tmp.path()));
+        let loglet = provider.get_loglet(&params(1)).await?;
+        let batch: Vec<Payload> = (1..=3)
+            .map(|i| Payload::from(format!("batch{}", i)))
+            .collect();
+
+        // a batch is committed at consecutive offsets, even with concurrent appends
+        let (batch_offset, single_offset) = tokio::join!(
+            loglet.append_batch(&batch),
+            loglet.append(Payload::from("single"))
+        );
+        let batch_offset = batch_offset?;
+        let single_offset = single_offset?;
+        assert_eq!(Some(LogletOffset(4)), loglet.find_tail().await?);
+
+        let records = loglet.read_next_n(LogletOffset::INVALID, 10).await?;
+        assert_eq!(4, records.len());
+        let first = batch_offset.0 - 2;
+        for (i, payload) in batch.iter().enumerate() {
+            let LogRecord { offset, record } = &records[(first - 1) as usize + i];
+            assert_eq!(LogletOffset(first + i as u64), *offset);
+            assert_eq!(Some(payload), record.payload());
+        }
+        assert_eq!(
+            Some(&Payload::from("single")),
+            records[(single_offset.0 - 1) as usize].record.payload()
+        );
+
+        // a waiting reader receives the whole batch
+        let handle: JoinHandle<Result<Vec<LogRecord<LogletOffset>>>> = tokio::spawn({
+            let loglet = Arc::clone(&loglet);
+            async move { Ok(loglet.read_next_n(LogletOffset(4), 10).await?) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!handle.is_finished());
+        assert_eq!(LogletOffset(6), loglet.append_batch(&batch[..2]).await?);
+        let records = tokio::time::timeout(Duration::from_secs(5), handle).await???;
+        let offsets: Vec<_> = records.iter().map(|record| record.offset).collect();
+        assert_eq!(vec![LogletOffset(5), LogletOffset(6)], offsets);
+
+        // batches are rejected as a whole once sealed
+        loglet.seal(SealReason::Resharding).await?;
+        assert!(matches!(
+            loglet.append_batch(&batch).await,
+            Err(Error::LogletSealed(SealReason::Resharding))
+        ));
+        let records = loglet.read_next_n(LogletOffset(6), 10).await?;
+        assert_eq!(1, records.len());
+        assert!(records[0].record.is_seal());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_trim() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let opts = test_options(tmp.path());
+
+        let provider = LocalLogletProvider::with_options(opts.clone());
+        let loglet = provider.get_loglet(&params(1)).await?;
+        let other_loglet = provider.get_loglet(&params(2)).await?;
+        for i in 1..=5 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+            other_loglet
+                .append(Payload::from(format!("other{}", i)))
+                .await?;
+        }
+
+        loglet.trim(LogletOffset(3)).await?;
+        assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        let_assert!(Record::TrimGap(TrimGap { until }) = record);
+        assert_eq!(LogletOffset(3), until);
+        let LogRecord { offset, record } = loglet.read_next_single(until).await?;
+        assert_eq!(LogletOffset(4), offset);
+        assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+
+        // trims committed in the same batch never move the trim point backwards
+        let (first, second) =
+            tokio::join!(loglet.trim(LogletOffset(4)), loglet.trim(LogletOffset(2)));
+        first?;
+        second?;
+        assert_eq!(LogletOffset(4), loglet.get_trim_point().await?);
+
+        // trim points beyond the tail are clamped to the tail
+        loglet.trim(LogletOffset(10)).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+
+        // other loglets are not affected
+        assert_eq!(LogletOffset::INVALID, other_loglet.get_trim_point().await?);
+        let LogRecord { record, .. } = other_loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(Payload::from("other1"), record.into_payload_unchecked());
+
+        provider.shutdown().await?;
+        drop((loglet, other_loglet, provider));
+
+        // trim point and tail survive a restart even though all records were removed
+        let provider = LocalLogletProvider::with_options(opts);
+        let loglet = provider.get_loglet(&params(1)).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        assert_eq!(
+            LogletOffset(6),
+            loglet.append(Payload::from("record6")).await?
+        );
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert!(record.is_trim_gap());
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(5)).await?;
+        assert_eq!(LogletOffset(6), offset);
+        assert_eq!(Payload::from("record6"), record.into_payload_unchecked());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_seal() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let opts = test_options(tmp.path());
+
+        let provider = LocalLogletProvider::with_options(opts.clone());
+        let loglet = provider.get_loglet(&params(1)).await?;
+        let other_loglet = provider.get_loglet(&params(2)).await?;
+        loglet.append(Payload::from("record1")).await?;
+        loglet.append(Payload::from("record2")).await?;
+
+        // a reader waiting for the next record is woken up by the seal
+        let waiting_reader: JoinHandle<Result<LogRecord<LogletOffset>>> = tokio::spawn({
+            let loglet = Arc::clone(&loglet);
+            async move { Ok(loglet.read_next_single(LogletOffset(2)).await?) }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!waiting_reader.is_finished());
+
+        // appends that are enqueued together with the seal are rejected as well
+        let (sealed, rejected) = tokio::join!(
+            loglet.seal(SealReason::Resharding),
+            loglet.append(Payload::from("record3"))
+        );
+        sealed?;
+        let record = waiting_reader.await.unwrap()?;
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+        assert_eq!(LogletOffset(3), record.offset);
+        assert!(matches!(
+            rejected,
+            Err(Error::LogletSealed(SealReason::Resharding))
+        ));
+
+        // other loglets are not affected
+        assert_eq!(
+            LogletOffset::OLDEST,
+            other_loglet.append(Payload::from("other1")).await?
+        );
+
+        provider.shutdown().await?;
+        drop((loglet, other_loglet, provider));
+
+        // the seal survives a restart and sealing again keeps the original reason
+        let provider = LocalLogletProvider::with_options(opts);
+        let loglet = provider.get_loglet(&params(1)).await?;
+        loglet.seal(SealReason::Reconfiguration).await?;
+        assert_eq!(Some(LogletOffset(2)), loglet.find_tail().await?);
+        let_assert!(Some(record) = loglet.read_next_single_opt(LogletOffset(2)).await?);
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+        assert!(loglet.append(Payload::from("record3")).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_rejects_invalid_params() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let provider = LocalLogletProvider::with_options(test_options(tmp.path()));
+        assert!(provider
+            .get_loglet(&LogletParams::from("logs/1".to_string()))
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_corrupted_metadata() -> Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let store = LogStore::open(&test_options(tmp.path()))?;
+        for kind in [MetadataKind::TrimPoint, MetadataKind::Seal] {
+            store.db().put_cf(
+                store.metadata_cf(),
+                MetadataKey::new(1, kind).to_bytes(),
+                b"xyz",
+            )?;
+        }
+
+        // corrupted values are reported instead of crashing the node
+        assert!(matches!(store.get_trim_point(1), Err(Error::Io(_))));
+        assert!(matches!(store.get_seal(1), Err(Error::Io(_))));
+        assert_eq!(LogletOffset::INVALID, store.get_trim_point(2)?);
+
+        Ok(())
+    }
+}
@@ -11,19 +11,19 @@
 use std::cmp::Reverse;
 use std::collections::{hash_map, BinaryHeap, HashMap};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use restate_types::logs::metadata::LogletParams;
 use restate_types::logs::{Payload, SequenceNumber};
 use tokio::sync::oneshot::{Receiver, Sender};
 use tokio::sync::Mutex as AsyncMutex;
 use tracing::info;
 
 use crate::loglet::{Loglet, LogletBase, LogletOffset, LogletProvider};
-use crate::metadata::LogletParams;
 use crate::Error;
-use crate::LogRecord;
+use crate::{LogRecord, SealReason};
 
 pub fn default_config() -> serde_json::Value {
     serde_json::Value::Null
@@ -118,6 +118,8 @@ pub struct MemoryLoglet {
     // internal offset of the first record (or slot available)
     trim_point_offset: AtomicU64,
     last_committed_offset: AtomicU64,
+    // set under the log lock, appends are rejected once the loglet is sealed.
+    sealed: OnceLock<SealReason>,
     // reversed comparator. The watcher with the lowest offset ranks
     // higher in the binary heap.
     watchers: Mutex<BinaryHeap<Reverse<OffsetWatcher>>>,
@@ -131,6 +133,7 @@ impl MemoryLoglet {
             // Trim point is 0 initially
             trim_point_offset: AtomicU64::new(0),
             last_committed_offset: AtomicU64::new(0),
+            sealed: OnceLock::new(),
             watchers: Mutex::new(BinaryHeap::new()),
         })
     }
@@ -164,10 +167,12 @@ impl MemoryLoglet {
     pub fn notify_watchers(&self) {
         // it's safe to not lock the logs mutex because commit offset increases monotonically.
         let committed = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
+        // a sealed loglet will never reach the offsets that watchers are waiting for.
+        let sealed = self.sealed.get().is_some();
         let mut watchers = self.watchers.lock().unwrap();
         // remove all watchers with offset <= committed and notify them
         while let Some(Reverse(watcher)) = watchers.peek() {
-            if watcher.offset <= committed {
+            if watcher.offset <= committed || sealed {
                 let Reverse(watcher) = watchers.pop().expect("watcher is present");
                 let _ = watcher.channel.send(());
             } else {
@@ -176,26 +181,41 @@ impl MemoryLoglet {
         }
     }
 
-    fn read_after(&self, after: LogletOffset) -> Result<Option<LogRecord<LogletOffset>>, Error> {
+    fn read_after(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<LogletOffset>>, Error> {
         let guard = self.log.lock().unwrap();
         let trim_point = LogletOffset(self.trim_point_offset.load(Ordering::Acquire));
         // are we reading after before the trim point? Note that if trim_point == after then we
         // don't return a trim gap, the next record is potentially a data record.
         if trim_point > after {
-            return Ok(Some(LogRecord::new_trim_gap(after.next(), trim_point)));
+            return Ok(vec![LogRecord::new_trim_gap(after.next(), trim_point)]);
         }
 
         let from_offset = after.next();
         // are we reading after commit offset?
         let commit_offset = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
         if from_offset > commit_offset {
-            Ok(None)
+            Ok(self
+                .sealed
+                .get()
+                .map(|reason| LogRecord::new_seal(commit_offset.next(), reason.clone()))
+                .into_iter()
+                .collect())
         } else {
             let index = self.saturating_offset_to_index(from_offset);
-            Ok(Some(LogRecord::new_data(
-                from_offset,
-                guard.get(index).expect("reading untrimmed data").clone(),
-            )))
+            let num_records = max_records
+                .max(1)
+                .min((commit_offset.0 - from_offset.0 + 1) as usize);
+            Ok(guard[index..index + num_records]
+                .iter()
+                .enumerate()
+                .map(|(i, payload)| {
+                    LogRecord::new_data(LogletOffset(from_offset.0 + i as u64), payload.clone())
+                })
+                .collect())
         }
     }
 }
@@ -206,6 +226,9 @@ impl LogletBase for MemoryLoglet {
 
     async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
         let mut log = self.log.lock().unwrap();
+        if let Some(reason) = self.sealed.get() {
+            return Err(Error::LogletSealed(reason.clone()));
+        }
         let offset = self.index_to_offset(log.len());
         info!(
             "Appending record to in-memory loglet {:?} at offset {}",
@@ -218,6 +241,26 @@ impl LogletBase for MemoryLoglet {
         Ok(offset)
     }
 
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<LogletOffset, Error> {
+        let mut log = self.log.lock().unwrap();
+        if let Some(reason) = self.sealed.get() {
+            return Err(Error::LogletSealed(reason.clone()));
+        }
+        info!(
+            "Appending {} records to in-memory loglet {:?} at offset {}",
+            payloads.len(),
+            self.params,
+            self.index_to_offset(log.len()),
+        );
+        log.extend_from_slice(payloads);
+        // mark as committed immediately.
+        let offset = LogletOffset(
+            self.last_committed_offset.load(Ordering::Acquire) + payloads.len() as u64,
+        );
+        self.advance_commit_offset(offset);
+        Ok(offset)
+    }
+
     async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
         let log = self.log.lock().unwrap();
         if log.is_empty() {
@@ -233,28 +276,66 @@ impl LogletBase for MemoryLoglet {
         Ok(LogletOffset(self.trim_point_offset.load(Ordering::Acquire)))
     }
 
+    async fn trim(&self, trim_point: LogletOffset) -> Result<(), Error> {
+        let mut log = self.log.lock().unwrap();
+        let current_trim_point = LogletOffset(self.trim_point_offset.load(Ordering::Acquire));
+        let committed = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
+        let trim_point = trim_point.min(committed);
+        if trim_point <= current_trim_point {
+            return Ok(());
+        }
+
+        info!(
+            "Trimming in-memory loglet {:?} to offset {}",
+            self.params, trim_point
+        );
+        let trimmed_records = (trim_point.0 - current_trim_point.0) as usize;
+        log.drain(..trimmed_records);
+        self.trim_point_offset
+            .store(trim_point.0, Ordering::Release);
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<(), Error> {
+        {
+            let _log = self.log.lock().unwrap();
+            if self.sealed.set(reason).is_ok() {
+                info!("Sealed in-memory loglet {:?}", self.params);
+            }
+        }
+        self.notify_watchers();
+        Ok(())
+    }
+
     async fn read_next_single(
         &self,
         after: LogletOffset,
     ) -> Result<LogRecord<Self::Offset>, Error> {
-        loop {
-            let next_record = self.read_after(after)?;
-            if let Some(next_record) = next_record {
-                break Ok(next_record);
-            } else {
-                // Wait and respond when available.
-                let receiver = self.watch_for_offset(after.next());
-                receiver.await.unwrap();
-                continue;
-            }
-        }
+        let mut records = self.read_next_n(after, 1).await?;
+        Ok(records.pop().expect("at least one record"))
     }
 
     async fn read_next_single_opt(
         &self,
         after: Self::Offset,
     ) -> Result<Option<LogRecord<Self::Offset>>, Error> {
-        self.read_after(after)
+        Ok(self.read_after(after, 1)?.pop())
+    }
+
+    async fn read_next_n(
+        &self,
+        after: LogletOffset,
+        max_records: usize,
+    ) -> Result<Vec<LogRecord<Self::Offset>>, Error> {
+        loop {
+            let records = self.read_after(after, max_records)?;
+            if !records.is_empty() {
+                break Ok(records);
+            }
+            // Wait and respond when available.
+            let receiver = self.watch_for_offset(after.next());
+            receiver.await.unwrap();
+        }
     }
 }
 
@@ -264,6 +345,8 @@ mod tests {
 
     use googletest::prelude::*;
     use restate_test_util::let_assert;
+
+    use crate::{Record, TrimGap};
     use tokio::task::JoinHandle;
     use tracing_test::traced_test;
 
@@ -368,4 +451,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_memory_loglet_trim() -> Result<()> {
+        let loglet = MemoryLoglet::new(LogletParams::from("113".to_string()));
+        for i in 1..=5 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+
+        loglet.trim(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(2), loglet.get_trim_point().await?);
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+        // reading before the trim point returns a gap up to the trim point
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        let_assert!(Record::TrimGap(TrimGap { until }) = record);
+        assert_eq!(LogletOffset(2), until);
+
+        let LogRecord { offset, record } = loglet.read_next_single(until).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert_eq!(Payload::from("record3"), record.into_payload_unchecked());
+
+        // trimming to an older trim point is a no-op
+        loglet.trim(LogletOffset::OLDEST).await?;
+        assert_eq!(LogletOffset(2), loglet.get_trim_point().await?);
+
+        // trim points beyond the tail are clamped to the tail
+        loglet.trim(LogletOffset(10)).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+        assert_eq!(None, loglet.find_tail().await?);
+        let_assert!(Some(record) = loglet.read_next_single_opt(LogletOffset(3)).await?);
+        assert!(record.record.is_trim_gap());
+        assert!(loglet
+            .read_next_single_opt(LogletOffset(5))
+            .await?
+            .is_none());
+
+        // appends continue after the trimmed records
+        assert_eq!(
+            LogletOffset(6),
+            loglet.append(Payload::from("record6")).await?
+        );
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(5)).await?;
+        assert_eq!(LogletOffset(6), offset);
+        assert_eq!(Payload::from("record6"), record.into_payload_unchecked());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_loglet_seal() -> Result<()> {
+        let loglet = MemoryLoglet::new(LogletParams::from("114".to_string()));
+        loglet.append(Payload::from("record1")).await?;
+        loglet.append(Payload::from("record2")).await?;
+
+        // a reader waiting for the next record is woken up by the seal
+        let waiting_reader: JoinHandle<Result<LogRecord<LogletOffset>>> = tokio::spawn({
+            let loglet = loglet.clone();
+            async move { Ok(loglet.read_next_single(LogletOffset(2)).await?) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting_reader.is_finished());
+
+        loglet.seal(SealReason::Resharding).await?;
+        let record = waiting_reader.await.unwrap()?;
+        assert_eq!(LogletOffset(3), record.offset);
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+
+        assert!(matches!(
+            loglet.append(Payload::from("record3")).await,
+            Err(Error::LogletSealed(SealReason::Resharding))
+        ));
+        assert_eq!(Some(LogletOffset(2)), loglet.find_tail().await?);
+
+        // sealing again keeps the original reason, committed records are still readable
+        loglet.seal(SealReason::Reconfiguration).await?;
+        let record = loglet.read_next_single(LogletOffset(1)).await?;
+        assert_eq!(
+            Payload::from("record2"),
+            record.record.into_payload_unchecked()
+        );
+        let_assert!(Some(record) = loglet.read_next_single_opt(LogletOffset(2)).await?);
+        let_assert!(Record::Seal(reason) = record.record);
+        assert_eq!(SealReason::Resharding, reason);
+        Ok(())
+    }
 }
@@ -9,5 +9,6 @@
 // by the Apache License, Version 2.0.
 
 pub mod file_loglet;
+pub mod local_loglet;
 #[cfg(any(test, feature = "memory_loglet"))]
 pub mod memory_loglet;
@@ -11,7 +11,10 @@
 use enum_map::EnumMap;
 use strum::IntoEnumIterator;
 
-use crate::loglet::{provider_default_config, ProviderKind};
+use restate_core::{Metadata, MetadataWriter};
+use restate_types::logs::metadata::ProviderKind;
+
+use crate::loglet::provider_default_config;
 use crate::service::BifrostService;
 
 /// # Bifrost options
@@ -45,8 +48,13 @@ impl Default for Options {
 }
 
 impl Options {
-    pub fn build(self, num_partitions: u64) -> BifrostService {
-        BifrostService::new(self, num_partitions)
+    pub fn build(
+        self,
+        num_partitions: u64,
+        metadata: Metadata,
+        metadata_writer: MetadataWriter,
+    ) -> BifrostService {
+        BifrostService::new(self, num_partitions, metadata, metadata_writer)
     }
 
     #[cfg(any(test, feature = "memory_loglet"))]
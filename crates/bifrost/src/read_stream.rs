@@ -8,17 +8,45 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::VecDeque;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
-use restate_types::logs::{LogId, Lsn};
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream, StreamExt};
+
+use restate_types::logs::{LogId, Lsn, SequenceNumber};
+use restate_types::Version;
 
 use crate::bifrost::BifrostInner;
-use crate::{Error, LogRecord};
+use crate::{Error, LogRecord, Record};
+
+/// Reads the next records, together with the version of the logs metadata they were read with.
+type ReadAhead = BoxFuture<'static, Result<(Version, Vec<LogRecord>), Error>>;
 
+/// A stream of the records of a log, starting after a given LSN.
+///
+/// The stream reads up to `prefetch` records ahead of the consumer and ends once the record
+/// at the `until` LSN has been delivered, it's open ended by default. A trim gap is delivered
+/// as a single record, the stream skips over the gap afterwards. A [`Record::Seal`] is
+/// delivered when reaching the end of a sealed log. The stream then waits for a newer version
+/// of the logs metadata before reading again, and continues in the new segment once the log
+/// has been extended.
 pub struct LogReadStream {
     inner: Arc<BifrostInner>,
     log_id: LogId,
+    /// LSN of the last record handed out to the consumer.
     read_pointer: Lsn,
+    /// LSN of the last record that was read into the buffer.
+    fetch_pointer: Lsn,
+    until: Lsn,
+    prefetch: usize,
+    buffer: VecDeque<LogRecord>,
+    /// Version of the logs metadata that was loaded when the last seal was read. Reading
+    /// continues once a newer version is loaded.
+    sealed_at: Option<Version>,
+    read_ahead: Option<ReadAhead>,
 }
 
 impl LogReadStream {
@@ -27,46 +55,176 @@ impl LogReadStream {
             inner,
             log_id,
             read_pointer: after,
+            fetch_pointer: after,
+            until: Lsn::MAX,
+            prefetch: 1,
+            buffer: VecDeque::new(),
+            sealed_at: None,
+            read_ahead: None,
         }
     }
 
-    fn seek_to(&mut self, record: &LogRecord) {
-        let read_pointer = match &record.record {
+    /// Reads up to `prefetch` records ahead of the consumer, the next records are read once
+    /// half of the buffered records are consumed. Defaults to a single record.
+    pub fn with_prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch.max(1);
+        self
+    }
+
+    /// Ends the stream once the record at `until` has been delivered.
+    pub fn with_until(mut self, until: Lsn) -> Self {
+        self.until = until;
+        self
+    }
+
+    /// The LSN that the pointer moves to after `record` has been read.
+    fn next_pointer(pointer: Lsn, record: &LogRecord) -> Lsn {
+        match &record.record {
             // On trim gaps, we fast-forward the read pointer to the end of the gap. We do
             // this after delivering a TrimGap record. This means that the next read operation
             // skips over the boundary of the gap.
-            crate::Record::TrimGap(trim_gap) => trim_gap.until,
-            crate::Record::Data(_) => record.offset,
-            crate::Record::Seal(_) => record.offset,
-        };
-        self.read_pointer = read_pointer;
+            Record::TrimGap(trim_gap) => trim_gap.until,
+            Record::Data(_) => record.offset,
+            // The seal record sits at the LSN after the last record of the log, there is
+            // nothing to skip over. Reading again continues in the next segment once the log
+            // is extended.
+            Record::Seal(_) => pointer,
+        }
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.buffer.is_empty() && self.read_pointer >= self.until
+    }
+
+    /// Buffers records that were read with the logs metadata at `version`.
+    fn buffer_records(&mut self, version: Version, records: Vec<LogRecord>) {
+        for record in records {
+            self.fetch_pointer = Self::next_pointer(self.fetch_pointer, &record);
+            self.sealed_at = record.record.is_seal().then_some(version);
+            self.buffer.push_back(record);
+        }
+    }
+
+    fn deliver(&mut self, record: LogRecord) -> LogRecord {
+        self.read_pointer = Self::next_pointer(self.read_pointer, &record);
+        record
+    }
+
+    /// Number of records that can be read without passing the `until` LSN.
+    fn remaining(&self, after: Lsn) -> usize {
+        let until: u64 = self.until.into();
+        let after: u64 = after.into();
+        usize::try_from(until.saturating_sub(after)).unwrap_or(usize::MAX)
+    }
+
+    /// Starts reading the next records into the buffer if it's running low. The records after a
+    /// seal are only read once the seal was consumed.
+    fn maybe_read_ahead(&mut self) {
+        let sealed = self
+            .buffer
+            .back()
+            .is_some_and(|record| record.record.is_seal());
+        if self.read_ahead.is_some()
+            || sealed
+            || self.buffer.len() > self.prefetch / 2
+            || self.fetch_pointer >= self.until
+        {
+            return;
+        }
+
+        let max_records =
+            (self.prefetch - self.buffer.len()).min(self.remaining(self.fetch_pointer));
+        let inner = Arc::clone(&self.inner);
+        let log_id = self.log_id;
+        let after = self.fetch_pointer;
+        let sealed_at = self.sealed_at;
+        self.read_ahead = Some(
+            async move {
+                if let Some(version) = sealed_at {
+                    inner.wait_for_logs_version_after(version).await?;
+                }
+                let version = inner.logs_version();
+                let records = inner.read_next_n(log_id, after, max_records).await?;
+                Ok((version, records))
+            }
+            .boxed(),
+        );
     }
 
     /// Read the next record from the log after the current read pointer. The future will resolve
     /// after the record is available to read, this will async-block indefinitely if no records are
-    /// ever written to the log beyond the read pointer.
+    /// ever written to the log beyond the read pointer. Returns `None` once the stream has ended.
     ///
     /// This future is "Cancellation" safe.
-    pub async fn read_next(&mut self) -> Result<LogRecord, Error> {
-        let record = self
-            .inner
-            .read_next_single(self.log_id, self.read_pointer)
-            .await?;
-
-        self.seek_to(&record);
-        Ok(record)
+    pub async fn read_next(&mut self) -> Result<Option<LogRecord>, Error> {
+        self.next().await.transpose()
     }
 
-    /// Like `read_next` but returns `None` if there are no more records to read.
+    /// Like `read_next` but returns `None` without waiting if there are no more records to read.
     pub async fn read_next_opt(&mut self) -> Result<Option<LogRecord>, Error> {
-        let record_opt = self
+        if let Some(record) = self.buffer.pop_front() {
+            return Ok(Some(self.deliver(record)));
+        }
+        if self.is_terminated() {
+            return Ok(None);
+        }
+        // Reads are side-effect free, an ongoing read-ahead can be abandoned.
+        self.read_ahead = None;
+        let version = self.inner.logs_version();
+        let record = self
             .inner
             .read_next_single_opt(self.log_id, self.read_pointer)
             .await?;
-        if let Some(ref record) = record_opt {
-            self.seek_to(record);
+        Ok(record.map(|record| {
+            self.buffer_records(version, vec![record]);
+            let record = self.buffer.pop_front().expect("buffered record");
+            self.deliver(record)
+        }))
+    }
+
+    /// Read the next record after the current read pointer together with the records that are
+    /// committed right after it, up to `max_records` records in total. Waits like `read_next`
+    /// if no record is available yet. Trim gaps and seals are returned on their own. Returns no
+    /// records once the stream has ended.
+    ///
+    /// This future is "Cancellation" safe.
+    pub async fn read_next_n(&mut self, max_records: usize) -> Result<Vec<LogRecord>, Error> {
+        if self.buffer.is_empty()
+            && self.read_ahead.is_none()
+            && self.sealed_at.is_none()
+            && !self.is_terminated()
+        {
+            // Read the whole batch at once rather than record by record.
+            let version = self.inner.logs_version();
+            let records = self
+                .inner
+                .read_next_n(
+                    self.log_id,
+                    self.fetch_pointer,
+                    max_records.min(self.remaining(self.fetch_pointer)),
+                )
+                .await?;
+            self.buffer_records(version, records);
         }
-        Ok(record_opt)
+
+        let Some(record) = self.read_next().await? else {
+            return Ok(Vec::new());
+        };
+        let mut records = Vec::with_capacity(max_records.min(self.buffer.len() + 1));
+        let is_data = record.record.is_data();
+        records.push(record);
+        // Trim gaps and seals are returned on their own, like the batches of the loglets.
+        while is_data
+            && records.len() < max_records
+            && self
+                .buffer
+                .front()
+                .is_some_and(|record| record.record.is_data())
+        {
+            let record = self.buffer.pop_front().expect("buffered record");
+            records.push(self.deliver(record));
+        }
+        Ok(records)
     }
 
     /// Current read pointer. This is the LSN of the last read record, or the
@@ -76,6 +234,37 @@ impl LogReadStream {
     }
 }
 
+impl Stream for LogReadStream {
+    type Item = Result<LogRecord, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.is_terminated() {
+                return Poll::Ready(None);
+            }
+            self.maybe_read_ahead();
+            if let Some(read_ahead) = self.read_ahead.as_mut() {
+                if let Poll::Ready(result) = read_ahead.poll_unpin(cx) {
+                    self.read_ahead = None;
+                    match result {
+                        Ok((version, records)) => self.buffer_records(version, records),
+                        // Nothing was read, the next poll retries the read.
+                        Err(err) => return Poll::Ready(Some(Err(err))),
+                    }
+                    // The buffer might need to be topped up again.
+                    continue;
+                }
+            }
+
+            let Some(record) = self.buffer.pop_front() else {
+                // waiting for the read-ahead
+                return Poll::Pending;
+            };
+            return Poll::Ready(Some(Ok(self.deliver(record))));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -83,14 +272,17 @@ mod tests {
 
     use googletest::prelude::*;
     use restate_core::TestCoreEnv;
+    use restate_test_util::let_assert;
     use tokio::task::JoinHandle;
     use tracing::info;
     use tracing_test::traced_test;
 
-    use restate_types::logs::Payload;
+    use restate_types::logs::metadata::{LogletParams, ProviderKind};
+    use restate_types::logs::{Payload, SequenceNumber};
 
-    use crate::loglet::ProviderKind;
-    use crate::Options;
+    use futures::TryStreamExt;
+
+    use crate::{Bifrost, Options, Record, SealReason, TrimGap};
 
     #[tokio::test]
     #[traced_test]
@@ -106,7 +298,11 @@ mod tests {
                 default_provider: ProviderKind::Memory,
                 ..Options::default()
             };
-            let bifrost_svc = bifrost_opts.build(num_partitions);
+            let bifrost_svc = bifrost_opts.build(
+                num_partitions,
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+            );
             let mut bifrost = bifrost_svc.handle();
 
             // start bifrost service in the background
@@ -123,7 +319,7 @@ mod tests {
             // spawn a reader that reads 5 records and exits.
             let reader_bg_handle: JoinHandle<Result<()>> = tokio::spawn(async move {
                 for i in 1..=5 {
-                    let record = reader.read_next().await?;
+                    let_assert!(Some(record) = reader.read_next().await?);
                     let expected_lsn = Lsn::from(i) + read_after;
                     info!(?record, "read record");
                     assert_eq!(expected_lsn, record.offset);
@@ -169,4 +365,153 @@ mod tests {
         })
         .await
     }
+
+    #[tokio::test]
+    async fn test_readstream_with_trims() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+
+            for i in 1..=10 {
+                bifrost
+                    .append(log_id, format!("record{}", i).into())
+                    .await?;
+            }
+            bifrost.trim(log_id, Lsn::from(5)).await?;
+            assert_eq!(Lsn::from(5), bifrost.get_trim_point(log_id).await?);
+
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+
+            // the trimmed records are delivered as a single gap
+            let_assert!(Some(record) = reader.read_next().await?);
+            assert_eq!(Lsn::OLDEST, record.offset);
+            let_assert!(Record::TrimGap(TrimGap { until }) = record.record);
+            assert_eq!(Lsn::from(5), until);
+            // the reader skips over the gap
+            assert_eq!(Lsn::from(5), reader.current_read_pointer());
+
+            for i in 6..=10 {
+                let_assert!(Some(record) = reader.read_next().await?);
+                assert_eq!(Lsn::from(i), record.offset);
+                assert_eq!(
+                    Payload::from(format!("record{}", i)),
+                    record.record.into_payload_unchecked()
+                );
+            }
+            assert!(reader.read_next_opt().await?.is_none());
+
+            // readers behind a newer trim point observe the trim gap as well
+            bifrost.trim(log_id, Lsn::from(8)).await?;
+            let mut reader = bifrost.create_reader(log_id, Lsn::from(6));
+            let_assert!(Some(record) = reader.read_next_opt().await?);
+            assert_eq!(Lsn::from(7), record.offset);
+            let_assert!(Record::TrimGap(TrimGap { until }) = record.record);
+            assert_eq!(Lsn::from(8), until);
+            let_assert!(Some(record) = reader.read_next().await?);
+            assert_eq!(Lsn::from(9), record.offset);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_readstream_prefetch_and_until() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(
+                node_env.metadata.clone(),
+                node_env.metadata_writer.clone(),
+                1,
+            )
+            .await;
+
+            for i in 1..=10 {
+                bifrost
+                    .append(log_id, format!("record{}", i).into())
+                    .await?;
+            }
+
+            // a bounded stream ends after the record at `until`
+            let mut reader = bifrost
+                .create_reader(log_id, Lsn::from(2))
+                .with_prefetch(4)
+                .with_until(Lsn::from(7));
+            let lsns: Vec<Lsn> = (&mut reader)
+                .map_ok(|record| record.offset)
+                .try_collect()
+                .await?;
+            assert_eq!((3..=7).map(Lsn::from).collect::<Vec<_>>(), lsns);
+            assert_eq!(Lsn::from(7), reader.current_read_pointer());
+            assert!(reader.read_next().await?.is_none());
+            assert!(reader.read_next_opt().await?.is_none());
+
+            // an open ended stream waits for new records
+            let reader = bifrost.create_reader(log_id, Lsn::from(8)).with_prefetch(3);
+            let reader_bg_handle: JoinHandle<Result<Vec<Lsn>>> = tokio::spawn(async move {
+                Ok(reader
+                    .take(4)
+                    .map_ok(|record| record.offset)
+                    .try_collect()
+                    .await?)
+            });
+            tokio::task::yield_now().await;
+            assert!(!reader_bg_handle.is_finished());
+            bifrost
+                .append_batch(
+                    log_id,
+                    vec![Payload::from("record11"), Payload::from("record12")],
+                )
+                .await?;
+            assert_eq!(
+                (9..=12).map(Lsn::from).collect::<Vec<_>>(),
+                reader_bg_handle.await.unwrap()?
+            );
+
+            // the seal of the log is delivered once all records are read
+            bifrost.seal(log_id, SealReason::Resharding).await?;
+            let mut reader = bifrost
+                .create_reader(log_id, Lsn::from(10))
+                .with_prefetch(10);
+            let records = reader.read_next_n(10).await?;
+            assert_eq!(2, records.len());
+            let_assert!(Some(record) = reader.next().await.transpose()?);
+            assert_eq!(Lsn::from(13), record.offset);
+            assert!(record.record.is_seal());
+            assert_eq!(Lsn::from(12), reader.current_read_pointer());
+
+            // the seal is not delivered again, the stream waits for the log to be continued
+            let reader_bg_handle: JoinHandle<Result<Option<LogRecord>>> =
+                tokio::spawn(async move { Ok(reader.next().await.transpose()?) });
+            tokio::task::yield_now().await;
+            assert!(!reader_bg_handle.is_finished());
+            bifrost
+                .extend_chain(
+                    log_id,
+                    ProviderKind::Memory,
+                    LogletParams::from("0-1".to_owned()),
+                )
+                .await?;
+            bifrost.append(log_id, Payload::from("record13")).await?;
+            let_assert!(Some(record) = reader_bg_handle.await.unwrap()?);
+            assert_eq!(Lsn::from(13), record.offset);
+            assert_eq!(
+                Payload::from("record13"),
+                record.record.into_payload_unchecked()
+            );
+
+            Ok(())
+        })
+        .await
+    }
 }
@@ -11,7 +11,7 @@
 use std::sync::Arc;
 
 use anyhow::Context;
-use restate_core::{task_center, TaskKind};
+use restate_core::{task_center, Metadata, MetadataWriter, TaskKind};
 
 use crate::bifrost::BifrostInner;
 use crate::options::Options;
@@ -25,11 +25,22 @@ pub struct BifrostService {
 }
 
 impl BifrostService {
-    pub fn new(opts: Options, num_partitions: u64) -> Self {
+    pub fn new(
+        opts: Options,
+        num_partitions: u64,
+        metadata: Metadata,
+        metadata_writer: MetadataWriter,
+    ) -> Self {
         let (watchdog_sender, watchdog_receiver) = tokio::sync::mpsc::unbounded_channel();
-        let inner = Arc::new(BifrostInner::new(opts, watchdog_sender, num_partitions));
+        let inner = Arc::new(BifrostInner::new(
+            opts,
+            watchdog_sender,
+            num_partitions,
+            metadata.clone(),
+            metadata_writer,
+        ));
         let bifrost = Bifrost::new(inner.clone());
-        let watchdog = Watchdog::new(inner.clone(), watchdog_receiver);
+        let watchdog = Watchdog::new(inner.clone(), watchdog_receiver, metadata);
         Self {
             inner,
             bifrost,
@@ -47,6 +58,11 @@ impl BifrostService {
     ///
     /// This requires to run within a task_center context.
     pub async fn start(self) -> anyhow::Result<()> {
+        // Publish the initial logs metadata if this is the first node to start.
+        self.inner
+            .bootstrap_metadata()
+            .await
+            .context("Bootstrapping bifrost metadata has failed!")?;
         // Perform an initial metadata sync.
         self.inner
             .sync_metadata()
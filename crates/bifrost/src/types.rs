@@ -29,23 +29,31 @@ pub(crate) trait LsnExt: SequenceNumber {
         Self::from(self_raw.wrapping_add(offset_raw) - S::OLDEST.into())
     }
 
-    /// Convert an LSN back to a loglet offset given a base_lsn.
+    /// Convert an LSN back to a loglet offset given a base_lsn. LSNs before the base_lsn map
+    /// to the invalid offset, the slot before the first offset of the loglet.
     fn into_offset(self, base_lsn: Lsn) -> LogletOffset {
         let base_lsn_raw: u64 = base_lsn.into();
         let self_raw: u64 = self.into();
         let oldest_offset: u64 = LogletOffset::OLDEST.into();
-        LogletOffset(self_raw.saturating_sub(base_lsn_raw) + oldest_offset)
+        LogletOffset(
+            self_raw
+                .saturating_add(oldest_offset)
+                .saturating_sub(base_lsn_raw),
+        )
     }
 }
 
 impl LsnExt for Lsn {}
 
 /// Details about why a log was sealed
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum SealReason {
     /// Log was sealed to perform a repartitioning operation (split or unsplit).
     /// The reader/writer need to figure out where to read/write next.
     Resharding,
+    /// Log was sealed to continue it in a new segment, e.g. to move it to another loglet
+    /// provider. Readers continue reading in the new segment once the chain is extended.
+    Reconfiguration,
     Other(String),
 }
 
@@ -71,9 +79,18 @@ impl<S: SequenceNumber> LogRecord<S> {
         }
     }
 
+    pub(crate) fn new_seal(offset: S, reason: SealReason) -> Self {
+        LogRecord {
+            offset,
+            record: Record::Seal(reason),
+        }
+    }
+
     pub(crate) fn with_base_lsn(self, base_lsn: Lsn) -> LogRecord<Lsn> {
         let record = match self.record {
-            Record::TrimGap(_) => todo!(),
+            Record::TrimGap(trim_gap) => Record::TrimGap(TrimGap {
+                until: base_lsn.offset_by(trim_gap.until),
+            }),
             Record::Data(payload) => Record::Data(payload),
             Record::Seal(reason) => Record::Seal(reason),
         };
@@ -12,12 +12,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use enum_map::Enum;
-use restate_core::cancellation_watcher;
+use restate_core::{cancellation_watcher, Metadata};
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::logs::metadata::ProviderKind;
 use tokio::task::JoinSet;
 use tracing::{debug, info, warn};
 
 use crate::bifrost::BifrostInner;
-use crate::loglet::{LogletProvider, ProviderKind};
+use crate::loglet::LogletProvider;
 
 pub type WatchdogSender = tokio::sync::mpsc::UnboundedSender<WatchdogCommand>;
 type WatchdogReceiver = tokio::sync::mpsc::UnboundedReceiver<WatchdogCommand>;
@@ -30,14 +32,16 @@ type WatchdogReceiver = tokio::sync::mpsc::UnboundedReceiver<WatchdogCommand>;
 pub struct Watchdog {
     inner: Arc<BifrostInner>,
     inbound: WatchdogReceiver,
+    metadata: Metadata,
     live_providers: Vec<Arc<dyn LogletProvider>>,
 }
 
 impl Watchdog {
-    pub fn new(inner: Arc<BifrostInner>, inbound: WatchdogReceiver) -> Self {
+    pub fn new(inner: Arc<BifrostInner>, inbound: WatchdogReceiver, metadata: Metadata) -> Self {
         Self {
             inner,
             inbound,
+            metadata,
             live_providers: Vec::with_capacity(ProviderKind::LENGTH),
         }
     }
@@ -67,6 +71,7 @@ impl Watchdog {
     pub async fn run(mut self) -> anyhow::Result<()> {
         let shutdown = cancellation_watcher();
         tokio::pin!(shutdown);
+        let mut logs_watch = self.metadata.watch(MetadataKind::Logs);
         info!("Bifrost watchdog started");
 
         loop {
@@ -79,6 +84,10 @@ impl Watchdog {
             Some(cmd) = self.inbound.recv() => {
                 self.handle_command(cmd)
             }
+            Ok(_) = logs_watch.changed() => {
+                // a newer version of the logs metadata is available
+                self.handle_command(WatchdogCommand::ScheduleMetadataSync)
+            }
             }
         }
         Ok(())
@@ -134,7 +143,6 @@ impl Watchdog {
 pub enum WatchdogCommand {
     /// Request to sync metadata if the client believes that it's outdated.
     /// i.e. attempting to write to a sealed segment.
-    #[allow(dead_code)]
     ScheduleMetadataSync,
     StartProvider(Arc<dyn LogletProvider>),
 }
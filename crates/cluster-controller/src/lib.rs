@@ -9,7 +9,8 @@
 // by the Apache License, Version 2.0.
 
 mod options;
+mod scheduler;
 mod service;
 
-pub use options::Options;
+pub use options::{Options, OptionsBuilder, OptionsBuilderError};
 pub use service::{ClusterControllerHandle, Error, Service};
@@ -8,12 +8,56 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use serde_with::serde_as;
+use std::time::Duration;
+
 /// # Controller service options
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
+#[serde_as]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
 #[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
 #[cfg_attr(
     feature = "options_schema",
     schemars(rename = "ClusterControllerOptions")
 )]
 #[cfg_attr(feature = "options_schema", schemars(default))]
-pub struct Options {}
+#[builder(default)]
+pub struct Options {
+    /// # Heartbeat interval
+    ///
+    /// Interval in which attached worker nodes send heartbeats to the cluster controller. The
+    /// same interval is used by the cluster controller to check the liveness of attached nodes.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    heartbeat_interval: humantime::Duration,
+
+    /// # Heartbeat timeout
+    ///
+    /// Time after which an attached node that has not sent a heartbeat is considered dead. The
+    /// partitions led by a dead node are moved to the remaining live nodes.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    heartbeat_timeout: humantime::Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(1).into(),
+            heartbeat_timeout: Duration::from_secs(5).into(),
+        }
+    }
+}
+
+impl Options {
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.heartbeat_interval.into()
+    }
+
+    pub fn heartbeat_timeout(&self) -> Duration {
+        self.heartbeat_timeout.into()
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use restate_types::identifiers::PartitionId;
+use restate_types::GenerationalNodeId;
+
+/// Decides which node leads each partition of the partition table.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct SchedulingPlan {
+    leaders: BTreeMap<PartitionId, GenerationalNodeId>,
+}
+
+impl SchedulingPlan {
+    pub fn partitions_led_by(&self, node_id: GenerationalNodeId) -> Vec<PartitionId> {
+        self.leaders
+            .iter()
+            .filter(|(_, leader)| **leader == node_id)
+            .map(|(partition_id, _)| *partition_id)
+            .collect()
+    }
+
+    /// Recomputes the leaders of the partitions `0..num_partitions` given the currently alive
+    /// nodes. Partitions keep their leader as long as it is alive and not overloaded. Partitions
+    /// without a leader are assigned to the least loaded alive node. Returns whether the plan
+    /// has changed.
+    pub fn update(
+        &mut self,
+        num_partitions: u64,
+        alive_nodes: &BTreeSet<GenerationalNodeId>,
+    ) -> bool {
+        let previous_leaders = self.leaders.clone();
+
+        self.leaders.retain(|partition_id, leader| {
+            *partition_id < num_partitions && alive_nodes.contains(leader)
+        });
+
+        if !alive_nodes.is_empty() {
+            let mut load: BTreeMap<GenerationalNodeId, Vec<PartitionId>> = alive_nodes
+                .iter()
+                .map(|node_id| (*node_id, Vec::new()))
+                .collect();
+            for (partition_id, leader) in &self.leaders {
+                load.get_mut(leader)
+                    .expect("leader is alive")
+                    .push(*partition_id);
+            }
+
+            // shed partitions of overloaded nodes so that newly attached nodes take over leadership
+            let alive_nodes = u64::try_from(alive_nodes.len()).expect("fits into u64");
+            let max_load =
+                usize::try_from(num_partitions.div_ceil(alive_nodes)).expect("fits into usize");
+            for partitions in load.values_mut() {
+                while partitions.len() > max_load {
+                    let partition_id = partitions.pop().expect("partitions are not empty");
+                    self.leaders.remove(&partition_id);
+                }
+            }
+
+            for partition_id in 0..num_partitions {
+                if self.leaders.contains_key(&partition_id) {
+                    continue;
+                }
+
+                let (node_id, partitions) = load
+                    .iter_mut()
+                    .min_by_key(|(_, partitions)| partitions.len())
+                    .expect("at least one alive node");
+                partitions.push(partition_id);
+                self.leaders.insert(partition_id, *node_id);
+            }
+        }
+
+        self.leaders != previous_leaders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nodes(node_ids: &[GenerationalNodeId]) -> BTreeSet<GenerationalNodeId> {
+        node_ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn spreads_partitions_evenly() {
+        let node_1 = GenerationalNodeId::new(1, 1);
+        let node_2 = GenerationalNodeId::new(2, 1);
+        let mut plan = SchedulingPlan::default();
+
+        assert!(plan.update(4, &nodes(&[node_1])));
+        assert_eq!(plan.partitions_led_by(node_1), vec![0, 1, 2, 3]);
+
+        // a newly attached node takes over half of the partitions
+        assert!(plan.update(4, &nodes(&[node_1, node_2])));
+        assert_eq!(plan.partitions_led_by(node_1), vec![0, 1]);
+        assert_eq!(plan.partitions_led_by(node_2), vec![2, 3]);
+
+        // the plan is stable as long as the alive nodes don't change
+        assert!(!plan.update(4, &nodes(&[node_1, node_2])));
+    }
+
+    #[test]
+    fn moves_partitions_of_dead_nodes() {
+        let node_1 = GenerationalNodeId::new(1, 1);
+        let node_2 = GenerationalNodeId::new(2, 1);
+        let mut plan = SchedulingPlan::default();
+
+        plan.update(3, &nodes(&[node_1, node_2]));
+        assert_eq!(plan.partitions_led_by(node_1), vec![0, 2]);
+        assert_eq!(plan.partitions_led_by(node_2), vec![1]);
+
+        assert!(plan.update(3, &nodes(&[node_2])));
+        assert_eq!(plan.partitions_led_by(node_2), vec![0, 1, 2]);
+
+        // a restarted node is a different generation and does not inherit its old partitions
+        let node_1_restarted = GenerationalNodeId::new(1, 2);
+        assert!(plan.update(3, &nodes(&[node_1_restarted, node_2])));
+        assert_eq!(plan.partitions_led_by(node_1), Vec::<PartitionId>::new());
+        assert_eq!(plan.partitions_led_by(node_1_restarted), vec![2]);
+
+        assert!(plan.update(3, &nodes(&[])));
+        assert!(plan.partitions_led_by(node_2).is_empty());
+    }
+}
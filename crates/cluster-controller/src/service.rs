@@ -8,37 +8,261 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::options::Options;
+use std::collections::BTreeMap;
+
 use codederror::CodedError;
-use restate_core::cancellation_watcher;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, info};
+
+use restate_core::{cancellation_watcher, metadata, ShutdownError};
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::identifiers::PartitionId;
+use restate_types::{GenerationalNodeId, PlainNodeId, Version};
+
+use crate::options::Options;
+use crate::scheduler::SchedulingPlan;
 
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum Error {
-    #[error("error")]
+    #[error("node {0} has been superseded by node {1}")]
+    #[code(unknown)]
+    StaleGeneration(GenerationalNodeId, GenerationalNodeId),
+    #[error(transparent)]
     #[code(unknown)]
-    Error,
+    Shutdown(#[from] ShutdownError),
+}
+
+#[derive(Debug)]
+enum Command {
+    AttachNode {
+        node_id: GenerationalNodeId,
+        response_tx: oneshot::Sender<Result<Vec<PartitionId>, Error>>,
+    },
+}
+
+#[derive(Debug)]
+struct NodeState {
+    node_id: GenerationalNodeId,
+    last_heartbeat: Instant,
 }
 
 #[derive(Debug)]
 pub struct Service {
-    #[allow(dead_code)]
     options: Options,
+    command_tx: mpsc::Sender<Command>,
+    command_rx: mpsc::Receiver<Command>,
+    nodes: BTreeMap<PlainNodeId, NodeState>,
+    scheduling_plan: SchedulingPlan,
 }
 
-// todo: Replace with proper handle
-pub struct ClusterControllerHandle;
+#[derive(Debug, Clone)]
+pub struct ClusterControllerHandle {
+    command_tx: mpsc::Sender<Command>,
+}
+
+impl ClusterControllerHandle {
+    /// Attaches the given node to the cluster controller or, if it is already attached, records
+    /// a heartbeat for it. Returns the partitions the node is supposed to lead.
+    pub async fn attach_node(
+        &self,
+        node_id: GenerationalNodeId,
+    ) -> Result<Vec<PartitionId>, Error> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.command_tx
+            .send(Command::AttachNode {
+                node_id,
+                response_tx,
+            })
+            .await
+            .map_err(|_| ShutdownError)?;
+        response_rx.await.map_err(|_| ShutdownError)?
+    }
+}
 
 impl Service {
     pub fn new(options: Options) -> Self {
-        Service { options }
+        let (command_tx, command_rx) = mpsc::channel(64);
+        Service {
+            options,
+            command_tx,
+            command_rx,
+            nodes: BTreeMap::default(),
+            scheduling_plan: SchedulingPlan::default(),
+        }
     }
 
     pub fn handle(&self) -> ClusterControllerHandle {
-        ClusterControllerHandle
+        ClusterControllerHandle {
+            command_tx: self.command_tx.clone(),
+        }
+    }
+
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut shutdown = std::pin::pin!(cancellation_watcher());
+        let mut partition_table_watch = metadata().watch(MetadataKind::PartitionTable);
+        let mut liveness_check = tokio::time::interval(self.options.heartbeat_interval());
+        liveness_check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        debug!("Running cluster controller");
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    break;
+                }
+                Some(command) = self.command_rx.recv() => {
+                    self.on_command(command);
+                }
+                _ = liveness_check.tick() => {
+                    self.check_liveness();
+                }
+                Ok(_) = partition_table_watch.changed() => {
+                    self.update_scheduling_plan();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_command(&mut self, command: Command) {
+        match command {
+            Command::AttachNode {
+                node_id,
+                response_tx,
+            } => {
+                let _ = response_tx.send(self.on_attach_node(node_id));
+            }
+        }
+    }
+
+    fn on_attach_node(&mut self, node_id: GenerationalNodeId) -> Result<Vec<PartitionId>, Error> {
+        let now = Instant::now();
+
+        match self.nodes.get_mut(&node_id.as_plain()) {
+            Some(state) if state.node_id == node_id => {
+                state.last_heartbeat = now;
+            }
+            Some(state) if state.node_id.is_newer_than(node_id) => {
+                return Err(Error::StaleGeneration(node_id, state.node_id));
+            }
+            _ => {
+                info!("Node {} attached to the cluster controller", node_id);
+                self.nodes.insert(
+                    node_id.as_plain(),
+                    NodeState {
+                        node_id,
+                        last_heartbeat: now,
+                    },
+                );
+                self.update_scheduling_plan();
+            }
+        }
+
+        Ok(self.scheduling_plan.partitions_led_by(node_id))
+    }
+
+    fn check_liveness(&mut self) {
+        let heartbeat_timeout = self.options.heartbeat_timeout();
+        let num_nodes = self.nodes.len();
+
+        self.nodes.retain(|_, state| {
+            let is_alive = state.last_heartbeat.elapsed() <= heartbeat_timeout;
+            if !is_alive {
+                info!(
+                    "Node {} missed its heartbeats for {:?}, considering it dead",
+                    state.node_id, heartbeat_timeout
+                );
+            }
+            is_alive
+        });
+
+        if self.nodes.len() != num_nodes {
+            self.update_scheduling_plan();
+        }
+    }
+
+    fn update_scheduling_plan(&mut self) {
+        let metadata = metadata();
+        let num_partitions = if metadata.partition_table_version() == Version::INVALID {
+            0
+        } else {
+            metadata.partition_table().num_partitions()
+        };
+        let alive_nodes = self.nodes.values().map(|state| state.node_id).collect();
+
+        if self.scheduling_plan.update(num_partitions, &alive_nodes) {
+            for state in self.nodes.values() {
+                debug!(
+                    "Node {} leads partitions {:?}",
+                    state.node_id,
+                    self.scheduling_plan.partitions_led_by(state.node_id)
+                );
+            }
+            info!(
+                "Updated scheduling plan for {} partitions across {} nodes",
+                num_partitions,
+                self.nodes.len()
+            );
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use restate_core::{TaskKind, TestCoreEnv};
+    use restate_types::partition_table::FixedPartitionTable;
+
+    use crate::options::OptionsBuilder;
+
+    #[tokio::test(start_paused = true)]
+    async fn attach_nodes_and_detect_failures() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        node_env
+            .metadata_writer
+            .update(FixedPartitionTable::new(Version::MIN, 4))
+            .await?;
+
+        let options = OptionsBuilder::default()
+            .heartbeat_interval(Duration::from_secs(1).into())
+            .heartbeat_timeout(Duration::from_secs(3).into())
+            .build()?;
+        let service = Service::new(options);
+        let handle = service.handle();
+        tc.spawn(
+            TaskKind::SystemService,
+            "cluster-controller",
+            None,
+            service.run(),
+        )?;
+
+        let node_1 = GenerationalNodeId::new(1, 1);
+        let node_2 = GenerationalNodeId::new(2, 1);
+
+        assert_eq!(handle.attach_node(node_1).await?, vec![0, 1, 2, 3]);
+        assert_eq!(handle.attach_node(node_2).await?, vec![2, 3]);
+        assert_eq!(handle.attach_node(node_1).await?, vec![0, 1]);
+
+        // an older generation of an attached node is rejected
+        assert!(matches!(
+            handle.attach_node(GenerationalNodeId::new(1, 0)).await,
+            Err(Error::StaleGeneration(_, _))
+        ));
+
+        // only node 2 keeps sending heartbeats, node 1 is considered dead eventually
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            handle.attach_node(node_2).await?;
+        }
+        assert_eq!(handle.attach_node(node_2).await?, vec![0, 1, 2, 3]);
 
-    pub async fn run(self) -> anyhow::Result<()> {
-        let _ = cancellation_watcher().await;
+        tc.cancel_tasks(None, None).await;
         Ok(())
     }
 }
@@ -8,16 +8,21 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use anyhow::Context;
 use arc_swap::ArcSwapOption;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
+use strum::IntoEnumIterator;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
-use tracing::{debug, info, warn};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, info, trace, warn};
 
-use restate_node_protocol::metadata::{MetadataMessage, MetadataUpdate};
+use restate_node_protocol::metadata::{GetMetadataRequest, MetadataMessage, MetadataUpdate};
 use restate_node_protocol::MessageEnvelope;
+use restate_types::logs::metadata::Logs;
 use restate_types::nodes_config::NodesConfiguration;
 use restate_types::partition_table::FixedPartitionTable;
 use restate_types::GenerationalNodeId;
@@ -26,16 +31,37 @@ use restate_types::{Version, Versioned};
 use crate::cancellation_watcher;
 use crate::is_cancellation_requested;
 use crate::metadata;
+use crate::metadata_store::{MetadataStoreClient, Precondition, ReadError, WriteError};
 use crate::network::{MessageHandler, MessageRouterBuilder, NetworkSender};
 use crate::task_center;
 
 use super::{Metadata, MetadataContainer, MetadataInner, MetadataKind, MetadataWriter};
 
+/// Interval in which the metadata manager asks an admin node for newer metadata.
+const SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
 pub(super) type CommandSender = mpsc::UnboundedSender<Command>;
 pub(super) type CommandReceiver = mpsc::UnboundedReceiver<Command>;
 
 pub(super) enum Command {
-    UpdateMetadata(MetadataContainer, Option<oneshot::Sender<()>>),
+    /// Writes the metadata through to the metadata store and updates the local cache
+    UpdateMetadata(
+        MetadataContainer,
+        Option<oneshot::Sender<Result<(), WriteError>>>,
+    ),
+    /// Updates only the local cache with metadata that has already been stored, e.g. metadata
+    /// received from peers
+    UpdateCachedMetadata(MetadataContainer),
+}
+
+/// Key under which the metadata of the given kind is kept in the metadata store.
+fn metadata_store_key(kind: MetadataKind) -> &'static str {
+    match kind {
+        MetadataKind::NodesConfiguration => "nodes_config",
+        MetadataKind::Schema => "schema",
+        MetadataKind::PartitionTable => "partition_table",
+        MetadataKind::Logs => "logs",
+    }
 }
 
 /// A handler for processing network messages targeting metadata manager
@@ -60,28 +86,70 @@ where
     ) {
         match metadata_kind {
             MetadataKind::NodesConfiguration => self.send_nodes_config(peer, min_version),
-            _ => {
-                todo!("Can't send metadata '{}' to peer", metadata_kind)
+            MetadataKind::PartitionTable => self.send_partition_table(peer, min_version),
+            MetadataKind::Logs => self.send_logs(peer, min_version),
+            MetadataKind::Schema => {
+                info!("Peer requested schema metadata which is not managed by the metadata manager yet, ignoring their request");
             }
         };
     }
 
     fn send_nodes_config(&self, to: GenerationalNodeId, version: Option<Version>) {
-        let config = metadata().nodes_config();
-        if version.is_some_and(|min_version| min_version > config.version()) {
+        let metadata = metadata();
+        if metadata.nodes_config_version() == Version::INVALID {
+            info!("Peer requested nodes config but we have none yet, ignoring their request");
+            return;
+        }
+        let config = metadata.nodes_config();
+        self.send_metadata_internal(to, version, config.deref(), "nodes config");
+    }
+
+    fn send_partition_table(&self, to: GenerationalNodeId, version: Option<Version>) {
+        let metadata = metadata();
+        if metadata.partition_table_version() == Version::INVALID {
+            info!("Peer requested partition table but we have none yet, ignoring their request");
+            return;
+        }
+        let partition_table = metadata.partition_table();
+        self.send_metadata_internal(to, version, partition_table.deref(), "partition table");
+    }
+
+    fn send_logs(&self, to: GenerationalNodeId, version: Option<Version>) {
+        let metadata = metadata();
+        if metadata.logs_version() == Version::INVALID {
+            info!("Peer requested logs metadata but we have none yet, ignoring their request");
+            return;
+        }
+        let logs = metadata.logs();
+        self.send_metadata_internal(to, version, logs.deref(), "logs");
+    }
+
+    fn send_metadata_internal<T>(
+        &self,
+        to: GenerationalNodeId,
+        version: Option<Version>,
+        metadata: &T,
+        metadata_name: &str,
+    ) where
+        T: Versioned + Clone + Into<MetadataContainer>,
+    {
+        if version.is_some_and(|min_version| min_version > metadata.version()) {
             // We don't have the version that the peer is asking for. Just ignore.
             info!(
-                "Peer requested nodes config version {} but we have {}, ignoring their request",
+                "Peer requested {} version {} but we have {}, ignoring their request",
+                metadata_name,
                 version.unwrap(),
-                config.version()
+                metadata.version()
             );
             return;
         }
         info!(
-            "Sending nodes config {} to peer, requested version? {:?}",
-            config.version(),
+            "Sending {} {} to peer, requested version? {:?}",
+            metadata_name,
+            metadata.version(),
             version,
         );
+        let container = metadata.clone().into();
         let _ = task_center().spawn_child(
             crate::TaskKind::Disposable,
             "send-metadata-to-peer",
@@ -92,11 +160,7 @@ where
                     networking
                         .send(
                             to.into(),
-                            &MetadataMessage::MetadataUpdate(MetadataUpdate {
-                                container: MetadataContainer::NodesConfiguration(
-                                    config.deref().clone(),
-                                ),
-                            }),
+                            &MetadataMessage::MetadataUpdate(MetadataUpdate { container }),
                         )
                         .await?;
                     Ok(())
@@ -123,7 +187,7 @@ where
                 );
                 if let Err(e) = self
                     .sender
-                    .send(Command::UpdateMetadata(update.container, None))
+                    .send(Command::UpdateCachedMetadata(update.container))
                 {
                     if !is_cancellation_requested() {
                         warn!("Failed to send metadata message to metadata manager: {}", e);
@@ -157,24 +221,33 @@ where
 /// - Schema metadata
 /// - NodesConfiguration
 /// - Partition table
+///
+/// On start, the metadata manager loads the latest metadata from the metadata store. Updates
+/// submitted through the [`MetadataWriter`] are written through to the metadata store with a
+/// compare-and-swap on the previously known version before they become visible locally.
+///
+/// In the background, the metadata manager periodically asks an admin node for newer versions
+/// of the metadata so that nodes which missed updates catch up on their own.
 pub struct MetadataManager<N> {
     self_sender: CommandSender,
     inner: Arc<MetadataInner>,
     inbound: CommandReceiver,
     networking: N,
+    metadata_store: MetadataStoreClient,
 }
 
 impl<N> MetadataManager<N>
 where
     N: NetworkSender + 'static + Clone,
 {
-    pub fn build(networking: N) -> Self {
+    pub fn build(networking: N, metadata_store: MetadataStoreClient) -> Self {
         let (self_sender, inbound) = mpsc::unbounded_channel();
         Self {
             inner: Arc::new(MetadataInner::default()),
             inbound,
             self_sender,
             networking,
+            metadata_store,
         }
     }
 
@@ -197,6 +270,15 @@ where
     pub async fn run(mut self) -> anyhow::Result<()> {
         info!("Metadata manager started");
 
+        for kind in MetadataKind::iter() {
+            self.load_from_metadata_store(kind)
+                .await
+                .with_context(|| format!("failed loading '{}' from the metadata store", kind))?;
+        }
+
+        let mut sync_interval = tokio::time::interval(SYNC_INTERVAL);
+        sync_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         loop {
             tokio::select! {
                 biased;
@@ -205,31 +287,177 @@ where
                     break;
                 }
                 Some(cmd) = self.inbound.recv() => {
-                    self.handle_command(cmd)
+                    self.handle_command(cmd).await
+                }
+                _ = sync_interval.tick() => {
+                    self.sync_with_admin_node();
                 }
             }
         }
         Ok(())
     }
 
-    fn handle_command(&mut self, cmd: Command) {
+    async fn handle_command(&mut self, cmd: Command) {
         match cmd {
-            Command::UpdateMetadata(value, callback) => self.update_metadata(value, callback),
+            Command::UpdateMetadata(value, callback) => self.update_metadata(value, callback).await,
+            Command::UpdateCachedMetadata(value) => self.update_cached_metadata(value),
         }
     }
 
-    fn update_metadata(&mut self, value: MetadataContainer, callback: Option<oneshot::Sender<()>>) {
+    /// Requests every kind of metadata that is newer than the locally known version from an
+    /// admin node. The admin node only responds if it knows a newer version.
+    fn sync_with_admin_node(&self) {
+        let Some(my_node_id) = self.inner.my_node_id.get() else {
+            // the node has not started yet
+            return;
+        };
+        let Some(nodes_config) = self.inner.nodes_config.load_full() else {
+            return;
+        };
+        let Some(admin_node) = nodes_config.get_admin_node() else {
+            return;
+        };
+        let admin_node_id = admin_node.current_generation.as_plain();
+        if admin_node_id == my_node_id.as_plain() {
+            // we are the admin node ourselves
+            return;
+        }
+
+        // schemas are not managed by the metadata manager yet
+        for kind in MetadataKind::iter().filter(|kind| *kind != MetadataKind::Schema) {
+            let min_version = self.inner.write_watches[kind].receive.borrow().next();
+            trace!(
+                "Requesting '{}' {} or newer from admin node {}",
+                kind,
+                min_version,
+                admin_node_id
+            );
+            let _ = task_center().spawn_child(
+                crate::TaskKind::Disposable,
+                "sync-metadata-from-peer",
+                None,
+                {
+                    let networking = self.networking.clone();
+                    async move {
+                        networking
+                            .send(
+                                admin_node_id.into(),
+                                &MetadataMessage::GetMetadataRequest(GetMetadataRequest {
+                                    metadata_kind: kind,
+                                    min_version: Some(min_version),
+                                }),
+                            )
+                            .await?;
+                        Ok(())
+                    }
+                },
+            );
+        }
+    }
+
+    /// Loads the stored metadata of the given kind into the local cache.
+    async fn load_from_metadata_store(&mut self, kind: MetadataKind) -> Result<(), ReadError> {
+        let key = metadata_store_key(kind);
+        let value: Option<MetadataContainer> = match kind {
+            MetadataKind::NodesConfiguration => self
+                .metadata_store
+                .get::<NodesConfiguration>(key)
+                .await?
+                .map(Into::into),
+            MetadataKind::PartitionTable => self
+                .metadata_store
+                .get::<FixedPartitionTable>(key)
+                .await?
+                .map(Into::into),
+            MetadataKind::Logs => self.metadata_store.get::<Logs>(key).await?.map(Into::into),
+            // schemas are not managed by the metadata manager yet
+            MetadataKind::Schema => None,
+        };
+
+        if let Some(value) = value {
+            debug!(
+                "Loaded '{}' {} from the metadata store",
+                kind,
+                value.version()
+            );
+            self.update_cached_metadata(value);
+        }
+        Ok(())
+    }
+
+    async fn update_metadata(
+        &mut self,
+        value: MetadataContainer,
+        callback: Option<oneshot::Sender<Result<(), WriteError>>>,
+    ) {
+        let kind = value.kind();
+        let result = self.write_to_metadata_store(&value).await;
+
+        match &result {
+            Ok(()) => self.update_cached_metadata(value),
+            Err(WriteError::FailedPrecondition(msg)) => {
+                info!(
+                    "Metadata store holds a different version of '{}' than expected ({}), reloading it",
+                    kind, msg
+                );
+                if let Err(err) = self.load_from_metadata_store(kind).await {
+                    warn!(
+                        "Failed reloading '{}' from the metadata store: {}",
+                        kind, err
+                    );
+                }
+            }
+            Err(err) => {
+                warn!("Failed writing '{}' to the metadata store: {}", kind, err);
+            }
+        }
+
+        if let Some(callback) = callback {
+            let _ = callback.send(result);
+        }
+    }
+
+    /// Writes the value to the metadata store if it's newer than the locally known version. The
+    /// write only succeeds if the stored version is the locally known version.
+    async fn write_to_metadata_store(&self, value: &MetadataContainer) -> Result<(), WriteError> {
+        let kind = value.kind();
+        let current_version = *self.inner.write_watches[kind].receive.borrow();
+        if value.version() <= current_version {
+            // The local cache is already newer, the update will be ignored.
+            return Ok(());
+        }
+
+        let precondition = if current_version == Version::INVALID {
+            Precondition::DoesNotExist
+        } else {
+            Precondition::MatchesVersion(current_version)
+        };
+        let key = metadata_store_key(kind);
+
         match value {
             MetadataContainer::NodesConfiguration(config) => {
-                self.update_nodes_configuration(config);
+                self.metadata_store.put(key, config, precondition).await
             }
             MetadataContainer::PartitionTable(partition_table) => {
-                self.update_partition_table(partition_table);
+                self.metadata_store
+                    .put(key, partition_table, precondition)
+                    .await
             }
+            MetadataContainer::Logs(logs) => self.metadata_store.put(key, logs, precondition).await,
         }
+    }
 
-        if let Some(callback) = callback {
-            let _ = callback.send(());
+    fn update_cached_metadata(&mut self, value: MetadataContainer) {
+        match value {
+            MetadataContainer::NodesConfiguration(config) => {
+                self.update_nodes_configuration(config);
+            }
+            MetadataContainer::PartitionTable(partition_table) => {
+                self.update_partition_table(partition_table);
+            }
+            MetadataContainer::Logs(logs) => {
+                self.update_logs(logs);
+            }
         }
     }
 
@@ -245,6 +473,12 @@ where
         self.notify_watches(maybe_new_version, MetadataKind::PartitionTable);
     }
 
+    fn update_logs(&mut self, logs: Logs) {
+        let maybe_new_version = Self::update_internal(&self.inner.logs, logs);
+
+        self.notify_watches(maybe_new_version, MetadataKind::Logs);
+    }
+
     fn update_internal<T: Versioned>(container: &ArcSwapOption<T>, new_value: T) -> Version {
         let current_value = container.load();
         let mut maybe_new_version = new_value.version();
@@ -294,7 +528,13 @@ mod tests {
     use restate_types::nodes_config::{AdvertisedAddress, NodeConfig, Role};
     use restate_types::{GenerationalNodeId, Version};
 
-    use crate::metadata::spawn_metadata_manager;
+    use enum_map::Enum;
+    use restate_node_protocol::codec::{Targeted, WireSerde};
+    use restate_node_protocol::common::CURRENT_PROTOCOL_VERSION;
+    use restate_types::NodeId;
+
+    use crate::metadata::{spawn_metadata_manager, MetadataUpdateError};
+    use crate::network::NetworkSendError;
     use crate::test_env::MockNetworkSender;
     use crate::TaskCenterFactory;
 
@@ -318,6 +558,16 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_logs_updates() -> Result<()> {
+        test_updates(
+            Logs::new(Version::MIN, Default::default()),
+            MetadataKind::Logs,
+            |metadata| metadata.logs_version(),
+        )
+        .await
+    }
+
     async fn test_updates<T, F>(value: T, kind: MetadataKind, config_version: F) -> Result<()>
     where
         T: Into<MetadataContainer> + Versioned + Clone,
@@ -325,7 +575,8 @@ mod tests {
     {
         let network_sender = MockNetworkSender;
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
-        let metadata_manager = MetadataManager::build(network_sender);
+        let metadata_manager =
+            MetadataManager::build(network_sender, MetadataStoreClient::new_in_memory());
         let metadata_writer = metadata_manager.writer();
         let metadata = metadata_manager.metadata();
 
@@ -392,6 +643,16 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_logs_watchers() -> Result<()> {
+        test_watchers(
+            Logs::new(Version::MIN, Default::default()),
+            MetadataKind::Logs,
+            |metadata| metadata.logs_version(),
+        )
+        .await
+    }
+
     async fn test_watchers<T, F>(value: T, kind: MetadataKind, config_version: F) -> Result<()>
     where
         T: Into<MetadataContainer> + Versioned + Clone,
@@ -399,7 +660,8 @@ mod tests {
     {
         let network_sender = MockNetworkSender;
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
-        let metadata_manager = MetadataManager::build(network_sender);
+        let metadata_manager =
+            MetadataManager::build(network_sender, MetadataStoreClient::new_in_memory());
         let metadata_writer = metadata_manager.writer();
         let metadata = metadata_manager.metadata();
 
@@ -444,6 +706,219 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_recover_from_metadata_store() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let metadata_store = MetadataStoreClient::new_in_memory();
+
+        let metadata_manager = MetadataManager::build(MockNetworkSender, metadata_store.clone());
+        let metadata_writer = metadata_manager.writer();
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        let mut value = create_mock_nodes_config();
+        metadata_writer.update(value.clone()).await?;
+        value.increment_version();
+        metadata_writer.update(value.clone()).await?;
+
+        // a restarted metadata manager picks up the stored metadata
+        let metadata_manager = MetadataManager::build(MockNetworkSender, metadata_store);
+        let metadata = metadata_manager.metadata();
+        let metadata_writer = metadata_manager.writer();
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        let version = metadata
+            .wait_for_version(MetadataKind::NodesConfiguration, Version::from(2))
+            .await?;
+        assert_eq!(Version::from(2), version);
+        assert_eq!(value, *metadata.nodes_config());
+
+        // stale updates are ignored and don't overwrite the stored metadata
+        metadata_writer.update(create_mock_nodes_config()).await?;
+        assert_eq!(Version::from(2), metadata.nodes_config_version());
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_updates() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let metadata_store = MetadataStoreClient::new_in_memory();
+        let value = FixedPartitionTable::new(Version::MIN, 42);
+        metadata_store
+            .put("partition_table", &value, Precondition::DoesNotExist)
+            .await?;
+
+        let metadata_manager_1 = MetadataManager::build(MockNetworkSender, metadata_store.clone());
+        let metadata_writer_1 = metadata_manager_1.writer();
+        spawn_metadata_manager(&tc, metadata_manager_1)?;
+
+        let metadata_manager_2 = MetadataManager::build(MockNetworkSender, metadata_store);
+        let metadata_2 = metadata_manager_2.metadata();
+        let metadata_writer_2 = metadata_manager_2.writer();
+        spawn_metadata_manager(&tc, metadata_manager_2)?;
+        metadata_2
+            .wait_for_version(MetadataKind::PartitionTable, Version::MIN)
+            .await?;
+
+        let mut update_1 = value.clone();
+        update_1.increment_version();
+        metadata_writer_1.update(update_1.clone()).await?;
+
+        // the second manager still knows version 1 only, its update must not override update 1
+        let mut update_2 = FixedPartitionTable::new(Version::MIN, 7);
+        update_2.increment_version();
+        let result = metadata_writer_2.update(update_2).await;
+        assert!(matches!(
+            result,
+            Err(MetadataUpdateError::Write(WriteError::FailedPrecondition(
+                _
+            )))
+        ));
+
+        // the failed update made the second manager pick up the stored version
+        assert_eq!(update_1, *metadata_2.partition_table());
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
+    /// Network sender which forwards all sent metadata messages to a channel.
+    #[derive(Clone)]
+    struct RecordingNetworkSender {
+        sent: mpsc::UnboundedSender<(NodeId, MetadataMessage)>,
+    }
+
+    impl NetworkSender for RecordingNetworkSender {
+        async fn send<M>(
+            &self,
+            to: NodeId,
+            message: &M,
+        ) -> std::result::Result<(), NetworkSendError>
+        where
+            M: WireSerde + Targeted + Send + Sync,
+        {
+            let payload = message.encode(CURRENT_PROTOCOL_VERSION).unwrap();
+            let message = MetadataMessage::decode(payload, CURRENT_PROTOCOL_VERSION).unwrap();
+            let _ = self.sent.send((to, message));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_metadata_to_peers() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let (sent_tx, mut sent_rx) = mpsc::unbounded_channel();
+        let networking = RecordingNetworkSender { sent: sent_tx };
+        let metadata_manager =
+            MetadataManager::build(networking.clone(), MetadataStoreClient::new_in_memory());
+        let metadata_writer = metadata_manager.writer();
+        tc.try_set_global_metadata(metadata_manager.metadata());
+        let handler = MetadataMessageHandler {
+            sender: metadata_manager.self_sender.clone(),
+            networking,
+        };
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        metadata_writer.update(create_mock_nodes_config()).await?;
+        metadata_writer
+            .update(FixedPartitionTable::new(Version::MIN, 42))
+            .await?;
+        metadata_writer
+            .update(Logs::new(Version::MIN, Default::default()))
+            .await?;
+
+        let peer = GenerationalNodeId::new(2, 1);
+        let request = |metadata_kind, min_version| {
+            MessageEnvelope::new(
+                peer,
+                0,
+                MetadataMessage::GetMetadataRequest(GetMetadataRequest {
+                    metadata_kind,
+                    min_version,
+                }),
+            )
+        };
+
+        for kind in [
+            MetadataKind::NodesConfiguration,
+            MetadataKind::PartitionTable,
+            MetadataKind::Logs,
+        ] {
+            tc.run_in_scope("test", None, async {
+                // requests for versions we don't know and for schemas are ignored
+                handler
+                    .on_message(request(kind, Some(Version::from(2))))
+                    .await;
+                handler
+                    .on_message(request(MetadataKind::Schema, None))
+                    .await;
+                handler.on_message(request(kind, Some(Version::MIN))).await;
+            })
+            .await;
+
+            let (to, message) = sent_rx.recv().await.unwrap();
+            assert_eq!(NodeId::from(peer), to);
+            let MetadataMessage::MetadataUpdate(update) = message else {
+                panic!("expected metadata update but got {:?}", message);
+            };
+            assert_eq!(kind, update.container.kind());
+            assert_eq!(Version::MIN, update.container.version());
+        }
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_background_sync_with_admin_node() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let (sent_tx, mut sent_rx) = mpsc::unbounded_channel();
+        let metadata_manager = MetadataManager::build(
+            RecordingNetworkSender { sent: sent_tx },
+            MetadataStoreClient::new_in_memory(),
+        );
+        let metadata_writer = metadata_manager.writer();
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        // node 1 is the admin node
+        let mut nodes_config = create_mock_nodes_config();
+        let address = AdvertisedAddress::from_str("http://127.0.0.1:5123/").unwrap();
+        let my_node_id = GenerationalNodeId::new(2, 1);
+        nodes_config.upsert_node(NodeConfig::new(
+            "MyNode-2".to_owned(),
+            my_node_id,
+            address,
+            Role::Worker.into(),
+        ));
+        metadata_writer.update(nodes_config).await?;
+        metadata_writer.set_my_node_id(my_node_id);
+
+        tokio::time::sleep(SYNC_INTERVAL).await;
+
+        let mut requested = Vec::new();
+        for _ in 0..3 {
+            let (to, message) = sent_rx.recv().await.unwrap();
+            assert_eq!(NodeId::new_plain(1), to);
+            let MetadataMessage::GetMetadataRequest(request) = message else {
+                panic!("expected metadata request but got {:?}", message);
+            };
+            requested.push((request.metadata_kind, request.min_version));
+        }
+        requested.sort_by_key(|(kind, _)| kind.into_usize());
+        assert_eq!(
+            vec![
+                (MetadataKind::NodesConfiguration, Some(Version::from(2))),
+                (MetadataKind::PartitionTable, Some(Version::MIN)),
+                (MetadataKind::Logs, Some(Version::MIN)),
+            ],
+            requested
+        );
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
     fn create_mock_nodes_config() -> NodesConfiguration {
         let mut nodes_config = NodesConfiguration::new(Version::MIN, "test-cluster".to_owned());
         let address = AdvertisedAddress::from_str("http://127.0.0.1:5122/").unwrap();
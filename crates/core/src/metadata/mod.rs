@@ -21,13 +21,23 @@ use enum_map::EnumMap;
 use tokio::sync::{oneshot, watch};
 
 use restate_node_protocol::metadata::{MetadataContainer, MetadataKind};
+use restate_types::logs::metadata::Logs;
 use restate_types::nodes_config::NodesConfiguration;
 use restate_types::partition_table::FixedPartitionTable;
 use restate_types::{GenerationalNodeId, Version};
 
+use crate::metadata_store::WriteError;
 use crate::network::NetworkSender;
 use crate::{ShutdownError, TaskCenter, TaskId, TaskKind};
 
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataUpdateError {
+    #[error("failed writing metadata: {0}")]
+    Write(#[from] WriteError),
+    #[error(transparent)]
+    Shutdown(#[from] ShutdownError),
+}
+
 /// The kind of versioned metadata that can be synchronized across nodes.
 
 #[derive(Clone)]
@@ -79,6 +89,24 @@ impl Metadata {
         }
     }
 
+    /// Panics if logs metadata is not loaded yet.
+    #[track_caller]
+    pub fn logs(&self) -> Arc<Logs> {
+        self.inner
+            .logs
+            .load_full()
+            .expect("logs metadata is loaded")
+    }
+
+    /// Returns Version::INVALID if logs metadata has not been loaded yet.
+    pub fn logs_version(&self) -> Version {
+        let c = self.inner.logs.load();
+        match c.as_deref() {
+            Some(c) => c.version,
+            None => Version::INVALID,
+        }
+    }
+
     // Returns when the metadata kind is at the provided version (or newer)
     pub async fn wait_for_version(
         &self,
@@ -104,6 +132,7 @@ struct MetadataInner {
     my_node_id: OnceLock<GenerationalNodeId>,
     nodes_config: ArcSwapOption<NodesConfiguration>,
     partition_table: ArcSwapOption<FixedPartitionTable>,
+    logs: ArcSwapOption<Logs>,
     write_watches: EnumMap<MetadataKind, VersionWatch>,
 }
 
@@ -124,18 +153,22 @@ impl MetadataWriter {
         Self { sender, inner }
     }
 
-    // Returns when the nodes configuration update is performed.
-    pub async fn update(&self, value: impl Into<MetadataContainer>) -> Result<(), ShutdownError> {
+    // Returns when the metadata update has been written to the metadata store and is visible
+    // locally. Fails if the metadata store holds a different version than the locally known one.
+    pub async fn update(
+        &self,
+        value: impl Into<MetadataContainer>,
+    ) -> Result<(), MetadataUpdateError> {
         let (callback, recv) = oneshot::channel();
         let o = self.sender.send(manager::Command::UpdateMetadata(
             value.into(),
             Some(callback),
         ));
         if o.is_ok() {
-            let _ = recv.await;
+            recv.await.map_err(|_| ShutdownError)??;
             Ok(())
         } else {
-            Err(ShutdownError)
+            Err(ShutdownError.into())
         }
     }
 
@@ -0,0 +1,122 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+
+use restate_types::Version;
+
+use super::{MetadataStore, Precondition, ReadError, VersionedValue, WriteError};
+
+/// Metadata store which keeps all values in memory. Useful for tests.
+#[derive(Debug, Default)]
+pub struct InMemoryMetadataStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    values: HashMap<String, VersionedValue>,
+    watches: HashMap<String, watch::Sender<Version>>,
+}
+
+#[async_trait]
+impl MetadataStore for InMemoryMetadataStore {
+    async fn get(&self, key: &str) -> Result<Option<VersionedValue>, ReadError> {
+        Ok(self.inner.lock().unwrap().values.get(key).cloned())
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        value: VersionedValue,
+        precondition: Precondition,
+    ) -> Result<(), WriteError> {
+        let mut guard = self.inner.lock().unwrap();
+        precondition.check(guard.values.get(key).map(|current| current.version))?;
+
+        let version = value.version;
+        guard.values.insert(key.to_owned(), value);
+        if let Some(watch) = guard.watches.get(key) {
+            watch.send_replace(version);
+        }
+        Ok(())
+    }
+
+    fn watch(&self, key: &str) -> watch::Receiver<Version> {
+        let mut guard = self.inner.lock().unwrap();
+        let current_version = guard
+            .values
+            .get(key)
+            .map(|current| current.version)
+            .unwrap_or(Version::INVALID);
+        guard
+            .watches
+            .entry(key.to_owned())
+            .or_insert_with(|| watch::channel(current_version).0)
+            .subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn value(version: u32) -> VersionedValue {
+        VersionedValue::new(Version::from(version), Bytes::from_static(b"value"))
+    }
+
+    #[tokio::test]
+    async fn put_with_preconditions() -> anyhow::Result<()> {
+        let store = InMemoryMetadataStore::default();
+        let mut watch = store.watch("key");
+        assert_eq!(Version::INVALID, *watch.borrow());
+        assert_eq!(None, store.get("key").await?);
+
+        assert!(matches!(
+            store
+                .put("key", value(1), Precondition::MatchesVersion(Version::MIN))
+                .await,
+            Err(WriteError::FailedPrecondition(_))
+        ));
+        store
+            .put("key", value(1), Precondition::DoesNotExist)
+            .await?;
+        assert!(matches!(
+            store.put("key", value(1), Precondition::DoesNotExist).await,
+            Err(WriteError::FailedPrecondition(_))
+        ));
+
+        watch.changed().await?;
+        assert_eq!(Version::MIN, *watch.borrow());
+
+        store
+            .put("key", value(2), Precondition::MatchesVersion(Version::MIN))
+            .await?;
+        assert!(matches!(
+            store
+                .put("key", value(3), Precondition::MatchesVersion(Version::MIN))
+                .await,
+            Err(WriteError::FailedPrecondition(_))
+        ));
+        assert_eq!(Some(value(2)), store.get("key").await?);
+
+        store.put("key", value(7), Precondition::None).await?;
+        assert_eq!(Some(value(7)), store.get("key").await?);
+        assert_eq!(Version::from(7), *store.watch("key").borrow());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod in_memory;
+pub use in_memory::InMemoryMetadataStore;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use restate_types::{Version, Versioned};
+
+pub type GenericError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    #[error("metadata store failed: {0}")]
+    Internal(GenericError),
+    #[error("cannot decode stored value: {0}")]
+    Codec(GenericError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    #[error("metadata store failed: {0}")]
+    Internal(GenericError),
+    #[error("cannot encode value: {0}")]
+    Codec(GenericError),
+}
+
+/// A value of the metadata store together with its version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedValue {
+    pub version: Version,
+    pub value: Bytes,
+}
+
+impl VersionedValue {
+    pub fn new(version: Version, value: Bytes) -> Self {
+        Self { version, value }
+    }
+}
+
+/// Condition that needs to hold for a [`MetadataStore::put`] to succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// The value is written unconditionally.
+    None,
+    /// The key must not exist yet.
+    DoesNotExist,
+    /// The key must exist and its stored value must have the given version.
+    MatchesVersion(Version),
+}
+
+impl Precondition {
+    /// Checks the precondition against the version of the currently stored value.
+    pub fn check(&self, current_version: Option<Version>) -> Result<(), WriteError> {
+        match (self, current_version) {
+            (Precondition::None, _) => Ok(()),
+            (Precondition::DoesNotExist, None) => Ok(()),
+            (Precondition::DoesNotExist, Some(current_version)) => {
+                Err(WriteError::FailedPrecondition(format!(
+                    "expected key to not exist but found version {}",
+                    current_version
+                )))
+            }
+            (Precondition::MatchesVersion(expected), Some(current_version))
+                if *expected == current_version =>
+            {
+                Ok(())
+            }
+            (Precondition::MatchesVersion(expected), current_version) => {
+                Err(WriteError::FailedPrecondition(format!(
+                    "expected version {} but found {:?}",
+                    expected, current_version
+                )))
+            }
+        }
+    }
+}
+
+/// Durable store for the cluster-wide metadata. Values are opaque bytes, the store only
+/// tracks their versions to support compare-and-swap updates.
+#[async_trait]
+pub trait MetadataStore {
+    /// Gets the value stored under the given key, if any.
+    async fn get(&self, key: &str) -> Result<Option<VersionedValue>, ReadError>;
+
+    /// Stores the value under the given key if the precondition holds.
+    async fn put(
+        &self,
+        key: &str,
+        value: VersionedValue,
+        precondition: Precondition,
+    ) -> Result<(), WriteError>;
+
+    /// Watches the version of the value stored under the given key. The version is
+    /// `Version::INVALID` as long as the key does not exist.
+    fn watch(&self, key: &str) -> watch::Receiver<Version>;
+}
+
+/// Typed access to a [`MetadataStore`]. Values are stored bincode-encoded.
+#[derive(Clone)]
+pub struct MetadataStoreClient {
+    inner: Arc<dyn MetadataStore + Send + Sync>,
+}
+
+impl MetadataStoreClient {
+    pub fn new<S>(metadata_store: S) -> Self
+    where
+        S: MetadataStore + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(metadata_store),
+        }
+    }
+
+    pub fn new_in_memory() -> Self {
+        Self::new(InMemoryMetadataStore::default())
+    }
+
+    pub async fn get<T>(&self, key: &str) -> Result<Option<T>, ReadError>
+    where
+        T: Versioned + DeserializeOwned,
+    {
+        let Some(versioned_value) = self.inner.get(key).await? else {
+            return Ok(None);
+        };
+
+        let (value, _): (T, _) =
+            bincode::serde::decode_from_slice(&versioned_value.value, bincode::config::standard())
+                .map_err(|err| ReadError::Codec(err.into()))?;
+        debug_assert_eq!(versioned_value.version, value.version());
+        Ok(Some(value))
+    }
+
+    pub async fn put<T>(
+        &self,
+        key: &str,
+        value: &T,
+        precondition: Precondition,
+    ) -> Result<(), WriteError>
+    where
+        T: Versioned + Serialize,
+    {
+        let bytes = bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map_err(|err| WriteError::Codec(err.into()))?;
+        self.inner
+            .put(
+                key,
+                VersionedValue::new(value.version(), bytes.into()),
+                precondition,
+            )
+            .await
+    }
+
+    pub fn watch(&self, key: &str) -> watch::Receiver<Version> {
+        self.inner.watch(key)
+    }
+}
@@ -15,6 +15,7 @@ use restate_node_protocol::metadata::MetadataKind;
 use restate_types::nodes_config::{AdvertisedAddress, NodeConfig, NodesConfiguration, Role};
 use restate_types::{GenerationalNodeId, NodeId, Version};
 
+use crate::metadata_store::MetadataStoreClient;
 use crate::network::{NetworkSendError, NetworkSender};
 use crate::spawn_metadata_manager;
 use crate::{Metadata, MetadataManager, MetadataWriter};
@@ -37,6 +38,8 @@ pub struct TestCoreEnv {
     pub tc: TaskCenter,
     pub metadata: Metadata,
     pub metadata_writer: MetadataWriter,
+    /// The metadata store behind the metadata manager, e.g. to simulate writes of other nodes.
+    pub metadata_store_client: MetadataStoreClient,
 }
 
 impl TestCoreEnv {
@@ -44,7 +47,8 @@ impl TestCoreEnv {
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
 
         let networking = MockNetworkSender;
-        let metadata_manager = MetadataManager::build(networking);
+        let metadata_store_client = MetadataStoreClient::new_in_memory();
+        let metadata_manager = MetadataManager::build(networking, metadata_store_client.clone());
         let metadata = metadata_manager.metadata();
         let metadata_writer = metadata_manager.writer();
         tc.try_set_global_metadata(metadata.clone());
@@ -54,6 +58,7 @@ impl TestCoreEnv {
             tc,
             metadata,
             metadata_writer,
+            metadata_store_client,
         }
     }
 
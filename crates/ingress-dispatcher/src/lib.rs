@@ -16,9 +16,16 @@ use restate_schema_api::subscription::{
     EventReceiverComponentType, EventReceiverServiceInstanceType, Sink, Subscription,
 };
 use restate_types::errors::InvocationError;
-use restate_types::identifiers::{FullInvocationId, InvocationUuid, ServiceId, WithPartitionKey};
-use restate_types::invocation::{ServiceInvocation, ServiceInvocationSpanContext, SpanRelation};
+use restate_types::identifiers::{
+    FullInvocationId, IdempotencyId, InvocationId, InvocationUuid, PartitionKey, ServiceId,
+    WithPartitionKey,
+};
+use restate_types::invocation::{
+    AttachInvocationRequest, Idempotency, ServiceInvocation, ServiceInvocationSpanContext,
+    SpanRelation,
+};
 use restate_types::message::MessageIndex;
+use restate_types::time::MillisSinceEpoch;
 use restate_types::GenerationalNodeId;
 use std::fmt::Display;
 use std::time::Duration;
@@ -32,6 +39,7 @@ mod service;
 pub use event_remapping::Error as EventError;
 use restate_core::metadata;
 use restate_types::ingress::IngressResponse;
+use restate_wal_protocol::timer::TimerValue;
 use restate_wal_protocol::{AckMode, Command, Destination, Envelope, Header, Source};
 pub use service::Error as ServiceError;
 pub use service::Service;
@@ -40,58 +48,89 @@ pub use service::Service;
 
 pub type IngressRequestSender = mpsc::UnboundedSender<IngressRequest>;
 pub type IngressRequestReceiver = mpsc::UnboundedReceiver<IngressRequest>;
-pub type IngressResponseSender = oneshot::Sender<ExpiringIngressResponse>;
-pub type IngressResponseReceiver = oneshot::Receiver<ExpiringIngressResponse>;
+pub type IngressResponseSender = oneshot::Sender<Result<Bytes, InvocationError>>;
+pub type IngressResponseReceiver = oneshot::Receiver<Result<Bytes, InvocationError>>;
 pub type AckSender = oneshot::Sender<()>;
 pub type AckReceiver = oneshot::Receiver<()>;
 
+/// Retention of idempotent invocations which have been requested without a retention period.
+pub const DEFAULT_IDEMPOTENCY_RETENTION: Duration = Duration::from_secs(30 * 60);
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum IdempotencyMode {
-    Key(Bytes, Option<Duration>),
+    Key(ByteString, Option<Duration>),
     None,
 }
 
 impl IdempotencyMode {
-    pub fn key(key: impl Into<Bytes>, retention_period: Option<Duration>) -> Self {
+    pub fn key(key: impl Into<ByteString>, retention_period: Option<Duration>) -> Self {
         Self::Key(key.into(), retention_period)
     }
+
+    /// Creates the id of the invocation requested with this idempotency mode. Idempotent
+    /// requests derive the id from the idempotency key, so that retried requests target the same
+    /// invocation. The `key` is `None` for unkeyed services.
+    pub fn full_invocation_id(
+        &self,
+        service_name: impl Into<ByteString>,
+        key: Option<impl Into<Bytes>>,
+        method_name: impl Into<ByteString>,
+    ) -> FullInvocationId {
+        match (self, key) {
+            (IdempotencyMode::Key(idempotency_key, _), Some(key)) => IdempotencyId::combine(
+                ServiceId::new(service_name, key),
+                method_name,
+                idempotency_key.clone(),
+            )
+            .full_invocation_id(),
+            (IdempotencyMode::Key(idempotency_key, _), None) => {
+                IdempotencyId::unkeyed(service_name, method_name, idempotency_key.clone())
+                    .full_invocation_id()
+            }
+            (IdempotencyMode::None, Some(key)) => {
+                FullInvocationId::generate(ServiceId::new(service_name, key))
+            }
+            (IdempotencyMode::None, None) => FullInvocationId::generate(ServiceId::new(
+                service_name,
+                InvocationUuid::new().to_string(),
+            )),
+        }
+    }
+
+    fn into_idempotency(self) -> Option<Idempotency> {
+        match self {
+            IdempotencyMode::Key(key, retention_period) => Some(Idempotency {
+                key,
+                retention: retention_period.unwrap_or(DEFAULT_IDEMPOTENCY_RETENTION),
+            }),
+            IdempotencyMode::None => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum IngressRequest {
+    Invocation(Box<InvocationRequest>),
+    Attach(AttachRequest),
 }
 
 #[derive(Debug)]
-pub struct IngressRequest {
+pub struct InvocationRequest {
     fid: FullInvocationId,
     method_name: ByteString,
     argument: Bytes,
     span_context: ServiceInvocationSpanContext,
     request_mode: IngressRequestMode,
     idempotency: IdempotencyMode,
+    execution_time: Option<MillisSinceEpoch>,
 }
 
-#[derive(Debug, Clone)]
-pub struct ExpiringIngressResponse {
-    idempotency_expiry_time: Option<String>,
-    result: Result<Bytes, InvocationError>,
-}
-
-impl ExpiringIngressResponse {
-    pub fn idempotency_expire_time(&self) -> Option<&String> {
-        self.idempotency_expiry_time.as_ref()
-    }
-}
-
-impl From<ExpiringIngressResponse> for Result<Bytes, InvocationError> {
-    fn from(value: ExpiringIngressResponse) -> Self {
-        value.result
-    }
-}
-
-impl From<Result<Bytes, InvocationError>> for ExpiringIngressResponse {
-    fn from(value: Result<Bytes, InvocationError>) -> Self {
-        Self {
-            idempotency_expiry_time: None,
-            result: value,
-        }
-    }
+/// Request to receive the result of an existing invocation.
+#[derive(Debug)]
+pub struct AttachRequest {
+    invocation_id: InvocationId,
+    block_on_inflight: bool,
+    response_sender: IngressResponseSender,
 }
 
 pub type IngressDeduplicationId = (String, MessageIndex);
@@ -119,30 +158,35 @@ impl IngressRequest {
         let (result_tx, result_rx) = oneshot::channel();
 
         (
-            IngressRequest {
+            IngressRequest::Invocation(Box::new(InvocationRequest {
                 fid,
                 method_name: method_name.into(),
                 argument: argument.into(),
                 request_mode: IngressRequestMode::RequestResponse(result_tx),
                 span_context,
                 idempotency,
-            },
+                execution_time: None,
+            })),
             result_rx,
         )
     }
 
+    /// Creates a request which is acknowledged once the invocation has been accepted. If an
+    /// `execution_time` is given, the invocation is scheduled to be executed at that time.
     pub fn background_invocation(
         fid: FullInvocationId,
         method_name: impl Into<ByteString>,
         argument: impl Into<Bytes>,
         related_span: SpanRelation,
         ingress_deduplication_id: Option<IngressDeduplicationId>,
+        idempotency: IdempotencyMode,
+        execution_time: Option<MillisSinceEpoch>,
     ) -> (Self, AckReceiver) {
         let span_context = ServiceInvocationSpanContext::start(&fid, related_span);
         let (ack_tx, ack_rx) = oneshot::channel();
 
         (
-            IngressRequest {
+            IngressRequest::Invocation(Box::new(InvocationRequest {
                 fid,
                 method_name: method_name.into(),
                 argument: argument.into(),
@@ -151,12 +195,32 @@ impl IngressRequest {
                     None => IngressRequestMode::FireAndForget(ack_tx),
                     Some(dedup_id) => IngressRequestMode::DedupFireAndForget(dedup_id, ack_tx),
                 },
-                idempotency: IdempotencyMode::None,
-            },
+                idempotency,
+                execution_time,
+            })),
             ack_rx,
         )
     }
 
+    /// Creates a request which completes with the result of the invocation `invocation_id`. If
+    /// the invocation is still in flight, the response is only sent once it completes if
+    /// `block_on_inflight` is set, otherwise the request fails immediately.
+    pub fn attach(
+        invocation_id: InvocationId,
+        block_on_inflight: bool,
+    ) -> (Self, IngressResponseReceiver) {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        (
+            IngressRequest::Attach(AttachRequest {
+                invocation_id,
+                block_on_inflight,
+                response_sender: result_tx,
+            }),
+            result_rx,
+        )
+    }
+
     pub fn event<D: DeduplicationId>(
         subscription: &Subscription,
         mut event: Event,
@@ -263,7 +327,7 @@ impl IngressRequest {
             ));
 
             (
-                IngressRequest {
+                IngressRequest::Invocation(Box::new(InvocationRequest {
                     fid: proxy_fid,
                     method_name: ByteString::from_static(
                         restate_pb::PROXY_PROXY_THROUGH_METHOD_NAME,
@@ -280,19 +344,21 @@ impl IngressRequest {
                     span_context,
                     request_mode,
                     idempotency: IdempotencyMode::None,
-                },
+                    execution_time: None,
+                })),
                 ack_rx,
             )
         } else {
             (
-                IngressRequest {
+                IngressRequest::Invocation(Box::new(InvocationRequest {
                     fid: target_fid,
                     method_name: ByteString::from(&**handler),
                     argument,
                     span_context,
                     request_mode,
                     idempotency: IdempotencyMode::None,
-                },
+                    execution_time: None,
+                })),
                 ack_rx,
             )
         })
@@ -331,26 +397,75 @@ pub fn wrap_service_invocation_in_envelope(
     deduplication_source: Option<String>,
     msg_index: MessageIndex,
 ) -> Envelope {
-    let ack_mode = if deduplication_source.is_some() {
-        AckMode::Dedup
-    } else {
-        AckMode::Ack
-    };
+    let header = create_envelope_header(
+        service_invocation.fid.partition_key(),
+        from_node_id,
+        deduplication_source,
+        msg_index,
+    );
+
+    Envelope::new(header, Command::Invoke(service_invocation))
+}
+
+/// Wraps the service invocation in a timer which fires at the given `execution_time`. The timer
+/// is owned by the partition of the invoked service.
+pub fn wrap_delayed_service_invocation_in_envelope(
+    service_invocation: ServiceInvocation,
+    execution_time: MillisSinceEpoch,
+    from_node_id: GenerationalNodeId,
+    deduplication_source: Option<String>,
+    msg_index: MessageIndex,
+) -> Envelope {
+    let header = create_envelope_header(
+        service_invocation.fid.partition_key(),
+        from_node_id,
+        deduplication_source,
+        msg_index,
+    );
+
+    Envelope::new(
+        header,
+        Command::ScheduleTimer(TimerValue::new_invoke(
+            service_invocation.fid.clone(),
+            execution_time,
+            0,
+            service_invocation,
+        )),
+    )
+}
+
+pub fn wrap_attach_invocation_request_in_envelope(
+    attach_invocation_request: AttachInvocationRequest,
+    from_node_id: GenerationalNodeId,
+    msg_index: MessageIndex,
+) -> Envelope {
+    let header = create_envelope_header(
+        attach_invocation_request.invocation_id.partition_key(),
+        from_node_id,
+        None,
+        msg_index,
+    );
+
+    Envelope::new(header, Command::AttachInvocation(attach_invocation_request))
+}
 
-    let header = Header {
+fn create_envelope_header(
+    partition_key: PartitionKey,
+    from_node_id: GenerationalNodeId,
+    deduplication_source: Option<String>,
+    msg_index: MessageIndex,
+) -> Header {
+    Header {
         source: Source::Ingress {
             node_id: from_node_id,
             sequence_number: msg_index,
             dedup_key: deduplication_source,
             nodes_config_version: metadata().nodes_config_version(),
         },
-        dest: Destination::Processor {
-            partition_key: service_invocation.fid.partition_key(),
-        },
-        ack_mode,
-    };
-
-    Envelope::new(header, Command::Invoke(service_invocation))
+        dest: Destination::Processor { partition_key },
+        // Without a deduplication source, messages are deduplicated per node generation
+        ack_mode: AckMode::Dedup,
+    }
 }
 
 #[cfg(feature = "mocks")]
@@ -370,8 +485,9 @@ pub mod mocks {
             IdempotencyMode,
             IngressResponseSender,
         ) {
+            let_assert!(IngressRequest::Invocation(invocation_request) = self);
             let_assert!(
-                IngressRequest {
+                InvocationRequest {
                     fid,
                     method_name,
                     argument,
@@ -379,7 +495,7 @@ pub mod mocks {
                     request_mode: IngressRequestMode::RequestResponse(ingress_response_sender),
                     idempotency,
                     ..
-                } = self
+                } = *invocation_request
             );
             (
                 fid,
@@ -391,6 +507,11 @@ pub mod mocks {
             )
         }
 
+        pub fn execution_time(&self) -> Option<MillisSinceEpoch> {
+            let_assert!(IngressRequest::Invocation(invocation_request) = self);
+            invocation_request.execution_time
+        }
+
         pub fn expect_background_invocation(
             self,
         ) -> (
@@ -400,15 +521,16 @@ pub mod mocks {
             ServiceInvocationSpanContext,
             AckSender,
         ) {
+            let_assert!(IngressRequest::Invocation(invocation_request) = self);
             let_assert!(
-                IngressRequest {
+                InvocationRequest {
                     fid,
                     method_name,
                     argument,
                     span_context,
                     request_mode: IngressRequestMode::FireAndForget(ack_sender),
                     ..
-                } = self
+                } = *invocation_request
             );
             (fid, method_name, argument, span_context, ack_sender)
         }
@@ -423,15 +545,16 @@ pub mod mocks {
             IngressDeduplicationId,
             AckSender,
         ) {
+            let_assert!(IngressRequest::Invocation(invocation_request) = self);
             let_assert!(
-                IngressRequest {
+                InvocationRequest {
                     fid,
                     method_name,
                     argument,
                     span_context,
                     request_mode: IngressRequestMode::DedupFireAndForget(dedup_id, ack_sender),
                     ..
-                } = self
+                } = *invocation_request
             );
             (
                 fid,
@@ -442,5 +565,16 @@ pub mod mocks {
                 ack_sender,
             )
         }
+
+        pub fn expect_attach(self) -> (InvocationId, bool, IngressResponseSender) {
+            let_assert!(
+                IngressRequest::Attach(AttachRequest {
+                    invocation_id,
+                    block_on_inflight,
+                    response_sender,
+                }) = self
+            );
+            (invocation_id, block_on_inflight, response_sender)
+        }
     }
 }
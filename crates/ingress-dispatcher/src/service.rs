@@ -10,18 +10,13 @@
 
 use super::*;
 
-use bytes::{BufMut, BytesMut};
-use prost::Message;
 use restate_core::cancellation_watcher;
 use restate_futures_util::pipe::{
     new_sender_pipe_target, Either, EitherPipeInput, Pipe, PipeError, ReceiverPipeInput,
     UnboundedReceiverPipeInput,
 };
-use restate_pb::restate::internal::{
-    idempotent_invoke_response, IdempotentInvokeRequest, IdempotentInvokeResponse,
-};
-use restate_types::identifiers::FullInvocationId;
-use restate_types::invocation::{ServiceInvocationResponseSink, Source};
+use restate_types::identifiers::IngressRequestId;
+use restate_types::invocation::{AttachInvocationRequest, ServiceInvocationResponseSink, Source};
 use restate_types::GenerationalNodeId;
 use std::collections::HashMap;
 use std::future::poll_fn;
@@ -121,58 +116,14 @@ impl Service {
     }
 }
 
-enum MapResponseAction {
-    // We need to map the output type from IdempotentInvokeResponse
-    IdempotentInvokerResponse,
-    // No need to map the output type
-    None,
-}
-
-impl MapResponseAction {
-    fn map(&self, buf: Bytes) -> ExpiringIngressResponse {
-        match self {
-            MapResponseAction::IdempotentInvokerResponse => {
-                use idempotent_invoke_response::Response;
-                let idempotent_invoke_response = match IdempotentInvokeResponse::decode(buf) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        return ExpiringIngressResponse {
-                            idempotency_expiry_time: None,
-                            result: Err(InvocationError::internal(
-                                "Unexpected response from IdempotentInvoker",
-                            )),
-                        }
-                    }
-                };
-
-                let result = match idempotent_invoke_response.response {
-                    None => Err(InvocationError::internal(
-                        "Unexpected response from IdempotentInvoker",
-                    )),
-                    Some(Response::Success(v)) => Ok(v),
-                    Some(Response::Failure(v)) => Err(v.into()),
-                };
-
-                ExpiringIngressResponse {
-                    idempotency_expiry_time: Some(idempotent_invoke_response.expiry_time),
-                    result,
-                }
-            }
-            MapResponseAction::None => ExpiringIngressResponse {
-                idempotency_expiry_time: None,
-                result: Ok(buf),
-            },
-        }
-    }
-}
-
 struct DispatcherLoopHandler {
     my_node_id: GenerationalNodeId,
     msg_index: MessageIndex,
+    next_request_id: IngressRequestId,
 
     // This map can be unbounded, because we enforce concurrency limits in the ingress
     // services using the global semaphore
-    waiting_responses: HashMap<FullInvocationId, (MapResponseAction, IngressResponseSender)>,
+    waiting_responses: HashMap<IngressRequestId, IngressResponseSender>,
     waiting_for_acks: HashMap<MessageIndex, AckSender>,
     waiting_for_acks_with_custom_id: HashMap<IngressDeduplicationId, AckSender>,
 }
@@ -182,6 +133,7 @@ impl DispatcherLoopHandler {
         Self {
             my_node_id,
             msg_index: 0,
+            next_request_id: 0,
             waiting_responses: HashMap::new(),
             waiting_for_acks: HashMap::default(),
             waiting_for_acks_with_custom_id: Default::default(),
@@ -191,17 +143,8 @@ impl DispatcherLoopHandler {
     fn handle_network_input(&mut self, input: IngressDispatcherInput) {
         match input {
             IngressDispatcherInput::Response(response) => {
-                if let Some((map_response_action, sender)) =
-                    self.waiting_responses.remove(&response.full_invocation_id)
-                {
-                    let mapped_response = match response.response.into() {
-                        Ok(v) => map_response_action.map(v),
-                        Err(e) => ExpiringIngressResponse {
-                            idempotency_expiry_time: None,
-                            result: Err(e),
-                        },
-                    };
-                    if let Err(response) = sender.send(mapped_response) {
+                if let Some(sender) = self.waiting_responses.remove(&response.request_id) {
+                    if let Err(response) = sender.send(response.response.into()) {
                         debug!(
                             "Failed to send response '{:?}' because the handler has been closed, \
                     probably caused by the client connection that went away",
@@ -235,76 +178,70 @@ impl DispatcherLoopHandler {
     }
 
     fn handle_ingress_command(&mut self, ingress_request: IngressRequest) -> Envelope {
-        let IngressRequest {
+        match ingress_request {
+            IngressRequest::Invocation(invocation_request) => {
+                self.handle_invocation_request(*invocation_request)
+            }
+            IngressRequest::Attach(attach_request) => self.handle_attach_request(attach_request),
+        }
+    }
+
+    fn handle_attach_request(&mut self, attach_request: AttachRequest) -> Envelope {
+        let AttachRequest {
+            invocation_id,
+            block_on_inflight,
+            response_sender,
+        } = attach_request;
+
+        let request_id = self.get_and_increment_request_id();
+        self.waiting_responses.insert(request_id, response_sender);
+
+        let msg_index = self.get_and_increment_msg_index();
+        wrap_attach_invocation_request_in_envelope(
+            AttachInvocationRequest {
+                invocation_id,
+                block_on_inflight,
+                response_sink: ServiceInvocationResponseSink::ingress(self.my_node_id, request_id),
+            },
+            self.my_node_id,
+            msg_index,
+        )
+    }
+
+    fn handle_invocation_request(&mut self, invocation_request: InvocationRequest) -> Envelope {
+        let InvocationRequest {
             fid,
             method_name,
             argument,
             span_context,
             request_mode,
             idempotency,
-        } = ingress_request;
+            execution_time,
+        } = invocation_request;
 
-        let response_sink = if matches!(request_mode, IngressRequestMode::RequestResponse(_)) {
-            Some(ServiceInvocationResponseSink::Ingress(self.my_node_id))
+        let request_id = if matches!(request_mode, IngressRequestMode::RequestResponse(_)) {
+            Some(self.get_and_increment_request_id())
         } else {
             None
         };
+        let response_sink = request_id
+            .map(|request_id| ServiceInvocationResponseSink::ingress(self.my_node_id, request_id));
 
-        let (service_invocation, map_response_action) =
-            if let IdempotencyMode::Key(idempotency_key, retention_period) = idempotency {
-                // Use service name + user provided idempotency key for the actual idempotency key
-                let mut idempotency_fid_key = BytesMut::with_capacity(
-                    fid.service_id.service_name.len() + idempotency_key.len(),
-                );
-                idempotency_fid_key.put(fid.service_id.service_name.clone().into_bytes());
-                idempotency_fid_key.put(idempotency_key.clone());
-
-                (
-                    ServiceInvocation {
-                        fid: FullInvocationId::generate(ServiceId::new(
-                            restate_pb::IDEMPOTENT_INVOKER_SERVICE_NAME,
-                            idempotency_fid_key.freeze(),
-                        )),
-                        method_name: restate_pb::IDEMPOTENT_INVOKER_INVOKE_METHOD_NAME
-                            .to_string()
-                            .into(),
-                        argument: IdempotentInvokeRequest {
-                            idempotency_id: idempotency_key,
-                            service_name: fid.service_id.service_name.into(),
-                            service_key: fid.service_id.key,
-                            invocation_uuid: fid.invocation_uuid.into(),
-                            method: method_name.into(),
-                            argument,
-                            retention_period_sec: retention_period.unwrap_or_default().as_secs()
-                                as u32,
-                        }
-                        .encode_to_vec()
-                        .into(),
-                        source: Source::Ingress,
-                        response_sink,
-                        span_context,
-                    },
-                    MapResponseAction::IdempotentInvokerResponse,
-                )
-            } else {
-                (
-                    ServiceInvocation {
-                        fid,
-                        method_name,
-                        argument,
-                        source: Source::Ingress,
-                        response_sink,
-                        span_context,
-                    },
-                    MapResponseAction::None,
-                )
-            };
+        let service_invocation = ServiceInvocation {
+            fid,
+            method_name,
+            argument,
+            source: Source::Ingress,
+            response_sink,
+            span_context,
+            idempotency: idempotency.into_idempotency(),
+        };
 
         let (dedup_source, msg_index) = match request_mode {
             IngressRequestMode::RequestResponse(response_sender) => {
                 self.waiting_responses.insert(
-                    service_invocation.fid.clone(),
-                    (map_response_action, response_sender),
+                    request_id.expect("request-response invocations have a request id"),
+                    response_sender,
                 );
                 (None, self.get_and_increment_msg_index())
             }
@@ -320,12 +257,22 @@ impl DispatcherLoopHandler {
             }
         };
 
-        wrap_service_invocation_in_envelope(
-            service_invocation,
-            self.my_node_id,
-            dedup_source,
-            msg_index,
-        )
+        if let Some(execution_time) = execution_time {
+            wrap_delayed_service_invocation_in_envelope(
+                service_invocation,
+                execution_time,
+                self.my_node_id,
+                dedup_source,
+                msg_index,
+            )
+        } else {
+            wrap_service_invocation_in_envelope(
+                service_invocation,
+                self.my_node_id,
+                dedup_source,
+                msg_index,
+            )
+        }
     }
 
     fn get_and_increment_msg_index(&mut self) -> MessageIndex {
@@ -333,6 +280,12 @@ impl DispatcherLoopHandler {
         self.msg_index += 1;
         current_msg_index
     }
+
+    fn get_and_increment_request_id(&mut self) -> IngressRequestId {
+        let current_request_id = self.next_request_id;
+        self.next_request_id += 1;
+        current_request_id
+    }
 }
 
 #[cfg(test)]
@@ -344,9 +297,10 @@ mod tests {
     use restate_core::TestCoreEnv;
     use test_log::test;
 
+    use restate_storage_api::timer_table::Timer;
     use restate_test_util::{let_assert, matchers::*};
-    use restate_types::identifiers::ServiceId;
-    use restate_types::invocation::{ResponseResult, SpanRelation};
+    use restate_types::identifiers::{FullInvocationId, InvocationId, ServiceId};
+    use restate_types::invocation::{Idempotency, ResponseResult, SpanRelation};
 
     #[test(tokio::test)]
     async fn test_closed_handler() {
@@ -383,9 +337,10 @@ mod tests {
         // Now let's send the response
         input_sender
             .send(IngressDispatcherInput::Response(IngressResponse {
-                full_invocation_id: fid.clone(),
-                response: ResponseResult::Success(Bytes::new()),
                 target_node: GenerationalNodeId::new(0, 0),
+                request_id: 0,
+                invocation_id: InvocationId::from(&fid),
+                response: ResponseResult::Success(Bytes::new()),
             }))
             .await
             .unwrap();
@@ -413,20 +368,19 @@ mod tests {
         )
         .unwrap();
 
-        // Ask for a response, then drop the receiver
-        let fid = FullInvocationId::generate(ServiceId::new("MySvc", "MyKey"));
+        let idempotency_mode = IdempotencyMode::key("123", None);
+        let fid = idempotency_mode.full_invocation_id("MySvc", Some("MyKey"), "pippo");
         let argument = Bytes::from_static(b"nbfjksdfs");
-        let idempotency_key = Bytes::copy_from_slice(b"123");
         let (invocation, res) = IngressRequest::invocation(
             fid.clone(),
             "pippo",
             argument.clone(),
             SpanRelation::None,
-            IdempotencyMode::key(idempotency_key.clone(), None),
+            idempotency_mode,
         );
         handler_tx.send(invocation).unwrap();
 
-        // Let's check we correct have a response
+        // The invocation is sent directly to the target service, carrying the idempotency key
         let output_message = output_rx.recv().await.unwrap();
 
         let_assert!(
@@ -438,51 +392,142 @@ mod tests {
         assert_that!(
             service_invocation,
             pat!(ServiceInvocation {
-                fid: pat!(FullInvocationId {
-                    service_id: pat!(ServiceId {
-                        service_name: displays_as(eq(restate_pb::IDEMPOTENT_INVOKER_SERVICE_NAME)),
-                        key: eq(Bytes::copy_from_slice(b"MySvc123")),
-                    }),
-                }),
-                method_name: displays_as(eq(restate_pb::IDEMPOTENT_INVOKER_INVOKE_METHOD_NAME)),
-                argument: protobuf_decoded(pat!(IdempotentInvokeRequest {
-                    idempotency_id: eq(idempotency_key),
-                    service_name: eq("MySvc"),
-                    service_key: eq("MyKey"),
-                    method: eq("pippo"),
-                    argument: eq(argument),
-                    retention_period_sec: eq(0)
+                fid: eq(fid.clone()),
+                method_name: displays_as(eq("pippo")),
+                argument: eq(argument),
+                idempotency: some(eq(Idempotency {
+                    key: ByteString::from_static("123"),
+                    retention: DEFAULT_IDEMPOTENCY_RETENTION
                 }))
             })
         );
 
         // Now check we get the response is routed back to the handler correctly
         let response = Bytes::from_static(b"vmoaifnuei");
-        let expiry_time = "2023-09-25T07:47:58.661309Z".to_string();
         network_tx
             .send(IngressDispatcherInput::Response(IngressResponse {
-                full_invocation_id: service_invocation.fid,
-                response: ResponseResult::Success(
-                    IdempotentInvokeResponse {
-                        expiry_time: expiry_time.clone(),
-                        response: Some(idempotent_invoke_response::Response::Success(
-                            response.clone(),
-                        )),
-                    }
-                    .encode_to_vec()
-                    .into(),
-                ),
                 target_node: GenerationalNodeId::new(0, 0),
+                request_id: 0,
+                invocation_id: InvocationId::from(&fid),
+                response: ResponseResult::Success(response.clone()),
             }))
             .await
             .unwrap();
 
-        assert_that!(
-            res.await.unwrap(),
-            pat!(ExpiringIngressResponse {
-                idempotency_expiry_time: some(eq(expiry_time)),
-                result: ok(eq(response))
-            })
+        assert_that!(res.await.unwrap(), ok(eq(response)));
+    }
+
+    #[test(tokio::test)]
+    async fn attach_invocation() {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        let (output_tx, mut output_rx) = mpsc::channel(2);
+
+        let ingress_dispatcher = Service::new(1);
+        let handler_tx = ingress_dispatcher.create_ingress_request_sender();
+        let network_tx = ingress_dispatcher.create_ingress_dispatcher_input_sender();
+
+        // Start the dispatcher loop
+        tc.spawn(
+            TaskKind::SystemService,
+            "ingress-dispatcher",
+            None,
+            ingress_dispatcher.run(output_tx),
+        )
+        .unwrap();
+
+        let invocation_id = InvocationId::from(&FullInvocationId::generate(ServiceId::new(
+            "MySvc", "MyKey",
+        )));
+        let (attach, res) = IngressRequest::attach(invocation_id.clone(), true);
+        handler_tx.send(attach).unwrap();
+
+        // The request is sent to the partition owning the invocation
+        let output_message = output_rx.recv().await.unwrap();
+        assert_eq!(
+            invocation_id.partition_key(),
+            output_message.partition_key()
+        );
+        let_assert!(
+            Envelope {
+                command: Command::AttachInvocation(attach_invocation_request),
+                ..
+            } = output_message
+        );
+        assert_eq!(invocation_id, attach_invocation_request.invocation_id);
+        assert!(attach_invocation_request.block_on_inflight);
+        let_assert!(
+            ServiceInvocationResponseSink::Ingress { request_id, .. } =
+                attach_invocation_request.response_sink
+        );
+
+        // The response is routed back using the request id of the attached sink
+        let response = Bytes::from_static(b"vmoaifnuei");
+        network_tx
+            .send(IngressDispatcherInput::Response(IngressResponse {
+                target_node: GenerationalNodeId::new(0, 0),
+                request_id,
+                invocation_id,
+                response: ResponseResult::Success(response.clone()),
+            }))
+            .await
+            .unwrap();
+
+        assert_that!(res.await.unwrap(), ok(eq(response)));
+    }
+
+    #[test(tokio::test)]
+    async fn delayed_background_invocation() {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        let (output_tx, mut output_rx) = mpsc::channel(2);
+
+        let ingress_dispatcher = Service::new(1);
+        let handler_tx = ingress_dispatcher.create_ingress_request_sender();
+        let network_tx = ingress_dispatcher.create_ingress_dispatcher_input_sender();
+
+        // Start the dispatcher loop
+        tc.spawn(
+            TaskKind::SystemService,
+            "ingress-dispatcher",
+            None,
+            ingress_dispatcher.run(output_tx),
+        )
+        .unwrap();
+
+        let fid = FullInvocationId::generate(ServiceId::new("MySvc", "MyKey"));
+        let execution_time = MillisSinceEpoch::new(1_700_000_000_000);
+        let (invocation, ack_rx) = IngressRequest::background_invocation(
+            fid.clone(),
+            "pippo",
+            Bytes::default(),
+            SpanRelation::None,
+            None,
+            IdempotencyMode::None,
+            Some(execution_time),
         );
+        handler_tx.send(invocation).unwrap();
+
+        // The invocation is wrapped in a timer owned by the partition of the target service
+        let output_message = output_rx.recv().await.unwrap();
+        assert_eq!(fid.partition_key(), output_message.partition_key());
+        let_assert!(
+            Envelope {
+                command: Command::ScheduleTimer(timer),
+                ..
+            } = output_message
+        );
+        assert_eq!(execution_time, timer.wake_up_time());
+        assert_eq!(fid.invocation_uuid, timer.key().invocation_uuid);
+        let_assert!(Timer::Invoke(_, service_invocation) = timer.value());
+        assert_eq!(fid, service_invocation.fid);
+        assert!(service_invocation.response_sink.is_none());
+
+        // The request is acknowledged once the timer has been registered
+        network_tx
+            .send(IngressDispatcherInput::message_ack(0))
+            .await
+            .unwrap();
+        ack_rx.await.unwrap();
     }
 }
@@ -31,9 +31,8 @@ use restate_pb::grpc::health;
 use restate_schema_api::json::JsonMapperResolver;
 use restate_schema_api::key::KeyExtractor;
 use restate_schema_api::proto_symbol::ProtoSymbolResolver;
-use restate_schema_api::service::ServiceMetadataResolver;
+use restate_schema_api::service::{InstanceType, ServiceMetadata, ServiceMetadataResolver};
 use restate_types::errors::InvocationError;
-use restate_types::identifiers::ServiceId;
 use restate_types::invocation::SpanRelation;
 use std::sync::Arc;
 use std::task::Poll;
@@ -333,12 +332,22 @@ where
                     })?;
 
 
-                let fid = FullInvocationId::generate(ServiceId::new(service_name, key));
                 let span_relation = SpanRelation::Parent(ingress_span_context);
 
                 // Check if Idempotency-Key is available
                 let idempotency_mode = parse_idempotency_key_and_retention_period(req_headers.metadata)?;
 
+                // Craft FullInvocationId, idempotent requests to unkeyed services ignore the random key
+                let is_unkeyed = matches!(
+                    schemas.resolve_latest_service_metadata(&service_name),
+                    Some(ServiceMetadata { instance_type: InstanceType::Unkeyed, .. })
+                );
+                let fid = idempotency_mode.full_invocation_id(
+                    service_name,
+                    (!is_unkeyed).then_some(key),
+                    method_name.clone(),
+                );
+
                 // Send the service invocation
                 let (invocation, response_rx) = IngressRequest::invocation(
                     fid,
@@ -360,22 +369,13 @@ where
                     return Err(Status::unavailable("Unavailable"));
                 };
 
-                // Prepare response metadata
-                let mut metadata = MetadataMap::new();
-                if let Some(expire_time) = response.idempotency_expire_time() {
-                    metadata.insert(
-                        MetadataKey::from_static("idempotency-expires"),
-                        expire_time.parse().expect("A RFC3339 string should be valid!")
-                    );
-                }
-
-                match response.into() {
+                match response {
                     Ok(response_payload) => {
                         trace!(rpc.response = ?response_payload, "Complete external gRPC request successfully");
-                        Ok(HandlerResponse::from_parts(metadata, response_payload))
+                        Ok(HandlerResponse::from_parts(MetadataMap::new(), response_payload))
                     },
                     Err(error) => {
-                        let status = Status::with_metadata(error.code().into(), error.message(), metadata);
+                        let status = Status::new(error.code().into(), error.message());
                         info!(rpc.grpc.status_code = ?status.code(), rpc.grpc.status_message = ?status.message(), "Complete external gRPC request with a failure");
                         Err(status)
                     }
@@ -435,9 +435,10 @@ fn parse_idempotency_key_and_retention_period(
 ) -> Result<IdempotencyMode, Status> {
     let idempotency_key =
         if let Some(idempotency_key) = headers.get(MetadataKey::from_static("idempotency-key")) {
-            idempotency_key.to_bytes().map_err(|e| {
-                Status::invalid_argument(format!("bad idempotency id format: {}", e))
-            })?
+            idempotency_key
+                .to_str()
+                .map_err(|e| Status::invalid_argument(format!("bad idempotency id format: {}", e)))?
+                .to_owned()
         } else {
             return Ok(IdempotencyMode::None);
         };
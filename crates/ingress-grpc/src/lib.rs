@@ -23,7 +23,6 @@ pub use server::{HyperServerIngress, IngressServerError, StartSignal};
 
 use bytes::Bytes;
 use opentelemetry::Context;
-use restate_types::identifiers::FullInvocationId;
 use tonic::metadata::MetadataMap;
 use tonic::Status;
 
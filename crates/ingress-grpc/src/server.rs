@@ -194,14 +194,11 @@ mod tests {
             assert_eq!(&greeting_req.person, "Francesco");
 
             response_tx
-                .send(
-                    Ok(restate_pb::mocks::greeter::GreetingResponse {
-                        greeting: "Igal".to_string(),
-                    }
-                    .encode_to_vec()
-                    .into())
-                    .into(),
-                )
+                .send(Ok(restate_pb::mocks::greeter::GreetingResponse {
+                    greeting: "Igal".to_string(),
+                }
+                .encode_to_vec()
+                .into()))
                 .unwrap();
         });
 
@@ -254,9 +251,7 @@ mod tests {
             let greeting_req =
                 restate_pb::mocks::greeter::GreetingRequest::decode(&mut argument).unwrap();
             assert_eq!(&greeting_req.person, "Francesco");
-            response_tx
-                .send(Ok(encoded_greeting_response).into())
-                .unwrap();
+            response_tx.send(Ok(encoded_greeting_response)).unwrap();
         });
 
         let mut client = restate_pb::mocks::greeter::greeter_client::GreeterClient::connect(
@@ -298,13 +293,8 @@ mod tests {
             let greeting_req =
                 restate_pb::mocks::greeter::GreetingRequest::decode(&mut argument).unwrap();
             assert_eq!(&greeting_req.person, "Francesco");
-            assert_eq!(
-                idempotency_mode,
-                IdempotencyMode::key(Bytes::from_static(b"123456"), None)
-            );
-            response_tx
-                .send(Ok(encoded_greeting_response).into())
-                .unwrap();
+            assert_eq!(idempotency_mode, IdempotencyMode::key("123456", None));
+            response_tx.send(Ok(encoded_greeting_response)).unwrap();
         });
 
         let mut client = restate_pb::mocks::greeter::greeter_client::GreeterClient::connect(
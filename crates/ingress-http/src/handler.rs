@@ -10,6 +10,7 @@
 
 use super::*;
 use std::num::ParseIntError;
+use std::str::FromStr;
 use std::string;
 
 use crate::metric_definitions::{
@@ -23,24 +24,27 @@ use metrics::{counter, histogram};
 use opentelemetry::propagation::{Extractor, TextMapPropagator};
 use opentelemetry::sdk::propagation::TraceContextPropagator;
 use opentelemetry::trace::{SpanContext, TraceContextExt};
-use restate_ingress_dispatcher::{IdempotencyMode, IngressRequest, IngressRequestSender};
+use restate_ingress_dispatcher::{
+    IdempotencyMode, IngressRequest, IngressRequestSender, IngressResponseReceiver,
+};
 use restate_schema_api::component::{ComponentMetadataResolver, ComponentType};
-use restate_types::errors::UserErrorCode;
-use restate_types::identifiers::{InvocationId, ServiceId};
+use restate_types::errors::{IdDecodeError, UserErrorCode};
+use restate_types::identifiers::InvocationId;
 use restate_types::invocation::SpanRelation;
+use restate_types::time::MillisSinceEpoch;
 use serde::Serialize;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::Semaphore;
 use tracing::{debug, info, info_span, trace, warn, Instrument};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
-use uuid::Uuid;
 
 const APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/json");
 const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
 const IDEMPOTENCY_RETENTION_PERIOD: HeaderName =
     HeaderName::from_static("idempotency-retention-period");
-const IDEMPOTENCY_EXPIRES: HeaderName = HeaderName::from_static("idempotency-expires");
+const DELAY_QUERY_PARAM: &str = "delay";
+const EXECUTE_AT_QUERY_PARAM: &str = "execute_at";
 const WILDCARD: HeaderValue = HeaderValue::from_static("*");
 const TRUE: HeaderValue = HeaderValue::from_static("true");
 
@@ -54,20 +58,28 @@ pub(crate) enum HandlerError {
         "bad path, expected either /:service-name/:handler or /:object-name/:object-key/:handler"
     )]
     BadPath,
+    #[error(
+        "bad path, expected either /restate/invocation/:invocation-id/attach or /restate/invocation/:invocation-id/output"
+    )]
+    BadInvocationPath,
+    #[error("bad path, cannot decode invocation id: {0:?}")]
+    BadInvocationId(IdDecodeError),
     #[error("bad path, cannot decode key: {0:?}")]
     UrlDecodingError(string::FromUtf8Error),
     #[error("the invoked component is not public")]
     PrivateComponent,
     #[error("bad idempotency header: {0:?}")]
     BadIdempotency(anyhow::Error),
+    #[error(
+        "bad send delay, expected either the 'delay' or the 'execute_at' query parameter: {0:?}"
+    )]
+    BadSendDelay(anyhow::Error),
     #[error("cannot read body: {0:?}")]
     Body(anyhow::Error),
     #[error("unavailable")]
     Unavailable,
     #[error("method not allowed")]
     MethodNotAllowed,
-    #[error("using the idempotency key and send together is not yet supported")]
-    SendAndIdempotencyKey,
 }
 
 impl HandlerError {
@@ -76,13 +88,15 @@ impl HandlerError {
             HandlerError::NotFound => StatusCode::NOT_FOUND,
             HandlerError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
             HandlerError::BadPath => StatusCode::BAD_REQUEST,
+            HandlerError::BadInvocationPath => StatusCode::BAD_REQUEST,
+            HandlerError::BadInvocationId(_) => StatusCode::BAD_REQUEST,
             HandlerError::PrivateComponent => StatusCode::BAD_REQUEST,
             HandlerError::BadIdempotency(_) => StatusCode::BAD_REQUEST,
+            HandlerError::BadSendDelay(_) => StatusCode::BAD_REQUEST,
             HandlerError::Body(_) => StatusCode::INTERNAL_SERVER_ERROR,
             HandlerError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
             HandlerError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
             HandlerError::UrlDecodingError(_) => StatusCode::BAD_REQUEST,
-            HandlerError::SendAndIdempotencyKey => StatusCode::NOT_IMPLEMENTED,
         };
 
         Response::builder()
@@ -193,6 +207,19 @@ where
         if path_parts.is_empty() {
             return Err(HandlerError::NotFound);
         }
+        if let ["restate", "invocation", invocation_path_parts @ ..] = path_parts.as_slice() {
+            if req.method() != Method::GET {
+                return Err(HandlerError::MethodNotAllowed);
+            }
+            let (invocation_id, block_on_inflight) =
+                parse_invocation_request_path_chunks(invocation_path_parts)?;
+            let result =
+                Self::handle_invocation_output(invocation_id, block_on_inflight, self.request_tx)
+                    .await;
+            // We hold the semaphore permit up to the end of the request processing
+            drop(permit);
+            return result;
+        }
         let component_name = path_parts[0].to_string();
 
         // Parse the rest of the path chunks
@@ -221,16 +248,6 @@ where
             return Err(HandlerError::NotFound);
         }
 
-        // Craft FullInvocationId
-        let fid = if let Some(key) = key {
-            FullInvocationId::generate(ServiceId::new(component_name.clone(), key.to_owned()))
-        } else {
-            FullInvocationId::generate(ServiceId::new(
-                component_name.clone(),
-                Uuid::now_v7().to_string(),
-            ))
-        };
-
         let (client_addr, client_port) = (connect_info.address(), connect_info.port());
 
         // Create the ingress span and attach it to the next async block.
@@ -258,6 +275,7 @@ where
         // We need the context to link it to the service invocation span
         let ingress_span_context = ingress_span.context().span().span_context().clone();
 
+        let cloned_component_name = component_name.clone();
         let cloned_handler_name = handler_name.clone();
         let handle_fut = async move {
             info!("Processing ingress request");
@@ -273,6 +291,20 @@ where
             // Check if Idempotency-Key is available
             let idempotency_mode = parse_idempotency_key_and_retention_period(req.headers())?;
 
+            // Craft FullInvocationId, idempotent requests to the same handler reuse the same id
+            let fid = idempotency_mode.full_invocation_id(
+                cloned_component_name,
+                key,
+                cloned_handler_name.clone(),
+            );
+
+            // Check if the send should be delayed
+            let execution_time = if matches!(request_type, RequestType::Send { .. }) {
+                parse_send_execution_time(req.uri().query(), SystemTime::now())?
+            } else {
+                None
+            };
+
             // Collect body
             let collected_request_bytes = req
                 .into_body()
@@ -301,6 +333,7 @@ where
                         fid,
                         cloned_handler_name,
                         idempotency_mode,
+                        execution_time,
                         collected_request_bytes,
                         span_relation,
                         self.request_tx,
@@ -356,6 +389,31 @@ where
             return Err(HandlerError::Unavailable);
         }
 
+        Self::await_response(response_rx).await
+    }
+
+    /// Handles `/restate/invocation/:invocation-id/attach`, which waits for the invocation to
+    /// complete, and `/restate/invocation/:invocation-id/output`, which fails if the invocation
+    /// is still in flight.
+    async fn handle_invocation_output(
+        invocation_id: InvocationId,
+        block_on_inflight: bool,
+        request_tx: IngressRequestSender,
+    ) -> Result<Response<Either<Empty<Bytes>, Full<Bytes>>>, HandlerError> {
+        let (attach, response_rx) = IngressRequest::attach(invocation_id, block_on_inflight);
+        if request_tx.send(attach).is_err() {
+            debug!(
+                "Ingress dispatcher is closed while there is still an attach request in flight."
+            );
+            return Err(HandlerError::Unavailable);
+        }
+
+        Self::await_response(response_rx).await
+    }
+
+    async fn await_response(
+        response_rx: IngressResponseReceiver,
+    ) -> Result<Response<Either<Empty<Bytes>, Full<Bytes>>>, HandlerError> {
         // Wait on response
         let response = if let Ok(response) = response_rx.await {
             response
@@ -367,12 +425,7 @@ where
         // Prepare response metadata
         let mut response_builder = hyper::Response::builder();
 
-        // Add idempotency expiry time if available
-        if let Some(expire_time) = response.idempotency_expire_time() {
-            response_builder = response_builder.header(IDEMPOTENCY_EXPIRES, expire_time);
-        }
-
-        match response.into() {
+        match response {
             Ok(response_payload) => {
                 trace!(rpc.response = ?response_payload, "Complete external HTTP request successfully");
                 // TODO this is a temporary solution until we have some format awareness.
@@ -403,15 +456,11 @@ where
         fid: FullInvocationId,
         handler_name: String,
         idempotency_mode: IdempotencyMode,
+        execution_time: Option<MillisSinceEpoch>,
         collected_request_bytes: Bytes,
         span_relation: SpanRelation,
         request_tx: IngressRequestSender,
     ) -> Result<Response<Either<Empty<Bytes>, Full<Bytes>>>, HandlerError> {
-        if !matches!(idempotency_mode, IdempotencyMode::None) {
-            // TODO https://github.com/restatedev/restate/issues/1233
-            return Err(HandlerError::SendAndIdempotencyKey);
-        }
-
         let invocation_id = InvocationId::from(&fid);
 
         // Send the service invocation and wait on ack
@@ -421,6 +470,8 @@ where
             collected_request_bytes,
             span_relation,
             None,
+            idempotency_mode,
+            execution_time,
         );
         if request_tx.send(invocation).is_err() {
             debug!("Ingress dispatcher is closed while there is still an invocation in flight.");
@@ -522,13 +573,27 @@ fn parse_component_request_path_chunks<'a>(
         return Ok((key, RequestType::Call { handler }));
     }
     if path_parts.len() == 1 && path_parts[0].eq_ignore_ascii_case("send") {
-        // TODO add support for parsing delay
         return Ok((key, RequestType::Send { handler }));
     }
 
     Err(HandlerError::BadPath)
 }
 
+fn parse_invocation_request_path_chunks(
+    path_parts: &[&str],
+) -> Result<(InvocationId, bool), HandlerError> {
+    let (invocation_id, block_on_inflight) = match path_parts {
+        [invocation_id, "attach"] => (invocation_id, true),
+        [invocation_id, "output"] => (invocation_id, false),
+        _ => return Err(HandlerError::BadInvocationPath),
+    };
+
+    Ok((
+        InvocationId::from_str(invocation_id).map_err(HandlerError::BadInvocationId)?,
+        block_on_inflight,
+    ))
+}
+
 fn span_relation(request_span: &SpanContext) -> SpanRelation {
     if request_span.is_valid() {
         SpanRelation::Parent(request_span.clone())
@@ -541,7 +606,10 @@ fn parse_idempotency_key_and_retention_period(
     headers: &HeaderMap,
 ) -> Result<IdempotencyMode, HandlerError> {
     let idempotency_key = if let Some(idempotency_key) = headers.get(IDEMPOTENCY_KEY) {
-        Bytes::copy_from_slice(idempotency_key.as_bytes())
+        idempotency_key
+            .to_str()
+            .map_err(|e| HandlerError::BadIdempotency(e.into()))?
+            .to_owned()
     } else {
         return Ok(IdempotencyMode::None);
     };
@@ -563,6 +631,44 @@ fn parse_idempotency_key_and_retention_period(
     }
 }
 
+/// Parses the time at which a send should be executed from the `delay` (e.g. `10s` or `1h 30m`)
+/// or the `execute_at` (RFC 3339 timestamp) query parameter. Returns `None` if the send should be
+/// executed immediately.
+fn parse_send_execution_time(
+    query: Option<&str>,
+    now: SystemTime,
+) -> Result<Option<MillisSinceEpoch>, HandlerError> {
+    let mut execution_time = None;
+
+    for (name, value) in query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter_map(|param| param.split_once('='))
+    {
+        let value = urlencoding::decode(value).map_err(|e| HandlerError::BadSendDelay(e.into()))?;
+        let parsed_time = match name {
+            DELAY_QUERY_PARAM => {
+                now + humantime::parse_duration(&value)
+                    .map_err(|e| HandlerError::BadSendDelay(e.into()))?
+            }
+            EXECUTE_AT_QUERY_PARAM => humantime::parse_rfc3339_weak(&value)
+                .map_err(|e| HandlerError::BadSendDelay(e.into()))?,
+            _ => continue,
+        };
+
+        if execution_time.replace(parsed_time).is_some() {
+            return Err(HandlerError::BadSendDelay(anyhow::anyhow!(
+                "only one of the parameters can be specified"
+            )));
+        }
+    }
+
+    // Sends whose execution time has already passed are executed immediately
+    Ok(execution_time
+        .filter(|execution_time| *execution_time > now)
+        .map(MillisSinceEpoch::from))
+}
+
 fn invocation_status_code_to_http_status_code(code: UserErrorCode) -> StatusCode {
     match code {
         UserErrorCode::Cancelled => StatusCode::REQUEST_TIMEOUT,
@@ -610,6 +716,8 @@ mod tests {
     use super::*;
 
     use restate_core::TestCoreEnv;
+    use restate_types::errors::NOT_READY_INVOCATION_ERROR;
+    use restate_types::identifiers::ServiceId;
     use tokio::sync::mpsc;
     use tracing_test::traced_test;
 
@@ -638,14 +746,11 @@ mod tests {
             restate_test_util::assert_eq!(&greeting_req.person, "Francesco");
 
             response_tx
-                .send(
-                    Ok(serde_json::to_vec(&GreetingResponse {
-                        greeting: "Igal".to_string(),
-                    })
-                    .unwrap()
-                    .into())
-                    .into(),
-                )
+                .send(Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into()))
                 .unwrap();
         })
         .await;
@@ -675,14 +780,11 @@ mod tests {
             assert!(argument.is_empty());
 
             response_tx
-                .send(
-                    Ok(serde_json::to_vec(&GreetingResponse {
-                        greeting: "Igal".to_string(),
-                    })
-                    .unwrap()
-                    .into())
-                    .into(),
-                )
+                .send(Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into()))
                 .unwrap();
         })
         .await;
@@ -720,14 +822,11 @@ mod tests {
             restate_test_util::assert_eq!(&greeting_req.person, "Francesco");
 
             response_tx
-                .send(
-                    Ok(serde_json::to_vec(&GreetingResponse {
-                        greeting: "Igal".to_string(),
-                    })
-                    .unwrap()
-                    .into())
-                    .into(),
-                )
+                .send(Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into()))
                 .unwrap();
         })
         .await;
@@ -810,6 +909,91 @@ mod tests {
         let _: SendResponse = serde_json::from_slice(&response_bytes).unwrap();
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn send_with_idempotency_key() {
+        let req = hyper::Request::builder()
+            .uri("http://localhost/greeter.GreeterObject/my-key/greet/send")
+            .method(Method::POST)
+            .header(IDEMPOTENCY_KEY, "123456")
+            .body(Empty::<Bytes>::default())
+            .unwrap();
+
+        let response = handle(req, |ingress_req| {
+            let (fid, _, _, _, ack_tx) = ingress_req.expect_background_invocation();
+            restate_test_util::assert_eq!(
+                fid,
+                IdempotencyMode::key("123456", None).full_invocation_id(
+                    "greeter.GreeterObject",
+                    Some("my-key"),
+                    "greet"
+                )
+            );
+
+            ack_tx.send(()).unwrap();
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn send_service_with_delay() {
+        let req = hyper::Request::builder()
+            .uri("http://localhost/greeter.Greeter/greet/send?delay=1h")
+            .method(Method::POST)
+            .body(Empty::<Bytes>::default())
+            .unwrap();
+
+        let before = MillisSinceEpoch::from(SystemTime::now() + Duration::from_secs(3600));
+        let response = handle(req, move |ingress_req| {
+            let execution_time = ingress_req
+                .execution_time()
+                .expect("send should be delayed");
+            assert!(execution_time >= before);
+
+            let (_, _, _, _, ack_tx) = ingress_req.expect_background_invocation();
+            ack_tx.send(()).unwrap();
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[test]
+    fn send_execution_time_parsing() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+
+        assert_eq!(None, parse_send_execution_time(None, now).unwrap());
+        assert_eq!(
+            None,
+            parse_send_execution_time(Some("other=value"), now).unwrap()
+        );
+        assert_eq!(
+            Some(MillisSinceEpoch::new(1_090_000)),
+            parse_send_execution_time(Some("delay=1m%2030s"), now).unwrap()
+        );
+        assert_eq!(
+            Some(MillisSinceEpoch::new(1_700_000_000_000)),
+            parse_send_execution_time(Some("execute_at=2023-11-14T22:13:20Z"), now).unwrap()
+        );
+        // execution times in the past are executed immediately
+        assert_eq!(
+            None,
+            parse_send_execution_time(Some("execute_at=1970-01-01T00:00:00Z"), now).unwrap()
+        );
+
+        assert!(matches!(
+            parse_send_execution_time(Some("delay=soon"), now),
+            Err(HandlerError::BadSendDelay(_))
+        ));
+        assert!(matches!(
+            parse_send_execution_time(Some("delay=1s&execute_at=2023-11-14T22:13:20Z"), now),
+            Err(HandlerError::BadSendDelay(_))
+        ));
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn idempotency_key_parsing() {
@@ -836,20 +1020,19 @@ mod tests {
             let greeting_req: GreetingRequest = serde_json::from_slice(&argument).unwrap();
             restate_test_util::assert_eq!(&greeting_req.person, "Francesco");
 
+            restate_test_util::assert_eq!(idempotency_mode, IdempotencyMode::key("123456", None));
+            // retries of the request target the same invocation
             restate_test_util::assert_eq!(
-                idempotency_mode,
-                IdempotencyMode::key(Bytes::from_static(b"123456"), None)
+                fid,
+                idempotency_mode.full_invocation_id("greeter.Greeter", None::<Bytes>, "greet")
             );
 
             response_tx
-                .send(
-                    Ok(serde_json::to_vec(&GreetingResponse {
-                        greeting: "Igal".to_string(),
-                    })
-                    .unwrap()
-                    .into())
-                    .into(),
-                )
+                .send(Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into()))
                 .unwrap();
         })
         .await;
@@ -955,6 +1138,91 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    #[traced_test]
+    async fn attach_invocation() {
+        let invocation_id = InvocationId::from(&FullInvocationId::generate(ServiceId::new(
+            "greeter.Greeter",
+            "",
+        )));
+
+        let req = hyper::Request::builder()
+            .uri(format!(
+                "http://localhost/restate/invocation/{invocation_id}/attach"
+            ))
+            .method(Method::GET)
+            .body(Empty::<Bytes>::default())
+            .unwrap();
+
+        let expected_invocation_id = invocation_id.clone();
+        let response = handle(req, move |ingress_req| {
+            let (invocation_id, block_on_inflight, response_tx) = ingress_req.expect_attach();
+            restate_test_util::assert_eq!(invocation_id, expected_invocation_id);
+            assert!(block_on_inflight);
+
+            response_tx
+                .send(Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into()))
+                .unwrap();
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let (_, response_body) = response.into_parts();
+        let response_bytes = response_body.collect().await.unwrap().to_bytes();
+        let response_value: GreetingResponse = serde_json::from_slice(&response_bytes).unwrap();
+        restate_test_util::assert_eq!(response_value.greeting, "Igal");
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn output_of_inflight_invocation() {
+        let invocation_id = InvocationId::from(&FullInvocationId::generate(ServiceId::new(
+            "greeter.Greeter",
+            "",
+        )));
+
+        let req = hyper::Request::builder()
+            .uri(format!(
+                "http://localhost/restate/invocation/{invocation_id}/output"
+            ))
+            .method(Method::GET)
+            .body(Empty::<Bytes>::default())
+            .unwrap();
+
+        let response = handle(req, |ingress_req| {
+            let (_, block_on_inflight, response_tx) = ingress_req.expect_attach();
+            assert!(!block_on_inflight);
+
+            response_tx.send(Err(NOT_READY_INVOCATION_ERROR)).unwrap();
+        })
+        .await;
+
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn bad_invocation_path() {
+        test_handle_with_status_code_error_response(
+            hyper::Request::get("http://localhost/restate/invocation/notanid/output")
+                .body(Empty::<Bytes>::default())
+                .unwrap(),
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+        test_handle_with_status_code_error_response(
+            hyper::Request::get("http://localhost/restate/invocation/notanid/unknown")
+                .body(Empty::<Bytes>::default())
+                .unwrap(),
+            StatusCode::BAD_REQUEST,
+        )
+        .await;
+    }
+
     #[tokio::test]
     #[traced_test]
     async fn health() {
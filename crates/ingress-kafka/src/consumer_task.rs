@@ -8,13 +8,15 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::subscription_status::SubscriptionStatusRegistry;
 use base64::Engine;
 use bytes::Bytes;
 use opentelemetry_api::trace::TraceContextExt;
-use rdkafka::consumer::{Consumer, DefaultConsumerContext, StreamConsumer};
+use rdkafka::consumer::{Consumer, ConsumerContext, StreamConsumer};
 use rdkafka::error::KafkaError;
 use rdkafka::message::BorrowedMessage;
-use rdkafka::{ClientConfig, Message};
+use rdkafka::statistics::Statistics;
+use rdkafka::{ClientConfig, ClientContext, Message};
 use restate_ingress_dispatcher::{
     DeduplicationId, EventError, IngressRequest, IngressRequestSender,
 };
@@ -25,10 +27,11 @@ use restate_schema_api::subscription::{
 use restate_types::identifiers::SubscriptionId;
 use restate_types::invocation::SpanRelation;
 use restate_types::message::MessageIndex;
+use restate_worker_api::SubscriptionPartitionStatus;
 use std::collections::HashMap;
 use std::fmt;
 use tokio::sync::oneshot;
-use tracing::{debug, info, info_span, Instrument};
+use tracing::{debug, info, info_span, warn, Instrument};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 #[derive(Debug, thiserror::Error)]
@@ -49,7 +52,46 @@ pub enum Error {
     IngressDispatcherClosed,
 }
 
-type MessageConsumer = StreamConsumer<DefaultConsumerContext>;
+type MessageConsumer = StreamConsumer<StatusReportingContext>;
+
+/// Consumer context reporting the consumer statistics and errors to the [`SubscriptionStatusRegistry`].
+struct StatusReportingContext {
+    subscription_id: SubscriptionId,
+    status: SubscriptionStatusRegistry,
+}
+
+impl ClientContext for StatusReportingContext {
+    fn stats(&self, statistics: Statistics) {
+        let partitions = statistics
+            .topics
+            .into_iter()
+            .flat_map(|(topic, topic_statistics)| {
+                topic_statistics
+                    .partitions
+                    .into_iter()
+                    // Partition -1 is the internal unassigned partition of librdkafka
+                    .filter(|(partition, statistics)| *partition >= 0 && statistics.desired)
+                    .map(move |(partition, statistics)| SubscriptionPartitionStatus {
+                        topic: topic.clone(),
+                        partition,
+                        // librdkafka uses negative values for invalid offsets
+                        committed_offset: u64::try_from(statistics.committed_offset).ok(),
+                        lag: u64::try_from(statistics.consumer_lag).ok(),
+                    })
+            })
+            .collect();
+        self.status
+            .notify_partitions(self.subscription_id, partitions);
+    }
+
+    fn error(&self, error: KafkaError, reason: &str) {
+        warn!("Kafka consumer error: {error}: {reason}");
+        self.status
+            .notify_error(self.subscription_id, format!("{error}: {reason}"));
+    }
+}
+
+impl ConsumerContext for StatusReportingContext {}
 
 pub struct KafkaDeduplicationId(String);
 
@@ -218,14 +260,21 @@ pub struct ConsumerTask {
     client_config: ClientConfig,
     topics: Vec<String>,
     sender: MessageSender,
+    status: SubscriptionStatusRegistry,
 }
 
 impl ConsumerTask {
-    pub fn new(client_config: ClientConfig, topics: Vec<String>, sender: MessageSender) -> Self {
+    pub fn new(
+        client_config: ClientConfig,
+        topics: Vec<String>,
+        sender: MessageSender,
+        status: SubscriptionStatusRegistry,
+    ) -> Self {
         Self {
             client_config,
             topics,
             sender,
+            status,
         }
     }
 
@@ -241,7 +290,12 @@ impl ConsumerTask {
             self.topics, self.client_config
         );
 
-        let consumer: MessageConsumer = self.client_config.create()?;
+        let consumer: MessageConsumer =
+            self.client_config
+                .create_with_context(StatusReportingContext {
+                    subscription_id: self.sender.subscription.id(),
+                    status: self.status.clone(),
+                })?;
         let topics: Vec<&str> = self.topics.iter().map(|x| &**x).collect();
         consumer.subscribe(&topics)?;
 
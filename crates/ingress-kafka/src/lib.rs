@@ -11,6 +11,7 @@
 mod consumer_task;
 mod options;
 mod subscription_controller;
+mod subscription_status;
 
 use tokio::sync::mpsc;
 
@@ -18,6 +19,7 @@ pub use options::{
     KafkaClusterOptions, Options, OptionsBuilder, OptionsBuilderError, ValidationError,
 };
 pub use subscription_controller::{Command, Error, Service};
+pub use subscription_status::SubscriptionStatusReader;
 
 pub type SubscriptionCommandSender = mpsc::Sender<Command>;
 pub type SubscriptionCommandReceiver = mpsc::Receiver<Command>;
@@ -14,6 +14,7 @@ use super::*;
 use std::collections::HashSet;
 
 use crate::subscription_controller::task_orchestrator::TaskOrchestrator;
+use crate::subscription_status::{SubscriptionStatusReader, SubscriptionStatusRegistry};
 use rdkafka::error::KafkaError;
 use restate_core::cancellation_watcher;
 use restate_ingress_dispatcher::IngressRequestSender;
@@ -41,6 +42,7 @@ pub enum Error {
 pub struct Service {
     options: Options,
     ingress_tx: IngressRequestSender,
+    status: SubscriptionStatusRegistry,
 
     commands_tx: SubscriptionCommandSender,
     commands_rx: SubscriptionCommandReceiver,
@@ -53,6 +55,7 @@ impl Service {
         Service {
             options,
             ingress_tx,
+            status: SubscriptionStatusRegistry::default(),
             commands_tx,
             commands_rx,
         }
@@ -62,16 +65,23 @@ impl Service {
         self.commands_tx.clone()
     }
 
+    pub fn status_reader(&self) -> SubscriptionStatusReader {
+        self.status.reader()
+    }
+
     pub async fn run(mut self) -> anyhow::Result<()> {
         let shutdown = cancellation_watcher();
         tokio::pin!(shutdown);
 
-        let mut task_orchestrator = TaskOrchestrator::new(RetryPolicy::exponential(
-            Duration::from_millis(200),
-            2.0,
-            usize::MAX,
-            Some(Duration::from_secs(10)),
-        ));
+        let mut task_orchestrator = TaskOrchestrator::new(
+            RetryPolicy::exponential(
+                Duration::from_millis(200),
+                2.0,
+                usize::MAX,
+                Some(Duration::from_secs(10)),
+            ),
+            self.status.clone(),
+        );
 
         loop {
             tokio::select! {
@@ -122,6 +132,10 @@ impl Service {
         // see ConsumerTask::run
         client_config.set("enable.auto.commit", "true");
         client_config.set("enable.auto.offset.store", "false");
+        // Statistics are used to report the consumer status, see StatusReportingContext
+        if client_config.get("statistics.interval.ms").is_none() {
+            client_config.set("statistics.interval.ms", "10000");
+        }
 
         let subscription_id = subscription.id();
 
@@ -130,6 +144,7 @@ impl Service {
             client_config,
             vec![topic.to_string()],
             MessageSender::new(subscription, self.ingress_tx.clone()),
+            self.status.clone(),
         );
 
         task_orchestrator.start(subscription_id, consumer_task);
@@ -167,6 +182,7 @@ impl Service {
 
 mod task_orchestrator {
     use crate::consumer_task;
+    use crate::subscription_status::SubscriptionStatusRegistry;
     use restate_timer_queue::TimerQueue;
     use restate_types::identifiers::SubscriptionId;
     use restate_types::retries::{RetryIter, RetryPolicy};
@@ -198,16 +214,18 @@ mod task_orchestrator {
         subscription_id_to_task_state: HashMap<SubscriptionId, TaskState>,
         tasks: JoinSet<Result<(), consumer_task::Error>>,
         timer_queue: TimerQueue<SubscriptionId>,
+        status: SubscriptionStatusRegistry,
     }
 
     impl TaskOrchestrator {
-        pub(super) fn new(retry_policy: RetryPolicy) -> Self {
+        pub(super) fn new(retry_policy: RetryPolicy, status: SubscriptionStatusRegistry) -> Self {
             Self {
                 retry_policy,
                 running_tasks_to_subscriptions: HashMap::default(),
                 subscription_id_to_task_state: HashMap::default(),
                 tasks: JoinSet::default(),
                 timer_queue: TimerQueue::default(),
+                status,
             }
         }
 
@@ -233,20 +251,20 @@ mod task_orchestrator {
             match result {
                 Ok((id, Ok(_))) => {
                     warn!("Consumer unexpectedly closed");
-                    self.start_retry_timer(id);
+                    self.start_retry_timer(id, "consumer unexpectedly closed".to_owned());
                 }
                 Ok((id, Err(e))) => {
                     warn!("Consumer unexpectedly closed with reason: {e}");
-                    self.start_retry_timer(id);
+                    self.start_retry_timer(id, e.to_string());
                 }
                 Err(e) => {
                     warn!("Consumer unexpectedly panicked with reason: {e}");
-                    self.start_retry_timer(e.id());
+                    self.start_retry_timer(e.id(), e.to_string());
                 }
             };
         }
 
-        fn start_retry_timer(&mut self, task_id: task::Id) {
+        fn start_retry_timer(&mut self, task_id: task::Id, error: String) {
             let subscription_id = if let Some(subscription_id) =
                 self.running_tasks_to_subscriptions.remove(&task_id)
             {
@@ -255,6 +273,7 @@ mod task_orchestrator {
                 // No need to do anything, as it's a correct closure
                 return;
             };
+            self.status.notify_failed(subscription_id, error);
 
             let task_state = self
                 .subscription_id_to_task_state
@@ -317,6 +336,7 @@ mod task_orchestrator {
                 subscription_id
             );
             let task_id = self.tasks.spawn(consumer_task_clone.clone().run(rx)).id();
+            self.status.notify_started(subscription_id);
 
             self.running_tasks_to_subscriptions
                 .insert(task_id, subscription_id);
@@ -341,6 +361,7 @@ mod task_orchestrator {
             {
                 self.running_tasks_to_subscriptions.remove(&task_id);
             }
+            self.status.notify_stopped(subscription_id);
         }
 
         pub(super) async fn shutdown(&mut self) {
@@ -0,0 +1,101 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use restate_types::identifiers::SubscriptionId;
+use restate_worker_api::{
+    SubscriptionPartitionStatus, SubscriptionStatusHandle, SubscriptionStatusReport,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+#[derive(Debug, Default)]
+struct SubscriptionStatus {
+    running: bool,
+    partitions: Vec<SubscriptionPartitionStatus>,
+    last_error: Option<(String, SystemTime)>,
+}
+
+type SubscriptionStatuses = Arc<Mutex<HashMap<SubscriptionId, SubscriptionStatus>>>;
+
+/// Status of the subscriptions run by the subscription controller.
+///
+/// It's updated by the consumer tasks and by the task orchestrator,
+/// and it's read through the [`SubscriptionStatusReader`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SubscriptionStatusRegistry(SubscriptionStatuses);
+
+impl SubscriptionStatusRegistry {
+    pub(crate) fn reader(&self) -> SubscriptionStatusReader {
+        SubscriptionStatusReader(Arc::clone(&self.0))
+    }
+
+    pub(crate) fn notify_started(&self, subscription_id: SubscriptionId) {
+        // Keep the last error across restarts of the consumer
+        let mut guard = self.0.lock().unwrap();
+        let status = guard.entry(subscription_id).or_default();
+        status.running = true;
+        status.partitions.clear();
+    }
+
+    pub(crate) fn notify_stopped(&self, subscription_id: SubscriptionId) {
+        self.0.lock().unwrap().remove(&subscription_id);
+    }
+
+    pub(crate) fn notify_failed(&self, subscription_id: SubscriptionId, error: String) {
+        if let Some(status) = self.0.lock().unwrap().get_mut(&subscription_id) {
+            status.running = false;
+            status.partitions.clear();
+            status.last_error = Some((error, SystemTime::now()));
+        }
+    }
+
+    pub(crate) fn notify_error(&self, subscription_id: SubscriptionId, error: String) {
+        if let Some(status) = self.0.lock().unwrap().get_mut(&subscription_id) {
+            status.last_error = Some((error, SystemTime::now()));
+        }
+    }
+
+    pub(crate) fn notify_partitions(
+        &self,
+        subscription_id: SubscriptionId,
+        partitions: Vec<SubscriptionPartitionStatus>,
+    ) {
+        if let Some(status) = self.0.lock().unwrap().get_mut(&subscription_id) {
+            status.partitions = partitions;
+        }
+    }
+}
+
+/// Read-only [`SubscriptionStatusHandle`] to the [`SubscriptionStatusRegistry`].
+#[derive(Debug, Clone)]
+pub struct SubscriptionStatusReader(SubscriptionStatuses);
+
+impl SubscriptionStatusHandle for SubscriptionStatusReader {
+    type Iterator = std::vec::IntoIter<SubscriptionStatusReport>;
+
+    async fn read_status(&self) -> Self::Iterator {
+        let guard = self.0.lock().unwrap();
+        guard
+            .iter()
+            .map(|(id, status)| {
+                let (last_error, last_error_at) = status.last_error.clone().unzip();
+                SubscriptionStatusReport {
+                    id: *id,
+                    running: status.running,
+                    partitions: status.partitions.clone(),
+                    last_error,
+                    last_error_at,
+                }
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
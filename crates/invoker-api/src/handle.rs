@@ -11,6 +11,7 @@
 use super::Effect;
 use super::JournalMetadata;
 
+use bytestring::ByteString;
 use restate_errors::NotRunningError;
 use restate_types::identifiers::{EntryIndex, PartitionLeaderEpoch};
 use restate_types::identifiers::{FullInvocationId, PartitionKey};
@@ -36,6 +37,7 @@ pub trait ServiceHandle {
         &mut self,
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
+        method: ByteString,
         journal: InvokeInputJournal,
     ) -> Self::Future;
 
@@ -43,6 +45,7 @@ pub trait ServiceHandle {
         &mut self,
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
+        method: ByteString,
         journal: InvokeInputJournal,
     ) -> Self::Future;
 
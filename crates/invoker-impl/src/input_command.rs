@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use bytestring::ByteString;
 use restate_errors::NotRunningError;
 use restate_invoker_api::{
     Effect, InvocationStatusReport, InvokeInputJournal, ServiceHandle, StatusHandle,
@@ -25,6 +26,7 @@ use tokio::sync::mpsc;
 pub(crate) struct InvokeCommand {
     pub(super) partition: PartitionLeaderEpoch,
     pub(super) full_invocation_id: FullInvocationId,
+    pub(super) method: ByteString,
     #[serde(skip)]
     pub(super) journal: InvokeInputJournal,
 }
@@ -84,6 +86,7 @@ impl ServiceHandle for ChannelServiceHandle {
         &mut self,
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
+        method: ByteString,
         journal: InvokeInputJournal,
     ) -> Self::Future {
         futures::future::ready(
@@ -91,6 +94,7 @@ impl ServiceHandle for ChannelServiceHandle {
                 .send(InputCommand::Invoke(InvokeCommand {
                     partition,
                     full_invocation_id,
+                    method,
                     journal,
                 }))
                 .map_err(|_| NotRunningError),
@@ -101,6 +105,7 @@ impl ServiceHandle for ChannelServiceHandle {
         &mut self,
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
+        method: ByteString,
         journal: InvokeInputJournal,
     ) -> Self::Future {
         futures::future::ready(
@@ -108,6 +113,7 @@ impl ServiceHandle for ChannelServiceHandle {
                 .send(InputCommand::Invoke(InvokeCommand {
                     partition,
                     full_invocation_id,
+                    method,
                     journal,
                 }))
                 .map_err(|_| NotRunningError),
@@ -10,6 +10,7 @@
 
 use super::*;
 
+use restate_schema_api::retry_policy::OnMaxAttempts;
 use restate_types::identifiers::DeploymentId;
 use restate_types::journal::Completion;
 use restate_types::retries;
@@ -21,8 +22,12 @@ use tokio::task::AbortHandle;
 /// Component encapsulating the business logic of the invocation state machine
 #[derive(Debug)]
 pub(super) struct InvocationStateMachine {
+    // key of the concurrency quota slot held by the invocation
+    quota_key: QuotaKey,
     invocation_state: InvocationState,
     retry_iter: retries::RetryIter,
+    on_max_attempts: OnMaxAttempts,
+    attempts: u32,
 }
 
 /// This struct tracks which entries the invocation task generates,
@@ -127,10 +132,17 @@ impl fmt::Debug for InvocationState {
 }
 
 impl InvocationStateMachine {
-    pub(super) fn create(retry_policy: RetryPolicy) -> InvocationStateMachine {
+    pub(super) fn create(
+        quota_key: QuotaKey,
+        retry_policy: RetryPolicy,
+        on_max_attempts: OnMaxAttempts,
+    ) -> InvocationStateMachine {
         Self {
+            quota_key,
             invocation_state: InvocationState::New,
             retry_iter: retry_policy.into_iter(),
+            on_max_attempts,
+            attempts: 0,
         }
     }
 
@@ -144,6 +156,7 @@ impl InvocationStateMachine {
             InvocationState::New | InvocationState::WaitingRetry { .. }
         ));
 
+        self.attempts += 1;
         self.invocation_state = InvocationState::InFlight {
             notifications_tx: Some(notifications_tx),
             journal_tracker: Default::default(),
@@ -308,6 +321,19 @@ impl InvocationStateMachine {
         }
     }
 
+    /// Number of times the invocation task has been started.
+    pub(super) fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub(super) fn on_max_attempts(&self) -> OnMaxAttempts {
+        self.on_max_attempts
+    }
+
+    pub(super) fn quota_key(&self) -> &QuotaKey {
+        &self.quota_key
+    }
+
     #[inline]
     pub(super) fn invocation_state_debug(&self) -> impl fmt::Debug + '_ {
         &self.invocation_state
@@ -330,8 +356,11 @@ mod tests {
 
     #[test]
     fn handle_error_when_waiting_for_retry() {
-        let mut invocation_state_machine =
-            InvocationStateMachine::create(RetryPolicy::fixed_delay(Duration::from_secs(1), 10));
+        let mut invocation_state_machine = InvocationStateMachine::create(
+            QuotaKey::new(None, "MyService".into(), "greet".into()),
+            RetryPolicy::fixed_delay(Duration::from_secs(1), 10),
+            OnMaxAttempts::Fail,
+        );
 
         assert!(invocation_state_machine.handle_task_error().is_some());
         check!(let InvocationState::WaitingRetry { .. } = invocation_state_machine.invocation_state);
@@ -345,8 +374,11 @@ mod tests {
 
     #[test(tokio::test)]
     async fn handle_requires_ack() {
-        let mut invocation_state_machine =
-            InvocationStateMachine::create(RetryPolicy::fixed_delay(Duration::from_secs(1), 10));
+        let mut invocation_state_machine = InvocationStateMachine::create(
+            QuotaKey::new(None, "MyService".into(), "greet".into()),
+            RetryPolicy::fixed_delay(Duration::from_secs(1), 10),
+            OnMaxAttempts::Fail,
+        );
 
         let abort_handle = tokio::spawn(async {}).abort_handle();
         let (tx, mut rx) = mpsc::unbounded_channel();
@@ -23,6 +23,7 @@ use invocation_state_machine::InvocationStateMachine;
 use invocation_task::InvocationTask;
 use invocation_task::{InvocationTaskOutput, InvocationTaskOutputInner};
 use metrics::counter;
+use quota::{ConcurrencyLimits, InvokerConcurrencyQuota, QuotaKey};
 use restate_core::cancellation_watcher;
 use restate_errors::warn_it;
 use restate_invoker_api::{
@@ -30,13 +31,15 @@ use restate_invoker_api::{
     StateReader,
 };
 use restate_queue::SegmentQueue;
+use restate_schema_api::concurrency_limit::ConcurrencyLimitResolver;
 use restate_schema_api::deployment::DeploymentResolver;
+use restate_schema_api::retry_policy::{InvocationRetryPolicyResolver, OnMaxAttempts};
 use restate_timer_queue::TimerQueue;
-use restate_types::errors::InvocationError;
+use restate_types::errors::{InvocationError, RestateErrorCode, CANCELED_INVOCATION_ERROR};
 use restate_types::identifiers::{DeploymentId, FullInvocationId, PartitionKey, WithPartitionKey};
 use restate_types::identifiers::{EntryIndex, PartitionLeaderEpoch};
 use restate_types::journal::enriched::EnrichedRawEntry;
-use restate_types::journal::Completion;
+use restate_types::journal::{Completion, CompletionResult};
 use restate_types::retries::RetryPolicy;
 use status_store::InvocationStatusStore;
 use std::collections::{HashMap, HashSet};
@@ -71,6 +74,38 @@ trait InvokerError: std::error::Error {
     fn to_invocation_error(&self) -> InvocationError;
 }
 
+/// Error used when an invocation exhausted the attempts of its retry policy
+#[derive(Debug, thiserror::Error, codederror::CodedError)]
+#[error(
+    "the invocation exhausted {attempts} attempts, the last attempt failed with: {last_failure}"
+)]
+#[code(restate_errors::RT0010)]
+struct MaxAttemptsExceededError {
+    attempts: u32,
+    last_failure: InvocationError,
+}
+
+impl InvokerError for MaxAttemptsExceededError {
+    fn is_transient(&self) -> bool {
+        false
+    }
+
+    fn to_invocation_error(&self) -> InvocationError {
+        let mut err = InvocationError::new(
+            RestateErrorCode::MaxAttemptsExceeded,
+            format!(
+                "The invocation exhausted {} attempts, the last attempt failed with: {}",
+                self.attempts,
+                self.last_failure.message()
+            ),
+        );
+        if let Some(desc) = self.last_failure.description() {
+            err = err.with_description(desc);
+        }
+        err
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum Notification {
     Completion(Completion),
@@ -159,10 +194,14 @@ pub struct Service<JournalReader, StateReader, EntryEnricher, DeploymentRegistry
     // which is a rather internal thing we have only for mocking.
     inner: ServiceInner<
         DefaultInvocationTaskRunner<JournalReader, StateReader, EntryEnricher, DeploymentRegistry>,
+        DeploymentRegistry,
     >,
 }
 
-impl<JR, SR, EE, DMR> Service<JR, SR, EE, DMR> {
+impl<JR, SR, EE, DMR> Service<JR, SR, EE, DMR>
+where
+    DMR: Clone,
+{
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         deployment_metadata_resolver: DMR,
@@ -174,7 +213,7 @@ impl<JR, SR, EE, DMR> Service<JR, SR, EE, DMR> {
         message_size_limit: Option<usize>,
         client: ServiceClient,
         tmp_dir: PathBuf,
-        concurrency_limit: Option<usize>,
+        concurrency_limits: ConcurrencyLimits,
         journal_reader: JR,
         state_reader: SR,
         entry_enricher: EE,
@@ -199,12 +238,14 @@ impl<JR, SR, EE, DMR> Service<JR, SR, EE, DMR> {
                     journal_reader,
                     state_reader,
                     entry_enricher,
-                    deployment_metadata_resolver,
+                    deployment_metadata_resolver: deployment_metadata_resolver.clone(),
                 },
+                schemas: deployment_metadata_resolver,
                 retry_policy,
                 invocation_tasks: Default::default(),
                 retry_timers: Default::default(),
-                quota: quota::InvokerConcurrencyQuota::new(concurrency_limit),
+                quota: InvokerConcurrencyQuota::new(concurrency_limits),
+                paused_invocations: Default::default(),
                 status_store: Default::default(),
                 invocation_state_machine_manager: Default::default(),
             },
@@ -219,7 +260,12 @@ where
     SR: StateReader + Clone + Send + Sync + 'static,
     <SR as StateReader>::StateIter: Send,
     EE: EntryEnricher + Clone + Send + 'static,
-    EMR: DeploymentResolver + Clone + Send + 'static,
+    EMR: DeploymentResolver
+        + ConcurrencyLimitResolver
+        + InvocationRetryPolicyResolver
+        + Clone
+        + Send
+        + 'static,
 {
     pub fn handle(&self) -> ChannelServiceHandle {
         ChannelServiceHandle {
@@ -262,7 +308,7 @@ where
 }
 
 #[derive(Debug)]
-struct ServiceInner<InvocationTaskRunner> {
+struct ServiceInner<InvocationTaskRunner, Schemas> {
     input_rx: mpsc::UnboundedReceiver<InputCommand>,
 
     // Channel to communicate with invocation tasks
@@ -272,20 +318,26 @@ struct ServiceInner<InvocationTaskRunner> {
     // Invocation task factory
     invocation_task_runner: InvocationTaskRunner,
 
+    // Used to resolve the concurrency quotas and the retry policies of invocations
+    schemas: Schemas,
+
     // Invoker service arguments
     retry_policy: RetryPolicy,
 
     // Invoker state machine
     invocation_tasks: JoinSet<()>,
     retry_timers: TimerQueue<(PartitionLeaderEpoch, FullInvocationId)>,
-    quota: quota::InvokerConcurrencyQuota,
+    quota: InvokerConcurrencyQuota,
+    // Invocations which exhausted their retry attempts, and wait for a completion to be resumed
+    paused_invocations: HashMap<(PartitionLeaderEpoch, FullInvocationId), QuotaKey>,
     status_store: InvocationStatusStore,
     invocation_state_machine_manager: state_machine_manager::InvocationStateMachineManager,
 }
 
-impl<ITR> ServiceInner<ITR>
+impl<ITR, Schemas> ServiceInner<ITR, Schemas>
 where
     ITR: InvocationTaskRunner,
+    Schemas: DeploymentResolver + ConcurrencyLimitResolver + InvocationRetryPolicyResolver,
 {
     // Returns true if we should execute another step, false if we should stop executing steps
     async fn step<F>(
@@ -334,7 +386,7 @@ where
             },
 
             Some(invoke_input_command) = segmented_input_queue.dequeue(), if !segmented_input_queue.is_empty() && self.quota.is_slot_available() => {
-                self.handle_invoke(invoke_input_command.partition, invoke_input_command.full_invocation_id, invoke_input_command.journal).await;
+                self.handle_invoke_command(invoke_input_command).await;
             },
 
             Some(invocation_task_msg) = self.invocation_tasks_rx.recv() => {
@@ -392,6 +444,9 @@ where
                 return false;
             }
         }
+
+        self.start_waiting_invocations().await;
+
         // Execute next loop
         true
     }
@@ -418,6 +473,50 @@ where
         );
     }
 
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            rpc.service = %invoke_command.full_invocation_id.service_id.service_name,
+            restate.invocation.id = %invoke_command.full_invocation_id,
+            restate.invoker.partition_leader_epoch = ?invoke_command.partition,
+        )
+    )]
+    async fn handle_invoke_command(&mut self, invoke_command: InvokeCommand) {
+        // Invocations which are not pinned to a deployment yet count against the quota of the
+        // latest deployment of the service, which is the deployment the invocation task picks.
+        let service_name = &invoke_command.full_invocation_id.service_id.service_name;
+        let deployment_id = match &invoke_command.journal {
+            InvokeInputJournal::CachedJournal(journal_metadata, _) => {
+                journal_metadata.deployment_id
+            }
+            InvokeInputJournal::NoCachedJournal => None,
+        }
+        .or_else(|| {
+            self.schemas
+                .resolve_latest_deployment_for_service(service_name)
+                .map(|deployment| deployment.id)
+        });
+        let quota_key = QuotaKey::new(
+            deployment_id,
+            service_name.clone(),
+            invoke_command.method.clone(),
+        );
+
+        if self.quota.can_start(&self.schemas, &quota_key) {
+            self.handle_invoke(
+                invoke_command.partition,
+                invoke_command.full_invocation_id,
+                quota_key,
+                invoke_command.journal,
+            )
+            .await
+        } else {
+            trace!("Concurrency quota exhausted, the invocation waits for a free slot");
+            self.quota.park(quota_key, invoke_command);
+        }
+    }
+
     #[instrument(
         level = "trace",
         skip_all,
@@ -431,6 +530,7 @@ where
         &mut self,
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
+        quota_key: QuotaKey,
         journal: InvokeInputJournal,
     ) {
         debug_assert!(self
@@ -441,12 +541,23 @@ where
             .resolve_invocation(partition, &full_invocation_id)
             .is_none());
 
-        self.quota.reserve_slot();
+        let (retry_policy, on_max_attempts) = self
+            .schemas
+            .resolve_invocation_retry_policy(quota_key.component(), quota_key.handler())
+            .map(|policy| (policy.retry_policy, policy.on_max_attempts))
+            .unwrap_or_else(|| (self.retry_policy.clone(), OnMaxAttempts::Fail));
+
+        self.quota.reserve_slot(
+            &self.schemas,
+            partition,
+            full_invocation_id.clone(),
+            quota_key.clone(),
+        );
         self.start_invocation_task(
             partition,
             full_invocation_id,
             journal,
-            InvocationStateMachine::create(self.retry_policy.clone()),
+            InvocationStateMachine::create(quota_key, retry_policy, on_max_attempts),
         )
         .await
     }
@@ -607,6 +718,39 @@ where
                 "Notifying completion"
             );
             ism.notify_completion(completion);
+        } else if self
+            .paused_invocations
+            .contains_key(&(partition, full_invocation_id.clone()))
+        {
+            if completion.result != CompletionResult::from(&CANCELED_INVOCATION_ERROR) {
+                // The completion is stored in the journal, the invocation observes it once it
+                // is resumed.
+                trace!(
+                    restate.journal.index = completion.entry_index,
+                    "Keeping paused invocation paused after receiving a completion"
+                );
+                return;
+            }
+
+            // The cancellation is stored in the journal, so resuming the paused invocation lets
+            // it observe the cancellation. Killed invocations are aborted instead.
+            let quota_key = self
+                .paused_invocations
+                .remove(&(partition, full_invocation_id.clone()))
+                .expect("paused invocation must be present");
+            debug!(
+                restate.journal.index = completion.entry_index,
+                "Resuming paused invocation after it has been cancelled"
+            );
+            self.quota.park(
+                quota_key.clone(),
+                InvokeCommand {
+                    partition,
+                    full_invocation_id,
+                    method: quota_key.handler().clone(),
+                    journal: InvokeInputJournal::NoCachedJournal,
+                },
+            );
         } else {
             // If no state machine is registered, the PP will send a new invoke
             trace!("No state machine found for given completion");
@@ -633,7 +777,8 @@ where
         {
             counter!(INVOKER_INVOCATION_TASK, "status" => TASK_OP_COMPLETED).increment(1);
             trace!("Invocation task closed correctly");
-            self.quota.unreserve_slot();
+            self.quota
+                .unreserve_slot(&self.schemas, partition, &full_invocation_id);
             self.status_store.on_end(&partition, &full_invocation_id);
             let _ = sender
                 .send(Effect {
@@ -668,7 +813,8 @@ where
         {
             counter!(INVOKER_INVOCATION_TASK, "status" => TASK_OP_SUSPENDED).increment(1);
             trace!("Suspending invocation");
-            self.quota.unreserve_slot();
+            self.quota
+                .unreserve_slot(&self.schemas, partition, &full_invocation_id);
             self.status_store.on_end(&partition, &full_invocation_id);
             let _ = sender
                 .send(Effect {
@@ -725,6 +871,9 @@ where
         partition: PartitionLeaderEpoch,
         full_invocation_id: FullInvocationId,
     ) {
+        self.quota.remove_waiting(|waiting_partition, waiting_fid| {
+            waiting_partition == partition && waiting_fid == &full_invocation_id
+        });
         if let Some((_, mut ism)) = self
             .invocation_state_machine_manager
             .remove_invocation(partition, &full_invocation_id)
@@ -735,7 +884,19 @@ where
                 "Aborting invocation"
             );
             ism.abort();
-            self.quota.unreserve_slot();
+            self.quota
+                .unreserve_slot(&self.schemas, partition, &full_invocation_id);
+            self.status_store.on_end(&partition, &full_invocation_id);
+        } else if self
+            .paused_invocations
+            .remove(&(partition, full_invocation_id.clone()))
+            .is_some()
+        {
+            trace!(
+                rpc.service = %full_invocation_id.service_id.service_name,
+                restate.invocation.id = %full_invocation_id,
+                "Aborting paused invocation"
+            );
             self.status_store.on_end(&partition, &full_invocation_id);
         } else {
             trace!(
@@ -755,6 +916,17 @@ where
         )
     )]
     fn handle_abort_partition(&mut self, partition: PartitionLeaderEpoch) {
+        self.quota
+            .remove_waiting(|waiting_partition, _| waiting_partition == partition);
+        self.paused_invocations
+            .retain(|(paused_partition, fid), _| {
+                if *paused_partition == partition {
+                    self.status_store.on_end(&partition, fid);
+                    false
+                } else {
+                    true
+                }
+            });
         if let Some(invocation_state_machines) = self
             .invocation_state_machine_manager
             .remove_partition(partition)
@@ -766,7 +938,7 @@ where
                     "Aborting invocation"
                 );
                 ism.abort();
-                self.quota.unreserve_slot();
+                self.quota.unreserve_slot(&self.schemas, partition, &fid);
                 self.status_store.on_end(&partition, &fid);
             }
         } else {
@@ -789,6 +961,19 @@ where
 
     // --- Helpers
 
+    /// Starts the waiting invocations for which slots have been released in the meantime.
+    async fn start_waiting_invocations(&mut self) {
+        while let Some((quota_key, invoke_command)) = self.quota.next_runnable(&self.schemas) {
+            self.handle_invoke(
+                invoke_command.partition,
+                invoke_command.full_invocation_id,
+                quota_key,
+                invoke_command.journal,
+            )
+            .await;
+        }
+    }
+
     async fn handle_error_event<E: InvokerError + CodedError + Send + Sync + 'static>(
         &mut self,
         partition: PartitionLeaderEpoch,
@@ -824,31 +1009,87 @@ where
                 self.retry_timers
                     .sleep_until(next_retry_at, (partition, full_invocation_id));
             }
+            None if error.is_transient() => {
+                let error = MaxAttemptsExceededError {
+                    attempts: ism.attempts(),
+                    last_failure: error.to_invocation_error(),
+                };
+                match ism.on_max_attempts() {
+                    OnMaxAttempts::Fail => {
+                        self.fail_invocation(partition, full_invocation_id, error)
+                            .await
+                    }
+                    OnMaxAttempts::Pause => {
+                        self.pause_invocation(partition, full_invocation_id, error, ism)
+                    }
+                }
+            }
             _ => {
-                counter!(INVOKER_INVOCATION_TASK,
-                    "status" => TASK_OP_FAILED,
-                    "transient" => "false"
-                )
-                .increment(1);
-                warn_it!(
-                    error,
-                    restate.invocation.id = %full_invocation_id,
-                    "Error when executing the invocation, not going to retry.");
-                self.quota.unreserve_slot();
-                self.status_store.on_end(&partition, &full_invocation_id);
-                let _ = self
-                    .invocation_state_machine_manager
-                    .resolve_partition_sender(partition)
-                    .expect("Partition should be registered")
-                    .send(Effect {
-                        full_invocation_id,
-                        kind: EffectKind::Failed(error.to_invocation_error()),
-                    })
-                    .await;
+                self.fail_invocation(partition, full_invocation_id, error)
+                    .await
             }
         }
     }
 
+    async fn fail_invocation<E: InvokerError + CodedError + Send + Sync + 'static>(
+        &mut self,
+        partition: PartitionLeaderEpoch,
+        full_invocation_id: FullInvocationId,
+        error: E,
+    ) {
+        counter!(INVOKER_INVOCATION_TASK,
+            "status" => TASK_OP_FAILED,
+            "transient" => "false"
+        )
+        .increment(1);
+        warn_it!(
+            error,
+            restate.invocation.id = %full_invocation_id,
+            "Error when executing the invocation, not going to retry.");
+        self.quota
+            .unreserve_slot(&self.schemas, partition, &full_invocation_id);
+        self.status_store.on_end(&partition, &full_invocation_id);
+        let _ = self
+            .invocation_state_machine_manager
+            .resolve_partition_sender(partition)
+            .expect("Partition should be registered")
+            .send(Effect {
+                full_invocation_id,
+                kind: EffectKind::Failed(error.to_invocation_error()),
+            })
+            .await;
+    }
+
+    fn pause_invocation(
+        &mut self,
+        partition: PartitionLeaderEpoch,
+        full_invocation_id: FullInvocationId,
+        error: MaxAttemptsExceededError,
+        ism: InvocationStateMachine,
+    ) {
+        counter!(INVOKER_INVOCATION_TASK,
+            "status" => TASK_OP_FAILED,
+            "transient" => "false"
+        )
+        .increment(1);
+        warn_it!(
+            error,
+            restate.invocation.id = %full_invocation_id,
+            "Error when executing the invocation, pausing it until it is killed or cancelled.");
+        self.quota
+            .unreserve_slot(&self.schemas, partition, &full_invocation_id);
+        self.paused_invocations.insert(
+            (partition, full_invocation_id.clone()),
+            ism.quota_key().clone(),
+        );
+        self.status_store.on_failure(
+            partition,
+            full_invocation_id,
+            InvocationErrorReport::new(error.to_invocation_error(), error.code()),
+            None,
+        );
+    }
+
     async fn start_invocation_task(
         &mut self,
         partition: PartitionLeaderEpoch,
@@ -940,27 +1181,86 @@ mod tests {
     use tokio_util::sync::CancellationToken;
 
     use restate_invoker_api::{entry_enricher, journal_reader, state_reader, ServiceHandle};
+    use restate_schema_api::concurrency_limit::mocks::MockConcurrencyLimitResolver;
+    use restate_schema_api::concurrency_limit::ConcurrencyLimitScope;
     use restate_schema_api::deployment::mocks::MockDeploymentMetadataRegistry;
+    use restate_schema_api::deployment::Deployment;
+    use restate_schema_api::retry_policy::mocks::MockInvocationRetryPolicyResolver;
+    use restate_schema_api::retry_policy::{InvocationRetryPolicy, RetryPolicyScope};
+    use restate_schema_api::service::ServiceMetadata;
     use restate_test_util::{check, let_assert};
+    use restate_types::errors::InvocationErrorCode;
+    use restate_types::identifiers::ComponentRevision;
     use restate_types::identifiers::InvocationUuid;
     use restate_types::identifiers::{FullInvocationId, LeaderEpoch};
     use restate_types::journal::enriched::EnrichedEntryHeader;
     use restate_types::journal::raw::RawEntry;
+    use restate_types::journal::CompletionResult;
     use restate_types::retries::RetryPolicy;
 
     use crate::invocation_task::InvocationTaskError;
     use crate::options::ServiceClientOptions;
-    use crate::quota::InvokerConcurrencyQuota;
 
     // -- Mocks
 
     const MOCK_PARTITION: PartitionLeaderEpoch = (0, LeaderEpoch::INITIAL);
 
-    impl<ITR> ServiceInner<ITR> {
+    #[derive(Default, Clone)]
+    struct MockSchemas(
+        MockDeploymentMetadataRegistry,
+        MockConcurrencyLimitResolver,
+        MockInvocationRetryPolicyResolver,
+    );
+
+    impl DeploymentResolver for MockSchemas {
+        fn resolve_latest_deployment_for_service(
+            &self,
+            service_name: impl AsRef<str>,
+        ) -> Option<Deployment> {
+            self.0.resolve_latest_deployment_for_service(service_name)
+        }
+
+        fn get_deployment(&self, deployment_id: &DeploymentId) -> Option<Deployment> {
+            self.0.get_deployment(deployment_id)
+        }
+
+        fn get_deployment_descriptor_pool(&self, deployment_id: &DeploymentId) -> Option<Bytes> {
+            self.0.get_deployment_descriptor_pool(deployment_id)
+        }
+
+        fn get_deployment_and_services(
+            &self,
+            deployment_id: &DeploymentId,
+        ) -> Option<(Deployment, Vec<ServiceMetadata>)> {
+            self.0.get_deployment_and_services(deployment_id)
+        }
+
+        fn get_deployments(&self) -> Vec<(Deployment, Vec<(String, ComponentRevision)>)> {
+            self.0.get_deployments()
+        }
+    }
+
+    impl ConcurrencyLimitResolver for MockSchemas {
+        fn resolve_concurrency_limit(&self, scope: &ConcurrencyLimitScope) -> Option<usize> {
+            self.1.resolve_concurrency_limit(scope)
+        }
+    }
+
+    impl InvocationRetryPolicyResolver for MockSchemas {
+        fn resolve_invocation_retry_policy(
+            &self,
+            component: impl AsRef<str>,
+            handler: impl AsRef<str>,
+        ) -> Option<InvocationRetryPolicy> {
+            self.2.resolve_invocation_retry_policy(component, handler)
+        }
+    }
+
+    impl<ITR> ServiceInner<ITR, MockSchemas> {
         fn mock(
             invocation_task_runner: ITR,
             retry_policy: RetryPolicy,
-            concurrency_limit: Option<usize>,
+            concurrency_limits: ConcurrencyLimits,
         ) -> (mpsc::UnboundedSender<InputCommand>, Self) {
             let (input_tx, input_rx) = mpsc::unbounded_channel();
             let (invocation_tasks_tx, invocation_tasks_rx) = mpsc::unbounded_channel();
@@ -970,10 +1270,12 @@ mod tests {
                 invocation_tasks_tx,
                 invocation_tasks_rx,
                 invocation_task_runner,
+                schemas: Default::default(),
                 retry_policy,
                 invocation_tasks: Default::default(),
                 retry_timers: Default::default(),
-                quota: InvokerConcurrencyQuota::new(concurrency_limit),
+                quota: InvokerConcurrencyQuota::new(concurrency_limits),
+                paused_invocations: Default::default(),
                 status_store: Default::default(),
                 invocation_state_machine_manager: Default::default(),
             };
@@ -1031,7 +1333,7 @@ mod tests {
         let tempdir = tempfile::tempdir().unwrap();
         let service = Service::new(
             // all invocations are unknown leading to immediate retries
-            MockDeploymentMetadataRegistry::default(),
+            MockSchemas::default(),
             // fixed amount of retries so that an invocation eventually completes with a failure
             RetryPolicy::fixed_delay(Duration::ZERO, 1),
             Duration::ZERO,
@@ -1042,7 +1344,7 @@ mod tests {
             ServiceClientOptions::default()
                 .build(restate_service_client::AssumeRoleCacheMode::None),
             tempdir.into_path(),
-            None,
+            Default::default(),
             journal_reader::mocks::EmptyJournalReader,
             state_reader::mocks::EmptyStateReader,
             entry_enricher::mocks::MockEntryEnricher,
@@ -1067,6 +1369,7 @@ mod tests {
             .invoke(
                 partition_leader_epoch,
                 fid,
+                "greet".into(),
                 InvokeInputJournal::NoCachedJournal,
             )
             .await
@@ -1090,8 +1393,14 @@ mod tests {
         let sid_1 = mock_sid();
         let sid_2 = mock_sid();
 
-        let (_invoker_tx, mut service_inner) =
-            ServiceInner::mock(|_, _, _, _, _| ready(()), Default::default(), Some(1));
+        let (_invoker_tx, mut service_inner) = ServiceInner::mock(
+            |_, _, _, _, _| ready(()),
+            Default::default(),
+            ConcurrencyLimits {
+                global: Some(1),
+                ..Default::default()
+            },
+        );
         let _ = service_inner.register_mock_partition();
 
         // Enqueue sid_1 and sid_2
@@ -1099,6 +1408,7 @@ mod tests {
             .enqueue(InvokeCommand {
                 partition: MOCK_PARTITION,
                 full_invocation_id: sid_1.clone(),
+                method: "greet".into(),
                 journal: InvokeInputJournal::NoCachedJournal,
             })
             .await;
@@ -1106,6 +1416,7 @@ mod tests {
             .enqueue(InvokeCommand {
                 partition: MOCK_PARTITION,
                 full_invocation_id: sid_2.clone(),
+                method: "greet".into(),
                 journal: InvokeInputJournal::NoCachedJournal,
             })
             .await;
@@ -1185,7 +1496,10 @@ mod tests {
                 pending() // Never ends
             },
             Default::default(),
-            Some(2),
+            ConcurrencyLimits {
+                global: Some(2),
+                ..Default::default()
+            },
         );
         let _ = service_inner.register_mock_partition();
 
@@ -1194,6 +1508,7 @@ mod tests {
             .handle_invoke(
                 MOCK_PARTITION,
                 fid.clone(),
+                QuotaKey::new(None, "MyService".into(), "greet".into()),
                 InvokeInputJournal::NoCachedJournal,
             )
             .await;
@@ -1204,15 +1519,13 @@ mod tests {
         check!(let InvocationTaskOutputInner::NewEntry { .. } = invoker_effect.inner);
 
         // Check the quota
-        let_assert!(InvokerConcurrencyQuota::Limited { available_slots } = &service_inner.quota);
-        assert_eq!(*available_slots, 1);
+        assert_eq!(service_inner.quota.in_use(None), 1);
 
         // Abort the invocation
         service_inner.handle_abort_invocation(MOCK_PARTITION, fid.clone());
 
         // Check the quota
-        let_assert!(InvokerConcurrencyQuota::Limited { available_slots } = &service_inner.quota);
-        assert_eq!(*available_slots, 2);
+        assert_eq!(service_inner.quota.in_use(None), 0);
 
         // Handle error coming after the abort (this should be noop)
         service_inner
@@ -1224,7 +1537,199 @@ mod tests {
             .await;
 
         // Check the quota, should not be changed
-        let_assert!(InvokerConcurrencyQuota::Limited { available_slots } = &service_inner.quota);
-        assert_eq!(*available_slots, 2);
+        assert_eq!(service_inner.quota.in_use(None), 0);
+    }
+
+    #[test(tokio::test)]
+    async fn handler_quota_parks_invocations() {
+        let mut segment_queue = SegmentQueue::new(tempdir().unwrap().into_path(), 1024);
+        let cancel_token = CancellationToken::new();
+        let shutdown = cancel_token.cancelled();
+        tokio::pin!(shutdown);
+
+        let sid_1 = mock_sid();
+        let sid_2 = mock_sid();
+        let sid_3 = mock_sid();
+
+        let (_invoker_tx, mut service_inner) = ServiceInner::mock(
+            |_, _, _, _, _| pending(),
+            Default::default(),
+            Default::default(),
+        );
+        service_inner.schemas.1.add(
+            ConcurrencyLimitScope::Handler {
+                component: "MyService".to_owned(),
+                handler: "greet".to_owned(),
+            },
+            1,
+        );
+        let _ = service_inner.register_mock_partition();
+
+        for (sid, method) in [(&sid_1, "greet"), (&sid_2, "greet"), (&sid_3, "hello")] {
+            segment_queue
+                .enqueue(InvokeCommand {
+                    partition: MOCK_PARTITION,
+                    full_invocation_id: sid.clone(),
+                    method: method.into(),
+                    journal: InvokeInputJournal::NoCachedJournal,
+                })
+                .await;
+        }
+
+        // sid_1 starts, sid_2 waits for the handler quota, sid_3 is not affected by it
+        for _ in 0..3 {
+            assert!(
+                service_inner
+                    .step(&mut segment_queue, shutdown.as_mut())
+                    .await
+            );
+        }
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &sid_1)
+            .unwrap()
+            .in_flight());
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &sid_2)
+            .is_none());
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &sid_3)
+            .unwrap()
+            .in_flight());
+
+        // Completing sid_1 releases the handler quota for sid_2
+        service_inner
+            .handle_invocation_task_closed(MOCK_PARTITION, sid_1.clone())
+            .await;
+        service_inner.start_waiting_invocations().await;
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &sid_2)
+            .unwrap()
+            .in_flight());
+        assert_eq!(service_inner.quota.in_use(None), 2);
+    }
+
+    #[test(tokio::test)]
+    async fn fail_invocation_when_max_attempts_exceeded() {
+        let fid = mock_sid();
+
+        let (_, mut service_inner) = ServiceInner::mock(
+            |_, _, _, _, _| pending(),
+            RetryPolicy::None,
+            Default::default(),
+        );
+        let mut partition_rx = service_inner.register_mock_partition();
+
+        service_inner
+            .handle_invoke(
+                MOCK_PARTITION,
+                fid.clone(),
+                QuotaKey::new(None, "MyService".into(), "greet".into()),
+                InvokeInputJournal::NoCachedJournal,
+            )
+            .await;
+        service_inner
+            .handle_invocation_task_failed(
+                MOCK_PARTITION,
+                fid.clone(),
+                InvocationTaskError::EmptySuspensionMessage, /* any transient error is fine */
+            )
+            .await;
+
+        let effect = partition_rx.recv().await.unwrap();
+        assert_eq!(effect.full_invocation_id, fid);
+        let_assert!(EffectKind::Failed(error) = effect.kind);
+        assert_eq!(
+            error.code(),
+            InvocationErrorCode::Restate(RestateErrorCode::MaxAttemptsExceeded)
+        );
+        assert_eq!(service_inner.quota.in_use(None), 0);
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &fid)
+            .is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn pause_invocation_when_max_attempts_exceeded() {
+        let fid = mock_sid();
+
+        let (_, mut service_inner) = ServiceInner::mock(
+            |_, _, _, _, _| pending(),
+            Default::default(),
+            Default::default(),
+        );
+        service_inner.schemas.2.add(
+            RetryPolicyScope::Component("MyService".to_owned()),
+            InvocationRetryPolicy {
+                retry_policy: RetryPolicy::None,
+                on_max_attempts: OnMaxAttempts::Pause,
+            },
+        );
+        let mut partition_rx = service_inner.register_mock_partition();
+
+        service_inner
+            .handle_invoke(
+                MOCK_PARTITION,
+                fid.clone(),
+                QuotaKey::new(None, "MyService".into(), "greet".into()),
+                InvokeInputJournal::NoCachedJournal,
+            )
+            .await;
+        service_inner
+            .handle_invocation_task_failed(
+                MOCK_PARTITION,
+                fid.clone(),
+                InvocationTaskError::EmptySuspensionMessage, /* any transient error is fine */
+            )
+            .await;
+
+        // The invocation is not failed, but it doesn't hold a slot anymore
+        assert!(partition_rx.try_recv().is_err());
+        assert_eq!(service_inner.quota.in_use(None), 0);
+        let report = service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &fid)
+            .unwrap();
+        assert!(!report.in_flight());
+        assert!(report.next_retry_at().is_none());
+        assert_eq!(
+            report
+                .last_retry_attempt_failure()
+                .unwrap()
+                .invocation_error_code(),
+            InvocationErrorCode::Restate(RestateErrorCode::MaxAttemptsExceeded)
+        );
+
+        // Other completions don't resume the invocation
+        service_inner.handle_completion(
+            MOCK_PARTITION,
+            fid.clone(),
+            Completion::new(1, CompletionResult::Empty),
+        );
+        service_inner.start_waiting_invocations().await;
+        assert!(!service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &fid)
+            .unwrap()
+            .in_flight());
+        assert_eq!(service_inner.quota.in_use(None), 0);
+
+        // The cancellation resumes the invocation
+        service_inner.handle_completion(
+            MOCK_PARTITION,
+            fid.clone(),
+            Completion::new(2, CompletionResult::from(&CANCELED_INVOCATION_ERROR)),
+        );
+        service_inner.start_waiting_invocations().await;
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &fid)
+            .unwrap()
+            .in_flight());
+        assert_eq!(service_inner.quota.in_use(None), 1);
     }
 }
@@ -10,10 +10,13 @@
 
 /// Optional to have but adds description/help message to the metrics emitted to
 /// the metrics' sink.
-use metrics::{describe_counter, Unit};
+use metrics::{describe_counter, describe_gauge, Unit};
 
 pub const INVOKER_ENQUEUE: &str = "restate.invoker.enqueue.total";
 pub const INVOKER_INVOCATION_TASK: &str = "restate.invoker.invocation_task.total";
+pub const INVOKER_CONCURRENCY_QUOTA_IN_USE: &str = "restate.invoker.concurrency_quota.in_use";
+pub const INVOKER_CONCURRENCY_QUOTA_LIMIT: &str = "restate.invoker.concurrency_quota.limit";
+pub const INVOKER_CONCURRENCY_QUOTA_WAITING: &str = "restate.invoker.concurrency_quota.waiting";
 
 pub const TASK_OP_STARTED: &str = "started";
 pub const TASK_OP_SUSPENDED: &str = "suspended";
@@ -32,4 +35,22 @@ pub(crate) fn describe_metrics() {
         Unit::Count,
         "Invocation task operation"
     );
+
+    describe_gauge!(
+        INVOKER_CONCURRENCY_QUOTA_IN_USE,
+        Unit::Count,
+        "Number of running invocations counted against the concurrency quota"
+    );
+
+    describe_gauge!(
+        INVOKER_CONCURRENCY_QUOTA_LIMIT,
+        Unit::Count,
+        "Concurrency limit of the quota"
+    );
+
+    describe_gauge!(
+        INVOKER_CONCURRENCY_QUOTA_WAITING,
+        Unit::Count,
+        "Number of invocations waiting for a free slot in the concurrency quota"
+    );
 }
@@ -11,10 +11,13 @@
 use crate::metric_definitions;
 
 use super::Service;
+use crate::quota::ConcurrencyLimits;
 
 use futures::Stream;
 use restate_invoker_api::{EntryEnricher, JournalReader};
+use restate_schema_api::concurrency_limit::ConcurrencyLimitResolver;
 use restate_schema_api::deployment::DeploymentResolver;
+use restate_schema_api::retry_policy::InvocationRetryPolicyResolver;
 use restate_service_client::AssumeRoleCacheMode;
 use restate_types::journal::raw::PlainRawEntry;
 use restate_types::retries::RetryPolicy;
@@ -40,6 +43,7 @@ pub struct Options {
     /// # Retry policy
     ///
     /// Retry policy to use for all the invocations handled by this invoker.
+    /// It can be overridden per service and handler through the admin API.
     retry_policy: RetryPolicy,
 
     /// # Inactivity timeout
@@ -92,6 +96,24 @@ pub struct Options {
     /// Number of concurrent invocations that can be processed by the invoker.
     concurrency_limit: Option<usize>,
 
+    /// # Deployment concurrency limit
+    ///
+    /// Number of concurrent invocations that can be processed by the invoker per deployment.
+    /// The limit of a specific deployment can be overridden through the admin API.
+    deployment_concurrency_limit: Option<usize>,
+
+    /// # Component concurrency limit
+    ///
+    /// Number of concurrent invocations that can be processed by the invoker per component.
+    /// The limit of a specific component can be overridden through the admin API.
+    component_concurrency_limit: Option<usize>,
+
+    /// # Handler concurrency limit
+    ///
+    /// Number of concurrent invocations that can be processed by the invoker per handler.
+    /// The limit of a specific handler can be overridden through the admin API.
+    handler_concurrency_limit: Option<usize>,
+
     service_client: ServiceClientOptions,
 
     // -- Private config options (not exposed in the schema)
@@ -114,6 +136,9 @@ impl Default for Options {
             message_size_limit: None,
             tmp_dir: restate_fs_util::generate_temp_dir_name("invoker"),
             concurrency_limit: None,
+            deployment_concurrency_limit: None,
+            component_concurrency_limit: None,
+            handler_concurrency_limit: None,
             service_client: Default::default(),
             disable_eager_state: false,
         }
@@ -132,7 +157,7 @@ impl Options {
         JR: JournalReader<JournalStream = JS> + Clone + Send + Sync + 'static,
         JS: Stream<Item = PlainRawEntry> + Unpin + Send + 'static,
         EE: EntryEnricher,
-        DMR: DeploymentResolver,
+        DMR: DeploymentResolver + ConcurrencyLimitResolver + InvocationRetryPolicyResolver + Clone,
     {
         metric_definitions::describe_metrics();
         let client = self.service_client.build(AssumeRoleCacheMode::Unbounded);
@@ -147,7 +172,12 @@ impl Options {
             self.message_size_limit,
             client,
             self.tmp_dir,
-            self.concurrency_limit,
+            ConcurrencyLimits {
+                global: self.concurrency_limit,
+                deployment: self.deployment_concurrency_limit,
+                component: self.component_concurrency_limit,
+                handler: self.handler_concurrency_limit,
+            },
             journal_reader,
             state_reader,
             entry_enricher,
@@ -8,39 +8,414 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::input_command::InvokeCommand;
+use crate::metric_definitions::{
+    INVOKER_CONCURRENCY_QUOTA_IN_USE, INVOKER_CONCURRENCY_QUOTA_LIMIT,
+    INVOKER_CONCURRENCY_QUOTA_WAITING,
+};
+
+use bytestring::ByteString;
+use metrics::gauge;
+use restate_invoker_api::InvokeInputJournal;
+use restate_schema_api::concurrency_limit::{ConcurrencyLimitResolver, ConcurrencyLimitScope};
+use restate_types::identifiers::{DeploymentId, FullInvocationId, PartitionLeaderEpoch};
+use std::collections::{HashMap, VecDeque};
+
+/// Default concurrency limits of the invoker. The limits of deployments, components and
+/// handlers apply to each of them individually, unless the schema registry contains a limit
+/// for the specific deployment, component or handler.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ConcurrencyLimits {
+    pub(super) global: Option<usize>,
+    pub(super) deployment: Option<usize>,
+    pub(super) component: Option<usize>,
+    pub(super) handler: Option<usize>,
+}
+
+/// Identifies the quotas an invocation counts against, besides the global one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct QuotaKey {
+    deployment_id: Option<DeploymentId>,
+    component: ByteString,
+    handler: ByteString,
+}
+
+impl QuotaKey {
+    pub(super) fn new(
+        deployment_id: Option<DeploymentId>,
+        component: ByteString,
+        handler: ByteString,
+    ) -> Self {
+        Self {
+            deployment_id,
+            component,
+            handler,
+        }
+    }
+
+    pub(super) fn component(&self) -> &ByteString {
+        &self.component
+    }
+
+    pub(super) fn handler(&self) -> &ByteString {
+        &self.handler
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = ConcurrencyLimitScope> + '_ {
+        self.deployment_id
+            .map(ConcurrencyLimitScope::Deployment)
+            .into_iter()
+            .chain([
+                ConcurrencyLimitScope::Component(self.component.to_string()),
+                ConcurrencyLimitScope::Handler {
+                    component: self.component.to_string(),
+                    handler: self.handler.to_string(),
+                },
+            ])
+    }
+}
+
+/// Hierarchical concurrency quota of the invoker: an invocation can only run if there is a free
+/// slot in the global quota, and in the quotas of its deployment, component and handler.
+///
+/// Invocations which cannot run because one of the non-global quotas is exhausted wait in a
+/// queue per [`QuotaKey`]. Once slots are released, the waiting queues are served in round
+/// robin order, so that a busy handler cannot starve the other handlers sharing a quota.
 #[derive(Debug)]
-pub(super) enum InvokerConcurrencyQuota {
-    Unlimited,
-    Limited { available_slots: usize },
+pub(super) struct InvokerConcurrencyQuota {
+    default_limits: ConcurrencyLimits,
+
+    global_in_use: usize,
+    in_use: HashMap<ConcurrencyLimitScope, usize>,
+    reserved: HashMap<(PartitionLeaderEpoch, FullInvocationId), QuotaKey>,
+
+    waiting: HashMap<QuotaKey, VecDeque<InvokeCommand>>,
+    // Round robin order of the keys with waiting invocations
+    waiting_order: VecDeque<QuotaKey>,
 }
 
 impl InvokerConcurrencyQuota {
-    pub(super) fn new(quota: Option<usize>) -> Self {
-        match quota {
-            Some(available_slots) => Self::Limited { available_slots },
-            None => Self::Unlimited,
+    pub(super) fn new(default_limits: ConcurrencyLimits) -> Self {
+        if let Some(limit) = default_limits.global {
+            gauge!(INVOKER_CONCURRENCY_QUOTA_LIMIT, "scope" => "global").set(limit as f64);
+        }
+        Self {
+            default_limits,
+            global_in_use: 0,
+            in_use: Default::default(),
+            reserved: Default::default(),
+            waiting: Default::default(),
+            waiting_order: Default::default(),
         }
     }
 
     pub(super) fn is_slot_available(&self) -> bool {
-        match self {
-            Self::Unlimited => true,
-            Self::Limited { available_slots } => *available_slots > 0,
+        self.default_limits
+            .global
+            .map_or(true, |limit| self.global_in_use < limit)
+    }
+
+    /// Returns true if an invocation with the given key can be started right away. This is
+    /// not the case if invocations with the same key are already waiting, in order to preserve
+    /// the order of invocations with the same key.
+    pub(super) fn can_start(
+        &self,
+        resolver: &impl ConcurrencyLimitResolver,
+        key: &QuotaKey,
+    ) -> bool {
+        !self.waiting.contains_key(key) && self.has_capacity(resolver, key)
+    }
+
+    pub(super) fn reserve_slot(
+        &mut self,
+        resolver: &impl ConcurrencyLimitResolver,
+        partition: PartitionLeaderEpoch,
+        full_invocation_id: FullInvocationId,
+        key: QuotaKey,
+    ) {
+        assert!(self.is_slot_available());
+        self.global_in_use += 1;
+        gauge!(INVOKER_CONCURRENCY_QUOTA_IN_USE, "scope" => "global")
+            .set(self.global_in_use as f64);
+        for scope in key.scopes() {
+            let limit = self.limit(resolver, &scope);
+            let in_use = self.in_use.entry(scope.clone()).or_default();
+            *in_use += 1;
+            record_scope_metrics(&scope, *in_use, limit);
         }
+        self.reserved.insert((partition, full_invocation_id), key);
     }
 
-    pub(super) fn unreserve_slot(&mut self) {
-        match self {
-            Self::Unlimited => {}
-            Self::Limited { available_slots } => *available_slots += 1,
+    /// Releases the slots of the given invocation, returning the key of the released slots.
+    /// Does nothing if the invocation holds no slot.
+    pub(super) fn unreserve_slot(
+        &mut self,
+        resolver: &impl ConcurrencyLimitResolver,
+        partition: PartitionLeaderEpoch,
+        full_invocation_id: &FullInvocationId,
+    ) -> Option<QuotaKey> {
+        let key = self
+            .reserved
+            .remove(&(partition, full_invocation_id.clone()))?;
+        self.global_in_use -= 1;
+        gauge!(INVOKER_CONCURRENCY_QUOTA_IN_USE, "scope" => "global")
+            .set(self.global_in_use as f64);
+        for scope in key.scopes() {
+            let limit = self.limit(resolver, &scope);
+            let in_use = self
+                .in_use
+                .get_mut(&scope)
+                .expect("reserved scopes must be tracked");
+            *in_use -= 1;
+            record_scope_metrics(&scope, *in_use, limit);
+            if *in_use == 0 {
+                self.in_use.remove(&scope);
+            }
         }
+        Some(key)
     }
 
-    pub(super) fn reserve_slot(&mut self) {
-        assert!(self.is_slot_available());
-        match self {
-            Self::Unlimited => {}
-            Self::Limited { available_slots } => *available_slots -= 1,
+    /// Parks the invocation until there are free slots in the quotas of its key. The cached
+    /// journal is dropped to bound the memory used by waiting invocations.
+    pub(super) fn park(&mut self, key: QuotaKey, mut invoke_command: InvokeCommand) {
+        invoke_command.journal = InvokeInputJournal::NoCachedJournal;
+        let queue = self.waiting.entry(key.clone()).or_insert_with(|| {
+            self.waiting_order.push_back(key.clone());
+            VecDeque::new()
+        });
+        queue.push_back(invoke_command);
+        record_waiting_metrics(&key, queue.len());
+    }
+
+    /// Returns the next waiting invocation which can be started, serving the waiting keys in
+    /// round robin order.
+    pub(super) fn next_runnable(
+        &mut self,
+        resolver: &impl ConcurrencyLimitResolver,
+    ) -> Option<(QuotaKey, InvokeCommand)> {
+        if !self.is_slot_available() {
+            return None;
         }
+
+        for _ in 0..self.waiting_order.len() {
+            let key = self.waiting_order.pop_front()?;
+            if !self.has_capacity(resolver, &key) {
+                self.waiting_order.push_back(key);
+                continue;
+            }
+
+            let queue = self
+                .waiting
+                .get_mut(&key)
+                .expect("keys in the waiting order must have a queue");
+            let invoke_command = queue
+                .pop_front()
+                .expect("queues of waiting keys must not be empty");
+            record_waiting_metrics(&key, queue.len());
+            if queue.is_empty() {
+                self.waiting.remove(&key);
+            } else {
+                self.waiting_order.push_back(key.clone());
+            }
+            return Some((key, invoke_command));
+        }
+
+        None
+    }
+
+    /// Removes the waiting invocations matching the given predicate.
+    pub(super) fn remove_waiting(
+        &mut self,
+        mut predicate: impl FnMut(PartitionLeaderEpoch, &FullInvocationId) -> bool,
+    ) {
+        self.waiting.retain(|key, queue| {
+            queue.retain(|cmd| !predicate(cmd.partition, &cmd.full_invocation_id));
+            record_waiting_metrics(key, queue.len());
+            !queue.is_empty()
+        });
+        let waiting = &self.waiting;
+        self.waiting_order.retain(|key| waiting.contains_key(key));
+    }
+
+    fn has_capacity(&self, resolver: &impl ConcurrencyLimitResolver, key: &QuotaKey) -> bool {
+        key.scopes()
+            .all(|scope| match self.limit(resolver, &scope) {
+                Some(limit) => self.in_use.get(&scope).copied().unwrap_or_default() < limit,
+                None => true,
+            })
+    }
+
+    fn limit(
+        &self,
+        resolver: &impl ConcurrencyLimitResolver,
+        scope: &ConcurrencyLimitScope,
+    ) -> Option<usize> {
+        resolver.resolve_concurrency_limit(scope).or(match scope {
+            ConcurrencyLimitScope::Deployment(_) => self.default_limits.deployment,
+            ConcurrencyLimitScope::Component(_) => self.default_limits.component,
+            ConcurrencyLimitScope::Handler { .. } => self.default_limits.handler,
+        })
+    }
+
+    #[cfg(test)]
+    pub(super) fn in_use(&self, scope: Option<&ConcurrencyLimitScope>) -> usize {
+        match scope {
+            None => self.global_in_use,
+            Some(scope) => self.in_use.get(scope).copied().unwrap_or_default(),
+        }
+    }
+}
+
+fn scope_labels(scope: &ConcurrencyLimitScope) -> (&'static str, String) {
+    match scope {
+        ConcurrencyLimitScope::Deployment(deployment_id) => {
+            ("deployment", deployment_id.to_string())
+        }
+        ConcurrencyLimitScope::Component(component) => ("component", component.clone()),
+        ConcurrencyLimitScope::Handler { component, handler } => {
+            ("handler", format!("{component}/{handler}"))
+        }
+    }
+}
+
+fn record_scope_metrics(scope: &ConcurrencyLimitScope, in_use: usize, limit: Option<usize>) {
+    let (scope, target) = scope_labels(scope);
+    gauge!(INVOKER_CONCURRENCY_QUOTA_IN_USE, "scope" => scope, "target" => target.clone())
+        .set(in_use as f64);
+    if let Some(limit) = limit {
+        gauge!(INVOKER_CONCURRENCY_QUOTA_LIMIT, "scope" => scope, "target" => target)
+            .set(limit as f64);
+    }
+}
+
+fn record_waiting_metrics(key: &QuotaKey, waiting: usize) {
+    gauge!(
+        INVOKER_CONCURRENCY_QUOTA_WAITING,
+        "scope" => "handler",
+        "target" => format!("{}/{}", key.component, key.handler)
+    )
+    .set(waiting as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_schema_api::concurrency_limit::mocks::MockConcurrencyLimitResolver;
+    use restate_types::identifiers::{InvocationUuid, LeaderEpoch};
+
+    const PARTITION: PartitionLeaderEpoch = (0, LeaderEpoch::INITIAL);
+
+    fn invoke_command(component: &str) -> InvokeCommand {
+        InvokeCommand {
+            partition: PARTITION,
+            full_invocation_id: FullInvocationId::new(component, "", InvocationUuid::new()),
+            method: "greet".into(),
+            journal: InvokeInputJournal::NoCachedJournal,
+        }
+    }
+
+    fn key(component: &str, handler: &str) -> QuotaKey {
+        QuotaKey::new(None, component.into(), handler.into())
+    }
+
+    #[test]
+    fn limits_of_schema_registry_override_defaults() {
+        let mut resolver = MockConcurrencyLimitResolver::default();
+        resolver.add(ConcurrencyLimitScope::Component("greeter".to_owned()), 2);
+
+        let mut quota = InvokerConcurrencyQuota::new(ConcurrencyLimits {
+            component: Some(1),
+            ..Default::default()
+        });
+
+        let greeter = key("greeter", "greet");
+        let counter = key("counter", "add");
+        for _ in 0..2 {
+            assert!(quota.can_start(&resolver, &greeter));
+            let cmd = invoke_command("greeter");
+            quota.reserve_slot(
+                &resolver,
+                cmd.partition,
+                cmd.full_invocation_id,
+                greeter.clone(),
+            );
+        }
+        assert!(!quota.can_start(&resolver, &greeter));
+
+        let cmd = invoke_command("counter");
+        assert!(quota.can_start(&resolver, &counter));
+        quota.reserve_slot(
+            &resolver,
+            cmd.partition,
+            cmd.full_invocation_id,
+            counter.clone(),
+        );
+        assert!(!quota.can_start(&resolver, &counter));
+        assert_eq!(quota.in_use(None), 3);
+    }
+
+    #[test]
+    fn waiting_invocations_are_served_fairly() {
+        let resolver = MockConcurrencyLimitResolver::default();
+        let mut quota = InvokerConcurrencyQuota::new(ConcurrencyLimits {
+            component: Some(1),
+            ..Default::default()
+        });
+
+        let greet = key("greeter", "greet");
+        let hello = key("greeter", "hello");
+        let running = invoke_command("greeter");
+        quota.reserve_slot(
+            &resolver,
+            running.partition,
+            running.full_invocation_id.clone(),
+            greet.clone(),
+        );
+
+        let greet_1 = invoke_command("greeter");
+        let greet_2 = invoke_command("greeter");
+        let hello_1 = invoke_command("greeter");
+        let expected_order = [
+            greet_1.full_invocation_id.clone(),
+            hello_1.full_invocation_id.clone(),
+            greet_2.full_invocation_id.clone(),
+        ];
+        quota.park(greet.clone(), greet_1);
+        quota.park(greet.clone(), greet_2);
+        quota.park(hello.clone(), hello_1);
+        assert!(quota.next_runnable(&resolver).is_none());
+
+        quota.unreserve_slot(&resolver, running.partition, &running.full_invocation_id);
+        for expected in expected_order {
+            let (key, cmd) = quota.next_runnable(&resolver).unwrap();
+            assert_eq!(cmd.full_invocation_id, expected);
+            quota.reserve_slot(
+                &resolver,
+                cmd.partition,
+                cmd.full_invocation_id.clone(),
+                key,
+            );
+            assert!(quota.next_runnable(&resolver).is_none());
+            quota.unreserve_slot(&resolver, cmd.partition, &cmd.full_invocation_id);
+        }
+        assert!(quota.next_runnable(&resolver).is_none());
+    }
+
+    #[test]
+    fn removed_waiting_invocations_are_not_served() {
+        let resolver = MockConcurrencyLimitResolver::default();
+        let mut quota = InvokerConcurrencyQuota::new(ConcurrencyLimits {
+            handler: Some(0),
+            ..Default::default()
+        });
+
+        let greet = key("greeter", "greet");
+        assert!(!quota.can_start(&resolver, &greet));
+        quota.park(greet.clone(), invoke_command("greeter"));
+        quota.remove_waiting(|partition, _| partition == PARTITION);
+
+        assert!(quota.waiting.is_empty());
+        assert!(quota.waiting_order.is_empty());
     }
 }
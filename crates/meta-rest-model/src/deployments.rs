@@ -153,6 +153,16 @@ pub enum RegisterDeploymentRequest {
     },
 }
 
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModifyDeploymentRequest {
+    /// # Concurrency limit
+    ///
+    /// Maximum number of concurrent invocations of this deployment that the invoker processes.
+    /// If unset, the limit is removed and the invoker default applies.
+    pub concurrency_limit: Option<usize>,
+}
+
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServiceNameRevPair {
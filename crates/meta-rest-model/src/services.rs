@@ -14,8 +14,10 @@ use std::collections::HashMap;
 
 // Export schema types to be used by other crates without exposing the fact
 // that we are using proxying to restate-schema-api or restate-types
+pub use restate_schema_api::retry_policy::{InvocationRetryPolicy, OnMaxAttempts};
 pub use restate_schema_api::service::{InstanceType, MethodMetadata, ServiceMetadata};
 pub use restate_types::identifiers::ComponentRevision;
+pub use restate_types::retries::RetryPolicy;
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,7 +32,51 @@ pub struct ModifyServiceRequest {
     ///
     /// If true, the service can be invoked through the ingress.
     /// If false, the service can be invoked only from another Restate service.
-    pub public: bool,
+    /// If unset, the visibility of the service is left unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+
+    /// # Concurrency limit
+    ///
+    /// Maximum number of concurrent invocations of this service that the invoker processes.
+    /// Set to `null` to remove the limit and fall back to the invoker default.
+    /// If unset, the concurrency limit of the service is left unchanged.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "serde_with::rust::double_option"
+    )]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<usize>"))]
+    pub concurrency_limit: Option<Option<usize>>,
+
+    /// # Handler concurrency limits
+    ///
+    /// Maximum number of concurrent invocations per handler of this service.
+    /// Set the limit of a handler to `null` to remove it and fall back to the invoker default.
+    /// Handlers which are not listed are left unchanged.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub handler_concurrency_limits: HashMap<String, Option<usize>>,
+
+    /// # Retry policy
+    ///
+    /// Retry policy of the invocations of this service, overriding the invoker default.
+    /// Set to `null` to remove the policy and fall back to the invoker default.
+    /// If unset, the retry policy of the service is left unchanged.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "serde_with::rust::double_option"
+    )]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<InvocationRetryPolicy>"))]
+    pub retry_policy: Option<Option<InvocationRetryPolicy>>,
+
+    /// # Handler retry policies
+    ///
+    /// Retry policies per handler of this service, overriding the retry policy of the service.
+    /// Set the policy of a handler to `null` to remove it and fall back to the service policy.
+    /// Handlers which are not listed are left unchanged.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub handler_retry_policies: HashMap<String, Option<InvocationRetryPolicy>>,
 }
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
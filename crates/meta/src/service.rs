@@ -22,7 +22,9 @@ use restate_core::cancellation_watcher;
 use restate_errors::warn_it;
 use restate_futures_util::command::{Command, UnboundedCommandReceiver, UnboundedCommandSender};
 use restate_schema_api::component::ComponentMetadata;
+use restate_schema_api::concurrency_limit::ConcurrencyLimitScope;
 use restate_schema_api::deployment::{DeliveryOptions, DeploymentMetadata};
+use restate_schema_api::retry_policy::{InvocationRetryPolicy, RetryPolicyScope};
 use restate_schema_api::service::ServiceMetadata;
 use restate_schema_api::subscription::{Subscription, SubscriptionValidator};
 use restate_schema_impl::{Schemas, SchemasUpdateCommand};
@@ -79,6 +81,12 @@ enum MetaHandleRequest {
         service_name: String,
         public: bool,
     },
+    ModifyConcurrencyLimits {
+        limits: Vec<(ConcurrencyLimitScope, Option<usize>)>,
+    },
+    ModifyRetryPolicies {
+        retry_policies: Vec<(RetryPolicyScope, Option<InvocationRetryPolicy>)>,
+    },
     RemoveDeployment {
         deployment_id: DeploymentId,
     },
@@ -107,6 +115,8 @@ enum MetaHandleResponse {
     OldDiscoverDeployment(Result<OldDiscoverDeploymentResponse, Error>),
     DiscoverDeployment(Result<DiscoverDeploymentResponse, Error>),
     ModifyService(Result<(), Error>),
+    ModifyConcurrencyLimits(Result<(), Error>),
+    ModifyRetryPolicies(Result<(), Error>),
     RemoveDeployment(Result<(), Error>),
     CreateSubscription(Result<Subscription, Error>),
     DeleteSubscription(Result<(), Error>),
@@ -173,6 +183,44 @@ impl MetaHandle {
             .map_err(|_e| Error::MetaClosed)?
     }
 
+    /// Sets the concurrency limits of the given scopes, removing the limit of a scope if `None`.
+    /// The limits are applied atomically.
+    pub async fn modify_concurrency_limits(
+        &self,
+        limits: Vec<(ConcurrencyLimitScope, Option<usize>)>,
+    ) -> Result<(), Error> {
+        let (cmd, response_tx) =
+            Command::prepare(MetaHandleRequest::ModifyConcurrencyLimits { limits });
+        self.0.send(cmd).map_err(|_e| Error::MetaClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                MetaHandleResponse::ModifyConcurrencyLimits(res) => res,
+                #[allow(unreachable_patterns)]
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::MetaClosed)?
+    }
+
+    /// Sets the retry policies of the given scopes, removing the policy of a scope if `None`.
+    /// The policies are applied atomically.
+    pub async fn modify_retry_policies(
+        &self,
+        retry_policies: Vec<(RetryPolicyScope, Option<InvocationRetryPolicy>)>,
+    ) -> Result<(), Error> {
+        let (cmd, response_tx) =
+            Command::prepare(MetaHandleRequest::ModifyRetryPolicies { retry_policies });
+        self.0.send(cmd).map_err(|_e| Error::MetaClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                MetaHandleResponse::ModifyRetryPolicies(res) => res,
+                #[allow(unreachable_patterns)]
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::MetaClosed)?
+    }
+
     pub async fn remove_deployment(&self, deployment_id: DeploymentId) -> Result<(), Error> {
         let (cmd, response_tx) =
             Command::prepare(MetaHandleRequest::RemoveDeployment { deployment_id });
@@ -322,6 +370,18 @@ where
                                     warn_it!(e); e
                                 })
                         ),
+                        MetaHandleRequest::ModifyConcurrencyLimits { limits } => MetaHandleResponse::ModifyConcurrencyLimits(
+                            self.modify_concurrency_limits(limits).await
+                                .map_err(|e| {
+                                    warn_it!(e); e
+                                })
+                        ),
+                        MetaHandleRequest::ModifyRetryPolicies { retry_policies } => MetaHandleResponse::ModifyRetryPolicies(
+                            self.modify_retry_policies(retry_policies).await
+                                .map_err(|e| {
+                                    warn_it!(e); e
+                                })
+                        ),
                         MetaHandleRequest::RemoveDeployment { deployment_id } => MetaHandleResponse::RemoveDeployment(
                             self.remove_deployment(deployment_id).await
                                 .map_err(|e| {
@@ -467,6 +527,41 @@ where
         Ok(())
     }
 
+    async fn modify_concurrency_limits(
+        &mut self,
+        limits: Vec<(ConcurrencyLimitScope, Option<usize>)>,
+    ) -> Result<(), Error> {
+        debug!(?limits, "Modify concurrency limits");
+
+        // Compute the diff and propagate updates
+        let update_commands = limits
+            .into_iter()
+            .map(|(scope, limit)| self.schemas.compute_modify_concurrency_limit(scope, limit))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.store_and_apply_updates(update_commands).await?;
+
+        Ok(())
+    }
+
+    async fn modify_retry_policies(
+        &mut self,
+        retry_policies: Vec<(RetryPolicyScope, Option<InvocationRetryPolicy>)>,
+    ) -> Result<(), Error> {
+        debug!(?retry_policies, "Modify retry policies");
+
+        // Compute the diff and propagate updates
+        let update_commands = retry_policies
+            .into_iter()
+            .map(|(scope, retry_policy)| {
+                self.schemas
+                    .compute_modify_retry_policy(scope, retry_policy)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.store_and_apply_updates(update_commands).await?;
+
+        Ok(())
+    }
+
     async fn remove_deployment(&mut self, deployment_id: DeploymentId) -> Result<(), Error> {
         debug!(restate.deployment.id = %deployment_id, "Remove deployment");
 
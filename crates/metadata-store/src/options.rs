@@ -0,0 +1,44 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::path::{Path, PathBuf};
+
+use crate::rocksdb_store::{BuildError, RocksDbMetadataStore};
+
+/// # Metadata store options
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "options_schema",
+    schemars(rename = "MetadataStoreOptions", default)
+)]
+#[builder(default)]
+pub struct Options {
+    /// # Storage path
+    ///
+    /// The path of the RocksDB database which stores the cluster metadata. If unset, the
+    /// database is stored in the `metadata-store` directory of the node's data directory.
+    pub path: Option<String>,
+}
+
+impl Options {
+    /// Path of the metadata store database, falling back to the `metadata-store` directory
+    /// within the given data directory if no path was configured.
+    pub fn storage_path(&self, data_dir: impl AsRef<Path>) -> PathBuf {
+        self.path
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| data_dir.as_ref().join("metadata-store"))
+    }
+
+    pub fn build(self, data_dir: impl AsRef<Path>) -> Result<RocksDbMetadataStore, BuildError> {
+        RocksDbMetadataStore::open(self.storage_path(data_dir))
+    }
+}
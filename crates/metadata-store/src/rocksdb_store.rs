@@ -0,0 +1,240 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bytes::{BufMut, Bytes, BytesMut};
+use codederror::CodedError;
+use rocksdb::{WriteOptions, DB};
+use tokio::sync::watch;
+use tracing::debug;
+
+use restate_core::metadata_store::{
+    MetadataStore, Precondition, ReadError, VersionedValue, WriteError,
+};
+use restate_types::Version;
+
+#[derive(Debug, thiserror::Error, CodedError)]
+pub enum BuildError {
+    #[error("failed opening metadata store: {0}")]
+    #[code(unknown)]
+    RocksDb(#[from] rocksdb::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+enum GetError {
+    #[error(transparent)]
+    RocksDb(#[from] rocksdb::Error),
+    #[error("stored value of key '{key}' is corrupted: expected at least 4 bytes, got {length}")]
+    Corrupted { key: String, length: usize },
+}
+
+/// Metadata store embedded in the node which keeps the metadata durably in RocksDB.
+///
+/// Values are stored as the big-endian encoded version followed by the value bytes.
+/// Compare-and-swap writes are serialized within the process.
+pub struct RocksDbMetadataStore {
+    db: Arc<DB>,
+    write_lock: Arc<Mutex<()>>,
+    watches: Mutex<HashMap<String, watch::Sender<Version>>>,
+}
+
+impl RocksDbMetadataStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BuildError> {
+        let path = path.as_ref();
+        let mut db_options = rocksdb::Options::default();
+        db_options.create_if_missing(true);
+
+        let db = DB::open(&db_options, path)?;
+        debug!("Opened metadata store at '{}'", path.display());
+
+        Ok(Self {
+            db: Arc::new(db),
+            write_lock: Arc::default(),
+            watches: Mutex::default(),
+        })
+    }
+
+    fn get_internal(db: &DB, key: &str) -> Result<Option<VersionedValue>, GetError> {
+        let Some(value) = db.get_pinned(key)? else {
+            return Ok(None);
+        };
+
+        if value.len() < std::mem::size_of::<u32>() {
+            return Err(GetError::Corrupted {
+                key: key.to_owned(),
+                length: value.len(),
+            });
+        }
+
+        let (version, value) = value.split_at(std::mem::size_of::<u32>());
+        let version = u32::from_be_bytes(version.try_into().expect("4 bytes checked above"));
+        Ok(Some(VersionedValue::new(
+            Version::from(version),
+            Bytes::copy_from_slice(value),
+        )))
+    }
+
+    fn encode(value: &VersionedValue) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(std::mem::size_of::<u32>() + value.value.len());
+        buf.put_u32(u32::from(value.version));
+        buf.put_slice(&value.value);
+        buf
+    }
+}
+
+#[async_trait]
+impl MetadataStore for RocksDbMetadataStore {
+    async fn get(&self, key: &str) -> Result<Option<VersionedValue>, ReadError> {
+        let db = Arc::clone(&self.db);
+        let key = key.to_owned();
+        tokio::task::spawn_blocking(move || Self::get_internal(&db, &key))
+            .await
+            .map_err(|err| ReadError::Internal(err.into()))?
+            .map_err(|err| match err {
+                GetError::RocksDb(err) => ReadError::Internal(err.into()),
+                err @ GetError::Corrupted { .. } => ReadError::Codec(err.into()),
+            })
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        value: VersionedValue,
+        precondition: Precondition,
+    ) -> Result<(), WriteError> {
+        let db = Arc::clone(&self.db);
+        let write_lock = Arc::clone(&self.write_lock);
+        let version = value.version;
+        let owned_key = key.to_owned();
+
+        tokio::task::spawn_blocking(move || {
+            let _guard = write_lock.lock().unwrap();
+            let current_version = Self::get_internal(&db, &owned_key)
+                .map_err(|err| match err {
+                    GetError::RocksDb(err) => WriteError::Internal(err.into()),
+                    err @ GetError::Corrupted { .. } => WriteError::Codec(err.into()),
+                })?
+                .map(|current| current.version);
+            precondition.check(current_version)?;
+
+            let mut write_opts = WriteOptions::default();
+            write_opts.set_sync(true);
+            db.put_opt(&owned_key, Self::encode(&value), &write_opts)
+                .map_err(|err| WriteError::Internal(err.into()))
+        })
+        .await
+        .map_err(|err| WriteError::Internal(err.into()))??;
+
+        if let Some(watch) = self.watches.lock().unwrap().get(key) {
+            watch.send_if_modified(|current| {
+                if version > *current {
+                    *current = version;
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn watch(&self, key: &str) -> watch::Receiver<Version> {
+        let mut watches = self.watches.lock().unwrap();
+        if let Some(watch) = watches.get(key) {
+            return watch.subscribe();
+        }
+
+        let current_version = Self::get_internal(&self.db, key)
+            .ok()
+            .flatten()
+            .map(|current| current.version)
+            .unwrap_or(Version::INVALID);
+        let (sender, receiver) = watch::channel(current_version);
+        watches.insert(key.to_owned(), sender);
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Options;
+
+    fn value(version: u32, value: &'static str) -> VersionedValue {
+        VersionedValue::new(Version::from(version), Bytes::from_static(value.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn values_survive_reopening() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let opts = Options::default();
+
+        {
+            let store = opts.clone().build(tmp.path())?;
+            let mut watch = store.watch("nodes_config");
+            assert_eq!(Version::INVALID, *watch.borrow());
+
+            store
+                .put(
+                    "nodes_config",
+                    value(1, "first"),
+                    Precondition::DoesNotExist,
+                )
+                .await?;
+            store
+                .put(
+                    "nodes_config",
+                    value(2, "second"),
+                    Precondition::MatchesVersion(Version::MIN),
+                )
+                .await?;
+            assert!(matches!(
+                store
+                    .put(
+                        "nodes_config",
+                        value(3, "third"),
+                        Precondition::MatchesVersion(Version::MIN)
+                    )
+                    .await,
+                Err(WriteError::FailedPrecondition(_))
+            ));
+
+            watch.changed().await?;
+            assert_eq!(Version::from(2), *watch.borrow());
+        }
+
+        let store = opts.build(tmp.path())?;
+        assert_eq!(Some(value(2, "second")), store.get("nodes_config").await?);
+        assert_eq!(None, store.get("logs").await?);
+        assert_eq!(Version::from(2), *store.watch("nodes_config").borrow());
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn corrupted_values_are_reported() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let store = RocksDbMetadataStore::open(tmp.path())?;
+        store.db.put("logs", [0, 1])?;
+
+        assert!(matches!(store.get("logs").await, Err(ReadError::Codec(_))));
+        assert!(matches!(
+            store
+                .put("logs", value(1, "first"), Precondition::DoesNotExist)
+                .await,
+            Err(WriteError::Codec(_))
+        ));
+
+        Ok(())
+    }
+}
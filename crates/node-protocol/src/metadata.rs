@@ -10,8 +10,10 @@
 
 use bytes::Bytes;
 use enum_map::Enum;
+use restate_types::logs::metadata::Logs;
 use restate_types::nodes_config::NodesConfiguration;
 use restate_types::partition_table::FixedPartitionTable;
+use restate_types::Version;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
@@ -79,6 +81,7 @@ pub enum MetadataKind {
 pub enum MetadataContainer {
     NodesConfiguration(NodesConfiguration),
     PartitionTable(FixedPartitionTable),
+    Logs(Logs),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +100,15 @@ impl MetadataContainer {
         match self {
             MetadataContainer::NodesConfiguration(_) => MetadataKind::NodesConfiguration,
             MetadataContainer::PartitionTable(_) => MetadataKind::PartitionTable,
+            MetadataContainer::Logs(_) => MetadataKind::Logs,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        match self {
+            MetadataContainer::NodesConfiguration(c) => c.version(),
+            MetadataContainer::PartitionTable(p) => p.version(),
+            MetadataContainer::Logs(l) => l.version,
         }
     }
 }
@@ -112,3 +124,9 @@ impl From<FixedPartitionTable> for MetadataContainer {
         MetadataContainer::PartitionTable(value)
     }
 }
+
+impl From<Logs> for MetadataContainer {
+    fn from(value: Logs) -> Self {
+        MetadataContainer::Logs(value)
+    }
+}
@@ -26,6 +26,7 @@ use anyhow::bail;
 use codederror::CodedError;
 use tracing::{error, info};
 
+use restate_core::metadata_store::MetadataStoreClient;
 use restate_core::{spawn_metadata_manager, MetadataManager};
 use restate_core::{task_center, TaskKind};
 use restate_types::nodes_config::{NodeConfig, NodesConfiguration, Role};
@@ -56,6 +57,12 @@ pub enum BuildError {
         #[code]
         roles::AdminRoleBuildError,
     ),
+    #[error("building metadata store failed: {0}")]
+    MetadataStore(
+        #[from]
+        #[code]
+        restate_metadata_store::BuildError,
+    ),
     #[error("node neither runs cluster controller nor its address has been configured")]
     #[code(unknown)]
     UnknownClusterController,
@@ -88,7 +95,9 @@ impl Node {
 
         let mut sr_builder = MessageRouterBuilder::default();
         let networking = Networking::default();
-        let metadata_manager = MetadataManager::build(networking.clone());
+        let metadata_store =
+            MetadataStoreClient::new(options.metadata_store.clone().build(options.data_dir())?);
+        let metadata_manager = MetadataManager::build(networking.clone(), metadata_store);
         metadata_manager.register_in_message_router(&mut sr_builder);
 
         let admin_role = if options.roles.contains(Role::Admin) {
@@ -97,14 +106,22 @@ impl Node {
             None
         };
 
+        let bifrost = options.bifrost.build(
+            options.worker.partitions,
+            metadata_manager.metadata(),
+            metadata_manager.writer(),
+        );
+
         let worker_role = if options.roles.contains(Role::Worker) {
-            Some(WorkerRole::new(options.clone(), networking.clone())?)
+            Some(WorkerRole::new(
+                options.clone(),
+                networking.clone(),
+                bifrost.handle(),
+            )?)
         } else {
             None
         };
 
-        let bifrost = options.bifrost.build(options.worker.partitions);
-
         let server = options.server.build(
             networking.connection_manager(),
             worker_role.as_ref().map(|worker| {
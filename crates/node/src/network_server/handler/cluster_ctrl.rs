@@ -9,8 +9,9 @@
 // by the Apache License, Version 2.0.
 
 use tonic::{async_trait, Request, Response, Status};
-use tracing::debug;
+use tracing::trace;
 
+use restate_cluster_controller::Error;
 use restate_meta::MetaReader;
 use restate_node_services::cluster_ctrl::cluster_ctrl_svc_server::ClusterCtrlSvc;
 use restate_node_services::cluster_ctrl::{AttachmentRequest, AttachmentResponse};
@@ -35,8 +36,22 @@ impl ClusterCtrlSvc for ClusterCtrlSvcHandler {
         request: Request<AttachmentRequest>,
     ) -> Result<Response<AttachmentResponse>, Status> {
         let node_id = request.into_inner().node_id.expect("node id must be set");
-        debug!("Attaching node '{:?}'", node_id);
-        Ok(Response::new(AttachmentResponse {}))
+        let node_id = restate_types::NodeId::from(node_id)
+            .as_generational()
+            .ok_or_else(|| Status::invalid_argument("node id must be generational"))?;
+        trace!("Attaching node '{}'", node_id);
+
+        let leader_partitions = self
+            .admin_deps
+            .cluster_controller_handle
+            .attach_node(node_id)
+            .await
+            .map_err(|err| match err {
+                Error::StaleGeneration(_, _) => Status::failed_precondition(err.to_string()),
+                Error::Shutdown(_) => Status::unavailable(err.to_string()),
+            })?;
+
+        Ok(Response::new(AttachmentResponse { leader_partitions }))
     }
 
     async fn fetch_schemas(
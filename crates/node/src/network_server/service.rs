@@ -190,7 +190,7 @@ impl WorkerDependencies {
 }
 
 pub struct AdminDependencies {
-    pub _cluster_controller_handle: ClusterControllerHandle,
+    pub cluster_controller_handle: ClusterControllerHandle,
     pub schema_reader: FileMetaReader,
 }
 
@@ -200,7 +200,7 @@ impl AdminDependencies {
         schema_reader: FileMetaReader,
     ) -> Self {
         AdminDependencies {
-            _cluster_controller_handle: cluster_controller_handle,
+            cluster_controller_handle,
             schema_reader,
         }
     }
@@ -14,6 +14,7 @@ use restate_types::nodes_config::{AdvertisedAddress, Role};
 use restate_types::PlainNodeId;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::path::{Path, PathBuf};
 
 #[serde_as]
 #[derive(Clone, Debug, Serialize, Deserialize, derive_builder::Builder)]
@@ -35,6 +36,7 @@ pub struct Options {
     pub server: network_server::Options,
     pub admin: restate_admin::Options,
     pub bifrost: restate_bifrost::Options,
+    pub metadata_store: restate_metadata_store::Options,
     pub cluster_controller: restate_cluster_controller::Options,
 
     /// Defines the roles which this Restate node should run
@@ -67,9 +69,21 @@ impl Default for Options {
             server: Default::default(),
             admin: Default::default(),
             bifrost: Default::default(),
+            metadata_store: Default::default(),
             cluster_controller: Default::default(),
             roles: Role::Worker | Role::Admin,
             admin_address: None,
         }
     }
 }
+
+impl Options {
+    /// Data directory of the node, which is the directory containing the worker's RocksDB
+    /// database. It is used to place storage which isn't configured explicitly.
+    pub fn data_dir(&self) -> PathBuf {
+        Path::new(self.worker.storage_path())
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    }
+}
@@ -8,27 +8,32 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashSet;
 use std::time::Duration;
 
 use codederror::CodedError;
+use restate_bifrost::Bifrost;
 use restate_network::Networking;
 use tonic::transport::Channel;
 use tracing::subscriber::NoSubscriber;
-use tracing::trace;
+use tracing::{debug, trace};
 
 use restate_core::TaskKind;
 use restate_core::{metadata, task_center};
 use restate_network::utils::create_grpc_channel_from_network_address;
 use restate_node_services::cluster_ctrl::cluster_ctrl_svc_client::ClusterCtrlSvcClient;
-use restate_node_services::cluster_ctrl::AttachmentRequest;
 use restate_node_services::cluster_ctrl::FetchSchemasRequest;
+use restate_node_services::cluster_ctrl::{AttachmentRequest, AttachmentResponse};
 use restate_schema_api::subscription::SubscriptionResolver;
 use restate_schema_impl::{Schemas, SchemasUpdateCommand};
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::PartitionId;
 use restate_types::nodes_config::AdvertisedAddress;
 use restate_types::retries::RetryPolicy;
-use restate_worker::{SubscriptionControllerHandle, Worker, WorkerCommandSender};
+use restate_worker::{
+    PartitionLeadershipSender, SubscriptionControllerHandle, Worker, WorkerCommandSender,
+};
 use restate_worker_api::SubscriptionController;
 use tracing::info;
 
@@ -54,6 +59,9 @@ pub enum WorkerRoleError {
     #[error("failed to attach to cluster at '{0}': {1}")]
     #[code(unknown)]
     Attachment(AdvertisedAddress, tonic::Status),
+    #[error("partition processors are not running")]
+    #[code(unknown)]
+    PartitionProcessorsStopped,
 }
 
 #[derive(Debug, thiserror::Error, CodedError)]
@@ -94,14 +102,24 @@ pub enum WorkerRoleBuildError {
 pub struct WorkerRole {
     schemas: Schemas,
     worker: Worker,
+    heartbeat_interval: Duration,
 }
 
 impl WorkerRole {
-    pub fn new(options: Options, networking: Networking) -> Result<Self, WorkerRoleBuildError> {
+    pub fn new(
+        options: Options,
+        networking: Networking,
+        bifrost: Bifrost,
+    ) -> Result<Self, WorkerRoleBuildError> {
         let schemas = Schemas::default();
-        let worker = options.worker.build(networking, schemas.clone())?;
-
-        Ok(WorkerRole { schemas, worker })
+        let worker = options.worker.build(networking, bifrost, schemas.clone())?;
+        let heartbeat_interval = options.cluster_controller.heartbeat_interval();
+
+        Ok(WorkerRole {
+            schemas,
+            worker,
+            heartbeat_interval,
+        })
     }
 
     pub fn rocksdb_storage(&self) -> &RocksDBStorage {
@@ -155,15 +173,30 @@ impl WorkerRole {
             Self::reload_schemas(subscription_controller, self.schemas, cluster_ctrl_client),
         )?;
 
-        task_center().spawn_child(TaskKind::RoleRunner, "worker-service", None, async {
-            Self::attach_node(admin_address).await?;
-            self.worker.run().await
+        let heartbeat_interval = self.heartbeat_interval;
+        let partition_leadership_tx = self.worker.partition_leadership_tx();
+        let worker = self.worker;
+
+        task_center().spawn_child(TaskKind::RoleRunner, "worker-service", None, async move {
+            let cc_client = Self::attach_node(admin_address, &partition_leadership_tx).await?;
+
+            task_center().spawn_child(
+                TaskKind::SystemService,
+                "cluster-controller-heartbeat",
+                None,
+                Self::send_heartbeats(cc_client, heartbeat_interval, partition_leadership_tx),
+            )?;
+
+            worker.run().await
         })?;
 
         Ok(())
     }
 
-    async fn attach_node(admin_address: AdvertisedAddress) -> Result<(), WorkerRoleError> {
+    async fn attach_node(
+        admin_address: AdvertisedAddress,
+        partition_leadership_tx: &PartitionLeadershipSender,
+    ) -> Result<ClusterCtrlSvcClient<Channel>, WorkerRoleError> {
         info!("Worker attaching to admin at '{admin_address}'");
 
         let channel = create_grpc_channel_from_network_address(admin_address.clone())
@@ -171,7 +204,7 @@ impl WorkerRole {
 
         let cc_client = ClusterCtrlSvcClient::new(channel);
 
-        let _response = RetryPolicy::exponential(Duration::from_millis(50), 2.0, 10, None)
+        let response = RetryPolicy::exponential(Duration::from_millis(50), 2.0, 10, None)
             .retry_operation(|| async {
                 cc_client
                     .clone()
@@ -182,7 +215,60 @@ impl WorkerRole {
             })
             .await
             .map_err(|err| WorkerRoleError::Attachment(admin_address, err))?;
-        Ok(())
+
+        Self::update_partition_leadership(partition_leadership_tx, response.into_inner()).await?;
+
+        Ok(cc_client)
+    }
+
+    /// Periodically re-attaches to the cluster controller to signal liveness and to learn
+    /// about changes of the partitions this worker is supposed to lead.
+    async fn send_heartbeats(
+        cc_client: ClusterCtrlSvcClient<Channel>,
+        heartbeat_interval: Duration,
+        partition_leadership_tx: PartitionLeadershipSender,
+    ) -> anyhow::Result<()> {
+        let mut heartbeat = tokio::time::interval(heartbeat_interval);
+        // the first tick completes immediately, but we have just attached
+        heartbeat.tick().await;
+
+        loop {
+            heartbeat.tick().await;
+
+            let response = cc_client
+                .clone()
+                .attach_node(AttachmentRequest {
+                    node_id: Some(metadata().my_node_id().into()),
+                })
+                .await;
+
+            match response {
+                Ok(response) => {
+                    Self::update_partition_leadership(
+                        &partition_leadership_tx,
+                        response.into_inner(),
+                    )
+                    .await?;
+                }
+                Err(err) => {
+                    debug!("Failed sending heartbeat to the cluster controller: {err}");
+                }
+            }
+        }
+    }
+
+    async fn update_partition_leadership(
+        partition_leadership_tx: &PartitionLeadershipSender,
+        response: AttachmentResponse,
+    ) -> Result<(), WorkerRoleError> {
+        let leader_partitions: HashSet<PartitionId> =
+            response.leader_partitions.into_iter().collect();
+        trace!("Leading partitions {:?}", leader_partitions);
+
+        partition_leadership_tx
+            .send(leader_partitions)
+            .await
+            .map_err(|_| WorkerRoleError::PartitionProcessorsStopped)
     }
 
     fn ignore_fetch_error(result: Result<(), SchemaError>) -> Result<(), SchemaError> {
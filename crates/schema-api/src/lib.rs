@@ -294,6 +294,145 @@ pub mod deployment {
     }
 }
 
+#[cfg(feature = "concurrency_limit")]
+pub mod concurrency_limit {
+    use restate_types::identifiers::DeploymentId;
+
+    /// Scope of a concurrency limit of the invoker.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ConcurrencyLimitScope {
+        Deployment(DeploymentId),
+        Component(String),
+        Handler { component: String, handler: String },
+    }
+
+    /// This API will return the concurrency limits which have been configured for specific
+    /// deployments, components and handlers.
+    pub trait ConcurrencyLimitResolver {
+        /// Returns None if no concurrency limit is configured for the given scope.
+        fn resolve_concurrency_limit(&self, scope: &ConcurrencyLimitScope) -> Option<usize>;
+    }
+
+    #[cfg(feature = "mocks")]
+    pub mod mocks {
+        use super::*;
+
+        use std::collections::HashMap;
+
+        #[derive(Debug, Default, Clone)]
+        pub struct MockConcurrencyLimitResolver(HashMap<ConcurrencyLimitScope, usize>);
+
+        impl MockConcurrencyLimitResolver {
+            pub fn add(&mut self, scope: ConcurrencyLimitScope, limit: usize) {
+                self.0.insert(scope, limit);
+            }
+        }
+
+        impl ConcurrencyLimitResolver for MockConcurrencyLimitResolver {
+            fn resolve_concurrency_limit(&self, scope: &ConcurrencyLimitScope) -> Option<usize> {
+                self.0.get(scope).copied()
+            }
+        }
+    }
+}
+
+#[cfg(feature = "retry_policy")]
+pub mod retry_policy {
+    use restate_types::retries::RetryPolicy;
+
+    /// What the invoker does with an invocation which exhausted the attempts of its retry policy.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+    #[cfg_attr(feature = "serde_schema", derive(schemars::JsonSchema))]
+    pub enum OnMaxAttempts {
+        /// # Fail
+        ///
+        /// Fail the invocation with a max attempts exceeded error.
+        #[default]
+        Fail,
+        /// # Pause
+        ///
+        /// Stop retrying the invocation, and keep it until it is manually killed or cancelled.
+        /// Paused invocations are retried again when the partition leadership changes.
+        Pause,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde_schema", derive(schemars::JsonSchema))]
+    pub struct InvocationRetryPolicy {
+        /// # Retry policy
+        ///
+        /// Retry policy to use for the invocations, instead of the one configured in the invoker.
+        pub retry_policy: RetryPolicy,
+
+        /// # On max attempts
+        ///
+        /// What to do with an invocation which exhausted the attempts of the retry policy.
+        #[cfg_attr(feature = "serde", serde(default))]
+        pub on_max_attempts: OnMaxAttempts,
+    }
+
+    /// Scope of an invocation retry policy.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum RetryPolicyScope {
+        Component(String),
+        Handler { component: String, handler: String },
+    }
+
+    /// This API will return the retry policies which have been configured for specific
+    /// components and handlers.
+    pub trait InvocationRetryPolicyResolver {
+        /// Returns the retry policy of the given handler, or the one of its component if the
+        /// handler has none. Returns None if neither of them has a retry policy.
+        fn resolve_invocation_retry_policy(
+            &self,
+            component: impl AsRef<str>,
+            handler: impl AsRef<str>,
+        ) -> Option<InvocationRetryPolicy>;
+    }
+
+    #[cfg(feature = "mocks")]
+    pub mod mocks {
+        use super::*;
+
+        use std::collections::HashMap;
+
+        #[derive(Debug, Default, Clone)]
+        pub struct MockInvocationRetryPolicyResolver(
+            HashMap<RetryPolicyScope, InvocationRetryPolicy>,
+        );
+
+        impl MockInvocationRetryPolicyResolver {
+            pub fn add(&mut self, scope: RetryPolicyScope, retry_policy: InvocationRetryPolicy) {
+                self.0.insert(scope, retry_policy);
+            }
+        }
+
+        impl InvocationRetryPolicyResolver for MockInvocationRetryPolicyResolver {
+            fn resolve_invocation_retry_policy(
+                &self,
+                component: impl AsRef<str>,
+                handler: impl AsRef<str>,
+            ) -> Option<InvocationRetryPolicy> {
+                self.0
+                    .get(&RetryPolicyScope::Handler {
+                        component: component.as_ref().to_owned(),
+                        handler: handler.as_ref().to_owned(),
+                    })
+                    .or_else(|| {
+                        self.0
+                            .get(&RetryPolicyScope::Component(component.as_ref().to_owned()))
+                    })
+                    .cloned()
+            }
+        }
+    }
+}
+
 #[cfg(feature = "component")]
 pub mod component {
     use restate_types::identifiers::{ComponentRevision, DeploymentId};
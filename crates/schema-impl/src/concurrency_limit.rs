@@ -0,0 +1,20 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::Schemas;
+
+use restate_schema_api::concurrency_limit::{ConcurrencyLimitResolver, ConcurrencyLimitScope};
+
+impl ConcurrencyLimitResolver for Schemas {
+    fn resolve_concurrency_limit(&self, scope: &ConcurrencyLimitScope) -> Option<usize> {
+        let schemas = self.0.load();
+        schemas.concurrency_limits.get(scope).copied()
+    }
+}
@@ -14,7 +14,9 @@ use bytes::Bytes;
 use http::Uri;
 use prost_reflect::{DescriptorPool, ServiceDescriptor};
 use restate_schema_api::component::{ComponentMetadata, ComponentType, HandlerMetadata};
+use restate_schema_api::concurrency_limit::ConcurrencyLimitScope;
 use restate_schema_api::deployment::DeploymentMetadata;
+use restate_schema_api::retry_policy::{InvocationRetryPolicy, RetryPolicyScope};
 use restate_schema_api::service::ServiceMetadata;
 use restate_schema_api::subscription::{Subscription, SubscriptionValidator};
 use restate_service_protocol::discovery::schema;
@@ -24,12 +26,14 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 mod component;
+mod concurrency_limit;
 mod deployment;
 mod json;
 mod json_key_conversion;
 mod key_expansion;
 mod key_extraction;
 mod proto_symbol;
+mod retry_policy;
 mod schemas_impl;
 mod service;
 mod subscriptions;
@@ -183,6 +187,17 @@ pub enum SchemasUpdateCommand {
     },
     AddSubscription(Subscription),
     RemoveSubscription(SubscriptionId),
+    /// Set the concurrency limit of the scope, or remove it if `limit` is `None`
+    ModifyConcurrencyLimit {
+        scope: ConcurrencyLimitScope,
+        limit: Option<usize>,
+    },
+    /// Set the retry policy of the scope, or remove it if `retry_policy` is `None`
+    ModifyRetryPolicy {
+        scope: RetryPolicyScope,
+        #[serde(with = "retry_policy_serde")]
+        retry_policy: Option<InvocationRetryPolicy>,
+    },
 }
 
 mod descriptor_pool_serde {
@@ -206,6 +221,32 @@ mod descriptor_pool_serde {
     }
 }
 
+// The retry policy is an internally tagged enum, which cannot be deserialized from formats which
+// are not self-describing, like the bincode format used by the meta storage.
+mod retry_policy_serde {
+    use restate_schema_api::retry_policy::InvocationRetryPolicy;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(
+        retry_policy: &Option<InvocationRetryPolicy>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer
+            .serialize_str(&serde_json::to_string(retry_policy).map_err(serde::ser::Error::custom)?)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<InvocationRetryPolicy>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        serde_json::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 /// The schema registry
 #[derive(Debug, Default, Clone)]
 pub struct Schemas(Arc<ArcSwap<schemas_impl::SchemasInner>>);
@@ -239,6 +280,9 @@ pub enum SchemasUpdateError {
     #[error("cannot insert/modify component {0} as it contains a reserved name")]
     #[code(restate_errors::META0005)]
     ReservedName(String),
+    #[error("unknown handler {handler} of component {component}")]
+    #[code(restate_errors::META0005)]
+    UnknownHandler { component: String, handler: String },
     #[error("unknown deployment id {0}")]
     UnknownDeployment(DeploymentId),
     #[error("unknown subscription id {0}")]
@@ -345,6 +389,26 @@ impl Schemas {
             .compute_modify_component_updates(service_name, public)
     }
 
+    pub fn compute_modify_concurrency_limit(
+        &self,
+        scope: ConcurrencyLimitScope,
+        limit: Option<usize>,
+    ) -> Result<SchemasUpdateCommand, SchemasUpdateError> {
+        self.0
+            .load()
+            .compute_modify_concurrency_limit_updates(scope, limit)
+    }
+
+    pub fn compute_modify_retry_policy(
+        &self,
+        scope: RetryPolicyScope,
+        retry_policy: Option<InvocationRetryPolicy>,
+    ) -> Result<SchemasUpdateCommand, SchemasUpdateError> {
+        self.0
+            .load()
+            .compute_modify_retry_policy_updates(scope, retry_policy)
+    }
+
     pub fn compute_remove_deployment(
         &self,
         deployment_id: DeploymentId,
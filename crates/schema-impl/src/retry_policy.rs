@@ -0,0 +1,37 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::Schemas;
+
+use restate_schema_api::retry_policy::{
+    InvocationRetryPolicy, InvocationRetryPolicyResolver, RetryPolicyScope,
+};
+
+impl InvocationRetryPolicyResolver for Schemas {
+    fn resolve_invocation_retry_policy(
+        &self,
+        component: impl AsRef<str>,
+        handler: impl AsRef<str>,
+    ) -> Option<InvocationRetryPolicy> {
+        let schemas = self.0.load();
+        schemas
+            .retry_policies
+            .get(&RetryPolicyScope::Handler {
+                component: component.as_ref().to_owned(),
+                handler: handler.as_ref().to_owned(),
+            })
+            .or_else(|| {
+                schemas
+                    .retry_policies
+                    .get(&RetryPolicyScope::Component(component.as_ref().to_owned()))
+            })
+            .cloned()
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::*;
+
+impl SchemasInner {
+    pub(crate) fn compute_modify_concurrency_limit_updates(
+        &self,
+        scope: ConcurrencyLimitScope,
+        limit: Option<usize>,
+    ) -> Result<SchemasUpdateCommand, SchemasUpdateError> {
+        match &scope {
+            ConcurrencyLimitScope::Deployment(deployment_id) => {
+                if !self.deployments.contains_key(deployment_id) {
+                    return Err(SchemasUpdateError::UnknownDeployment(*deployment_id));
+                }
+            }
+            ConcurrencyLimitScope::Component(component) => {
+                self.check_component_exists(component)?;
+            }
+            ConcurrencyLimitScope::Handler { component, handler } => {
+                self.check_handler_exists(component, handler)?;
+            }
+        }
+
+        Ok(SchemasUpdateCommand::ModifyConcurrencyLimit { scope, limit })
+    }
+
+    pub(crate) fn apply_modify_concurrency_limit(
+        &mut self,
+        scope: ConcurrencyLimitScope,
+        limit: Option<usize>,
+    ) -> Result<(), SchemasUpdateError> {
+        debug!(?scope, ?limit, "Modify concurrency limit");
+
+        match limit {
+            Some(limit) => {
+                self.concurrency_limits.insert(scope, limit);
+            }
+            None => {
+                self.concurrency_limits.remove(&scope);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -593,6 +593,8 @@ impl SchemasInner {
         deployment_id: DeploymentId,
     ) -> Result<(), SchemasUpdateError> {
         self.deployments.remove(&deployment_id);
+        self.concurrency_limits
+            .remove(&ConcurrencyLimitScope::Deployment(deployment_id));
 
         Ok(())
     }
@@ -1384,8 +1386,14 @@ mod tests {
     use super::*;
 
     use restate_schema_api::component::ComponentMetadataResolver;
+    use restate_schema_api::concurrency_limit::ConcurrencyLimitResolver;
     use restate_schema_api::deployment::{Deployment, DeploymentResolver};
+    use restate_schema_api::retry_policy::{
+        InvocationRetryPolicyResolver, OnMaxAttempts, RetryPolicyScope,
+    };
     use restate_test_util::{assert, assert_eq, let_assert};
+    use restate_types::retries::RetryPolicy;
+    use std::time::Duration;
     use test_log::test;
 
     const GREETER_SERVICE_NAME: &str = "greeter.Greeter";
@@ -1736,6 +1744,157 @@ mod tests {
         assert!(schemas.get_deployment(&deployment_1.id).is_none());
     }
 
+    #[test]
+    fn modify_concurrency_limits() {
+        let schemas = Schemas::default();
+
+        let deployment = Deployment::mock();
+        schemas
+            .apply_updates(
+                schemas
+                    .compute_new_deployment(
+                        Some(deployment.id),
+                        deployment.metadata.clone(),
+                        vec![greeter_service()],
+                        false,
+                    )
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let deployment_scope = ConcurrencyLimitScope::Deployment(deployment.id);
+        let handler_scope = ConcurrencyLimitScope::Handler {
+            component: GREETER_SERVICE_NAME.to_owned(),
+            handler: "greet".to_owned(),
+        };
+        schemas
+            .apply_updates([
+                schemas
+                    .compute_modify_concurrency_limit(deployment_scope.clone(), Some(10))
+                    .unwrap(),
+                schemas
+                    .compute_modify_concurrency_limit(handler_scope.clone(), Some(2))
+                    .unwrap(),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            schemas.resolve_concurrency_limit(&deployment_scope),
+            Some(10)
+        );
+        assert_eq!(schemas.resolve_concurrency_limit(&handler_scope), Some(2));
+        assert_eq!(
+            schemas.resolve_concurrency_limit(&ConcurrencyLimitScope::Component(
+                GREETER_SERVICE_NAME.to_owned()
+            )),
+            None
+        );
+
+        assert!(let Err(SchemasUpdateError::UnknownHandler { .. }) = schemas
+        .compute_modify_concurrency_limit(
+            ConcurrencyLimitScope::Handler {
+                component: GREETER_SERVICE_NAME.to_owned(),
+                handler: "unknown".to_owned(),
+            },
+            Some(1),
+        ));
+
+        // removing the limit falls back to the default of the invoker
+        schemas
+            .apply_updates([schemas
+                .compute_modify_concurrency_limit(handler_scope.clone(), None)
+                .unwrap()])
+            .unwrap();
+        assert_eq!(schemas.resolve_concurrency_limit(&handler_scope), None);
+
+        // the limit of a deployment is removed together with the deployment
+        schemas
+            .apply_updates(schemas.compute_remove_deployment(deployment.id).unwrap())
+            .unwrap();
+        assert_eq!(schemas.resolve_concurrency_limit(&deployment_scope), None);
+    }
+
+    #[test]
+    fn modify_retry_policies() {
+        let schemas = Schemas::default();
+
+        let deployment = Deployment::mock();
+        schemas
+            .apply_updates(
+                schemas
+                    .compute_new_deployment(
+                        Some(deployment.id),
+                        deployment.metadata.clone(),
+                        vec![greeter_service()],
+                        false,
+                    )
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let component_policy = InvocationRetryPolicy {
+            retry_policy: RetryPolicy::fixed_delay(Duration::from_millis(100), 3),
+            on_max_attempts: OnMaxAttempts::Fail,
+        };
+        let handler_policy = InvocationRetryPolicy {
+            retry_policy: RetryPolicy::None,
+            on_max_attempts: OnMaxAttempts::Pause,
+        };
+        let handler_scope = RetryPolicyScope::Handler {
+            component: GREETER_SERVICE_NAME.to_owned(),
+            handler: "greet".to_owned(),
+        };
+
+        let updates = vec![
+            schemas
+                .compute_modify_retry_policy(
+                    RetryPolicyScope::Component(GREETER_SERVICE_NAME.to_owned()),
+                    Some(component_policy.clone()),
+                )
+                .unwrap(),
+            schemas
+                .compute_modify_retry_policy(handler_scope.clone(), Some(handler_policy.clone()))
+                .unwrap(),
+        ];
+
+        // the updates must survive the encoding used by the meta storage
+        let config = bincode::config::standard();
+        let encoded = bincode::serde::encode_to_vec(&updates, config).unwrap();
+        let (updates, _): (Vec<SchemasUpdateCommand>, _) =
+            bincode::serde::decode_from_slice(&encoded, config).unwrap();
+        schemas.apply_updates(updates).unwrap();
+
+        assert_eq!(
+            schemas.resolve_invocation_retry_policy(GREETER_SERVICE_NAME, "greet"),
+            Some(handler_policy)
+        );
+        assert_eq!(
+            schemas.resolve_invocation_retry_policy(GREETER_SERVICE_NAME, "other"),
+            Some(component_policy.clone())
+        );
+        assert_eq!(
+            schemas.resolve_invocation_retry_policy(ANOTHER_GREETER_SERVICE_NAME, "greet"),
+            None
+        );
+
+        assert!(let Err(SchemasUpdateError::UnknownComponent(_)) = schemas
+        .compute_modify_retry_policy(
+            RetryPolicyScope::Component(ANOTHER_GREETER_SERVICE_NAME.to_owned()),
+            None,
+        ));
+
+        // removing the handler policy falls back to the component policy
+        schemas
+            .apply_updates([schemas
+                .compute_modify_retry_policy(handler_scope, None)
+                .unwrap()])
+            .unwrap();
+        assert_eq!(
+            schemas.resolve_invocation_retry_policy(GREETER_SERVICE_NAME, "greet"),
+            Some(component_policy)
+        );
+    }
+
     mod remove_method {
         use super::*;
 
@@ -24,7 +24,9 @@ use std::collections::HashMap;
 use tracing::{debug, info, warn};
 
 mod component;
+mod concurrency_limit;
 pub(crate) mod deployment;
+mod retry_policy;
 mod service;
 mod subscription;
 
@@ -58,6 +60,8 @@ pub(crate) struct SchemasInner {
     pub(crate) services: HashMap<String, ServiceSchemas>,
     pub(crate) deployments: HashMap<DeploymentId, DeploymentSchemas>,
     pub(crate) subscriptions: HashMap<SubscriptionId, Subscription>,
+    pub(crate) concurrency_limits: HashMap<ConcurrencyLimitScope, usize>,
+    pub(crate) retry_policies: HashMap<RetryPolicyScope, InvocationRetryPolicy>,
     pub(crate) proto_symbols: ProtoSymbols,
 }
 
@@ -117,11 +121,50 @@ impl SchemasInner {
                 SchemasUpdateCommand::ModifyComponent { name, public } => {
                     self.apply_modify_component(name, public)?;
                 }
+                SchemasUpdateCommand::ModifyConcurrencyLimit { scope, limit } => {
+                    self.apply_modify_concurrency_limit(scope, limit)?;
+                }
+                SchemasUpdateCommand::ModifyRetryPolicy {
+                    scope,
+                    retry_policy,
+                } => {
+                    self.apply_modify_retry_policy(scope, retry_policy)?;
+                }
             }
         }
 
         Ok(())
     }
+
+    fn check_component_exists(&self, component: &str) -> Result<(), SchemasUpdateError> {
+        if !self.services.contains_key(component) && !self.components.contains_key(component) {
+            return Err(SchemasUpdateError::UnknownComponent(component.to_owned()));
+        }
+        Ok(())
+    }
+
+    fn check_handler_exists(
+        &self,
+        component: &str,
+        handler: &str,
+    ) -> Result<(), SchemasUpdateError> {
+        self.check_component_exists(component)?;
+        let has_handler = self
+            .services
+            .get(component)
+            .is_some_and(|s| s.methods.contains_key(handler))
+            || self
+                .components
+                .get(component)
+                .is_some_and(|c| c.handlers.contains_key(handler));
+        if !has_handler {
+            return Err(SchemasUpdateError::UnknownHandler {
+                component: component.to_owned(),
+                handler: handler.to_owned(),
+            });
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -410,6 +453,8 @@ impl Default for SchemasInner {
             services: Default::default(),
             deployments: Default::default(),
             subscriptions: Default::default(),
+            concurrency_limits: Default::default(),
+            retry_policies: Default::default(),
             proto_symbols: Default::default(),
         };
 
@@ -0,0 +1,52 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::*;
+
+impl SchemasInner {
+    pub(crate) fn compute_modify_retry_policy_updates(
+        &self,
+        scope: RetryPolicyScope,
+        retry_policy: Option<InvocationRetryPolicy>,
+    ) -> Result<SchemasUpdateCommand, SchemasUpdateError> {
+        match &scope {
+            RetryPolicyScope::Component(component) => {
+                self.check_component_exists(component)?;
+            }
+            RetryPolicyScope::Handler { component, handler } => {
+                self.check_handler_exists(component, handler)?;
+            }
+        }
+
+        Ok(SchemasUpdateCommand::ModifyRetryPolicy {
+            scope,
+            retry_policy,
+        })
+    }
+
+    pub(crate) fn apply_modify_retry_policy(
+        &mut self,
+        scope: RetryPolicyScope,
+        retry_policy: Option<InvocationRetryPolicy>,
+    ) -> Result<(), SchemasUpdateError> {
+        debug!(?scope, ?retry_policy, "Modify retry policy");
+
+        match retry_policy {
+            Some(retry_policy) => {
+                self.retry_policies.insert(scope, retry_policy);
+            }
+            None => {
+                self.retry_policies.remove(&scope);
+            }
+        }
+
+        Ok(())
+    }
+}
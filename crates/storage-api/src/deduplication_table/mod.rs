@@ -12,12 +12,36 @@ use crate::Result;
 use bytestring::ByteString;
 use futures_util::Stream;
 use restate_types::identifiers::PartitionId;
+use restate_types::PlainNodeId;
 use std::future::Future;
 
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone)]
 pub enum SequenceNumberSource {
+    /// Shuffle of another partition processor
     Partition(PartitionId),
+    /// Ingress which deduplicates its messages with a custom dedup key
     Ingress(ByteString),
+    /// Ingress of a node, the epoch is the generation of the node
+    IngressNode(PlainNodeId),
+    /// Leader of this partition, the epoch is the leader epoch
+    SelfProposal,
+}
+
+/// Highest sequence number that has been observed from a producer, together with the highest
+/// epoch of the producer. Messages from lower epochs of a producer are rejected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EpochSequenceNumber {
+    pub epoch: u64,
+    pub sequence_number: u64,
+}
+
+impl EpochSequenceNumber {
+    pub fn new(epoch: u64, sequence_number: u64) -> Self {
+        Self {
+            epoch,
+            sequence_number,
+        }
+    }
 }
 
 pub trait DeduplicationTable {
@@ -25,17 +49,17 @@ pub trait DeduplicationTable {
         &mut self,
         partition_id: PartitionId,
         source: SequenceNumberSource,
-    ) -> impl Future<Output = Result<Option<u64>>> + Send;
+    ) -> impl Future<Output = Result<Option<EpochSequenceNumber>>> + Send;
 
     fn put_sequence_number(
         &mut self,
         partition_id: PartitionId,
         source: SequenceNumberSource,
-        sequence_number: u64,
+        sequence_number: EpochSequenceNumber,
     ) -> impl Future<Output = ()> + Send;
 
     fn get_all_sequence_numbers(
         &mut self,
         partition_id: PartitionId,
-    ) -> impl Stream<Item = Result<(SequenceNumberSource, u64)>> + Send;
+    ) -> impl Stream<Item = Result<(SequenceNumberSource, EpochSequenceNumber)>> + Send;
 }
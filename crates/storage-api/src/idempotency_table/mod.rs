@@ -0,0 +1,39 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::Result;
+use restate_types::identifiers::{IdempotencyId, InvocationId};
+use std::future::Future;
+
+/// Invocation which has been started by an idempotent request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyMetadata {
+    pub invocation_id: InvocationId,
+}
+
+pub trait ReadOnlyIdempotencyTable {
+    fn get_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> impl Future<Output = Result<Option<IdempotencyMetadata>>> + Send;
+}
+
+pub trait IdempotencyTable: ReadOnlyIdempotencyTable {
+    fn put_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+        metadata: IdempotencyMetadata,
+    ) -> impl Future<Output = ()> + Send;
+
+    fn delete_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> impl Future<Output = ()> + Send;
+}
@@ -15,7 +15,8 @@ use restate_types::identifiers::{
     DeploymentId, EntryIndex, FullInvocationId, InvocationId, PartitionKey, ServiceId,
 };
 use restate_types::invocation::{
-    ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source,
+    Idempotency, ResponseResult, ServiceInvocationResponseSink, ServiceInvocationSpanContext,
+    Source,
 };
 use restate_types::time::MillisSinceEpoch;
 use std::collections::HashSet;
@@ -84,6 +85,8 @@ pub enum InvocationStatus {
         completion_notification_target: NotificationTarget,
         kill_notification_target: NotificationTarget,
     },
+    /// Invocation has completed, its result and journal are retained until the expiry time
+    Completed(CompletedInvocation),
     /// Service instance is currently not invoked
     #[default]
     Free,
@@ -95,6 +98,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(metadata.service_id.clone()),
             InvocationStatus::Suspended { metadata, .. } => Some(metadata.service_id.clone()),
+            InvocationStatus::Completed(completed) => Some(completed.service_id.clone()),
             _ => None,
         }
     }
@@ -104,6 +108,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(metadata.journal_metadata),
+            InvocationStatus::Completed(completed) => Some(completed.journal_metadata),
             InvocationStatus::Free => None,
             InvocationStatus::Virtual {
                 journal_metadata, ..
@@ -116,6 +121,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(&metadata.journal_metadata),
+            InvocationStatus::Completed(completed) => Some(&completed.journal_metadata),
             InvocationStatus::Free => None,
             InvocationStatus::Virtual {
                 journal_metadata, ..
@@ -128,6 +134,8 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&mut metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(&mut metadata.journal_metadata),
+            // the journal of a completed invocation must not change anymore
+            InvocationStatus::Completed(_) => None,
             InvocationStatus::Free => None,
             InvocationStatus::Virtual {
                 journal_metadata, ..
@@ -135,11 +143,21 @@ impl InvocationStatus {
         }
     }
 
+    #[inline]
+    pub fn get_invocation_metadata_mut(&mut self) -> Option<&mut InvocationMetadata> {
+        match self {
+            InvocationStatus::Invoked(metadata) => Some(metadata),
+            InvocationStatus::Suspended { metadata, .. } => Some(metadata),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn get_timestamps(&self) -> Option<&StatusTimestamps> {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&metadata.timestamps),
             InvocationStatus::Suspended { metadata, .. } => Some(&metadata.timestamps),
+            InvocationStatus::Completed(completed) => Some(&completed.timestamps),
             InvocationStatus::Free => None,
             InvocationStatus::Virtual { timestamps, .. } => Some(timestamps),
         }
@@ -150,6 +168,7 @@ impl InvocationStatus {
             InvocationStatus::Invoked(metadata) => metadata.timestamps.update(),
             InvocationStatus::Suspended { metadata, .. } => metadata.timestamps.update(),
             InvocationStatus::Virtual { timestamps, .. } => timestamps.update(),
+            InvocationStatus::Completed(completed) => completed.timestamps.update(),
             InvocationStatus::Free => {}
         }
     }
@@ -175,35 +194,56 @@ impl JournalMetadata {
     }
 }
 
+/// Result of a completed invocation, which is retained until [`CompletedInvocation::expiry`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedInvocation {
+    pub service_id: ServiceId,
+    pub method: ByteString,
+    pub journal_metadata: JournalMetadata,
+    pub result: ResponseResult,
+    pub timestamps: StatusTimestamps,
+    pub source: Source,
+    /// Time after which the status and the journal of the invocation are removed.
+    pub expiry: MillisSinceEpoch,
+    /// Idempotency key of the invocation, whose entry in the idempotency table is removed
+    /// together with the status.
+    pub idempotency_key: Option<ByteString>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct InvocationMetadata {
     pub service_id: ServiceId,
     pub journal_metadata: JournalMetadata,
     pub deployment_id: Option<DeploymentId>,
     pub method: ByteString,
-    pub response_sink: Option<ServiceInvocationResponseSink>,
+    /// Sinks which receive the result of the invocation
+    pub response_sinks: HashSet<ServiceInvocationResponseSink>,
     pub timestamps: StatusTimestamps,
     pub source: Source,
+    pub idempotency: Option<Idempotency>,
 }
 
 impl InvocationMetadata {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         service_id: ServiceId,
         journal_metadata: JournalMetadata,
         deployment_id: Option<DeploymentId>,
         method: ByteString,
-        response_sink: Option<ServiceInvocationResponseSink>,
+        response_sinks: HashSet<ServiceInvocationResponseSink>,
         timestamps: StatusTimestamps,
         source: Source,
+        idempotency: Option<Idempotency>,
     ) -> Self {
         Self {
             service_id,
             journal_metadata,
             deployment_id,
             method,
-            response_sink,
+            response_sinks,
             timestamps,
             source,
+            idempotency,
         }
     }
 }
@@ -244,9 +284,10 @@ mod mocks {
                 journal_metadata: JournalMetadata::initialize(ServiceInvocationSpanContext::empty()),
                 deployment_id: None,
                 method: ByteString::from("mock"),
-                response_sink: None,
+                response_sinks: HashSet::new(),
                 timestamps: StatusTimestamps::now(),
                 source: Source::Ingress,
+                idempotency: None,
             }
         }
     }
@@ -27,6 +27,7 @@ pub type Result<T> = std::result::Result<T, StorageError>;
 
 pub mod deduplication_table;
 pub mod fsm_table;
+pub mod idempotency_table;
 pub mod inbox_table;
 pub mod invocation_status_table;
 pub mod journal_table;
@@ -53,6 +54,7 @@ pub trait Transaction:
     + journal_table::JournalTable
     + fsm_table::FsmTable
     + timer_table::TimerTable
+    + idempotency_table::IdempotencyTable
     + Send
 {
     fn commit(self) -> impl Future<Output = Result<()>> + Send;
@@ -45,6 +45,8 @@ impl Ord for TimerKey {
 pub enum Timer {
     CompleteSleepEntry(ServiceId),
     Invoke(ServiceId, ServiceInvocation),
+    /// Removes the retained status and journal of a completed invocation
+    CleanInvocationStatus(ServiceId),
 }
 
 impl Timer {
@@ -52,6 +54,7 @@ impl Timer {
         match self {
             CompleteSleepEntry(service_id) => service_id,
             Timer::Invoke(service_id, _) => service_id,
+            Timer::CleanInvocationStatus(service_id) => service_id,
         }
     }
 }
@@ -24,7 +24,9 @@ pub mod storage {
                 Awakeable, BackgroundCall, ClearAllState, ClearState, CompleteAwakeable, Custom,
                 GetState, GetStateKeys, Invoke, OutputStream, PollInputStream, SetState, Sleep,
             };
-            use crate::storage::v1::invocation_status::{Free, Invoked, Suspended, Virtual};
+            use crate::storage::v1::invocation_status::{
+                Completed, Free, Invoked, Suspended, Virtual,
+            };
             use crate::storage::v1::journal_entry::completion_result::{Empty, Failure, Success};
             use crate::storage::v1::journal_entry::{
                 completion_result, CompletionResult, Entry, Kind,
@@ -40,11 +42,11 @@ pub mod storage {
                 enriched_entry_header, inbox_entry, invocation_resolution_result,
                 invocation_status, maybe_full_invocation_id, outbox_message, response_result,
                 service_status, source, span_relation, timer, BackgroundCallResolutionResult,
-                EnrichedEntryHeader, FullInvocationId, InboxEntry, InvocationResolutionResult,
-                InvocationStatus, JournalEntry, JournalMeta, KvPair, MaybeFullInvocationId,
-                OutboxMessage, ResponseResult, ServiceId, ServiceInvocation,
-                ServiceInvocationResponseSink, ServiceStatus, Source, SpanContext, SpanRelation,
-                StateMutation, Timer,
+                EnrichedEntryHeader, FullInvocationId, Idempotency, IdempotencyMetadata,
+                InboxEntry, InvocationResolutionResult, InvocationStatus, JournalEntry,
+                JournalMeta, KvPair, MaybeFullInvocationId, OutboxMessage, ResponseResult,
+                ServiceId, ServiceInvocation, ServiceInvocationResponseSink, ServiceStatus, Source,
+                SpanContext, SpanRelation, StateMutation, Timer,
             };
             use anyhow::anyhow;
             use bytes::{Buf, Bytes};
@@ -58,6 +60,7 @@ pub mod storage {
             use restate_types::GenerationalNodeId;
             use std::collections::HashSet;
             use std::str::FromStr;
+            use std::time::Duration;
 
             /// Error type for conversion related problems (e.g. Rust <-> Protobuf)
             #[derive(Debug, thiserror::Error)]
@@ -160,6 +163,11 @@ pub mod storage {
                                 timestamps,
                             }
                         }
+                        invocation_status::Status::Completed(completed) => {
+                            restate_storage_api::invocation_status_table::InvocationStatus::Completed(
+                                completed.try_into()?,
+                            )
+                        }
                         invocation_status::Status::Free(_) => {
                             restate_storage_api::invocation_status_table::InvocationStatus::Free
                         }
@@ -195,6 +203,9 @@ pub mod storage {
                             kill_notification_target,
                             timestamps,
                         ))),
+                        restate_storage_api::invocation_status_table::InvocationStatus::Completed(
+                            completed,
+                        ) => invocation_status::Status::Completed(Completed::from(completed)),
                         restate_storage_api::invocation_status_table::InvocationStatus::Free => {
                             invocation_status::Status::Free(Free {})
                         }
@@ -235,13 +246,7 @@ pub mod storage {
                                 .journal_meta
                                 .ok_or(ConversionError::missing_field("journal_meta"))?,
                         )?;
-                    let response_sink = Option::<
-                        restate_types::invocation::ServiceInvocationResponseSink,
-                    >::try_from(
-                        value
-                            .response_sink
-                            .ok_or(ConversionError::missing_field("response_sink"))?,
-                    )?;
+                    let response_sinks = try_from_response_sinks(value.response_sinks)?;
 
                     let source = restate_types::invocation::Source::try_from(
                         value
@@ -249,18 +254,24 @@ pub mod storage {
                             .ok_or(ConversionError::missing_field("source"))?,
                     )?;
 
+                    let idempotency = value
+                        .idempotency
+                        .map(restate_types::invocation::Idempotency::try_from)
+                        .transpose()?;
+
                     Ok(
                         restate_storage_api::invocation_status_table::InvocationMetadata::new(
                             service_id,
                             journal_metadata,
                             deployment_id,
                             method_name,
-                            response_sink,
+                            response_sinks,
                             restate_storage_api::invocation_status_table::StatusTimestamps::new(
                                 MillisSinceEpoch::new(value.creation_time),
                                 MillisSinceEpoch::new(value.modification_time),
                             ),
                             source,
+                            idempotency,
                         ),
                     )
                 }
@@ -274,15 +285,16 @@ pub mod storage {
                         service_id,
                         deployment_id,
                         method,
-                        response_sink,
+                        response_sinks,
                         journal_metadata,
                         timestamps,
                         source,
+                        idempotency,
                     } = value;
 
                     Invoked {
                         service_id: Some(service_id.into()),
-                        response_sink: Some(ServiceInvocationResponseSink::from(response_sink)),
+                        response_sinks: from_response_sinks(response_sinks),
                         method_name: method.into_bytes(),
                         deployment_id: Some(match deployment_id {
                             None => invocation_status::invoked::DeploymentId::None(()),
@@ -294,6 +306,89 @@ pub mod storage {
                         creation_time: timestamps.creation_time().as_u64(),
                         modification_time: timestamps.modification_time().as_u64(),
                         source: Some(Source::from(source)),
+                        idempotency: idempotency.map(Idempotency::from),
+                    }
+                }
+            }
+
+            impl TryFrom<Completed> for restate_storage_api::invocation_status_table::CompletedInvocation {
+                type Error = ConversionError;
+
+                fn try_from(value: Completed) -> Result<Self, Self::Error> {
+                    let service_id = value
+                        .service_id
+                        .ok_or(ConversionError::missing_field("service_id"))?
+                        .try_into()?;
+
+                    let method = value.method_name.try_into().map_err(|e| {
+                        ConversionError::InvalidData(anyhow!(
+                            "Cannot decode method_name string {e}"
+                        ))
+                    })?;
+
+                    let journal_metadata =
+                        restate_storage_api::invocation_status_table::JournalMetadata::try_from(
+                            value
+                                .journal_meta
+                                .ok_or(ConversionError::missing_field("journal_meta"))?,
+                        )?;
+
+                    let result = restate_types::invocation::ResponseResult::try_from(
+                        value
+                            .result
+                            .ok_or(ConversionError::missing_field("result"))?,
+                    )?;
+
+                    let source = restate_types::invocation::Source::try_from(
+                        value
+                            .source
+                            .ok_or(ConversionError::missing_field("source"))?,
+                    )?;
+
+                    Ok(
+                        restate_storage_api::invocation_status_table::CompletedInvocation {
+                            service_id,
+                            method,
+                            journal_metadata,
+                            result,
+                            timestamps:
+                                restate_storage_api::invocation_status_table::StatusTimestamps::new(
+                                    MillisSinceEpoch::new(value.creation_time),
+                                    MillisSinceEpoch::new(value.modification_time),
+                                ),
+                            source,
+                            expiry: MillisSinceEpoch::new(value.expiry_time),
+                            idempotency_key: value.idempotency_key.map(ByteString::from),
+                        },
+                    )
+                }
+            }
+
+            impl From<restate_storage_api::invocation_status_table::CompletedInvocation> for Completed {
+                fn from(
+                    value: restate_storage_api::invocation_status_table::CompletedInvocation,
+                ) -> Self {
+                    let restate_storage_api::invocation_status_table::CompletedInvocation {
+                        service_id,
+                        method,
+                        journal_metadata,
+                        result,
+                        timestamps,
+                        source,
+                        expiry,
+                        idempotency_key,
+                    } = value;
+
+                    Completed {
+                        service_id: Some(service_id.into()),
+                        journal_meta: Some(JournalMeta::from(journal_metadata)),
+                        method_name: method.into_bytes(),
+                        result: Some(ResponseResult::from(result)),
+                        creation_time: timestamps.creation_time().as_u64(),
+                        modification_time: timestamps.modification_time().as_u64(),
+                        source: Some(Source::from(source)),
+                        expiry_time: expiry.as_u64(),
+                        idempotency_key: idempotency_key.map(|key| key.to_string()),
                     }
                 }
             }
@@ -330,13 +425,7 @@ pub mod storage {
                                 .journal_meta
                                 .ok_or(ConversionError::missing_field("journal_meta"))?,
                         )?;
-                    let response_sink = Option::<
-                        restate_types::invocation::ServiceInvocationResponseSink,
-                    >::try_from(
-                        value
-                            .response_sink
-                            .ok_or(ConversionError::missing_field("response_sink"))?,
-                    )?;
+                    let response_sinks = try_from_response_sinks(value.response_sinks)?;
 
                     let waiting_for_completed_entries =
                         value.waiting_for_completed_entries.into_iter().collect();
@@ -347,18 +436,24 @@ pub mod storage {
                             .ok_or(ConversionError::missing_field("source"))?,
                     )?;
 
+                    let idempotency = value
+                        .idempotency
+                        .map(restate_types::invocation::Idempotency::try_from)
+                        .transpose()?;
+
                     Ok((
                         restate_storage_api::invocation_status_table::InvocationMetadata::new(
                             service_id,
                             journal_metadata,
                             deployment_id.map(|d| d.parse().expect("valid deployment id")),
                             method_name,
-                            response_sink,
+                            response_sinks,
                             restate_storage_api::invocation_status_table::StatusTimestamps::new(
                                 MillisSinceEpoch::new(value.creation_time),
                                 MillisSinceEpoch::new(value.modification_time),
                             ),
                             caller,
+                            idempotency,
                         ),
                         waiting_for_completed_entries,
                     ))
@@ -377,14 +472,14 @@ pub mod storage {
                         HashSet<restate_types::identifiers::EntryIndex>,
                     ),
                 ) -> Self {
-                    let response_sink = ServiceInvocationResponseSink::from(metadata.response_sink);
+                    let response_sinks = from_response_sinks(metadata.response_sinks);
                     let journal_meta = JournalMeta::from(metadata.journal_metadata);
                     let waiting_for_completed_entries =
                         waiting_for_completed_entries.into_iter().collect();
 
                     Suspended {
                         service_id: Some(metadata.service_id.into()),
-                        response_sink: Some(response_sink),
+                        response_sinks,
                         journal_meta: Some(journal_meta),
                         method_name: metadata.method.into_bytes(),
                         deployment_id: Some(match metadata.deployment_id {
@@ -399,6 +494,7 @@ pub mod storage {
                         modification_time: metadata.timestamps.modification_time().as_u64(),
                         waiting_for_completed_entries,
                         source: Some(Source::from(metadata.source)),
+                        idempotency: metadata.idempotency.map(Idempotency::from),
                     }
                 }
             }
@@ -635,6 +731,7 @@ pub mod storage {
                         span_context,
                         argument,
                         source,
+                        idempotency,
                     } = value;
 
                     let id = restate_types::identifiers::FullInvocationId::try_from(
@@ -659,6 +756,10 @@ pub mod storage {
                         source.ok_or(ConversionError::missing_field("source"))?,
                     )?;
 
+                    let idempotency = idempotency
+                        .map(restate_types::invocation::Idempotency::try_from)
+                        .transpose()?;
+
                     Ok(restate_types::invocation::ServiceInvocation {
                         fid: id,
                         method_name,
@@ -666,6 +767,7 @@ pub mod storage {
                         source,
                         response_sink,
                         span_context,
+                        idempotency,
                     })
                 }
             }
@@ -685,6 +787,54 @@ pub mod storage {
                         method_name,
                         argument: value.argument,
                         source: Some(source),
+                        idempotency: value.idempotency.map(Idempotency::from),
+                    }
+                }
+            }
+
+            impl TryFrom<Idempotency> for restate_types::invocation::Idempotency {
+                type Error = ConversionError;
+
+                fn try_from(value: Idempotency) -> Result<Self, Self::Error> {
+                    Ok(restate_types::invocation::Idempotency {
+                        key: ByteString::from(value.key),
+                        retention: Duration::from_millis(value.retention_millis),
+                    })
+                }
+            }
+
+            impl From<restate_types::invocation::Idempotency> for Idempotency {
+                fn from(value: restate_types::invocation::Idempotency) -> Self {
+                    Idempotency {
+                        key: value.key.to_string(),
+                        retention_millis: u64::try_from(value.retention.as_millis())
+                            .unwrap_or(u64::MAX),
+                    }
+                }
+            }
+
+            impl TryFrom<IdempotencyMetadata> for restate_storage_api::idempotency_table::IdempotencyMetadata {
+                type Error = ConversionError;
+
+                fn try_from(value: IdempotencyMetadata) -> Result<Self, Self::Error> {
+                    let invocation_id =
+                        restate_types::identifiers::InvocationId::from_slice(&value.invocation_id)
+                            .map_err(ConversionError::invalid_data)?;
+
+                    Ok(
+                        restate_storage_api::idempotency_table::IdempotencyMetadata {
+                            invocation_id,
+                        },
+                    )
+                }
+            }
+
+            impl From<restate_storage_api::idempotency_table::IdempotencyMetadata> for IdempotencyMetadata {
+                fn from(
+                    value: restate_storage_api::idempotency_table::IdempotencyMetadata,
+                ) -> Self {
+                    IdempotencyMetadata {
+                        invocation_id: value.invocation_id.to_bytes().to_vec().into(),
                     }
                 }
             }
@@ -986,9 +1136,13 @@ pub mod storage {
                                 .ok_or(ConversionError::missing_field("node_id"))?;
 
                             Some(
-                                restate_types::invocation::ServiceInvocationResponseSink::Ingress(
-                                    GenerationalNodeId::new(proto_id.id, proto_id.generation),
-                                ),
+                                restate_types::invocation::ServiceInvocationResponseSink::Ingress {
+                                    node_id: GenerationalNodeId::new(
+                                        proto_id.id,
+                                        proto_id.generation,
+                                    ),
+                                    request_id: ingress.request_id,
+                                },
                             )
                         }
                         ResponseSink::NewInvocation(new_invocation) => {
@@ -1028,11 +1182,13 @@ pub mod storage {
                             entry_index,
                             caller: Some(FullInvocationId::from(caller)),
                         }),
-                        Some(restate_types::invocation::ServiceInvocationResponseSink::Ingress(node_id)) => {
-                            ResponseSink::Ingress(Ingress {
-                                node_id: Some(super::GenerationalNodeId::from(node_id)),
-                            })
-                        },
+                        Some(restate_types::invocation::ServiceInvocationResponseSink::Ingress {
+                            node_id,
+                            request_id,
+                        }) => ResponseSink::Ingress(Ingress {
+                            node_id: Some(super::GenerationalNodeId::from(node_id)),
+                            request_id,
+                        }),
                         Some(
                             restate_types::invocation::ServiceInvocationResponseSink::NewInvocation {
                                target, method, caller_context
@@ -1051,6 +1207,32 @@ pub mod storage {
                 }
             }
 
+            fn try_from_response_sinks(
+                value: Vec<ServiceInvocationResponseSink>,
+            ) -> Result<
+                HashSet<restate_types::invocation::ServiceInvocationResponseSink>,
+                ConversionError,
+            > {
+                value
+                    .into_iter()
+                    .filter_map(|response_sink| {
+                        Option::<restate_types::invocation::ServiceInvocationResponseSink>::try_from(
+                            response_sink,
+                        )
+                        .transpose()
+                    })
+                    .collect()
+            }
+
+            fn from_response_sinks(
+                value: HashSet<restate_types::invocation::ServiceInvocationResponseSink>,
+            ) -> Vec<ServiceInvocationResponseSink> {
+                value
+                    .into_iter()
+                    .map(|response_sink| ServiceInvocationResponseSink::from(Some(response_sink)))
+                    .collect()
+            }
+
             impl From<GenerationalNodeId> for super::GenerationalNodeId {
                 fn from(value: GenerationalNodeId) -> Self {
                     super::GenerationalNodeId {
@@ -1666,6 +1848,11 @@ pub mod storage {
                                     restate_types::invocation::ServiceInvocation::try_from(si)?,
                                 )
                             }
+                            timer::Value::CleanInvocationStatus(_) => {
+                                restate_storage_api::timer_table::Timer::CleanInvocationStatus(
+                                    service_id,
+                                )
+                            }
                         },
                     )
                 }
@@ -1686,6 +1873,13 @@ pub mod storage {
                             service_key: service_id.key,
                             value: Some(timer::Value::Invoke(ServiceInvocation::from(si))),
                         },
+                        restate_storage_api::timer_table::Timer::CleanInvocationStatus(
+                            service_id,
+                        ) => Timer {
+                            service_name: service_id.service_name.into_bytes(),
+                            service_key: service_id.key,
+                            value: Some(timer::Value::CleanInvocationStatus(Default::default())),
+                        },
                     }
                 }
             }
@@ -11,6 +11,7 @@
 use crate::{analyzer, physical_optimizer};
 use datafusion::execution::context::SessionState;
 use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::logical_expr::LogicalPlan;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::prelude::{SessionConfig, SessionContext};
 
@@ -77,9 +78,22 @@ impl QueryContext {
         &self,
         sql: &str,
     ) -> datafusion::common::Result<SendableRecordBatchStream> {
+        let plan = self.plan(sql).await?;
+        self.execute_plan(plan).await
+    }
+
+    /// Plans the given sql statement. The plan can contain placeholders (`$1`, `$2`, ...)
+    /// which must be replaced with [`LogicalPlan::with_param_values`] before executing it.
+    pub async fn plan(&self, sql: &str) -> datafusion::common::Result<LogicalPlan> {
         let state = self.datafusion_context.state();
         let statement = state.sql_to_statement(sql, "postgres")?;
-        let plan = state.statement_to_plan(statement).await?;
+        state.statement_to_plan(statement).await
+    }
+
+    pub async fn execute_plan(
+        &self,
+        plan: LogicalPlan,
+    ) -> datafusion::common::Result<SendableRecordBatchStream> {
         let df = self.datafusion_context.execute_logical_plan(plan).await?;
         df.execute_stream().await
     }
@@ -0,0 +1,50 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::DedupBuilder;
+use crate::table_util::format_using;
+use restate_storage_api::deduplication_table::SequenceNumberSource;
+use restate_storage_rocksdb::deduplication_table::OwnedDeduplicationRow;
+
+#[inline]
+pub(crate) fn append_dedup_row(
+    builder: &mut DedupBuilder,
+    output: &mut String,
+    dedup_row: OwnedDeduplicationRow,
+) {
+    let mut row = builder.row();
+
+    row.partition_id(dedup_row.partition_id);
+
+    match dedup_row.source {
+        SequenceNumberSource::Partition(partition_id) => {
+            row.source("partition");
+            if row.is_source_id_defined() {
+                row.source_id(format_using(output, &partition_id));
+            }
+        }
+        SequenceNumberSource::Ingress(producer_id) => {
+            row.source("ingress");
+            row.source_id(&producer_id);
+        }
+        SequenceNumberSource::IngressNode(node_id) => {
+            row.source("ingress_node");
+            if row.is_source_id_defined() {
+                row.source_id(format_using(output, &node_id));
+            }
+        }
+        SequenceNumberSource::SelfProposal => {
+            row.source("self_proposal");
+        }
+    }
+
+    row.epoch(dedup_row.sequence_number.epoch);
+    row.sequence_number(dedup_row.sequence_number.sequence_number);
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+
+use crate::context::QueryContext;
+use crate::dedup::row::append_dedup_row;
+use crate::dedup::schema::DedupBuilder;
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+pub use datafusion_expr::UserDefinedLogicalNode;
+use restate_storage_rocksdb::deduplication_table::OwnedDeduplicationRow;
+use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use tokio::sync::mpsc::Sender;
+
+pub(crate) fn register_self(
+    ctx: &QueryContext,
+    storage: RocksDBStorage,
+) -> datafusion::common::Result<()> {
+    let table = GenericTableProvider::new(DedupBuilder::schema(), Arc::new(DedupScanner(storage)));
+
+    ctx.as_ref()
+        .register_table("sys_dedup", Arc::new(table))
+        .map(|_| ())
+}
+
+#[derive(Debug, Clone)]
+struct DedupScanner(RocksDBStorage);
+
+/// The deduplication table is keyed by partition id rather than partition key,
+/// hence the partition key range is ignored and all the partitions are scanned.
+impl RangeScanner for DedupScanner {
+    fn scan(
+        &self,
+        _range: RangeInclusive<PartitionKey>,
+        _filter: KeyFilter,
+        limit: Option<usize>,
+        projection: SchemaRef,
+    ) -> SendableRecordBatchStream {
+        let db = self.0.clone();
+        let schema = projection.clone();
+        let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
+        let tx = stream_builder.tx();
+        let background_task = move || {
+            let rows = db
+                .all_sequence_numbers(0..=PartitionId::MAX)
+                .take(limit.unwrap_or(usize::MAX));
+            for_each_dedup(schema, tx, rows);
+            Ok(())
+        };
+        stream_builder.spawn_blocking(background_task);
+        stream_builder.build()
+    }
+}
+
+fn for_each_dedup<'a, I>(
+    schema: SchemaRef,
+    tx: Sender<datafusion::common::Result<RecordBatch>>,
+    rows: I,
+) where
+    I: Iterator<Item = OwnedDeduplicationRow> + 'a,
+{
+    let mut builder = DedupBuilder::new(schema.clone());
+    let mut temp = String::new();
+    for row in rows {
+        append_dedup_row(&mut builder, &mut temp, row);
+        if builder.full() {
+            let batch = builder.finish();
+            if tx.blocking_send(Ok(batch)).is_err() {
+                // not sure what to do here?
+                // the other side has hung up on us.
+                // we probably don't want to panic, is it will cause the entire process to exit
+                return;
+            }
+            builder = DedupBuilder::new(schema.clone());
+        }
+    }
+    if !builder.empty() {
+        let result = builder.finish();
+        let _ = tx.blocking_send(Ok(result));
+    }
+}
@@ -12,7 +12,7 @@ use super::schema::DeploymentBuilder;
 
 use crate::context::QueryContext;
 use crate::deployment::row::append_deployment_row;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -50,13 +50,16 @@ impl<DMR: DeploymentResolver + Debug + Sync + Send + 'static> RangeScanner
     fn scan(
         &self,
         _range: RangeInclusive<PartitionKey>,
+        _filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let schema = projection.clone();
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
 
-        let rows = self.0.get_deployments();
+        let mut rows = self.0.get_deployments();
+        rows.truncate(limit.unwrap_or(usize::MAX));
         stream_builder.spawn(async move {
             for_each_state(schema, tx, rows).await;
             Ok(())
@@ -11,44 +11,209 @@
 use std::any::Any;
 use std::fmt::{Debug, Formatter};
 use std::ops::RangeInclusive;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use bytestring::ByteString;
 
 use datafusion::arrow::datatypes::SchemaRef;
 
 use crate::table_util::compute_ordering;
-use datafusion::common::DataFusionError;
+use datafusion::common::{DataFusionError, ScalarValue};
 use datafusion::datasource::{TableProvider, TableType};
 use datafusion::execution::context::{SessionState, TaskContext};
-use datafusion::logical_expr::{Expr, TableProviderFilterPushDown};
+use datafusion::logical_expr::{Between, BinaryExpr, Expr, Operator, TableProviderFilterPushDown};
 use datafusion::physical_expr::PhysicalSortExpr;
 use datafusion::physical_plan::{
-    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    DisplayAs, DisplayFormatType, EmptyRecordBatchStream, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream,
 };
+use datafusion_expr::utils::split_conjunction;
 pub use datafusion_expr::UserDefinedLogicalNode;
-use restate_types::identifiers::PartitionKey;
+use restate_types::identifiers::{InvocationId, PartitionKey, ServiceId, WithPartitionKey};
 
 // TODO This trait assumes every table's primary key contains a PartitionKey.
 //      This assumption is incorrect with some tables, such as sys_deployment.
 pub(crate) trait RangeScanner: Send + Sync + Debug + 'static {
+    /// Scans the rows within the given partition key `range`.
+    ///
+    /// The `filter` contains the key predicates extracted from the query, which the scanner
+    /// can use to perform prefix or point lookups instead of a full range scan.
+    /// Rows not matching the `filter` may still be returned, as the query engine re-evaluates
+    /// the predicates. At most `limit` rows need to be returned, if set.
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream;
 }
 
+/// Equality predicates on the key columns of a table, extracted from the query filters.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyFilter {
+    pub(crate) service: Option<ByteString>,
+    pub(crate) service_key: Option<Bytes>,
+    pub(crate) invocation_id: Option<InvocationId>,
+}
+
+impl KeyFilter {
+    /// Returns the [`ServiceId`] identified by the filter, if both service and key are known.
+    pub(crate) fn service_id(&self) -> Option<ServiceId> {
+        match (&self.service, &self.service_key) {
+            (Some(service), Some(key)) => Some(ServiceId::new(service.clone(), key.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Narrows the partition key range and fills the [`KeyFilter`] using the given filters.
+///
+/// Filters on the `service_key` column are only used if `service_key_partitioned` is set, i.e.
+/// if the partition key of every row is the hash of its service key.
+fn extract_key_filter(
+    filters: &[Expr],
+    service_key_partitioned: bool,
+) -> (RangeInclusive<PartitionKey>, KeyFilter) {
+    let mut start = 0;
+    let mut end = PartitionKey::MAX;
+    let mut key_filter = KeyFilter::default();
+
+    let mut restrict = |low: PartitionKey, high: PartitionKey| {
+        start = start.max(low);
+        end = end.min(high);
+    };
+
+    for expr in filters.iter().flat_map(split_conjunction) {
+        match expr {
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+                let Some((column, op, value)) = column_comparison(left, *op, right) else {
+                    continue;
+                };
+                match (column, op) {
+                    ("partition_key", op) => {
+                        let Some(pk) = partition_key_literal(value) else {
+                            continue;
+                        };
+                        match op {
+                            Operator::Eq => restrict(pk, pk),
+                            Operator::GtEq => restrict(pk, PartitionKey::MAX),
+                            Operator::Gt => match pk.checked_add(1) {
+                                Some(low) => restrict(low, PartitionKey::MAX),
+                                None => restrict(PartitionKey::MAX, 0),
+                            },
+                            Operator::LtEq => restrict(0, pk),
+                            Operator::Lt => match pk.checked_sub(1) {
+                                Some(high) => restrict(0, high),
+                                None => restrict(PartitionKey::MAX, 0),
+                            },
+                            _ => {}
+                        }
+                    }
+                    ("service", Operator::Eq) => {
+                        if let Some(service) = string_literal(value) {
+                            key_filter.service = Some(ByteString::from(service));
+                        }
+                    }
+                    ("service_key", Operator::Eq) if service_key_partitioned => {
+                        if let Some(key) = string_literal(value) {
+                            let pk = ServiceId::partition_key_of(key.as_bytes());
+                            restrict(pk, pk);
+                            key_filter.service_key = Some(Bytes::copy_from_slice(key.as_bytes()));
+                        }
+                    }
+                    ("id" | "invocation_id", Operator::Eq) => {
+                        if let Some(invocation_id) =
+                            string_literal(value).and_then(|s| InvocationId::from_str(s).ok())
+                        {
+                            let pk = invocation_id.partition_key();
+                            restrict(pk, pk);
+                            key_filter.invocation_id = Some(invocation_id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) => {
+                if let (Expr::Column(column), Some(low), Some(high)) = (
+                    expr.as_ref(),
+                    partition_key_literal(low),
+                    partition_key_literal(high),
+                ) {
+                    if column.name == "partition_key" {
+                        restrict(low, high);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (start..=end, key_filter)
+}
+
+/// Normalizes `column op literal` and `literal op column` to `(column, op, literal)`.
+fn column_comparison<'a>(
+    left: &'a Expr,
+    op: Operator,
+    right: &'a Expr,
+) -> Option<(&'a str, Operator, &'a Expr)> {
+    match (left, right) {
+        (Expr::Column(column), value @ Expr::Literal(_)) => Some((column.name.as_str(), op, value)),
+        (value @ Expr::Literal(_), Expr::Column(column)) => {
+            Some((column.name.as_str(), op.swap()?, value))
+        }
+        _ => None,
+    }
+}
+
+fn partition_key_literal(expr: &Expr) -> Option<PartitionKey> {
+    match expr {
+        Expr::Literal(ScalarValue::UInt64(Some(value))) => Some(*value),
+        Expr::Literal(ScalarValue::Int64(Some(value))) => PartitionKey::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
+fn string_literal(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Literal(ScalarValue::Utf8(Some(value)))
+        | Expr::Literal(ScalarValue::LargeUtf8(Some(value))) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
 pub(crate) type RangeScannerRef = Arc<dyn RangeScanner>;
 
 pub(crate) struct GenericTableProvider {
     schema: SchemaRef,
     scanner: RangeScannerRef,
+    service_key_partitioned: bool,
 }
 
 impl GenericTableProvider {
     pub(crate) fn new(schema: SchemaRef, scanner: RangeScannerRef) -> Self {
-        Self { schema, scanner }
+        Self {
+            schema,
+            scanner,
+            service_key_partitioned: false,
+        }
+    }
+
+    /// Marks the table as partitioned by its `service_key` column, which means that the
+    /// `partition_key` of every row is the hash of its `service_key`. This allows narrowing the
+    /// scan to a single partition key when filtering by service key.
+    pub(crate) fn with_service_key_partitioning(mut self) -> Self {
+        self.service_key_partitioned = true;
+        self
     }
 }
 
@@ -70,21 +235,34 @@ impl TableProvider for GenericTableProvider {
         &self,
         _state: &SessionState,
         projection: Option<&Vec<usize>>,
-        _filters: &[Expr],
-        _limit: Option<usize>,
+        filters: &[Expr],
+        limit: Option<usize>,
     ) -> datafusion::common::Result<Arc<dyn ExecutionPlan>> {
         let projected_schema = match projection {
             Some(p) => SchemaRef::new(self.schema.project(p)?),
             None => self.schema.clone(),
         };
 
+        // Key filters are only meaningful for tables keyed by partition key
+        let (range, filter) = if self.schema.column_with_name("partition_key").is_some() {
+            extract_key_filter(filters, self.service_key_partitioned)
+        } else {
+            (0..=PartitionKey::MAX, KeyFilter::default())
+        };
+
         Ok(Arc::new(GenericTableExecutionPlan {
             output_ordering: compute_ordering(projected_schema.clone()),
             projected_schema,
+            range,
+            filter,
+            limit,
             scanner: Arc::clone(&self.scanner),
         }))
     }
 
+    /// Filters are used to narrow down the scan, but they are always re-evaluated by DataFusion.
+    /// Because of this, DataFusion pushes a `LIMIT` down to [`Self::scan`] only when no filter
+    /// is applied on top of the scan, so the scanners can safely stop early.
     fn supports_filters_pushdown(
         &self,
         filters: &[&Expr],
@@ -102,6 +280,9 @@ impl TableProvider for GenericTableProvider {
 struct GenericTableExecutionPlan {
     output_ordering: Option<Vec<PhysicalSortExpr>>,
     projected_schema: SchemaRef,
+    range: RangeInclusive<PartitionKey>,
+    filter: KeyFilter,
+    limit: Option<usize>,
     scanner: RangeScannerRef,
 }
 
@@ -144,8 +325,17 @@ impl ExecutionPlan for GenericTableExecutionPlan {
         _partition: usize,
         _context: Arc<TaskContext>,
     ) -> datafusion::common::Result<SendableRecordBatchStream> {
-        let range = 0..=PartitionKey::MAX;
-        let stream = self.scanner.scan(range, self.projected_schema.clone());
+        if self.range.is_empty() {
+            return Ok(Box::pin(EmptyRecordBatchStream::new(
+                self.projected_schema.clone(),
+            )));
+        }
+        let stream = self.scanner.scan(
+            self.range.clone(),
+            self.filter.clone(),
+            self.limit,
+            self.projected_schema.clone(),
+        );
         Ok(stream)
     }
 }
@@ -154,8 +344,57 @@ impl DisplayAs for GenericTableExecutionPlan {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut Formatter) -> std::fmt::Result {
         match t {
             DisplayFormatType::Default | DisplayFormatType::Verbose => {
-                write!(f, "GenericTableExecutionPlan()",)
+                write!(
+                    f,
+                    "GenericTableExecutionPlan(range={:?}, limit={:?})",
+                    self.range, self.limit
+                )
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use datafusion::prelude::{col, lit};
+    use restate_types::identifiers::InvocationUuid;
+
+    #[test]
+    fn service_key_narrows_service_key_partitioned_tables() {
+        let filters = [
+            col("service").eq(lit("greeter")),
+            col("service_key").eq(lit("bob")),
+        ];
+        let pk = ServiceId::partition_key_of(b"bob");
+
+        let (range, key_filter) = extract_key_filter(&filters, true);
+
+        assert_eq!(pk..=pk, range);
+        assert_eq!(
+            Some(ServiceId::new("greeter", Bytes::from_static(b"bob"))),
+            key_filter.service_id()
+        );
+    }
+
+    #[test]
+    fn service_key_does_not_narrow_other_tables() {
+        // Rows such as virtual invocations are not stored under the hash of their service key
+        let invocation_id = InvocationId::new(
+            ServiceId::partition_key_of(b"bob").wrapping_add(1),
+            InvocationUuid::new(),
+        );
+        let filters = [
+            col("id").eq(lit(invocation_id.to_string())),
+            col("service_key").eq(lit("bob")),
+        ];
+        let pk = invocation_id.partition_key();
+
+        let (range, key_filter) = extract_key_filter(&filters, false);
+
+        assert_eq!(pk..=pk, range);
+        assert_eq!(None, key_filter.service_key);
+        assert_eq!(Some(invocation_id), key_filter.invocation_id);
+    }
+}
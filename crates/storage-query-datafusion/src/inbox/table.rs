@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::inbox::row::append_inbox_row;
 use crate::inbox::schema::InboxBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -33,7 +33,8 @@ pub(crate) fn register_self(
     ctx: &QueryContext,
     storage: RocksDBStorage,
 ) -> datafusion::common::Result<()> {
-    let table = GenericTableProvider::new(InboxBuilder::schema(), Arc::new(InboxScanner(storage)));
+    let table = GenericTableProvider::new(InboxBuilder::schema(), Arc::new(InboxScanner(storage)))
+        .with_service_key_partitioning();
 
     ctx.as_ref()
         .register_table("sys_inbox", Arc::new(table))
@@ -47,6 +48,8 @@ impl RangeScanner for InboxScanner {
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let mut db = self.0.clone();
@@ -55,8 +58,17 @@ impl RangeScanner for InboxScanner {
         let tx = stream_builder.tx();
         let background_task = async move {
             let mut transaction = db.transaction();
-            let rows = transaction.all_inboxes(range);
-            for_each_state(schema, tx, rows).await;
+            let limit = limit.unwrap_or(usize::MAX);
+            match filter.service_id() {
+                Some(service_id) => {
+                    let rows = transaction.inbox(&service_id).take(limit);
+                    for_each_state(schema, tx, rows).await;
+                }
+                None => {
+                    let rows = transaction.all_inboxes(range).take(limit);
+                    for_each_state(schema, tx, rows).await;
+                }
+            }
             Ok(())
         };
         stream_builder.spawn(background_task);
@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::invocation_state::row::append_state_row;
 use crate::invocation_state::schema::StateBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -31,7 +31,8 @@ pub(crate) fn register_self(
     status: impl StatusHandle + Send + Sync + Debug + Clone + 'static,
 ) -> datafusion::common::Result<()> {
     let status_table =
-        GenericTableProvider::new(StateBuilder::schema(), Arc::new(StatusScanner(status)));
+        GenericTableProvider::new(StateBuilder::schema(), Arc::new(StatusScanner(status)))
+            .with_service_key_partitioning();
 
     ctx.as_ref()
         .register_table("sys_invocation_state", Arc::new(status_table))
@@ -45,6 +46,8 @@ impl<S: StatusHandle + Send + Sync + Debug + Clone + 'static> RangeScanner for S
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        _filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let status = self.0.clone();
@@ -53,7 +56,7 @@ impl<S: StatusHandle + Send + Sync + Debug + Clone + 'static> RangeScanner for S
         let tx = stream_builder.tx();
         let background_task = async move {
             let rows = status.read_status(range).await;
-            for_each_state(schema, tx, rows).await;
+            for_each_state(schema, tx, rows, limit).await;
             Ok(())
         };
         stream_builder.spawn(background_task);
@@ -65,6 +68,7 @@ async fn for_each_state<'a, I>(
     schema: SchemaRef,
     tx: Sender<datafusion::common::Result<RecordBatch>>,
     rows: I,
+    limit: Option<usize>,
 ) where
     I: Iterator<Item = InvocationStatusReport> + 'a,
 {
@@ -73,6 +77,7 @@ async fn for_each_state<'a, I>(
     let mut rows = rows.collect::<Vec<_>>();
     // need to be ordered by partition key for symmetric joins
     rows.sort_unstable_by_key(|row| row.full_invocation_id().service_id.partition_key());
+    rows.truncate(limit.unwrap_or(usize::MAX));
     for row in rows {
         append_state_row(&mut builder, &mut temp, row);
         if builder.full() {
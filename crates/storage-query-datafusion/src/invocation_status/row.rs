@@ -11,11 +11,11 @@
 use crate::invocation_status::schema::{InvocationStatusBuilder, InvocationStatusRowBuilder};
 use crate::table_util::format_using;
 use restate_storage_api::invocation_status_table::{
-    InvocationMetadata, InvocationStatus, JournalMetadata, StatusTimestamps,
+    CompletedInvocation, InvocationMetadata, InvocationStatus, JournalMetadata, StatusTimestamps,
 };
 use restate_storage_rocksdb::invocation_status_table::OwnedInvocationStatusRow;
 use restate_types::identifiers::InvocationId;
-use restate_types::invocation::{Source, TraceId};
+use restate_types::invocation::{ResponseResult, Source, TraceId};
 
 #[inline]
 pub(crate) fn append_invocation_status_row(
@@ -63,6 +63,11 @@ pub(crate) fn append_invocation_status_row(
             row.status("virtual");
             None
         }
+        InvocationStatus::Completed(completed) => {
+            row.status("completed");
+            fill_completed_invocation(&mut row, output, completed);
+            None
+        }
         InvocationStatus::Free => {
             row.status("free");
             None
@@ -73,6 +78,32 @@ pub(crate) fn append_invocation_status_row(
     }
 }
 
+#[inline]
+fn fill_completed_invocation(
+    row: &mut InvocationStatusRowBuilder,
+    output: &mut String,
+    completed: CompletedInvocation,
+) {
+    // journal_metadata and stats are filled by other functions
+    row.method(completed.method);
+    fill_source(row, output, completed.source);
+    match completed.result {
+        ResponseResult::Success(_) => {
+            row.completion_result("success");
+        }
+        ResponseResult::Failure(code, message) => {
+            row.completion_result("failure");
+            if row.is_completion_failure_defined() {
+                row.completion_failure(format_using(
+                    output,
+                    &format_args!("[{}] {}", code, message),
+                ));
+            }
+        }
+    }
+    row.completion_expires_at(completed.expiry.as_u64() as i64);
+}
+
 #[inline]
 fn fill_invocation_metadata(
     row: &mut InvocationStatusRowBuilder,
@@ -84,7 +115,12 @@ fn fill_invocation_metadata(
     if let Some(deployment_id) = meta.deployment_id {
         row.pinned_deployment_id(deployment_id.to_string());
     }
-    match meta.source {
+    fill_source(row, output, meta.source);
+}
+
+#[inline]
+fn fill_source(row: &mut InvocationStatusRowBuilder, output: &mut String, source: Source) {
+    match source {
         Source::Service(caller) => {
             row.invoked_by("service");
             row.invoked_by_service(&caller.service_id.service_name);
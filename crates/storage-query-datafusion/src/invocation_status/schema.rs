@@ -29,4 +29,7 @@ define_table!(invocation_status(
     journal_size: DataType::UInt32,
     created_at: DataType::Date64,
     modified_at: DataType::Date64,
+    completion_result: DataType::LargeUtf8,
+    completion_failure: DataType::LargeUtf8,
+    completion_expires_at: DataType::Date64,
 ));
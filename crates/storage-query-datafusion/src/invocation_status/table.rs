@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::invocation_status::row::append_invocation_status_row;
 use crate::invocation_status::schema::InvocationStatusBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -48,6 +48,8 @@ impl RangeScanner for StatusScanner {
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let db = self.0.clone();
@@ -55,8 +57,17 @@ impl RangeScanner for StatusScanner {
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
         let background_task = move || {
-            let rows = db.all_invocation_status(range);
-            for_each_status(schema, tx, rows);
+            let limit = limit.unwrap_or(usize::MAX);
+            match filter.invocation_id {
+                Some(invocation_id) => {
+                    let rows = db.invocation_status_of(&invocation_id).take(limit);
+                    for_each_status(schema, tx, rows);
+                }
+                None => {
+                    let rows = db.all_invocation_status(range).take(limit);
+                    for_each_status(schema, tx, rows);
+                }
+            }
             Ok(())
         };
         stream_builder.spawn_blocking(background_task);
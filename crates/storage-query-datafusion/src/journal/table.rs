@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::journal::row::append_journal_row;
 use crate::journal::schema::JournalBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -46,6 +46,8 @@ impl RangeScanner for JournalScanner {
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let db = self.0.clone();
@@ -53,8 +55,17 @@ impl RangeScanner for JournalScanner {
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
         let background_task = move || {
-            let rows = db.all_journal(range);
-            for_each_journal(schema, tx, rows);
+            let limit = limit.unwrap_or(usize::MAX);
+            match filter.invocation_id {
+                Some(invocation_id) => {
+                    let rows = db.journal_of(&invocation_id).take(limit);
+                    for_each_journal(schema, tx, rows);
+                }
+                None => {
+                    let rows = db.all_journal(range).take(limit);
+                    for_each_journal(schema, tx, rows);
+                }
+            }
             Ok(())
         };
         stream_builder.spawn_blocking(background_task);
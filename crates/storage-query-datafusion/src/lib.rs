@@ -10,6 +10,7 @@
 
 mod analyzer;
 pub mod context;
+mod dedup;
 mod deployment;
 mod generic_table;
 mod inbox;
@@ -17,11 +18,14 @@ mod invocation_state;
 mod invocation_status;
 mod journal;
 mod options;
+mod outbox;
 mod physical_optimizer;
 mod service;
 mod service_status;
 mod state;
+mod subscription;
 mod table_macro;
 mod table_util;
+mod timer;
 
 pub use crate::options::{BuildError, Options, OptionsBuilder, OptionsBuilderError};
@@ -14,7 +14,9 @@ use datafusion::error::DataFusionError;
 use restate_invoker_api::StatusHandle;
 use restate_schema_api::deployment::DeploymentResolver;
 use restate_schema_api::service::ServiceMetadataResolver;
+use restate_schema_api::subscription::SubscriptionResolver;
 use restate_storage_rocksdb::RocksDBStorage;
+use restate_worker_api::SubscriptionStatusHandle;
 use std::fmt::Debug;
 
 /// # Storage query datafusion options
@@ -47,8 +49,10 @@ impl Options {
         self,
         rocksdb: RocksDBStorage,
         status: impl StatusHandle + Send + Sync + Debug + Clone + 'static,
+        subscription_status: impl SubscriptionStatusHandle + Send + Sync + Debug + Clone + 'static,
         schemas: impl DeploymentResolver
             + ServiceMetadataResolver
+            + SubscriptionResolver
             + Send
             + Sync
             + Debug
@@ -67,9 +71,13 @@ impl Options {
         crate::state::register_self(&ctx, rocksdb.clone())?;
         crate::journal::register_self(&ctx, rocksdb.clone())?;
         crate::invocation_state::register_self(&ctx, status)?;
-        crate::inbox::register_self(&ctx, rocksdb)?;
+        crate::inbox::register_self(&ctx, rocksdb.clone())?;
+        crate::outbox::register_self(&ctx, rocksdb.clone())?;
+        crate::timer::register_self(&ctx, rocksdb.clone())?;
+        crate::dedup::register_self(&ctx, rocksdb)?;
         crate::deployment::register_self(&ctx, schemas.clone())?;
-        crate::service::register_self(&ctx, schemas)?;
+        crate::service::register_self(&ctx, schemas.clone())?;
+        crate::subscription::register_self(&ctx, schemas, subscription_status)?;
 
         Ok(ctx)
     }
@@ -0,0 +1,69 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::{OutboxBuilder, OutboxRowBuilder};
+use crate::table_util::format_using;
+use restate_storage_api::outbox_table::OutboxMessage;
+use restate_storage_rocksdb::outbox_table::OwnedOutboxRow;
+use restate_types::identifiers::{FullInvocationId, InvocationId, WithPartitionKey};
+use restate_types::invocation::MaybeFullInvocationId;
+
+#[inline]
+pub(crate) fn append_outbox_row(
+    builder: &mut OutboxBuilder,
+    output: &mut String,
+    outbox_row: OwnedOutboxRow,
+) {
+    let mut row = builder.row();
+
+    row.partition_id(outbox_row.partition_id);
+    row.sequence_number(outbox_row.sequence_number);
+
+    match outbox_row.outbox_message {
+        OutboxMessage::ServiceInvocation(service_invocation) => {
+            row.kind("invocation");
+            row.target_method(&service_invocation.method_name);
+            fill_target_fid(&mut row, output, &service_invocation.fid);
+        }
+        OutboxMessage::ServiceResponse(response) => {
+            row.kind("response");
+            fill_target_id(&mut row, output, response.id);
+        }
+        OutboxMessage::InvocationTermination(termination) => {
+            row.kind("termination");
+            fill_target_id(&mut row, output, termination.maybe_fid);
+        }
+    }
+}
+
+#[inline]
+fn fill_target_id(row: &mut OutboxRowBuilder, output: &mut String, id: MaybeFullInvocationId) {
+    match id {
+        MaybeFullInvocationId::Full(fid) => fill_target_fid(row, output, &fid),
+        MaybeFullInvocationId::Partial(invocation_id) => {
+            row.target_partition_key(invocation_id.partition_key());
+            if row.is_target_id_defined() {
+                row.target_id(format_using(output, &invocation_id));
+            }
+        }
+    }
+}
+
+#[inline]
+fn fill_target_fid(row: &mut OutboxRowBuilder, output: &mut String, fid: &FullInvocationId) {
+    row.target_partition_key(fid.partition_key());
+    row.target_service(&fid.service_id.service_name);
+    row.target_service_key(
+        std::str::from_utf8(&fid.service_id.key).expect("The key must be a string!"),
+    );
+    if row.is_target_id_defined() {
+        row.target_id(format_using(output, &InvocationId::from(fid)));
+    }
+}
@@ -11,7 +11,7 @@
 use super::schema::ServiceBuilder;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::service::row::append_service_row;
 use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
@@ -50,13 +50,16 @@ impl<SMR: ServiceMetadataResolver + Debug + Sync + Send + 'static> RangeScanner
     fn scan(
         &self,
         _range: RangeInclusive<PartitionKey>,
+        _filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let schema = projection.clone();
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
 
-        let rows = self.0.list_services();
+        let mut rows = self.0.list_services();
+        rows.truncate(limit.unwrap_or(usize::MAX));
         stream_builder.spawn(async move {
             for_each_state(schema, tx, rows).await;
             Ok(())
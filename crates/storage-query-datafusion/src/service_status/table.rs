@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::service_status::row::append_service_status_row;
 use crate::service_status::schema::ServiceStatusBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -34,7 +34,8 @@ pub(crate) fn register_self(
     let status_table = GenericTableProvider::new(
         ServiceStatusBuilder::schema(),
         Arc::new(ServiceStatusScanner(storage)),
-    );
+    )
+    .with_service_key_partitioning();
 
     ctx.as_ref()
         .register_table("sys_service_status", Arc::new(status_table))
@@ -48,6 +49,8 @@ impl RangeScanner for ServiceStatusScanner {
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let db = self.0.clone();
@@ -55,8 +58,17 @@ impl RangeScanner for ServiceStatusScanner {
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
         let background_task = move || {
-            let rows = db.all_service_status(range);
-            for_each_status(schema, tx, rows);
+            let limit = limit.unwrap_or(usize::MAX);
+            match filter.service_id() {
+                Some(service_id) => {
+                    let rows = db.service_status_of(&service_id).take(limit);
+                    for_each_status(schema, tx, rows);
+                }
+                None => {
+                    let rows = db.all_service_status(range).take(limit);
+                    for_each_status(schema, tx, rows);
+                }
+            }
             Ok(())
         };
         stream_builder.spawn_blocking(background_task);
@@ -16,7 +16,7 @@ use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
 
 use crate::context::QueryContext;
-use crate::generic_table::{GenericTableProvider, RangeScanner};
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
 use crate::state::row::append_state_row;
 use crate::state::schema::StateBuilder;
 use datafusion::physical_plan::stream::RecordBatchReceiverStream;
@@ -31,7 +31,8 @@ pub(crate) fn register_self(
     ctx: &QueryContext,
     storage: RocksDBStorage,
 ) -> datafusion::common::Result<()> {
-    let table = GenericTableProvider::new(StateBuilder::schema(), Arc::new(StateScanner(storage)));
+    let table = GenericTableProvider::new(StateBuilder::schema(), Arc::new(StateScanner(storage)))
+        .with_service_key_partitioning();
 
     ctx.as_ref()
         .register_table("state", Arc::new(table))
@@ -45,6 +46,8 @@ impl RangeScanner for StateScanner {
     fn scan(
         &self,
         range: RangeInclusive<PartitionKey>,
+        filter: KeyFilter,
+        limit: Option<usize>,
         projection: SchemaRef,
     ) -> SendableRecordBatchStream {
         let db = self.0.clone();
@@ -52,8 +55,17 @@ impl RangeScanner for StateScanner {
         let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
         let tx = stream_builder.tx();
         let background_task = move || {
-            let rows = db.all_states(range);
-            for_each_state(schema, tx, rows);
+            let limit = limit.unwrap_or(usize::MAX);
+            match filter.service_id() {
+                Some(service_id) => {
+                    let rows = db.states_of(&service_id).take(limit);
+                    for_each_state(schema, tx, rows);
+                }
+                None => {
+                    let rows = db.all_states(range).take(limit);
+                    for_each_state(schema, tx, rows);
+                }
+            }
             Ok(())
         };
         stream_builder.spawn_blocking(background_task);
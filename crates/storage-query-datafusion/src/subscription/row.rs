@@ -0,0 +1,103 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::{SubscriptionBuilder, SubscriptionRowBuilder};
+use crate::table_util::format_using;
+use restate_schema_api::subscription::{Sink, Subscription};
+use restate_worker_api::{SubscriptionPartitionStatus, SubscriptionStatusReport};
+use std::time::UNIX_EPOCH;
+
+/// Appends one row per partition assigned to the subscription consumer,
+/// or a single row if no partition is assigned.
+#[inline]
+pub(crate) fn append_subscription_rows(
+    builder: &mut SubscriptionBuilder,
+    output: &mut String,
+    subscription: Subscription,
+    status: Option<SubscriptionStatusReport>,
+) {
+    let partitions = status
+        .as_ref()
+        .map(|status| status.partitions.as_slice())
+        .unwrap_or_default();
+
+    if partitions.is_empty() {
+        let mut row = builder.row();
+        fill_subscription(&mut row, output, &subscription, status.as_ref());
+    }
+    for partition in partitions {
+        let mut row = builder.row();
+        fill_subscription(&mut row, output, &subscription, status.as_ref());
+        fill_partition(&mut row, partition);
+    }
+}
+
+#[inline]
+fn fill_subscription(
+    row: &mut SubscriptionRowBuilder,
+    output: &mut String,
+    subscription: &Subscription,
+    status: Option<&SubscriptionStatusReport>,
+) {
+    if row.is_id_defined() {
+        row.id(format_using(output, &subscription.id()));
+    }
+    if row.is_source_defined() {
+        row.source(format_using(output, subscription.source()));
+    }
+    if row.is_sink_defined() {
+        row.sink(format_using(output, subscription.sink()));
+    }
+    match subscription.sink() {
+        Sink::Service { name, method, .. } => {
+            row.sink_service(name);
+            row.sink_method(method);
+        }
+        Sink::Component { name, handler, .. } => {
+            row.sink_service(name);
+            row.sink_method(handler);
+        }
+    }
+    if row.is_metadata_defined() {
+        row.metadata(
+            serde_json::to_string(subscription.metadata())
+                .expect("Serializing a string map must not fail"),
+        );
+    }
+
+    // Status is reported only for the subscriptions run by this worker
+    if let Some(status) = status {
+        row.status(if status.running {
+            "running"
+        } else {
+            "backing_off"
+        });
+        if let Some(last_error) = &status.last_error {
+            row.last_error(last_error);
+        }
+        if let Some(last_error_at) = status.last_error_at {
+            if let Ok(duration) = last_error_at.duration_since(UNIX_EPOCH) {
+                row.last_error_at(duration.as_millis() as i64);
+            }
+        }
+    }
+}
+
+#[inline]
+fn fill_partition(row: &mut SubscriptionRowBuilder, partition: &SubscriptionPartitionStatus) {
+    row.topic(&partition.topic);
+    row.topic_partition(partition.partition);
+    if let Some(committed_offset) = partition.committed_offset {
+        row.committed_offset(committed_offset);
+    }
+    if let Some(lag) = partition.lag {
+        row.lag(lag);
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![allow(dead_code)]
+
+use crate::table_macro::*;
+
+use datafusion::arrow::datatypes::DataType;
+
+define_table!(subscription(
+    id: DataType::LargeUtf8,
+    source: DataType::LargeUtf8,
+    sink: DataType::LargeUtf8,
+    sink_service: DataType::LargeUtf8,
+    sink_method: DataType::LargeUtf8,
+    metadata: DataType::LargeUtf8,
+
+    status: DataType::LargeUtf8,
+    topic: DataType::LargeUtf8,
+    topic_partition: DataType::Int32,
+    committed_offset: DataType::UInt64,
+    lag: DataType::UInt64,
+    last_error: DataType::LargeUtf8,
+    last_error_at: DataType::Date64,
+));
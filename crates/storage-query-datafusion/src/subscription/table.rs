@@ -0,0 +1,108 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::SubscriptionBuilder;
+
+use crate::context::QueryContext;
+use crate::generic_table::{GenericTableProvider, KeyFilter, RangeScanner};
+use crate::subscription::row::append_subscription_rows;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+pub use datafusion_expr::UserDefinedLogicalNode;
+use restate_schema_api::subscription::{Subscription, SubscriptionResolver};
+use restate_types::identifiers::{PartitionKey, SubscriptionId};
+use restate_worker_api::{SubscriptionStatusHandle, SubscriptionStatusReport};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+
+pub(crate) fn register_self(
+    ctx: &QueryContext,
+    resolver: impl SubscriptionResolver + Send + Sync + Debug + 'static,
+    status: impl SubscriptionStatusHandle + Send + Sync + Debug + Clone + 'static,
+) -> datafusion::common::Result<()> {
+    let subscription_table = GenericTableProvider::new(
+        SubscriptionBuilder::schema(),
+        Arc::new(SubscriptionScanner(resolver, status)),
+    );
+
+    ctx.as_ref()
+        .register_table("sys_subscription", Arc::new(subscription_table))
+        .map(|_| ())
+}
+
+#[derive(Debug, Clone)]
+struct SubscriptionScanner<SR, S>(SR, S);
+
+/// TODO This trait makes little sense for sys_subscription,
+///  but it's fine nevertheless as the caller always uses the full range
+impl<
+        SR: SubscriptionResolver + Debug + Sync + Send + 'static,
+        S: SubscriptionStatusHandle + Send + Sync + Debug + Clone + 'static,
+    > RangeScanner for SubscriptionScanner<SR, S>
+{
+    fn scan(
+        &self,
+        _range: RangeInclusive<PartitionKey>,
+        _filter: KeyFilter,
+        limit: Option<usize>,
+        projection: SchemaRef,
+    ) -> SendableRecordBatchStream {
+        let schema = projection.clone();
+        let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
+        let tx = stream_builder.tx();
+
+        let mut rows = self.0.list_subscriptions(&[]);
+        rows.truncate(limit.unwrap_or(usize::MAX));
+        let status = self.1.clone();
+        stream_builder.spawn(async move {
+            let status = status
+                .read_status()
+                .await
+                .map(|report| (report.id, report))
+                .collect();
+            for_each_subscription(schema, tx, rows, status).await;
+            Ok(())
+        });
+        stream_builder.build()
+    }
+}
+
+async fn for_each_subscription(
+    schema: SchemaRef,
+    tx: Sender<datafusion::common::Result<RecordBatch>>,
+    rows: Vec<Subscription>,
+    mut status: HashMap<SubscriptionId, SubscriptionStatusReport>,
+) {
+    let mut builder = SubscriptionBuilder::new(schema.clone());
+    let mut temp = String::new();
+    for subscription in rows {
+        let subscription_status = status.remove(&subscription.id());
+        append_subscription_rows(&mut builder, &mut temp, subscription, subscription_status);
+        if builder.full() {
+            let batch = builder.finish();
+            if tx.send(Ok(batch)).await.is_err() {
+                // not sure what to do here?
+                // the other side has hung up on us.
+                // we probably don't want to panic, is it will cause the entire process to exit
+                return;
+            }
+            builder = SubscriptionBuilder::new(schema.clone());
+        }
+    }
+    if !builder.empty() {
+        let result = builder.finish();
+        let _ = tx.send(Ok(result)).await;
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::{TimerBuilder, TimerRowBuilder};
+use crate::table_util::format_using;
+use restate_storage_api::timer_table::Timer;
+use restate_storage_rocksdb::timer_table::OwnedTimerRow;
+use restate_types::identifiers::{InvocationId, InvocationUuid, ServiceId, WithPartitionKey};
+
+#[inline]
+pub(crate) fn append_timer_row(
+    builder: &mut TimerBuilder,
+    output: &mut String,
+    timer_row: OwnedTimerRow,
+) {
+    let mut row = builder.row();
+
+    row.partition_id(timer_row.partition_id);
+    row.fire_at(timer_row.timer_key.timestamp as i64);
+    row.journal_index(timer_row.timer_key.journal_index);
+
+    let invocation_uuid = timer_row.timer_key.invocation_uuid;
+    match timer_row.timer {
+        Timer::CompleteSleepEntry(service_id) => {
+            row.kind("sleep");
+            fill_target(&mut row, output, &service_id, invocation_uuid);
+        }
+        Timer::Invoke(_, service_invocation) => {
+            // The target is the invocation started when the timer fires
+            row.kind("invoke");
+            row.target_method(&service_invocation.method_name);
+            fill_target(
+                &mut row,
+                output,
+                &service_invocation.fid.service_id,
+                service_invocation.fid.invocation_uuid,
+            );
+        }
+        Timer::CleanInvocationStatus(service_id) => {
+            row.kind("clean_invocation_status");
+            fill_target(&mut row, output, &service_id, invocation_uuid);
+        }
+    }
+}
+
+#[inline]
+fn fill_target(
+    row: &mut TimerRowBuilder,
+    output: &mut String,
+    service_id: &ServiceId,
+    invocation_uuid: InvocationUuid,
+) {
+    row.target_partition_key(service_id.partition_key());
+    row.target_service(&service_id.service_name);
+    row.target_service_key(
+        std::str::from_utf8(&service_id.key).expect("The key must be a string!"),
+    );
+    if row.is_target_id_defined() {
+        row.target_id(format_using(
+            output,
+            &InvocationId::new(service_id.partition_key(), invocation_uuid),
+        ));
+    }
+}
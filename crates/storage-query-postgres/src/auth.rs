@@ -0,0 +1,143 @@
+// Copyright (c) 2023 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::options::{AuthenticationMethod, UserOptions};
+use async_trait::async_trait;
+use datafusion::sql::parser::Statement as DFStatement;
+use datafusion::sql::sqlparser::ast::Statement as SQLStatement;
+use pgwire::api::auth::md5pass::hash_md5_password;
+use pgwire::api::auth::scram::gen_salted_password;
+use pgwire::api::auth::{AuthSource, LoginInfo, Password};
+use pgwire::api::{ClientInfo, METADATA_USER};
+use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
+use restate_storage_query_datafusion::context::QueryContext;
+use std::collections::{HashMap, HashSet};
+
+pub(crate) const SCRAM_ITERATIONS: usize = 4096;
+
+struct User {
+    password: String,
+    /// Salt and salted password, only computed when authenticating with SCRAM.
+    scram: Option<(Vec<u8>, Vec<u8>)>,
+    allowed_tables: Option<HashSet<String>>,
+}
+
+/// The configured users, used as password source for pgwire's startup handlers and to restrict
+/// the tables a user can query.
+pub(crate) struct Users {
+    method: AuthenticationMethod,
+    users: HashMap<String, User>,
+}
+
+impl Users {
+    pub(crate) fn new(method: AuthenticationMethod, users: HashMap<String, UserOptions>) -> Self {
+        let users = users
+            .into_iter()
+            .map(|(name, options)| {
+                let scram = (method == AuthenticationMethod::ScramSha256).then(|| {
+                    let salt = rand::random::<[u8; 16]>().to_vec();
+                    let salted_password =
+                        gen_salted_password(&options.password, &salt, SCRAM_ITERATIONS);
+                    (salt, salted_password)
+                });
+                let user = User {
+                    password: options.password,
+                    scram,
+                    allowed_tables: options
+                        .allowed_tables
+                        .map(|tables| tables.into_iter().collect()),
+                };
+                (name, user)
+            })
+            .collect();
+
+        Self { method, users }
+    }
+
+    /// Checks whether the user of the connection can run the given sql. Users with allowed tables
+    /// can only run queries reading from these tables.
+    pub(crate) fn check_access<C: ClientInfo>(
+        &self,
+        client: &C,
+        query_context: &QueryContext,
+        sql: &str,
+    ) -> PgWireResult<()> {
+        let Some(allowed_tables) = client
+            .metadata()
+            .get(METADATA_USER)
+            .and_then(|user| self.users.get(user))
+            .and_then(|user| user.allowed_tables.as_ref())
+        else {
+            return Ok(());
+        };
+
+        let state = query_context.as_ref().state();
+        let statement = state
+            .sql_to_statement(sql, "postgres")
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+        let is_query = match &statement {
+            DFStatement::Statement(statement) => {
+                matches!(statement.as_ref(), SQLStatement::Query(_))
+            }
+            _ => false,
+        };
+        if !is_query {
+            return Err(permission_denied("only queries are allowed".to_owned()));
+        }
+
+        for table in state
+            .resolve_table_references(&statement)
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?
+        {
+            let table = table.to_string();
+            if !allowed_tables.contains(&table) {
+                return Err(permission_denied(format!(
+                    "permission denied for table {table}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuthSource for Users {
+    async fn get_password(&self, login: &LoginInfo) -> PgWireResult<Password> {
+        let name = login
+            .user()
+            .as_ref()
+            .map(|user| user.to_string())
+            .unwrap_or_default();
+        let Some(user) = self.users.get(&name) else {
+            return Err(PgWireError::InvalidPassword(name));
+        };
+
+        Ok(match (self.method, &user.scram) {
+            (AuthenticationMethod::Md5, _) => {
+                let salt = rand::random::<[u8; 4]>().to_vec();
+                let hashed_password = hash_md5_password(&name, &user.password, &salt);
+                Password::new(Some(salt), hashed_password.into_bytes())
+            }
+            (AuthenticationMethod::ScramSha256, Some((salt, salted_password))) => {
+                Password::new(Some(salt.clone()), salted_password.clone())
+            }
+            _ => Password::new(None, user.password.as_bytes().to_vec()),
+        })
+    }
+}
+
+fn permission_denied(message: String) -> PgWireError {
+    PgWireError::UserError(Box::new(ErrorInfo::new(
+        "ERROR".to_owned(),
+        "42501".to_owned(),
+        message,
+    )))
+}
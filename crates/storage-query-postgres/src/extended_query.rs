@@ -8,35 +8,97 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::auth::Users;
+use crate::pgwire_server::{arrow_to_pg_encoder, into_pg_fields, into_pg_type};
 use async_trait::async_trait;
+use datafusion::arrow::datatypes::{DataType, Schema};
+use datafusion::logical_expr::LogicalPlan;
+use datafusion::scalar::ScalarValue;
 use pgwire::api::portal::Portal;
 use pgwire::api::query::{ExtendedQueryHandler, StatementOrPortal};
-use pgwire::api::results::{DescribeResponse, Response};
-use pgwire::api::stmt::NoopQueryParser;
+use pgwire::api::results::{DescribeResponse, FieldFormat, Response};
+use pgwire::api::stmt::{QueryParser, StoredStatement};
 use pgwire::api::store::MemPortalStore;
-use pgwire::api::ClientInfo;
-use pgwire::error::{PgWireError, PgWireResult};
+use pgwire::api::{ClientInfo, Type};
+use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
+use restate_storage_query_datafusion::context::QueryContext;
 use std::sync::Arc;
+use tokio::sync::OnceCell;
 
+/// Statement of the extended query protocol. It is planned by DataFusion when being described or
+/// executed for the first time, the plan is then reused by all portals bound to the statement.
 #[derive(Debug, Clone)]
-pub struct NoopExtendedQueryHandler {
-    portal_store: Arc<MemPortalStore<String>>,
-    query_parser: Arc<NoopQueryParser>,
+pub struct DfStatement {
+    sql: String,
+    plan: Arc<OnceCell<LogicalPlan>>,
 }
 
-impl NoopExtendedQueryHandler {
-    pub(crate) fn new() -> Self {
-        NoopExtendedQueryHandler {
+impl DfStatement {
+    fn is_empty(&self) -> bool {
+        self.sql.trim().trim_end_matches(';').trim().is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DfQueryParser;
+
+impl QueryParser for DfQueryParser {
+    type Statement = DfStatement;
+
+    fn parse_sql(&self, sql: &str, _types: &[Type]) -> PgWireResult<Self::Statement> {
+        Ok(DfStatement {
+            sql: sql.to_owned(),
+            plan: Arc::new(OnceCell::new()),
+        })
+    }
+}
+
+/// Handles the extended query protocol (parse/bind/describe/execute).
+///
+/// Placeholders (`$1`, `$2`, ...) get their types either from the client provided parameter
+/// types or, if those are unspecified, from the types DataFusion infers for them.
+pub struct DfExtendedQueryHandler {
+    query_context: QueryContext,
+    users: Arc<Users>,
+    portal_store: Arc<MemPortalStore<DfStatement>>,
+    query_parser: Arc<DfQueryParser>,
+}
+
+impl DfExtendedQueryHandler {
+    pub(crate) fn new(query_context: QueryContext, users: Arc<Users>) -> Self {
+        DfExtendedQueryHandler {
+            query_context,
+            users,
             portal_store: Arc::new(Default::default()),
             query_parser: Arc::new(Default::default()),
         }
     }
+
+    /// Returns the plan of the statement, planning it if this has not happened yet.
+    async fn plan<'s, C: ClientInfo>(
+        &self,
+        client: &C,
+        statement: &'s DfStatement,
+    ) -> PgWireResult<&'s LogicalPlan> {
+        // The access depends on the user of the client, hence it is checked on every use
+        self.users
+            .check_access(client, &self.query_context, &statement.sql)?;
+        statement
+            .plan
+            .get_or_try_init(|| async {
+                self.query_context
+                    .plan(&statement.sql)
+                    .await
+                    .map_err(|e| PgWireError::ApiError(Box::new(e)))
+            })
+            .await
+    }
 }
 
 #[async_trait]
-impl ExtendedQueryHandler for NoopExtendedQueryHandler {
-    type Statement = String;
-    type QueryParser = NoopQueryParser;
+impl ExtendedQueryHandler for DfExtendedQueryHandler {
+    type Statement = DfStatement;
+    type QueryParser = DfQueryParser;
     type PortalStore = MemPortalStore<Self::Statement>;
 
     fn portal_store(&self) -> Arc<Self::PortalStore> {
@@ -49,28 +111,269 @@ impl ExtendedQueryHandler for NoopExtendedQueryHandler {
 
     async fn do_describe<C>(
         &self,
-        _client: &mut C,
-        _statement: StatementOrPortal<'_, Self::Statement>,
+        client: &mut C,
+        target: StatementOrPortal<'_, Self::Statement>,
     ) -> PgWireResult<DescribeResponse>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
-        Err(PgWireError::ApiError(
-            "Extended Query is not implemented on this server.".into(),
-        ))
+        match target {
+            StatementOrPortal::Statement(statement) => {
+                if statement.statement().is_empty() {
+                    return Ok(DescribeResponse::new(Some(vec![]), vec![]));
+                }
+                let plan = self.plan(client, statement.statement()).await?;
+                let placeholder_types = placeholder_types(plan)?;
+                let parameter_types = (0..placeholder_types
+                    .len()
+                    .max(statement.parameter_types().len()))
+                    .map(|idx| parameter_type(statement, &placeholder_types, idx))
+                    .collect();
+                let fields = into_pg_fields(&Schema::from(plan.schema().as_ref()), None)?;
+
+                Ok(DescribeResponse::new(Some(parameter_types), fields))
+            }
+            StatementOrPortal::Portal(portal) => {
+                if portal.statement().statement().is_empty() {
+                    return Ok(DescribeResponse::new(None, vec![]));
+                }
+                let plan = self.plan(client, portal.statement().statement()).await?;
+                let fields = into_pg_fields(
+                    &Schema::from(plan.schema().as_ref()),
+                    Some(portal.result_column_format()),
+                )?;
+
+                Ok(DescribeResponse::new(None, fields))
+            }
+        }
     }
 
     async fn do_query<'a, 'b: 'a, C>(
         &'b self,
-        _client: &mut C,
-        _portal: &'a Portal<Self::Statement>,
-        _max_rows: usize,
+        client: &mut C,
+        portal: &'a Portal<Self::Statement>,
+        max_rows: usize,
     ) -> PgWireResult<Response<'a>>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
-        Err(PgWireError::ApiError(
-            "Extended Query is not implemented on this server.".into(),
-        ))
+        // Suspending portals is not supported, hence the rows must be fetched at once
+        if max_rows > 0 {
+            return Err(user_error(
+                "0A000",
+                "Fetching a limited number of rows is not supported, use LIMIT instead".to_owned(),
+            ));
+        }
+
+        let statement = portal.statement();
+        if statement.statement().is_empty() {
+            return Ok(Response::EmptyQuery);
+        }
+
+        let mut plan = self.plan(client, statement.statement()).await?.clone();
+        if !portal.parameters().is_empty() {
+            let parameters = decode_parameters(portal, &placeholder_types(&plan)?)?;
+            plan = plan
+                .with_param_values(parameters)
+                .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+        }
+
+        let stream = self
+            .query_context
+            .execute_plan(plan)
+            .await
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+        let resp = arrow_to_pg_encoder(stream, Some(portal.result_column_format())).await?;
+        Ok(Response::Query(resp))
+    }
+}
+
+/// Returns the types DataFusion inferred for the placeholders of the plan, ordered by their
+/// position. Placeholders whose type could not be inferred are `None`.
+fn placeholder_types(plan: &LogicalPlan) -> PgWireResult<Vec<Option<DataType>>> {
+    let types = plan
+        .get_parameter_types()
+        .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+
+    let mut ordered = vec![None; types.len()];
+    for (id, data_type) in types {
+        let idx = id
+            .strip_prefix('$')
+            .and_then(|idx| idx.parse::<usize>().ok())
+            .and_then(|idx| idx.checked_sub(1))
+            .ok_or_else(|| {
+                user_error(
+                    "42P02",
+                    format!("Unsupported placeholder {id}, expected $1, $2, ..."),
+                )
+            })?;
+        if idx >= ordered.len() {
+            ordered.resize(idx + 1, None);
+        }
+        ordered[idx] = data_type;
     }
+    Ok(ordered)
+}
+
+/// The type of the parameter at `idx`. Client provided types take precedence over inferred ones,
+/// parameters of unknown type are treated as text.
+fn parameter_type(
+    statement: &StoredStatement<DfStatement>,
+    placeholder_types: &[Option<DataType>],
+    idx: usize,
+) -> Type {
+    statement
+        .parameter_types()
+        .get(idx)
+        .filter(|pg_type| **pg_type != Type::UNKNOWN)
+        .cloned()
+        .or_else(|| {
+            placeholder_types
+                .get(idx)
+                .and_then(Option::as_ref)
+                .and_then(|data_type| into_pg_type(data_type).ok())
+        })
+        .unwrap_or(Type::VARCHAR)
+}
+
+/// Decodes the bound parameters of the portal and casts them to the types DataFusion inferred for
+/// the respective placeholders, so that they can be passed to [`LogicalPlan::with_param_values`].
+fn decode_parameters(
+    portal: &Portal<DfStatement>,
+    placeholder_types: &[Option<DataType>],
+) -> PgWireResult<Vec<ScalarValue>> {
+    portal
+        .parameters()
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| {
+            let pg_type = parameter_type(portal.statement(), placeholder_types, idx);
+            let value = decode_parameter(
+                value.as_deref(),
+                portal.parameter_format().format_for(idx),
+                &pg_type,
+            )?;
+
+            match placeholder_types.get(idx).and_then(Option::as_ref) {
+                Some(data_type) if value.data_type() != *data_type => value
+                    .cast_to(data_type)
+                    .map_err(|e| PgWireError::ApiError(Box::new(e))),
+                _ => Ok(value),
+            }
+        })
+        .collect()
+}
+
+fn decode_parameter(
+    value: Option<&[u8]>,
+    format: FieldFormat,
+    pg_type: &Type,
+) -> PgWireResult<ScalarValue> {
+    let text = matches!(format, FieldFormat::Text);
+
+    let scalar = if *pg_type == Type::BOOL {
+        ScalarValue::Boolean(decode(
+            value,
+            text,
+            pg_type,
+            |s| match s {
+                "t" | "true" => Some(true),
+                "f" | "false" => Some(false),
+                _ => None,
+            },
+            |b| b.first().map(|b| *b != 0),
+        )?)
+    } else if *pg_type == Type::INT2 {
+        ScalarValue::Int16(decode(
+            value,
+            text,
+            pg_type,
+            |s| s.parse().ok(),
+            |b| b.try_into().ok().map(i16::from_be_bytes),
+        )?)
+    } else if *pg_type == Type::INT4 {
+        ScalarValue::Int32(decode(
+            value,
+            text,
+            pg_type,
+            |s| s.parse().ok(),
+            |b| b.try_into().ok().map(i32::from_be_bytes),
+        )?)
+    } else if *pg_type == Type::INT8 {
+        ScalarValue::Int64(decode(
+            value,
+            text,
+            pg_type,
+            |s| s.parse().ok(),
+            |b| b.try_into().ok().map(i64::from_be_bytes),
+        )?)
+    } else if *pg_type == Type::FLOAT4 {
+        ScalarValue::Float32(decode(
+            value,
+            text,
+            pg_type,
+            |s| s.parse().ok(),
+            |b| b.try_into().ok().map(f32::from_be_bytes),
+        )?)
+    } else if *pg_type == Type::FLOAT8 {
+        ScalarValue::Float64(decode(
+            value,
+            text,
+            pg_type,
+            |s| s.parse().ok(),
+            |b| b.try_into().ok().map(f64::from_be_bytes),
+        )?)
+    } else if *pg_type == Type::VARCHAR
+        || *pg_type == Type::TEXT
+        || *pg_type == Type::BPCHAR
+        || *pg_type == Type::NAME
+    {
+        // text and binary representation of strings are the same
+        ScalarValue::Utf8(decode(
+            value,
+            true,
+            pg_type,
+            |s| Some(s.to_owned()),
+            |_| None,
+        )?)
+    } else {
+        return Err(user_error(
+            "0A000",
+            format!("Unsupported parameter type {pg_type}"),
+        ));
+    };
+
+    Ok(scalar)
+}
+
+fn decode<T>(
+    value: Option<&[u8]>,
+    text: bool,
+    pg_type: &Type,
+    from_text: impl FnOnce(&str) -> Option<T>,
+    from_binary: impl FnOnce(&[u8]) -> Option<T>,
+) -> PgWireResult<Option<T>> {
+    value
+        .map(|bytes| {
+            let decoded = if text {
+                std::str::from_utf8(bytes).ok().and_then(from_text)
+            } else {
+                from_binary(bytes)
+            };
+            decoded.ok_or_else(|| {
+                user_error(
+                    "22P02",
+                    format!("Invalid value for parameter of type {pg_type}"),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn user_error(code: &str, message: String) -> PgWireError {
+    PgWireError::UserError(Box::new(ErrorInfo::new(
+        "ERROR".to_owned(),
+        code.to_owned(),
+        message,
+    )))
 }
@@ -10,7 +10,9 @@
 
 use crate::service::PostgresQueryService;
 use restate_storage_query_datafusion::context::QueryContext;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 /// # Storage query postgres options
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
@@ -20,28 +22,112 @@ use std::net::SocketAddr;
     schemars(rename = "StorageQueryPostgresOptions")
 )]
 #[cfg_attr(feature = "options_schema", schemars(default))]
+#[builder(default)]
 pub struct Options {
     /// # Bind address
     ///
     /// The address to bind for the psql service.
     pub bind_address: SocketAddr,
+
+    /// # TLS
+    ///
+    /// Certificate and private key used to accept TLS connections. If unset, TLS is not offered
+    /// to clients.
+    pub tls: Option<TlsOptions>,
+
+    /// # Authentication
+    ///
+    /// How clients are authenticated against the configured `users`. With `trust`, every
+    /// connection is accepted without a password.
+    pub authentication: AuthenticationMethod,
+
+    /// # Users
+    ///
+    /// Users allowed to connect, keyed by user name.
+    pub users: HashMap<String, UserOptions>,
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             bind_address: "0.0.0.0:9071".parse().unwrap(),
+            tls: None,
+            authentication: AuthenticationMethod::default(),
+            users: HashMap::default(),
         }
     }
 }
 
 impl Options {
     pub fn build(self, query_context: QueryContext) -> PostgresQueryService {
-        let Options { bind_address } = self;
+        let Options {
+            bind_address,
+            tls,
+            authentication,
+            users,
+        } = self;
 
         PostgresQueryService {
             bind_address,
+            tls,
+            authentication,
+            users,
             query_context,
         }
     }
 }
+
+/// # TLS options
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+pub struct TlsOptions {
+    /// # Certificate path
+    ///
+    /// Path to the PEM encoded certificate chain.
+    pub cert_path: PathBuf,
+
+    /// # Private key path
+    ///
+    /// Path to the PEM encoded private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: PathBuf,
+}
+
+/// # Authentication method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AuthenticationMethod {
+    /// # Trust
+    ///
+    /// Accept every connection without asking for a password.
+    #[default]
+    Trust,
+    /// # Cleartext
+    ///
+    /// Ask for the password in cleartext. Requires `tls` to be configured, connections which
+    /// don't use TLS are rejected.
+    Cleartext,
+    /// # MD5
+    ///
+    /// Ask for a MD5 hashed password.
+    Md5,
+    /// # SCRAM-SHA-256
+    ///
+    /// Authenticate using SCRAM-SHA-256.
+    ScramSha256,
+}
+
+/// # User options
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+pub struct UserOptions {
+    /// # Password
+    pub password: String,
+
+    /// # Allowed tables
+    ///
+    /// If set, the user can only run queries reading from these tables, e.g. `sys_status`.
+    /// Any other statement is rejected.
+    #[serde(default)]
+    pub allowed_tables: Option<Vec<String>>,
+}
@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::fmt::Debug;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -23,61 +24,158 @@ use datafusion::arrow::datatypes::Int16Type;
 use datafusion::arrow::datatypes::Int32Type;
 use datafusion::arrow::datatypes::Int64Type;
 use datafusion::arrow::datatypes::Int8Type;
+use datafusion::arrow::datatypes::Schema;
 use datafusion::arrow::datatypes::UInt32Type;
 use datafusion::arrow::datatypes::UInt64Type;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::temporal_conversions::{date32_to_datetime, date64_to_datetime};
 use datafusion::physical_plan::SendableRecordBatchStream;
-use futures::{stream, StreamExt};
+use futures::{stream, Sink, StreamExt};
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 
-use crate::extended_query::NoopExtendedQueryHandler;
+use crate::auth::{Users, SCRAM_ITERATIONS};
+use crate::extended_query::DfExtendedQueryHandler;
+use crate::options::AuthenticationMethod;
+use pgwire::api::auth::cleartext::CleartextPasswordAuthStartupHandler;
+use pgwire::api::auth::md5pass::MakeMd5PasswordAuthStartupHandler;
 use pgwire::api::auth::noop::NoopStartupHandler;
+use pgwire::api::auth::scram::MakeSASLScramAuthStartupHandler;
+use pgwire::api::auth::{DefaultServerParameterProvider, StartupHandler};
+use pgwire::api::portal::Format;
 use pgwire::api::query::SimpleQueryHandler;
 use pgwire::api::results::{DataRowEncoder, FieldFormat, FieldInfo, QueryResponse, Response};
 use pgwire::api::{ClientInfo, MakeHandler, StatelessMakeHandler, Type};
 use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
 use pgwire::messages::data::DataRow;
+use pgwire::messages::{PgWireBackendMessage, PgWireFrontendMessage};
 use pgwire::tokio::process_socket;
 use restate_storage_query_datafusion::context::QueryContext;
+use tokio_rustls::TlsAcceptor;
 use tracing::warn;
 
+enum Authenticator {
+    Trust(StatelessMakeHandler<NoopStartupHandler>),
+    Cleartext(
+        StatelessMakeHandler<
+            TlsOnlyStartupHandler<
+                CleartextPasswordAuthStartupHandler<Users, DefaultServerParameterProvider>,
+            >,
+        >,
+    ),
+    Md5(MakeMd5PasswordAuthStartupHandler<Users, DefaultServerParameterProvider>),
+    Scram(MakeSASLScramAuthStartupHandler<Users, DefaultServerParameterProvider>),
+}
+
+impl Authenticator {
+    fn new(method: AuthenticationMethod, users: Arc<Users>) -> Self {
+        let parameters = Arc::new(DefaultServerParameterProvider::default());
+        match method {
+            AuthenticationMethod::Trust => {
+                Authenticator::Trust(StatelessMakeHandler::new(Arc::new(NoopStartupHandler)))
+            }
+            // Passwords must not be sent in cleartext over unencrypted connections
+            AuthenticationMethod::Cleartext => Authenticator::Cleartext(StatelessMakeHandler::new(
+                Arc::new(TlsOnlyStartupHandler(
+                    CleartextPasswordAuthStartupHandler::new(users, parameters),
+                )),
+            )),
+            // The MD5 and SCRAM handlers keep the state of the login, hence they are created per
+            // connection
+            AuthenticationMethod::Md5 => {
+                Authenticator::Md5(MakeMd5PasswordAuthStartupHandler::new(users, parameters))
+            }
+            AuthenticationMethod::ScramSha256 => {
+                let mut handler = MakeSASLScramAuthStartupHandler::new(users, parameters);
+                handler.set_iterations(SCRAM_ITERATIONS);
+                Authenticator::Scram(handler)
+            }
+        }
+    }
+}
+
+/// Startup handler which rejects connections that did not negotiate TLS, before delegating
+/// the authentication to the wrapped handler.
+struct TlsOnlyStartupHandler<H>(H);
+
+#[async_trait]
+impl<H: StartupHandler> StartupHandler for TlsOnlyStartupHandler<H> {
+    async fn on_startup<C>(
+        &self,
+        client: &mut C,
+        message: PgWireFrontendMessage,
+    ) -> PgWireResult<()>
+    where
+        C: ClientInfo + Sink<PgWireBackendMessage> + Unpin + Send,
+        C::Error: Debug,
+        PgWireError: From<<C as Sink<PgWireBackendMessage>>::Error>,
+    {
+        if !client.is_secure() {
+            return Err(PgWireError::UserError(Box::new(ErrorInfo::new(
+                "FATAL".to_owned(),
+                "28000".to_owned(),
+                "password authentication requires a TLS connection".to_owned(),
+            ))));
+        }
+        self.0.on_startup(client, message).await
+    }
+}
+
 pub(crate) struct HandlerFactory {
     processor: Arc<StatelessMakeHandler<DfSessionService>>,
-    placeholder: Arc<StatelessMakeHandler<NoopExtendedQueryHandler>>,
-    authenticator: Arc<StatelessMakeHandler<NoopStartupHandler>>,
+    extended_processor: Arc<StatelessMakeHandler<DfExtendedQueryHandler>>,
+    authenticator: Authenticator,
+    tls_acceptor: Option<Arc<TlsAcceptor>>,
 }
 
 impl HandlerFactory {
-    pub fn new(ctx: QueryContext) -> Self {
+    pub fn new(
+        ctx: QueryContext,
+        tls_acceptor: Option<TlsAcceptor>,
+        authentication: AuthenticationMethod,
+        users: Users,
+    ) -> Self {
+        let users = Arc::new(users);
+        let extended_processor = Arc::new(StatelessMakeHandler::new(Arc::new(
+            DfExtendedQueryHandler::new(ctx.clone(), users.clone()),
+        )));
         let processor = Arc::new(StatelessMakeHandler::new(Arc::new(DfSessionService::new(
             ctx,
+            users.clone(),
         ))));
-        // We have not implemented extended query in this server, use placeholder instead
-        let placeholder = Arc::new(StatelessMakeHandler::new(Arc::new(
-            NoopExtendedQueryHandler::new(),
-        )));
-        let authenticator = Arc::new(StatelessMakeHandler::new(Arc::new(NoopStartupHandler)));
+        let authenticator = Authenticator::new(authentication, users);
 
         Self {
             processor,
-            placeholder,
+            extended_processor,
             authenticator,
+            tls_acceptor: tls_acceptor.map(Arc::new),
         }
     }
 
     pub fn spawn_connection(&self, incoming_socket: TcpStream, addr: SocketAddr) {
-        let authenticator_ref = self.authenticator.make();
+        match &self.authenticator {
+            Authenticator::Trust(handler) => self.spawn(incoming_socket, addr, handler.make()),
+            Authenticator::Cleartext(handler) => self.spawn(incoming_socket, addr, handler.make()),
+            Authenticator::Md5(handler) => self.spawn(incoming_socket, addr, handler.make()),
+            Authenticator::Scram(handler) => self.spawn(incoming_socket, addr, handler.make()),
+        }
+    }
+
+    fn spawn<A>(&self, incoming_socket: TcpStream, addr: SocketAddr, authenticator_ref: Arc<A>)
+    where
+        A: StartupHandler + 'static,
+    {
         let processor_ref = self.processor.make();
-        let placeholder_ref = self.placeholder.make();
+        let extended_processor_ref = self.extended_processor.make();
+        let tls_acceptor_ref = self.tls_acceptor.clone();
         tokio::spawn(async move {
             let result = process_socket(
                 incoming_socket,
-                None,
+                tls_acceptor_ref,
                 authenticator_ref,
                 processor_ref,
-                placeholder_ref,
+                extended_processor_ref,
             )
             .await;
 
@@ -90,34 +188,37 @@ impl HandlerFactory {
 
 pub struct DfSessionService {
     session_context: Mutex<QueryContext>,
+    users: Arc<Users>,
 }
 
 impl DfSessionService {
-    pub fn new(ctx: QueryContext) -> DfSessionService {
+    pub(crate) fn new(ctx: QueryContext, users: Arc<Users>) -> DfSessionService {
         DfSessionService {
             session_context: Mutex::new(ctx),
+            users,
         }
     }
 }
 
 #[async_trait]
 impl SimpleQueryHandler for DfSessionService {
-    async fn do_query<'a, C>(&self, _client: &C, query: &'a str) -> PgWireResult<Vec<Response<'a>>>
+    async fn do_query<'a, C>(&self, client: &C, query: &'a str) -> PgWireResult<Vec<Response<'a>>>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
         let ctx = self.session_context.lock().await;
+        self.users.check_access(client, &ctx, query)?;
         let df = ctx
             .execute(query)
             .await
             .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
 
-        let resp = arrow_to_pg_encoder(df).await?;
+        let resp = arrow_to_pg_encoder(df, None).await?;
         Ok(vec![Response::Query(resp)])
     }
 }
 
-fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
+pub(crate) fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
     Ok(match df_type {
         DataType::Null => Type::UNKNOWN,
         DataType::Boolean => Type::BOOL,
@@ -148,26 +249,35 @@ fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
     })
 }
 
-async fn arrow_to_pg_encoder<'a>(
+/// Describes the given schema as row fields. If no result column `format` was requested by the
+/// client (simple query protocol), only binary columns are sent in binary format.
+pub(crate) fn into_pg_fields(
+    schema: &Schema,
+    format: Option<&Format>,
+) -> PgWireResult<Vec<FieldInfo>> {
+    schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let pg_type = into_pg_type(f.data_type())?;
+            let format = match format {
+                Some(format) => format.format_for(idx),
+                None if matches!(f.data_type(), DataType::Binary) => FieldFormat::Binary,
+                None => FieldFormat::Text,
+            };
+
+            Ok(FieldInfo::new(f.name().into(), None, None, pg_type, format))
+        })
+        .collect()
+}
+
+pub(crate) async fn arrow_to_pg_encoder<'a>(
     recordbatch_stream: SendableRecordBatchStream,
+    format: Option<&Format>,
 ) -> PgWireResult<QueryResponse<'a>> {
     let schema = recordbatch_stream.schema();
-    let fields = Arc::new(
-        schema
-            .fields()
-            .iter()
-            .map(|f| {
-                let pg_type = into_pg_type(f.data_type())?;
-                let format = if matches!(f.data_type(), DataType::Binary) {
-                    FieldFormat::Binary
-                } else {
-                    FieldFormat::Text
-                };
-
-                Ok(FieldInfo::new(f.name().into(), None, None, pg_type, format))
-            })
-            .collect::<PgWireResult<Vec<FieldInfo>>>()?,
-    );
+    let fields = Arc::new(into_pg_fields(&schema, format)?);
 
     let fields_ref = fields.clone();
     let pg_row_stream = recordbatch_stream
@@ -323,3 +433,146 @@ fn encode_value(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::options::UserOptions;
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
+    use pgwire::api::auth::md5pass::hash_md5_password;
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const PROTOCOL_VERSION_3: i32 = 196608;
+    const AUTHENTICATION_OK: i32 = 0;
+    const AUTHENTICATION_MD5_PASSWORD: i32 = 5;
+
+    async fn write_message(stream: &mut TcpStream, tag: Option<u8>, body: &[u8]) {
+        let mut message = BytesMut::new();
+        if let Some(tag) = tag {
+            message.put_u8(tag);
+        }
+        message.put_i32(body.len() as i32 + 4);
+        message.put_slice(body);
+        stream.write_all(&message).await.unwrap();
+    }
+
+    async fn read_message(stream: &mut TcpStream) -> (u8, Bytes) {
+        let tag = stream.read_u8().await.unwrap();
+        let len = stream.read_i32().await.unwrap();
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).await.unwrap();
+        (tag, Bytes::from(body))
+    }
+
+    /// Connects as `user` without TLS and sends the startup message.
+    async fn start_login(
+        factory: &HandlerFactory,
+        listener: &TcpListener,
+        user: &str,
+    ) -> TcpStream {
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (incoming_socket, addr) = listener.accept().await.unwrap();
+        factory.spawn_connection(incoming_socket, addr);
+
+        let mut startup = BytesMut::new();
+        startup.put_i32(PROTOCOL_VERSION_3);
+        for value in ["user", user, "database", "restate", ""] {
+            startup.put_slice(value.as_bytes());
+            startup.put_u8(0);
+        }
+        write_message(&mut stream, None, &startup).await;
+        stream
+    }
+
+    /// Connects as `user` and returns the connection together with the salt the server asked to
+    /// hash the password with.
+    async fn start_md5_login(
+        factory: &HandlerFactory,
+        listener: &TcpListener,
+        user: &str,
+    ) -> (TcpStream, Bytes) {
+        let mut stream = start_login(factory, listener, user).await;
+
+        let (tag, mut body) = read_message(&mut stream).await;
+        assert_eq!(b'R', tag);
+        assert_eq!(AUTHENTICATION_MD5_PASSWORD, body.get_i32());
+        (stream, body)
+    }
+
+    /// Sends the hashed password and returns whether the login succeeded.
+    async fn finish_md5_login(
+        mut stream: TcpStream,
+        user: &str,
+        password: &str,
+        salt: &[u8],
+    ) -> bool {
+        let mut hashed_password =
+            hash_md5_password(&user.to_owned(), &password.to_owned(), salt).into_bytes();
+        hashed_password.push(0);
+        write_message(&mut stream, Some(b'p'), &hashed_password).await;
+
+        let (tag, mut body) = read_message(&mut stream).await;
+        tag == b'R' && body.get_i32() == AUTHENTICATION_OK
+    }
+
+    #[tokio::test]
+    async fn concurrent_md5_logins() {
+        let users = HashMap::from([
+            (
+                "alice".to_owned(),
+                UserOptions {
+                    password: "alice-password".to_owned(),
+                    allowed_tables: None,
+                },
+            ),
+            (
+                "bob".to_owned(),
+                UserOptions {
+                    password: "bob-password".to_owned(),
+                    allowed_tables: None,
+                },
+            ),
+        ]);
+        let factory = HandlerFactory::new(
+            QueryContext::default(),
+            None,
+            AuthenticationMethod::Md5,
+            Users::new(AuthenticationMethod::Md5, users),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        // Both logins are started before any of them is finished
+        let (alice, alice_salt) = start_md5_login(&factory, &listener, "alice").await;
+        let (bob, bob_salt) = start_md5_login(&factory, &listener, "bob").await;
+
+        assert!(finish_md5_login(alice, "alice", "alice-password", &alice_salt).await);
+        assert!(finish_md5_login(bob, "bob", "bob-password", &bob_salt).await);
+    }
+    #[tokio::test]
+    async fn cleartext_login_requires_tls() {
+        let users = HashMap::from([(
+            "alice".to_owned(),
+            UserOptions {
+                password: "alice-password".to_owned(),
+                allowed_tables: None,
+            },
+        )]);
+        let factory = HandlerFactory::new(
+            QueryContext::default(),
+            None,
+            AuthenticationMethod::Cleartext,
+            Users::new(AuthenticationMethod::Cleartext, users),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        // The server fails the login instead of asking for the password
+        let mut stream = start_login(&factory, &listener, "alice").await;
+        let (tag, _) = read_message(&mut stream).await;
+        assert_eq!(b'E', tag);
+    }
+}
@@ -8,11 +8,15 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::auth::Users;
+use crate::options::{AuthenticationMethod, TlsOptions, UserOptions};
 use crate::pgwire_server::HandlerFactory;
+use crate::tls::{load_tls_acceptor, TlsError};
 use codederror::CodedError;
 use restate_core::cancellation_watcher;
 use restate_storage_query_datafusion::context::QueryContext;
 
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
@@ -28,6 +32,14 @@ pub enum Error {
     )]
     #[code(unknown)]
     AddrInUse(SocketAddr),
+    #[error(
+        "'worker.storage_query_postgres.authentication' is 'cleartext', which requires 'worker.storage_query_postgres.tls' to be configured"
+    )]
+    #[code(unknown)]
+    CleartextWithoutTls,
+    #[error("failed loading 'worker.storage_query_postgres.tls': {0}")]
+    #[code(unknown)]
+    Tls(#[from] TlsError),
     #[error("error: {0:?}")]
     #[code(unknown)]
     Other(#[from] GenericError),
@@ -35,6 +47,9 @@ pub enum Error {
 
 pub struct PostgresQueryService {
     pub bind_address: SocketAddr,
+    pub tls: Option<TlsOptions>,
+    pub authentication: AuthenticationMethod,
+    pub users: HashMap<String, UserOptions>,
     pub query_context: QueryContext,
 }
 
@@ -42,9 +57,23 @@ impl PostgresQueryService {
     pub async fn run(self) -> anyhow::Result<()> {
         let PostgresQueryService {
             bind_address,
+            tls,
+            authentication,
+            users,
             query_context,
         } = self;
 
+        // Passwords must not be sent in cleartext over unencrypted connections
+        if authentication == AuthenticationMethod::Cleartext && tls.is_none() {
+            return Err(Error::CleartextWithoutTls.into());
+        }
+
+        let tls_acceptor = tls
+            .as_ref()
+            .map(load_tls_acceptor)
+            .transpose()
+            .map_err(Error::Tls)?;
+
         let listener = TcpListener::bind(&bind_address).await.map_err(|e| {
             if e.kind() == ErrorKind::AddrInUse {
                 Error::AddrInUse(bind_address)
@@ -56,7 +85,12 @@ impl PostgresQueryService {
         let shutdown = cancellation_watcher();
         tokio::pin!(shutdown);
 
-        let factory = HandlerFactory::new(query_context);
+        let factory = HandlerFactory::new(
+            query_context,
+            tls_acceptor,
+            authentication,
+            Users::new(authentication, users),
+        );
         loop {
             select! {
                 incoming_socket = listener.accept() => {
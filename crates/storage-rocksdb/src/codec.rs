@@ -13,9 +13,10 @@ use bytes::{Buf, BufMut, Bytes};
 use bytestring::ByteString;
 use prost::encoding::encoded_len_varint;
 use prost::Message;
-use restate_storage_api::deduplication_table::SequenceNumberSource;
+use restate_storage_api::deduplication_table::{EpochSequenceNumber, SequenceNumberSource};
 use restate_storage_api::StorageError;
 use restate_types::identifiers::InvocationUuid;
+use restate_types::PlainNodeId;
 
 pub trait Codec: Sized {
     fn encode<B: BufMut>(&self, target: &mut B);
@@ -201,6 +202,11 @@ impl Codec for SequenceNumberSource {
                 target.put_u8(1);
                 Codec::encode(i, target)
             }
+            SequenceNumberSource::IngressNode(node_id) => {
+                target.put_u8(2);
+                Codec::encode(&u32::from(*node_id), target)
+            }
+            SequenceNumberSource::SelfProposal => target.put_u8(3),
         }
     }
 
@@ -208,6 +214,10 @@ impl Codec for SequenceNumberSource {
         Ok(match source.get_u8() {
             0 => SequenceNumberSource::Partition(Codec::decode(source)?),
             1 => SequenceNumberSource::Ingress(Codec::decode(source)?),
+            2 => SequenceNumberSource::IngressNode(PlainNodeId::from(<u32 as Codec>::decode(
+                source,
+            )?)),
+            3 => SequenceNumberSource::SelfProposal,
             i => {
                 return Err(StorageError::Generic(anyhow!(
                     "Unexpected wrong discriminator for SequenceNumberSource: {}",
@@ -221,10 +231,44 @@ impl Codec for SequenceNumberSource {
         1 + match self {
             SequenceNumberSource::Partition(p) => Codec::serialized_length(p),
             SequenceNumberSource::Ingress(i) => Codec::serialized_length(i),
+            SequenceNumberSource::IngressNode(node_id) => {
+                Codec::serialized_length(&u32::from(*node_id))
+            }
+            SequenceNumberSource::SelfProposal => 0,
         }
     }
 }
 
+/// Encodes the sequence number before the epoch. Values which only consist of a sequence number
+/// have been written before producers had epochs and are decoded with epoch `0`.
+impl Codec for EpochSequenceNumber {
+    fn encode<B: BufMut>(&self, target: &mut B) {
+        target.put_u64(self.sequence_number);
+        target.put_u64(self.epoch);
+    }
+
+    fn decode<B: Buf>(source: &mut B) -> crate::Result<Self> {
+        if source.remaining() < 8 {
+            return Err(StorageError::DataIntegrityError);
+        }
+        let sequence_number = source.get_u64();
+        let epoch = if source.remaining() >= 8 {
+            source.get_u64()
+        } else {
+            0
+        };
+
+        Ok(EpochSequenceNumber {
+            epoch,
+            sequence_number,
+        })
+    }
+
+    fn serialized_length(&self) -> usize {
+        16
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,4 +314,54 @@ mod tests {
 
         assert_eq!(uuid, got);
     }
+
+    #[test]
+    fn epoch_sequence_number_roundtrip() {
+        let sequence_number = EpochSequenceNumber::new(3, 42);
+
+        let mut buf = BytesMut::new();
+        sequence_number.encode(&mut buf);
+
+        let mut got_bytes = buf.freeze();
+
+        assert_eq!(got_bytes.len(), sequence_number.serialized_length());
+        assert_eq!(
+            sequence_number,
+            EpochSequenceNumber::decode(&mut got_bytes).expect("deserialization should work")
+        );
+    }
+
+    #[test]
+    fn sequence_number_without_epoch_decodes_with_initial_epoch() {
+        let mut buf = BytesMut::new();
+        Codec::encode(&42u64, &mut buf);
+
+        let mut got_bytes = buf.freeze();
+
+        assert_eq!(
+            EpochSequenceNumber::new(0, 42),
+            EpochSequenceNumber::decode(&mut got_bytes).expect("deserialization should work")
+        );
+    }
+
+    #[test]
+    fn sequence_number_source_roundtrip() {
+        for source in [
+            SequenceNumberSource::Partition(7),
+            SequenceNumberSource::Ingress("kafka".into()),
+            SequenceNumberSource::IngressNode(PlainNodeId::from(3)),
+            SequenceNumberSource::SelfProposal,
+        ] {
+            let mut buf = BytesMut::new();
+            source.encode(&mut buf);
+
+            let mut got_bytes = buf.freeze();
+
+            assert_eq!(got_bytes.len(), source.serialized_length());
+            assert_eq!(
+                source,
+                SequenceNumberSource::decode(&mut got_bytes).expect("deserialization should work")
+            );
+        }
+    }
 }
@@ -8,15 +8,22 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::codec::Codec;
 use crate::keys::{define_table_key, TableKey};
+use crate::owned_iter::OwnedIterator;
 use crate::TableKind::Deduplication;
-use crate::{RocksDBTransaction, StorageAccess, TableScan, TableScanIterationDecision};
+use crate::{
+    RocksDBStorage, RocksDBTransaction, StorageAccess, TableScan, TableScanIterationDecision,
+};
 use futures::Stream;
 use futures_util::stream;
-use restate_storage_api::deduplication_table::{DeduplicationTable, SequenceNumberSource};
+use restate_storage_api::deduplication_table::{
+    DeduplicationTable, EpochSequenceNumber, SequenceNumberSource,
+};
 use restate_storage_api::{Result, StorageError};
 use restate_types::identifiers::PartitionId;
 use std::io::Cursor;
+use std::ops::RangeInclusive;
 
 define_table_key!(
     Deduplication,
@@ -28,19 +35,15 @@ impl<'a> DeduplicationTable for RocksDBTransaction<'a> {
         &mut self,
         partition_id: PartitionId,
         source: SequenceNumberSource,
-    ) -> Result<Option<u64>> {
+    ) -> Result<Option<EpochSequenceNumber>> {
         let key = DeduplicationKey::default()
             .partition_id(partition_id)
             .source(source);
 
         self.get_blocking(key, move |_k, maybe_sequence_number_slice| {
-            let maybe_sequence_number = maybe_sequence_number_slice.map(|slice| {
-                let mut buf = [0u8; 8];
-                buf.copy_from_slice(slice.as_ref());
-                u64::from_be_bytes(buf)
-            });
-
-            Ok(maybe_sequence_number)
+            maybe_sequence_number_slice
+                .map(|mut slice| EpochSequenceNumber::decode(&mut slice))
+                .transpose()
         })
     }
 
@@ -48,7 +51,7 @@ impl<'a> DeduplicationTable for RocksDBTransaction<'a> {
         &mut self,
         partition_id: PartitionId,
         source: SequenceNumberSource,
-        sequence_number: u64,
+        sequence_number: EpochSequenceNumber,
     ) {
         let key = DeduplicationKey::default()
             .partition_id(partition_id)
@@ -59,7 +62,7 @@ impl<'a> DeduplicationTable for RocksDBTransaction<'a> {
     fn get_all_sequence_numbers(
         &mut self,
         partition_id: PartitionId,
-    ) -> impl Stream<Item = Result<(SequenceNumberSource, u64)>> + Send {
+    ) -> impl Stream<Item = Result<(SequenceNumberSource, EpochSequenceNumber)>> + Send {
         stream::iter(self.for_each_key_value_in_place(
             TableScan::Partition::<DeduplicationKey>(partition_id),
             move |k, v| {
@@ -68,10 +71,9 @@ impl<'a> DeduplicationTable for RocksDBTransaction<'a> {
 
                 let res = if let Ok(Some(source)) = key {
                     // read out the value
-                    let mut buf = [0u8; 8];
-                    buf.copy_from_slice(v);
-                    let sequence_number = u64::from_be_bytes(buf);
-                    Ok((source, sequence_number))
+                    let mut v = v;
+                    EpochSequenceNumber::decode(&mut v)
+                        .map(|sequence_number| (source, sequence_number))
                 } else {
                     Err(StorageError::DataIntegrityError)
                 };
@@ -80,3 +82,28 @@ impl<'a> DeduplicationTable for RocksDBTransaction<'a> {
         ))
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct OwnedDeduplicationRow {
+    pub partition_id: PartitionId,
+    pub source: SequenceNumberSource,
+    pub sequence_number: EpochSequenceNumber,
+}
+
+impl RocksDBStorage {
+    pub fn all_sequence_numbers(
+        &self,
+        range: RangeInclusive<PartitionId>,
+    ) -> impl Iterator<Item = OwnedDeduplicationRow> + '_ {
+        let iter = self.iterator_from(TableScan::PartitionIdRange::<DeduplicationKey>(range));
+        OwnedIterator::new(iter).map(|(mut key, mut value)| {
+            let key = DeduplicationKey::deserialize_from(&mut key).unwrap();
+            let sequence_number = EpochSequenceNumber::decode(&mut value).unwrap();
+            OwnedDeduplicationRow {
+                partition_id: key.partition_id.unwrap(),
+                source: key.source.unwrap(),
+                sequence_number,
+            }
+        })
+    }
+}
@@ -12,7 +12,7 @@ use crate::codec::ProtoValue;
 use crate::keys::{define_table_key, TableKey};
 use crate::owned_iter::OwnedIterator;
 use crate::TableScan::PartitionKeyRange;
-use crate::{RocksDBStorage, TableKind, TableScanIterationDecision};
+use crate::{RocksDBStorage, TableKind, TableScan, TableScanIterationDecision};
 use crate::{RocksDBTransaction, StorageAccess};
 use futures::Stream;
 use futures_util::stream;
@@ -184,7 +184,24 @@ impl RocksDBStorage {
         &self,
         range: RangeInclusive<PartitionKey>,
     ) -> impl Iterator<Item = OwnedInvocationStatusRow> + '_ {
-        let iter = self.iterator_from(PartitionKeyRange::<InvocationStatusKey>(range));
+        self.invocation_status_rows(PartitionKeyRange::<InvocationStatusKey>(range))
+    }
+
+    /// Returns the status of the given invocation, if any.
+    pub fn invocation_status_of(
+        &self,
+        invocation_id: &InvocationId,
+    ) -> impl Iterator<Item = OwnedInvocationStatusRow> + '_ {
+        self.invocation_status_rows(TableScan::KeyPrefix(write_invocation_status_key(
+            invocation_id,
+        )))
+    }
+
+    fn invocation_status_rows(
+        &self,
+        scan: TableScan<InvocationStatusKey>,
+    ) -> impl Iterator<Item = OwnedInvocationStatusRow> + '_ {
+        let iter = self.iterator_from(scan);
         OwnedIterator::new(iter).map(|(mut key, value)| {
             let state_key = InvocationStatusKey::deserialize_from(&mut key).unwrap();
             let state_value = storage::v1::InvocationStatus::decode(value).unwrap();
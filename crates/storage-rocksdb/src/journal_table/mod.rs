@@ -184,7 +184,26 @@ impl RocksDBStorage {
         &self,
         range: RangeInclusive<PartitionKey>,
     ) -> impl Iterator<Item = OwnedJournalRow> + '_ {
-        let iter = self.iterator_from(PartitionKeyRange::<JournalKey>(range));
+        self.journal_rows(PartitionKeyRange::<JournalKey>(range))
+    }
+
+    /// Returns the journal entries of the given invocation.
+    pub fn journal_of(
+        &self,
+        invocation_id: &InvocationId,
+    ) -> impl Iterator<Item = OwnedJournalRow> + '_ {
+        self.journal_rows(TableScan::KeyPrefix(
+            JournalKey::default()
+                .partition_key(invocation_id.partition_key())
+                .invocation_uuid(invocation_id.invocation_uuid()),
+        ))
+    }
+
+    fn journal_rows(
+        &self,
+        scan: TableScan<JournalKey>,
+    ) -> impl Iterator<Item = OwnedJournalRow> + '_ {
+        let iter = self.iterator_from(scan);
         OwnedIterator::new(iter).map(|(mut key, value)| {
             let journal_key = JournalKey::deserialize_from(&mut key)
                 .expect("journal key must deserialize into JournalKey");
@@ -11,6 +11,7 @@
 pub mod codec;
 pub mod deduplication_table;
 pub mod fsm_table;
+pub mod idempotency_table;
 pub mod inbox_table;
 pub mod invocation_status_table;
 pub mod journal_table;
@@ -19,6 +20,7 @@ pub mod outbox_table;
 mod owned_iter;
 pub mod scan;
 pub mod service_status_table;
+pub mod snapshot;
 pub mod state_table;
 pub mod timer_table;
 mod writer;
@@ -28,8 +30,8 @@ use crate::keys::TableKey;
 use crate::scan::{PhysicalScan, TableScan};
 use crate::writer::{Writer, WriterHandle};
 use crate::TableKind::{
-    Deduplication, Inbox, InvocationStatus, Journal, Outbox, PartitionStateMachine, ServiceStatus,
-    State, Timers,
+    Deduplication, Idempotency, Inbox, InvocationStatus, Journal, Outbox, PartitionStateMachine,
+    ServiceStatus, State, Timers,
 };
 use bytes::BytesMut;
 use codederror::CodedError;
@@ -67,6 +69,7 @@ const DEDUP_TABLE_NAME: &str = "dedup";
 const FSM_TABLE_NAME: &str = "fsm";
 const TIMERS_TABLE_NAME: &str = "timers";
 const JOURNAL_TABLE_NAME: &str = "journal";
+const IDEMPOTENCY_TABLE_NAME: &str = "idempotency";
 
 type StorageFormatVersion = u32;
 
@@ -99,6 +102,7 @@ const fn cf_name(kind: TableKind) -> &'static str {
         PartitionStateMachine => FSM_TABLE_NAME,
         Timers => TIMERS_TABLE_NAME,
         Journal => JOURNAL_TABLE_NAME,
+        Idempotency => IDEMPOTENCY_TABLE_NAME,
     }
 }
 
@@ -113,6 +117,7 @@ pub enum TableKind {
     PartitionStateMachine,
     Timers,
     Journal,
+    Idempotency,
 }
 
 impl TableKind {
@@ -131,6 +136,7 @@ impl TableKind {
             PartitionStateMachine,
             Timers,
             Journal,
+            Idempotency,
         ];
         VARIANTS.iter()
     }
@@ -416,6 +422,10 @@ impl RocksDBStorage {
                 cf_name(Journal),
                 cf_options(&opts, cache.clone()),
             ),
+            rocksdb::ColumnFamilyDescriptor::new(
+                cf_name(Idempotency),
+                cf_options(&opts, cache.clone()),
+            ),
             //
             // keyed by partition id + suffix
             //
@@ -10,6 +10,7 @@
 
 use crate::codec::ProtoValue;
 use crate::keys::{define_table_key, TableKey};
+use crate::owned_iter::OwnedIterator;
 use crate::TableKind::Outbox;
 use crate::{RocksDBStorage, RocksDBTransaction, StorageAccess, TableScan};
 
@@ -19,7 +20,7 @@ use restate_storage_api::{Result, StorageError};
 use restate_storage_proto::storage;
 use restate_types::identifiers::PartitionId;
 use std::io::Cursor;
-use std::ops::Range;
+use std::ops::{Range, RangeInclusive};
 
 define_table_key!(
     Outbox,
@@ -158,6 +159,31 @@ impl<'a> OutboxTable for RocksDBTransaction<'a> {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct OwnedOutboxRow {
+    pub partition_id: PartitionId,
+    pub sequence_number: u64,
+    pub outbox_message: OutboxMessage,
+}
+
+impl RocksDBStorage {
+    pub fn all_outboxes(
+        &self,
+        range: RangeInclusive<PartitionId>,
+    ) -> impl Iterator<Item = OwnedOutboxRow> + '_ {
+        let iter = self.iterator_from(TableScan::PartitionIdRange::<OutboxKey>(range));
+        OwnedIterator::new(iter).map(|(mut key, value)| {
+            let outbox_key = OutboxKey::deserialize_from(&mut key).unwrap();
+            let outbox_message = decode_value(&value).unwrap();
+            OwnedOutboxRow {
+                partition_id: outbox_key.partition_id.unwrap(),
+                sequence_number: outbox_key.message_index.unwrap(),
+                outbox_message,
+            }
+        })
+    }
+}
+
 fn decode_key_value(k: &[u8], v: &[u8]) -> crate::Result<(u64, OutboxMessage)> {
     // decode key
     let key = OutboxKey::deserialize_from(&mut Cursor::new(k))?;
@@ -9,7 +9,9 @@
 // by the Apache License, Version 2.0.
 
 use crate::keys::TableKey;
-use crate::scan::TableScan::{KeyPrefix, KeyRangeInclusive, Partition, PartitionKeyRange};
+use crate::scan::TableScan::{
+    KeyPrefix, KeyRangeInclusive, Partition, PartitionIdRange, PartitionKeyRange,
+};
 use crate::TableKind;
 use bytes::BytesMut;
 use restate_types::identifiers::{PartitionId, PartitionKey};
@@ -20,6 +22,8 @@ pub enum TableScan<K> {
     Partition(PartitionId),
     /// Scan an inclusive key-range.
     PartitionKeyRange(RangeInclusive<PartitionKey>),
+    /// Scan an inclusive range of partitions of a given table.
+    PartitionIdRange(RangeInclusive<PartitionId>),
     /// Key Prefix
     KeyPrefix(K),
     /// Inclusive Key Range
@@ -50,7 +54,7 @@ impl<K: TableKey> From<TableScan<K>> for PhysicalScan {
                 let prefix = BytesMut::from(&buf[..]);
                 PhysicalScan::Prefix(K::table(), prefix)
             }
-            PartitionKeyRange(range) => {
+            PartitionKeyRange(range) | PartitionIdRange(range) => {
                 let (start, end) = (range.start(), range.end());
                 let start_bytes = start.to_be_bytes();
                 match end.checked_add(1) {
@@ -12,7 +12,7 @@ use crate::codec::ProtoValue;
 use crate::keys::{define_table_key, TableKey};
 use crate::owned_iter::OwnedIterator;
 use crate::TableScan::PartitionKeyRange;
-use crate::{RocksDBStorage, TableKind};
+use crate::{RocksDBStorage, TableKind, TableScan};
 use crate::{RocksDBTransaction, StorageAccess};
 use bytes::Bytes;
 use bytestring::ByteString;
@@ -130,7 +130,22 @@ impl RocksDBStorage {
         &self,
         range: RangeInclusive<PartitionKey>,
     ) -> impl Iterator<Item = OwnedServiceStatusRow> + '_ {
-        let iter = self.iterator_from(PartitionKeyRange::<ServiceStatusKey>(range));
+        self.service_status_rows(PartitionKeyRange::<ServiceStatusKey>(range))
+    }
+
+    /// Returns the status of the given service instance, if any.
+    pub fn service_status_of(
+        &self,
+        service_id: &ServiceId,
+    ) -> impl Iterator<Item = OwnedServiceStatusRow> + '_ {
+        self.service_status_rows(TableScan::KeyPrefix(write_status_key(service_id)))
+    }
+
+    fn service_status_rows(
+        &self,
+        scan: TableScan<ServiceStatusKey>,
+    ) -> impl Iterator<Item = OwnedServiceStatusRow> + '_ {
+        let iter = self.iterator_from(scan);
         OwnedIterator::new(iter).map(|(mut key, value)| {
             let state_key = ServiceStatusKey::deserialize_from(&mut key).unwrap();
             let state_value = storage::v1::ServiceStatus::decode(value).unwrap();
@@ -0,0 +1,236 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Snapshots of the tables of a single partition.
+//!
+//! A snapshot is a directory which contains one SST file per non-empty table and a
+//! `metadata.json` file describing the partition and the log position the snapshot corresponds
+//! to. Snapshots are taken from a consistent RocksDB snapshot and are ingested as external SST
+//! files when bootstrapping a partition.
+
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use rocksdb::{ReadOptions, SnapshotWithThreadMode, SstFileWriter};
+
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::logs::Lsn;
+
+use crate::TableKind::{
+    Deduplication, Idempotency, Inbox, InvocationStatus, Journal, Outbox, PartitionStateMachine,
+    ServiceStatus, State, Timers,
+};
+use crate::{
+    DBIterator, RocksDBStorage, StorageFormatVersion, TableKind, WriteBatch, DB,
+    STORAGE_FORMAT_VERSION,
+};
+
+const METADATA_FILE_NAME: &str = "metadata.json";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("rocksdb error: {0}")]
+    RocksDb(#[from] rocksdb::Error),
+    #[error("snapshot I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid snapshot metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+    #[error("snapshot contains incompatible storage format version '{0}'; supported version is '{STORAGE_FORMAT_VERSION}'")]
+    IncompatibleStorageFormat(StorageFormatVersion),
+    #[error("snapshot contains unknown table '{0}'")]
+    UnknownTable(String),
+    #[error("partition '{0}' already contains data, cannot bootstrap it from a snapshot")]
+    PartitionNotEmpty(PartitionId),
+}
+
+/// Describes the contents of a partition snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PartitionSnapshotMetadata {
+    pub storage_format_version: StorageFormatVersion,
+    pub partition_id: PartitionId,
+    pub key_range: RangeInclusive<PartitionKey>,
+    /// LSN of the last log record whose effects are contained in the snapshot. Partition
+    /// processors bootstrapped from the snapshot continue reading the log after this LSN.
+    pub applied_lsn: Lsn,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotFile {
+    /// Name of the column family the file belongs to.
+    pub table: String,
+    /// Name of the SST file relative to the snapshot directory.
+    pub file_name: String,
+}
+
+impl RocksDBStorage {
+    /// Exports the tables of the given partition into the directory `path`. The caller must make
+    /// sure that `applied_lsn` corresponds to the state of the partition at the time of calling
+    /// this method, e.g. by calling it from the partition processor in between transactions.
+    ///
+    /// This method performs blocking I/O.
+    pub fn export_partition_snapshot(
+        &self,
+        partition_id: PartitionId,
+        key_range: RangeInclusive<PartitionKey>,
+        applied_lsn: Lsn,
+        path: impl AsRef<Path>,
+    ) -> Result<PartitionSnapshotMetadata, SnapshotError> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+
+        let snapshot = self.db.snapshot();
+        let mut files = Vec::new();
+
+        for table in TableKind::all() {
+            let mut iterator =
+                self.partition_iterator(*table, partition_id, &key_range, Some(&snapshot));
+            iterator.seek_to_first();
+            if !iterator.valid() {
+                iterator.status()?;
+                continue;
+            }
+
+            let file_name = format!("{}.sst", table.cf_name());
+            let sst_options = rocksdb::Options::default();
+            let mut writer = SstFileWriter::create(&sst_options);
+            writer.open(path.join(&file_name))?;
+
+            while let Some((key, value)) = iterator.item() {
+                writer.put(key, value)?;
+                iterator.next();
+            }
+            iterator.status()?;
+            writer.finish()?;
+
+            files.push(SnapshotFile {
+                table: table.cf_name().to_owned(),
+                file_name,
+            });
+        }
+
+        let metadata = PartitionSnapshotMetadata {
+            storage_format_version: STORAGE_FORMAT_VERSION,
+            partition_id,
+            key_range,
+            applied_lsn,
+            files,
+        };
+        std::fs::write(
+            path.join(METADATA_FILE_NAME),
+            serde_json::to_vec_pretty(&metadata)?,
+        )?;
+
+        Ok(metadata)
+    }
+
+    /// Reads the metadata of the snapshot stored in the directory `path`.
+    pub fn read_partition_snapshot_metadata(
+        path: impl AsRef<Path>,
+    ) -> Result<PartitionSnapshotMetadata, SnapshotError> {
+        let metadata: PartitionSnapshotMetadata =
+            serde_json::from_slice(&std::fs::read(path.as_ref().join(METADATA_FILE_NAME))?)?;
+
+        if metadata.storage_format_version != STORAGE_FORMAT_VERSION {
+            return Err(SnapshotError::IncompatibleStorageFormat(
+                metadata.storage_format_version,
+            ));
+        }
+
+        Ok(metadata)
+    }
+
+    /// Ingests the snapshot stored in the directory `path`. The partition of the snapshot must
+    /// not contain any data yet.
+    ///
+    /// This method performs blocking I/O.
+    pub fn import_partition_snapshot(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<PartitionSnapshotMetadata, SnapshotError> {
+        let path = path.as_ref();
+        let metadata = Self::read_partition_snapshot_metadata(path)?;
+
+        for table in TableKind::all() {
+            let mut iterator =
+                self.partition_iterator(*table, metadata.partition_id, &metadata.key_range, None);
+            iterator.seek_to_first();
+            if iterator.valid() {
+                return Err(SnapshotError::PartitionNotEmpty(metadata.partition_id));
+            }
+            iterator.status()?;
+        }
+
+        for file in &metadata.files {
+            let table = TableKind::all()
+                .find(|table| table.cf_name() == file.table)
+                .ok_or_else(|| SnapshotError::UnknownTable(file.table.clone()))?;
+
+            self.db.ingest_external_file_cf(
+                self.table_handle(*table),
+                vec![path.join(&file.file_name)],
+            )?;
+        }
+
+        Ok(metadata)
+    }
+
+    /// Removes all data of the given partition, e.g. to replace it with a newer snapshot.
+    ///
+    /// This method performs blocking I/O.
+    pub fn clear_partition(
+        &self,
+        partition_id: PartitionId,
+        key_range: RangeInclusive<PartitionKey>,
+    ) -> Result<(), SnapshotError> {
+        let mut batch = WriteBatch::default();
+        for table in TableKind::all() {
+            let mut iterator = self.partition_iterator(*table, partition_id, &key_range, None);
+            iterator.seek_to_first();
+            while let Some(key) = iterator.key() {
+                batch.delete_cf(self.table_handle(*table), key);
+                iterator.next();
+            }
+            iterator.status()?;
+        }
+        self.db.write(batch)?;
+
+        Ok(())
+    }
+
+    /// Iterates over all keys of the given partition in the table.
+    fn partition_iterator(
+        &self,
+        table: TableKind,
+        partition_id: PartitionId,
+        key_range: &RangeInclusive<PartitionKey>,
+        snapshot: Option<&SnapshotWithThreadMode<DB>>,
+    ) -> DBIterator<'_> {
+        let (start, end) = match table {
+            // keyed by partition key
+            State | InvocationStatus | ServiceStatus | Inbox | Journal | Idempotency => {
+                (*key_range.start(), *key_range.end())
+            }
+            // keyed by partition id
+            Outbox | Timers | Deduplication | PartitionStateMachine => (partition_id, partition_id),
+        };
+
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_lower_bound(start.to_be_bytes());
+        if let Some(end) = end.checked_add(1) {
+            opts.set_iterate_upper_bound(end.to_be_bytes());
+        }
+        if let Some(snapshot) = snapshot {
+            opts.set_snapshot(snapshot);
+        }
+
+        self.db.raw_iterator_cf_opt(self.table_handle(table), opts)
+    }
+}
@@ -194,7 +194,21 @@ impl RocksDBStorage {
         &self,
         range: RangeInclusive<PartitionKey>,
     ) -> impl Iterator<Item = OwnedStateRow> + '_ {
-        let iter = self.iterator_from(TableScan::PartitionKeyRange::<StateKey>(range));
+        self.state_rows(TableScan::PartitionKeyRange::<StateKey>(range))
+    }
+
+    /// Returns the state entries of the given service instance.
+    pub fn states_of(&self, service_id: &ServiceId) -> impl Iterator<Item = OwnedStateRow> + '_ {
+        self.state_rows(TableScan::KeyPrefix(
+            StateKey::default()
+                .partition_key(service_id.partition_key())
+                .service_name(service_id.service_name.clone())
+                .service_key(service_id.key.clone()),
+        ))
+    }
+
+    fn state_rows(&self, scan: TableScan<StateKey>) -> impl Iterator<Item = OwnedStateRow> + '_ {
+        let iter = self.iterator_from(scan);
         OwnedIterator::new(iter).map(|(mut key, value)| {
             let row_key = StateKey::deserialize_from(&mut key).unwrap();
             OwnedStateRow {
@@ -10,6 +10,7 @@
 
 use crate::codec::ProtoValue;
 use crate::keys::{define_table_key, TableKey};
+use crate::owned_iter::OwnedIterator;
 use crate::TableKind::Timers;
 use crate::TableScanIterationDecision::Emit;
 use crate::{RocksDBStorage, RocksDBTransaction, StorageAccess};
@@ -21,6 +22,7 @@ use restate_storage_api::timer_table::{Timer, TimerKey, TimerTable};
 use restate_storage_api::{Result, StorageError};
 use restate_storage_proto::storage;
 use restate_types::identifiers::{InvocationUuid, PartitionId};
+use std::ops::RangeInclusive;
 
 define_table_key!(
     Timers,
@@ -172,6 +174,35 @@ impl<'a> TimerTable for RocksDBTransaction<'a> {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct OwnedTimerRow {
+    pub partition_id: PartitionId,
+    pub timer_key: TimerKey,
+    pub timer: Timer,
+}
+
+impl RocksDBStorage {
+    pub fn all_timers(
+        &self,
+        range: RangeInclusive<PartitionId>,
+    ) -> impl Iterator<Item = OwnedTimerRow> + '_ {
+        let iter = self.iterator_from(TableScan::PartitionIdRange::<TimersKey>(range));
+        OwnedIterator::new(iter).map(|(mut key, value)| {
+            let timers_key = TimersKey::deserialize_from(&mut key).unwrap();
+            let timer = Timer::try_from(storage::v1::Timer::decode(value).unwrap()).unwrap();
+            OwnedTimerRow {
+                partition_id: timers_key.partition_id.unwrap(),
+                timer_key: TimerKey {
+                    invocation_uuid: timers_key.invocation_id.unwrap(),
+                    journal_index: timers_key.journal_index.unwrap(),
+                    timestamp: timers_key.timestamp.unwrap(),
+                },
+                timer,
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,83 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use restate_storage_api::idempotency_table::{IdempotencyMetadata, IdempotencyTable};
+use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::{IdempotencyId, InvocationId, InvocationUuid, ServiceId};
+
+const FIXTURE_INVOCATION: InvocationUuid =
+    InvocationUuid::from_parts(1706027034946, 12345678900001);
+
+fn idempotency_id(idempotency_key: &'static str) -> IdempotencyId {
+    IdempotencyId::combine(
+        ServiceId::with_partition_key(1337, "svc-1", "key-1"),
+        "greet",
+        idempotency_key,
+    )
+}
+
+async fn populate_data<T: IdempotencyTable>(txn: &mut T) {
+    txn.put_idempotency_metadata(
+        &idempotency_id("my-key-1"),
+        IdempotencyMetadata {
+            invocation_id: InvocationId::new(1337, FIXTURE_INVOCATION),
+        },
+    )
+    .await;
+    txn.put_idempotency_metadata(
+        &idempotency_id("my-key-2"),
+        IdempotencyMetadata {
+            invocation_id: InvocationId::new(1337, FIXTURE_INVOCATION.increment_random()),
+        },
+    )
+    .await;
+}
+
+async fn verify_point_lookups<T: IdempotencyTable>(txn: &mut T) {
+    assert_eq!(
+        txn.get_idempotency_metadata(&idempotency_id("my-key-1"))
+            .await
+            .expect("should not fail"),
+        Some(IdempotencyMetadata {
+            invocation_id: InvocationId::new(1337, FIXTURE_INVOCATION),
+        })
+    );
+    assert_eq!(
+        txn.get_idempotency_metadata(&idempotency_id("unknown-key"))
+            .await
+            .expect("should not fail"),
+        None
+    );
+}
+
+async fn verify_delete<T: IdempotencyTable>(txn: &mut T) {
+    txn.delete_idempotency_metadata(&idempotency_id("my-key-1"))
+        .await;
+
+    assert_eq!(
+        txn.get_idempotency_metadata(&idempotency_id("my-key-1"))
+            .await
+            .expect("should not fail"),
+        None
+    );
+    assert!(txn
+        .get_idempotency_metadata(&idempotency_id("my-key-2"))
+        .await
+        .expect("should not fail")
+        .is_some());
+}
+
+pub(crate) async fn run_tests(mut rocksdb: RocksDBStorage) {
+    let mut txn = rocksdb.transaction();
+    populate_data(&mut txn).await;
+
+    verify_point_lookups(&mut txn).await;
+    verify_delete(&mut txn).await;
+}
@@ -23,11 +23,13 @@ use std::pin::pin;
 use tempfile::tempdir;
 use tokio_stream::StreamExt;
 
+mod idempotency_table_test;
 mod inbox_table_test;
 mod invocation_status_table_test;
 mod journal_table_test;
 mod outbox_table_test;
 mod service_status_table_test;
+mod snapshot_test;
 mod state_table_test;
 mod timer_table_test;
 
@@ -72,11 +74,21 @@ async fn test_read_write() {
     state_table_test::run_tests(rocksdb.clone()).await;
     invocation_status_table_test::run_tests(rocksdb.clone()).await;
     service_status_table_test::run_tests(rocksdb.clone()).await;
+    idempotency_table_test::run_tests(rocksdb.clone()).await;
     timer_table_test::run_tests(rocksdb).await;
 
     close.await;
 }
 
+#[tokio::test]
+async fn test_partition_snapshot() {
+    let (rocksdb, close) = storage_test_environment();
+
+    snapshot_test::run_tests(rocksdb).await;
+
+    close.await;
+}
+
 pub(crate) fn mock_service_invocation(service_id: ServiceId) -> ServiceInvocation {
     ServiceInvocation::new(
         FullInvocationId::generate(service_id),
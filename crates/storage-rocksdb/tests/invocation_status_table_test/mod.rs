@@ -11,15 +11,22 @@
 use crate::assert_stream_eq;
 use once_cell::sync::Lazy;
 use restate_storage_api::invocation_status_table::{
-    InvocationMetadata, InvocationStatus, InvocationStatusTable, JournalMetadata, StatusTimestamps,
+    CompletedInvocation, InvocationMetadata, InvocationStatus, InvocationStatusTable,
+    JournalMetadata, StatusTimestamps,
 };
 use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::errors::UserErrorCode;
 use restate_types::identifiers::{
     FullInvocationId, InvocationId, InvocationUuid, ServiceId, WithPartitionKey,
 };
-use restate_types::invocation::{ServiceInvocationSpanContext, Source};
+use restate_types::invocation::{
+    Idempotency, ResponseResult, ServiceInvocationResponseSink, ServiceInvocationSpanContext,
+    Source,
+};
 use restate_types::time::MillisSinceEpoch;
+use restate_types::GenerationalNodeId;
 use std::collections::HashSet;
+use std::time::Duration;
 
 static SERVICE_ID_1: Lazy<ServiceId> = Lazy::new(|| ServiceId::new("abc", "1"));
 static INVOCATION_ID_1: Lazy<InvocationId> = Lazy::new(|| {
@@ -45,15 +52,30 @@ static INVOCATION_ID_3: Lazy<InvocationId> = Lazy::new(|| {
     )
 });
 
+static SERVICE_ID_4: Lazy<ServiceId> = Lazy::new(|| ServiceId::new("abc", "4"));
+static INVOCATION_ID_4: Lazy<InvocationId> = Lazy::new(|| {
+    InvocationId::new(
+        SERVICE_ID_4.partition_key(),
+        InvocationUuid::from_parts(1706027034946, 12345678900004),
+    )
+});
+
 fn invoked_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
     InvocationStatus::Invoked(InvocationMetadata::new(
         service_id.into(),
         JournalMetadata::new(0, ServiceInvocationSpanContext::empty()),
         None,
         "service".into(),
-        None,
+        HashSet::from([
+            ServiceInvocationResponseSink::ingress(GenerationalNodeId::new(1, 1), 1),
+            ServiceInvocationResponseSink::ingress(GenerationalNodeId::new(1, 1), 2),
+        ]),
         StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(0)),
         Source::Ingress,
+        Some(Idempotency {
+            key: "my-key".into(),
+            retention: Duration::from_secs(60),
+        }),
     ))
 }
 
@@ -64,14 +86,28 @@ fn suspended_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
             JournalMetadata::new(0, ServiceInvocationSpanContext::empty()),
             None,
             "service".into(),
-            None,
+            HashSet::new(),
             StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(0)),
             Source::Ingress,
+            None,
         ),
         waiting_for_completed_entries: HashSet::default(),
     }
 }
 
+fn completed_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
+    InvocationStatus::Completed(CompletedInvocation {
+        service_id: service_id.into(),
+        method: "service".into(),
+        journal_metadata: JournalMetadata::new(2, ServiceInvocationSpanContext::empty()),
+        result: ResponseResult::Failure(UserErrorCode::Internal, "boom".into()),
+        timestamps: StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(1)),
+        source: Source::Ingress,
+        expiry: MillisSinceEpoch::new(2),
+        idempotency_key: Some("my-key".into()),
+    })
+}
+
 async fn populate_data<T: InvocationStatusTable>(txn: &mut T) {
     txn.put_invocation_status(&INVOCATION_ID_1, invoked_status(SERVICE_ID_1.clone()))
         .await;
@@ -81,6 +117,9 @@ async fn populate_data<T: InvocationStatusTable>(txn: &mut T) {
 
     txn.put_invocation_status(&INVOCATION_ID_3, suspended_status(SERVICE_ID_3.clone()))
         .await;
+
+    txn.put_invocation_status(&INVOCATION_ID_4, completed_status(SERVICE_ID_4.clone()))
+        .await;
 }
 
 async fn verify_point_lookups<T: InvocationStatusTable>(txn: &mut T) {
@@ -90,6 +129,13 @@ async fn verify_point_lookups<T: InvocationStatusTable>(txn: &mut T) {
         .expect("should not fail");
 
     assert_eq!(status, invoked_status(SERVICE_ID_1.clone()));
+
+    let status = txn
+        .get_invocation_status(&INVOCATION_ID_4)
+        .await
+        .expect("should not fail");
+
+    assert_eq!(status, completed_status(SERVICE_ID_4.clone()));
 }
 
 async fn verify_all_svc_with_status_invoked<T: InvocationStatusTable>(txn: &mut T) {
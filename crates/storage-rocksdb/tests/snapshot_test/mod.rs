@@ -0,0 +1,122 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use bytes::Bytes;
+use restate_storage_api::fsm_table::{FsmTable, ReadOnlyFsmTable};
+use restate_storage_api::state_table::{ReadOnlyStateTable, StateTable};
+use restate_storage_api::Transaction;
+use restate_storage_rocksdb::snapshot::SnapshotError;
+use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::ServiceId;
+use restate_types::logs::Lsn;
+use tempfile::tempdir;
+
+async fn populate_data(rocksdb: &mut RocksDBStorage) {
+    let mut txn = rocksdb.transaction();
+    // inside of the partition
+    txn.put_user_state(
+        &ServiceId::with_partition_key(1337, "svc-1", "key-1"),
+        &Bytes::from_static(b"k1"),
+        &Bytes::from_static(b"v1"),
+    )
+    .await;
+    txn.put(1, 0, Bytes::from_static(b"fsm-1")).await;
+    // outside of the partition
+    txn.put_user_state(
+        &ServiceId::with_partition_key(4242, "svc-1", "key-1"),
+        &Bytes::from_static(b"k1"),
+        &Bytes::from_static(b"v2"),
+    )
+    .await;
+    txn.put(2, 0, Bytes::from_static(b"fsm-2")).await;
+    txn.commit().await.expect("commit should succeed");
+}
+
+pub(crate) async fn run_tests(mut source: RocksDBStorage) {
+    populate_data(&mut source).await;
+
+    let snapshot_dir = tempdir().unwrap();
+    let exported = source
+        .export_partition_snapshot(1, 0..=2000, Lsn::from(42), snapshot_dir.path())
+        .expect("export should succeed");
+    assert_eq!(Lsn::from(42), exported.applied_lsn);
+    assert_eq!(
+        exported,
+        RocksDBStorage::read_partition_snapshot_metadata(snapshot_dir.path()).unwrap()
+    );
+
+    // keep the directory of the target storage alive until the end of the test
+    let target_dir = tempdir().unwrap();
+    let (mut target, writer) = restate_storage_rocksdb::Options {
+        path: target_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    }
+    .build()
+    .expect("RocksDB storage creation should succeed");
+    let (signal, watch) = drain::channel();
+    let writer_join_handle = writer.run(watch);
+
+    let imported = target
+        .import_partition_snapshot(snapshot_dir.path())
+        .expect("import should succeed");
+    assert_eq!(exported, imported);
+
+    let mut txn = target.transaction();
+    assert_eq!(
+        Some(Bytes::from_static(b"v1")),
+        txn.get_user_state(
+            &ServiceId::with_partition_key(1337, "svc-1", "key-1"),
+            &Bytes::from_static(b"k1"),
+        )
+        .await
+        .unwrap()
+    );
+    assert_eq!(
+        Some(Bytes::from_static(b"fsm-1")),
+        txn.get(1, 0).await.unwrap()
+    );
+    assert_eq!(
+        None,
+        txn.get_user_state(
+            &ServiceId::with_partition_key(4242, "svc-1", "key-1"),
+            &Bytes::from_static(b"k1"),
+        )
+        .await
+        .unwrap()
+    );
+    assert_eq!(None, txn.get(2, 0).await.unwrap());
+    drop(txn);
+
+    // the partition now contains data
+    assert!(matches!(
+        target.import_partition_snapshot(snapshot_dir.path()),
+        Err(SnapshotError::PartitionNotEmpty(1))
+    ));
+
+    // a cleared partition can be bootstrapped again
+    target
+        .clear_partition(1, 0..=2000)
+        .expect("clear should succeed");
+    let mut txn = target.transaction();
+    assert_eq!(None, txn.get(1, 0).await.unwrap());
+    drop(txn);
+    target
+        .import_partition_snapshot(snapshot_dir.path())
+        .expect("import should succeed");
+    let mut txn = target.transaction();
+    assert_eq!(
+        Some(Bytes::from_static(b"fsm-1")),
+        txn.get(1, 0).await.unwrap()
+    );
+    drop(txn);
+
+    signal.drain().await;
+    writer_join_handle.await.unwrap().unwrap();
+}
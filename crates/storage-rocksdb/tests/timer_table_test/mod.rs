@@ -146,6 +146,25 @@ async fn verify_next_timer_after_deletion<T: TimerTable>(txn: &mut T) {
     }
 }
 
+async fn clean_invocation_status_timer_round_trip<T: TimerTable>(txn: &mut T) {
+    let timer_key = TimerKey {
+        invocation_uuid: FIXTURE_INVOCATION,
+        journal_index: 0,
+        timestamp: 42,
+    };
+    let timer = Timer::CleanInvocationStatus(ServiceId::new("svc-1", "key-1"));
+    txn.add_timer(1339, &timer_key, timer.clone()).await;
+
+    let mut stream = pin!(txn.next_timers_greater_than(1339, None, usize::MAX));
+
+    if let Some(Ok((key, value))) = stream.next().await {
+        assert_eq!(key, timer_key);
+        assert_eq!(value, timer);
+    } else {
+        panic!("test failure");
+    }
+}
+
 pub(crate) async fn run_tests(mut rocksdb: RocksDBStorage) {
     let mut txn = rocksdb.transaction();
 
@@ -158,4 +177,5 @@ pub(crate) async fn run_tests(mut rocksdb: RocksDBStorage) {
 
     let mut txn = rocksdb.transaction();
     verify_next_timer_after_deletion(&mut txn).await;
+    clean_invocation_status_timer_round_trip(&mut txn).await;
 }
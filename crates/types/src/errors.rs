@@ -138,6 +138,9 @@ pub enum RestateErrorCode {
 
     // The invocation was killed by Restate
     Killed = 64,
+
+    // The invocation exhausted the attempts of its retry policy.
+    MaxAttemptsExceeded = 65,
 }
 
 impl Display for RestateErrorCode {
@@ -189,6 +192,7 @@ impl From<u32> for InvocationErrorCode {
             32 => Restate(JournalMismatch),
             33 => Restate(ProtocolViolation),
             64 => Restate(Killed),
+            65 => Restate(MaxAttemptsExceeded),
 
             c => Unknown(c),
         }
@@ -351,6 +355,16 @@ pub const CANCELED_INVOCATION_ERROR: InvocationError = InvocationError::new_stat
     "canceled",
 );
 
+pub const NOT_FOUND_INVOCATION_ERROR: InvocationError = InvocationError::new_static(
+    InvocationErrorCode::User(UserErrorCode::NotFound),
+    "invocation not found",
+);
+
+pub const NOT_READY_INVOCATION_ERROR: InvocationError = InvocationError::new_static(
+    InvocationErrorCode::User(UserErrorCode::FailedPrecondition),
+    "invocation not ready",
+);
+
 #[cfg(feature = "tonic_conversions")]
 mod tonic_conversions_impl {
     use super::{InvocationError, InvocationErrorCode};
@@ -41,6 +41,7 @@ pub type PeerId = PartitionId;
     Copy,
     Hash,
     derive_more::From,
+    derive_more::Into,
     derive_more::Display,
 )]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -48,11 +49,18 @@ pub type PeerId = PartitionId;
 pub struct LeaderEpoch(u64);
 impl LeaderEpoch {
     pub const INITIAL: Self = Self(1);
+
+    pub fn next(self) -> Self {
+        Self(self.0 + 1)
+    }
 }
 
 /// Identifying the partition
 pub type PartitionId = u64;
 
+/// Identifying a request of an ingress node. It is unique within a [`crate::GenerationalNodeId`].
+pub type IngressRequestId = u64;
+
 /// The leader epoch of a given partition
 pub type PartitionLeaderEpoch = (PartitionId, LeaderEpoch);
 
@@ -257,10 +265,15 @@ pub struct ServiceId {
 impl ServiceId {
     pub fn new(service_name: impl Into<ByteString>, key: impl Into<Bytes>) -> Self {
         let key = key.into();
-        let partition_key = partitioner::HashPartitioner::compute_partition_key(&key);
+        let partition_key = Self::partition_key_of(&key);
         Self::with_partition_key(partition_key, service_name, key)
     }
 
+    /// Computes the [`PartitionKey`] of the service instances with the given `key`.
+    pub fn partition_key_of(key: &[u8]) -> PartitionKey {
+        partitioner::HashPartitioner::compute_partition_key(&key)
+    }
+
     /// # Important
     /// The `partition_key` must be hash of the `key` computed via [`HashPartitioner`].
     pub fn with_partition_key(
@@ -507,6 +520,91 @@ impl From<FullInvocationId> for EncodedInvocationId {
     }
 }
 
+/// Id of an idempotent request. Requests to the same method of the same service instance with the
+/// same idempotency key refer to the same invocation.
+#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdempotencyId {
+    pub service_id: ServiceId,
+    pub method_name: ByteString,
+    pub idempotency_key: ByteString,
+}
+
+impl IdempotencyId {
+    pub fn combine(
+        service_id: ServiceId,
+        method_name: impl Into<ByteString>,
+        idempotency_key: impl Into<ByteString>,
+    ) -> Self {
+        Self {
+            service_id,
+            method_name: method_name.into(),
+            idempotency_key: idempotency_key.into(),
+        }
+    }
+
+    /// Creates the id of an idempotent request to an unkeyed service. Unkeyed services are
+    /// usually invoked with a random key, instead the key is derived from the idempotency key so
+    /// that all requests with the same idempotency key are routed to the same partition.
+    pub fn unkeyed(
+        service_name: impl Into<ByteString>,
+        method_name: impl Into<ByteString>,
+        idempotency_key: impl Into<ByteString>,
+    ) -> Self {
+        let service_name = service_name.into();
+        let method_name = method_name.into();
+        let idempotency_key = idempotency_key.into();
+
+        let key = format!(
+            "{:032x}",
+            Self::hash(&[
+                service_name.as_bytes(),
+                method_name.as_bytes(),
+                idempotency_key.as_bytes(),
+            ])
+        );
+
+        Self {
+            service_id: ServiceId::new(service_name, key),
+            method_name,
+            idempotency_key,
+        }
+    }
+
+    /// Returns the id of the invocation which is started by the idempotent request. The
+    /// [`InvocationUuid`] is derived from the idempotency id, so that retries of the request
+    /// yield the same invocation id.
+    pub fn full_invocation_id(&self) -> FullInvocationId {
+        let invocation_uuid = InvocationUuid::from(Self::hash(&[
+            self.service_id.service_name.as_bytes(),
+            &self.service_id.key,
+            self.method_name.as_bytes(),
+            self.idempotency_key.as_bytes(),
+        ]));
+
+        FullInvocationId {
+            service_id: self.service_id.clone(),
+            invocation_uuid,
+        }
+    }
+
+    fn hash(parts: &[&[u8]]) -> u128 {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::default();
+        for part in parts {
+            // length prefix the parts to avoid collisions between different splits
+            hasher.update(&(part.len() as u64).to_be_bytes());
+            hasher.update(part);
+        }
+        hasher.digest128()
+    }
+}
+
+impl WithPartitionKey for IdempotencyId {
+    fn partition_key(&self) -> PartitionKey {
+        self.service_id.partition_key()
+    }
+}
+
 /// Incremental id defining the service revision.
 pub type ComponentRevision = u32;
 
@@ -780,6 +878,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn idempotency_id_derives_stable_ids() {
+        let id = IdempotencyId::unkeyed("greeter", "greet", "my-key");
+        assert_eq!(id, IdempotencyId::unkeyed("greeter", "greet", "my-key"));
+        assert_eq!(
+            id.full_invocation_id(),
+            IdempotencyId::unkeyed("greeter", "greet", "my-key").full_invocation_id()
+        );
+
+        // a different method or key leads to a different invocation
+        assert_ne!(
+            id.full_invocation_id(),
+            IdempotencyId::unkeyed("greeter", "other", "my-key").full_invocation_id()
+        );
+        assert_ne!(
+            id.full_invocation_id(),
+            IdempotencyId::unkeyed("greeter", "greet", "other-key").full_invocation_id()
+        );
+
+        let keyed = IdempotencyId::combine(ServiceId::new("counter", "bob"), "add", "my-key");
+        assert_eq!(
+            keyed.partition_key(),
+            ServiceId::new("counter", "bob").partition_key()
+        );
+        assert_eq!(
+            keyed.service_id,
+            keyed.full_invocation_id().service_id
+        );
+    }
+
     #[test]
     fn roundtrip_lambda_arn() {
         let good = "arn:aws:lambda:eu-central-1:1234567890:function:e2e-node-services:version";
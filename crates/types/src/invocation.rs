@@ -12,7 +12,7 @@
 
 use crate::errors::{InvocationError, UserErrorCode};
 use crate::identifiers::{
-    EntryIndex, FullInvocationId, InvocationId, PartitionKey, WithPartitionKey,
+    EntryIndex, FullInvocationId, IngressRequestId, InvocationId, PartitionKey, WithPartitionKey,
 };
 use crate::GenerationalNodeId;
 use bytes::Bytes;
@@ -21,6 +21,7 @@ use opentelemetry_api::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags,
 use opentelemetry_api::Context;
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
@@ -40,6 +41,9 @@ pub struct ServiceInvocation {
     pub source: Source,
     pub response_sink: Option<ServiceInvocationResponseSink>,
     pub span_context: ServiceInvocationSpanContext,
+    /// Set if the invocation has been requested with an idempotency key. Requests with the same
+    /// idempotency key are deduplicated by the partition processor.
+    pub idempotency: Option<Idempotency>,
 }
 
 impl ServiceInvocation {
@@ -65,10 +69,20 @@ impl ServiceInvocation {
             source,
             response_sink,
             span_context,
+            idempotency: None,
         }
     }
 }
 
+/// Idempotency key of an invocation together with the period for which the result of the
+/// invocation is retained to answer requests with the same key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Idempotency {
+    pub key: ByteString,
+    pub retention: Duration,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MaybeFullInvocationId {
@@ -165,7 +179,7 @@ impl From<&InvocationError> for ResponseResult {
 }
 
 /// Definition of the sink where to send the result of a service invocation.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ServiceInvocationResponseSink {
     /// The invocation has been created by a partition processor and is expecting a response.
@@ -181,7 +195,33 @@ pub enum ServiceInvocationResponseSink {
         caller_context: Bytes,
     },
     /// The invocation has been generated by a request received at an ingress, and the client is expecting a response back.
-    Ingress(GenerationalNodeId),
+    Ingress {
+        node_id: GenerationalNodeId,
+        request_id: IngressRequestId,
+    },
+}
+
+impl ServiceInvocationResponseSink {
+    pub fn ingress(node_id: GenerationalNodeId, request_id: IngressRequestId) -> Self {
+        Self::Ingress {
+            node_id,
+            request_id,
+        }
+    }
+}
+
+/// Request to send the result of an existing invocation to the given response sink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttachInvocationRequest {
+    pub invocation_id: InvocationId,
+    /// If `true`, the response sink is notified once the invocation completes. Otherwise, the
+    /// response sink is notified immediately, and receives [`NOT_READY_INVOCATION_ERROR`] if the
+    /// invocation has not completed yet.
+    ///
+    /// [`NOT_READY_INVOCATION_ERROR`]: crate::errors::NOT_READY_INVOCATION_ERROR
+    pub block_on_inflight: bool,
+    pub response_sink: ServiceInvocationResponseSink,
 }
 
 /// Source of an invocation
@@ -536,6 +576,7 @@ mod mocks {
                 source: Source::Service(FullInvocationId::mock_random()),
                 response_sink: None,
                 span_context: Default::default(),
+                idempotency: None,
             }
         }
     }
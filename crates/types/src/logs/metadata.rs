@@ -0,0 +1,214 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use enum_map::Enum;
+
+use crate::logs::{LogId, Lsn, SequenceNumber};
+use crate::{Version, Versioned};
+
+/// An enum with the list of supported loglet providers.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Copy, Enum, strum_macros::EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProviderKind {
+    /// A file-backed loglet.
+    File,
+    /// A loglet backed by a RocksDB instance that is shared by all local loglets.
+    Local,
+    /// An in-memory loglet, only meant for testing.
+    Memory,
+}
+
+/// Log metadata is the map of logs known to the system with the corresponding chain.
+/// Metadata updates are versioned and atomic.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Logs {
+    pub version: Version,
+    pub logs: HashMap<LogId, Chain>,
+}
+
+/// the chain is a list of segments in (from Lsn) order.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chain {
+    pub chain: BTreeMap<Lsn, Arc<LogletConfig>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub base_lsn: Lsn,
+    pub config: Arc<LogletConfig>,
+}
+
+/// A segment in the chain of loglet instances.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogletConfig {
+    pub kind: ProviderKind,
+    pub params: LogletParams,
+}
+
+impl LogletConfig {
+    pub fn new(kind: ProviderKind, params: LogletParams) -> Self {
+        Self { kind, params }
+    }
+}
+
+/// The configuration of a single loglet segment. This holds information needed
+/// for a loglet kind to construct a configured loglet instance modulo the log-id
+/// and start-lsn. It's provided by bifrost on loglet creation. This allows the
+/// parameters to be shared between segments and logs if needed.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, derive_more::From)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogletParams(String);
+
+impl LogletParams {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Logs {
+    pub fn new(version: Version, logs: HashMap<LogId, Chain>) -> Self {
+        Self { version, logs }
+    }
+
+    /// empty metadata with an invalid version
+    pub fn empty() -> Self {
+        Self {
+            version: Version::INVALID,
+            logs: Default::default(),
+        }
+    }
+
+    pub fn tail_segment(&self, log_id: LogId) -> Option<Segment> {
+        self.logs
+            .get(&log_id)
+            .and_then(|chain| chain.tail())
+            .map(|(base_lsn, config)| Segment {
+                base_lsn: *base_lsn,
+                config: Arc::clone(config),
+            })
+    }
+
+    /// Finds the segment that holds the record at `lsn`, that's the last segment whose base LSN
+    /// is at or before `lsn`. LSNs before the first segment map to the first segment.
+    pub fn find_segment_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Option<Segment> {
+        // NOTE: Hopefully at some point we will use the nightly Cursor API for
+        // effecient cursor seeks in the chain (or use nightly channel)
+        // Reference: https://github.com/rust-lang/rust/issues/107540
+        //
+        self.logs.get(&log_id).and_then(|chain| {
+            chain
+                .chain
+                .range(..=lsn)
+                .next_back()
+                .or_else(|| chain.chain.first_key_value())
+                .map(|(base_lsn, config)| Segment {
+                    base_lsn: *base_lsn,
+                    config: Arc::clone(config),
+                })
+        })
+    }
+
+    /// All segments of the log in LSN order.
+    pub fn segments(&self, log_id: LogId) -> Option<Vec<Segment>> {
+        self.logs
+            .get(&log_id)
+            .map(|chain| chain.segments().collect())
+    }
+}
+
+impl Versioned for Logs {
+    fn version(&self) -> Version {
+        self.version
+    }
+
+    fn increment_version(&mut self) {
+        self.version = self.version.next();
+    }
+}
+
+impl Chain {
+    /// Creates a new chain starting from Lsn(1) with a given loglet config.
+    pub fn new(kind: ProviderKind, config: LogletParams) -> Self {
+        let mut chain = BTreeMap::new();
+        let from_lsn = Lsn::OLDEST;
+        chain.insert(from_lsn, Arc::new(LogletConfig::new(kind, config)));
+        Self { chain }
+    }
+
+    pub fn tail(&self) -> Option<(&Lsn, &Arc<LogletConfig>)> {
+        self.chain.last_key_value()
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.chain.iter().map(|(base_lsn, config)| Segment {
+            base_lsn: *base_lsn,
+            config: Arc::clone(config),
+        })
+    }
+
+    /// Extends the chain with a new tail segment starting at `base_lsn`. The previous tail
+    /// segment must be sealed with its last record right before `base_lsn`. A tail segment
+    /// that starts at `base_lsn` holds no records, it's replaced by the new segment.
+    pub fn append_segment(&mut self, base_lsn: Lsn, config: LogletConfig) {
+        let (tail_base_lsn, _) = self.tail().expect("chain has a tail segment");
+        assert!(
+            base_lsn >= *tail_base_lsn,
+            "new segment at {} starts before the tail segment at {}",
+            base_lsn,
+            tail_base_lsn
+        );
+        self.chain.insert(base_lsn, Arc::new(config));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use restate_test_util::let_assert;
+
+    use super::*;
+
+    #[test]
+    fn test_chain_new() {
+        let chain = Chain::new(ProviderKind::File, LogletParams::from("test".to_string()));
+        assert_eq!(chain.chain.len(), 1);
+        let_assert!(Some((lsn, loglet_config)) = chain.tail());
+        assert_eq!(Lsn::OLDEST, *lsn);
+        assert_eq!(ProviderKind::File, loglet_config.kind);
+        assert_eq!("test".to_string(), loglet_config.params.0);
+    }
+
+    #[test]
+    fn test_find_segment_for_lsn() {
+        let log_id = LogId::from(0);
+        let mut chain = Chain::new(ProviderKind::Memory, LogletParams::from("1".to_string()));
+        chain.append_segment(
+            Lsn::from(11),
+            LogletConfig::new(ProviderKind::File, LogletParams::from("2".to_string())),
+        );
+        let logs = Logs::new(Version::MIN, HashMap::from([(log_id, chain)]));
+
+        for (lsn, expected_base_lsn) in [(0, 1), (1, 1), (10, 1), (11, 11), (100, 11)] {
+            let_assert!(Some(segment) = logs.find_segment_for_lsn(log_id, Lsn::from(lsn)));
+            assert_eq!(Lsn::from(expected_base_lsn), segment.base_lsn);
+        }
+        let_assert!(Some(segment) = logs.tail_segment(log_id));
+        assert_eq!(ProviderKind::File, segment.config.kind);
+        assert_eq!(2, logs.segments(log_id).unwrap().len());
+        assert!(logs
+            .find_segment_for_lsn(LogId::from(1), Lsn::OLDEST)
+            .is_none());
+    }
+}
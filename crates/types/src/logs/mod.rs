@@ -8,6 +8,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod metadata;
+
 use bytes::Bytes;
 
 #[derive(
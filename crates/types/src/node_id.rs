@@ -32,7 +32,18 @@ pub enum NodeId {
     Generational(GenerationalNodeId),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, derive_more::From, derive_more::Display)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Ord,
+    PartialOrd,
+    Clone,
+    Copy,
+    Hash,
+    derive_more::From,
+    derive_more::Display,
+)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[display(fmt = "{}:{}", _0, _1)]
 pub struct GenerationalNodeId(PlainNodeId, u32);
@@ -49,6 +49,10 @@ impl FixedPartitionTable {
         self.version = self.version.next();
     }
 
+    pub fn num_partitions(&self) -> u64 {
+        self.num_partitions
+    }
+
     pub fn partitioner(&self) -> Partitioner {
         Partitioner::new(self.num_partitions)
     }
@@ -0,0 +1,722 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Binary format of the [`Envelope`]s which are written to the logs.
+//!
+//! An encoded envelope has the following layout:
+//!
+//! ```text
+//! +----------------+-------------+------------------+-------------------+
+//! | format version | command tag | header (bincode) | command (bincode) |
+//! |     1 byte     |   1 byte    |                  |                   |
+//! +----------------+-------------+------------------+-------------------+
+//! ```
+//!
+//! The header and the value of the command are encoded with bincode's standard configuration
+//! (little endian, variable length integers). The command tag identifies the [`Command`] variant
+//! independently of its position within the enum. Both the tags and the layout of an encoding
+//! version must never change once released, because logs written by older Restate versions have
+//! to remain readable. Changes to the format require a new format version.
+//!
+//! Format versions:
+//!
+//! * 1: initial format.
+//! * 2: the ingress response sink carries the id of the ingress request and ingress responses
+//!   are addressed by it. Service invocations carry their idempotency key. Adds the
+//!   `AttachInvocation` command. See the `v1` module for how version 1 envelopes are decoded.
+
+use bytes::{BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use restate_types::logs::Payload;
+
+use crate::{Command, Envelope, Header};
+
+mod v1;
+
+/// Format version written by [`encode`].
+pub const CURRENT_FORMAT_VERSION: u8 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    #[error("failed encoding envelope header: {0}")]
+    Header(#[source] bincode::error::EncodeError),
+    #[error("failed encoding command '{command}': {source}")]
+    Command {
+        command: &'static str,
+        #[source]
+        source: bincode::error::EncodeError,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("envelope is truncated, missing the format version or command tag")]
+    Truncated,
+    #[error("unsupported envelope format version '{0}'")]
+    UnsupportedFormatVersion(u8),
+    #[error("unknown command tag '{0}'")]
+    UnknownCommand(u8),
+    #[error("failed decoding header of command '{command}': {source}")]
+    Header {
+        command: &'static str,
+        #[source]
+        source: bincode::error::DecodeError,
+    },
+    #[error("failed decoding command '{command}': {source}")]
+    Command {
+        command: &'static str,
+        #[source]
+        source: bincode::error::DecodeError,
+    },
+    #[error("command '{command}' is followed by {count} unexpected trailing bytes")]
+    TrailingBytes { command: &'static str, count: usize },
+}
+
+/// Defines the stable tags of the [`Command`] variants. Never reuse or change the tag of a
+/// variant, only append new ones.
+macro_rules! command_tags {
+    ($($tag:literal => $variant:ident),+ $(,)?) => {
+        fn command_tag(command: &Command) -> u8 {
+            match command {
+                $(Command::$variant(_) => $tag,)+
+            }
+        }
+
+        fn encode_command(command: &Command, buf: &mut BytesMut) -> Result<(), EncodeError> {
+            match command {
+                $(Command::$variant(value) => encode_value(value, buf).map_err(|source| {
+                    EncodeError::Command {
+                        command: stringify!($variant),
+                        source,
+                    }
+                }),)+
+            }
+        }
+
+        fn command_name(tag: u8) -> Result<&'static str, DecodeError> {
+            match tag {
+                $($tag => Ok(stringify!($variant)),)+
+                _ => Err(DecodeError::UnknownCommand(tag)),
+            }
+        }
+
+        fn decode_command(tag: u8, bytes: &[u8]) -> Result<Command, DecodeError> {
+            match tag {
+                $($tag => Ok(Command::$variant(decode_value(stringify!($variant), bytes)?)),)+
+                _ => Err(DecodeError::UnknownCommand(tag)),
+            }
+        }
+    };
+}
+
+command_tags! {
+    1 => AnnounceLeader,
+    2 => PatchState,
+    3 => TerminateInvocation,
+    4 => Invoke,
+    5 => TruncateOutbox,
+    6 => InvokerEffect,
+    7 => Timer,
+    8 => InvocationResponse,
+    9 => BuiltInInvokerEffect,
+    10 => ScheduleTimer,
+    11 => AttachInvocation,
+}
+
+/// Encodes the envelope with the [`CURRENT_FORMAT_VERSION`].
+pub fn encode(envelope: &Envelope) -> Result<Payload, EncodeError> {
+    let mut buf = BytesMut::new();
+    buf.put_u8(CURRENT_FORMAT_VERSION);
+    buf.put_u8(command_tag(&envelope.command));
+    encode_value(&envelope.header, &mut buf).map_err(EncodeError::Header)?;
+    encode_command(&envelope.command, &mut buf)?;
+
+    Ok(Payload::from(buf.freeze()))
+}
+
+/// Decodes an envelope which has been encoded by [`encode`] in any supported format version.
+pub fn decode(payload: &[u8]) -> Result<Envelope, DecodeError> {
+    let [format_version, tag, rest @ ..] = payload else {
+        return Err(DecodeError::Truncated);
+    };
+
+    let command = match *format_version {
+        1 => v1::command_name(*tag)?,
+        CURRENT_FORMAT_VERSION => command_name(*tag)?,
+        version => return Err(DecodeError::UnsupportedFormatVersion(version)),
+    };
+    let (header, read): (Header, _) =
+        bincode::serde::decode_from_slice(rest, bincode::config::standard())
+            .map_err(|source| DecodeError::Header { command, source })?;
+    let command = match *format_version {
+        1 => v1::decode_command(*tag, &rest[read..])?,
+        _ => decode_command(*tag, &rest[read..])?,
+    };
+
+    Ok(Envelope::new(header, command))
+}
+
+fn encode_value<T: Serialize>(
+    value: &T,
+    buf: &mut BytesMut,
+) -> Result<(), bincode::error::EncodeError> {
+    bincode::serde::encode_into_std_write(value, &mut buf.writer(), bincode::config::standard())
+        .map(|_| ())
+}
+
+fn decode_value<T: DeserializeOwned>(
+    command: &'static str,
+    bytes: &[u8],
+) -> Result<T, DecodeError> {
+    let (value, read) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+        .map_err(|source| DecodeError::Command { command, source })?;
+
+    if read != bytes.len() {
+        return Err(DecodeError::TrailingBytes {
+            command,
+            count: bytes.len() - read,
+        });
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use restate_invoker_api::{Effect, EffectKind};
+    use restate_storage_api::outbox_table::OutboxMessage;
+    use restate_types::errors::InvocationError;
+    use restate_types::identifiers::{
+        FullInvocationId, IngressRequestId, InvocationId, LeaderEpoch, ServiceId,
+    };
+    use restate_types::ingress::IngressResponse;
+    use restate_types::invocation::{
+        AttachInvocationRequest, Idempotency, InvocationResponse, InvocationTermination,
+        MaybeFullInvocationId, ResponseResult, ServiceInvocation, ServiceInvocationResponseSink,
+        ServiceInvocationSpanContext, Source as InvocationSource,
+    };
+    use restate_types::state_mut::ExternalStateMutation;
+    use restate_types::time::MillisSinceEpoch;
+    use restate_types::{GenerationalNodeId, PlainNodeId, Version};
+
+    use crate::control::AnnounceLeader;
+    use crate::effects::{BuiltinServiceEffect, BuiltinServiceEffects};
+    use crate::timer::TimerValue;
+    use crate::{AckMode, Destination, Source};
+
+    /// Request id of the ingress response sinks and responses of the version 2 fixtures.
+    const REQUEST_ID: IngressRequestId = 7;
+
+    fn fid() -> FullInvocationId {
+        FullInvocationId::new(
+            "greeter",
+            "bob",
+            0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10_u128,
+        )
+    }
+
+    fn ingress_node() -> GenerationalNodeId {
+        GenerationalNodeId::new(1, 2)
+    }
+
+    fn ingress_header() -> Header {
+        Header {
+            source: Source::Ingress {
+                node_id: ingress_node(),
+                nodes_config_version: Version::MIN,
+                dedup_key: Some("key".to_owned()),
+                sequence_number: 7,
+            },
+            dest: Destination::Processor {
+                partition_key: 1337,
+            },
+            ack_mode: AckMode::Dedup,
+        }
+    }
+
+    fn processor_header() -> Header {
+        Header {
+            source: Source::Processor {
+                partition_id: 3,
+                partition_key: Some(1337),
+                leader_epoch: LeaderEpoch::INITIAL,
+                sequence_number: None,
+                node_id: PlainNodeId::from(4),
+            },
+            dest: Destination::Processor {
+                partition_key: 1337,
+            },
+            ack_mode: AckMode::None,
+        }
+    }
+
+    fn service_invocation(request_id: IngressRequestId) -> ServiceInvocation {
+        ServiceInvocation {
+            fid: fid(),
+            method_name: "greet".into(),
+            argument: Bytes::from_static(b"Bob"),
+            source: InvocationSource::Ingress,
+            response_sink: Some(ServiceInvocationResponseSink::ingress(
+                ingress_node(),
+                request_id,
+            )),
+            span_context: ServiceInvocationSpanContext::empty(),
+            idempotency: None,
+        }
+    }
+
+    fn announce_leader() -> Envelope {
+        Envelope::new(
+            Header {
+                source: Source::Processor {
+                    partition_id: 3,
+                    partition_key: Some(1337),
+                    leader_epoch: LeaderEpoch::from(5),
+                    sequence_number: None,
+                    node_id: PlainNodeId::from(4),
+                },
+                ..processor_header()
+            },
+            Command::AnnounceLeader(AnnounceLeader::new(GenerationalNodeId::new(4, 6))),
+        )
+    }
+
+    fn patch_state() -> Envelope {
+        Envelope::new(
+            Header {
+                source: Source::ControlPlane {},
+                ..processor_header()
+            },
+            Command::PatchState(ExternalStateMutation {
+                service_id: ServiceId::new("greeter", "bob"),
+                version: Some("v1".to_owned()),
+                state: HashMap::from([(Bytes::from_static(b"name"), Bytes::from_static(b"Bob"))]),
+            }),
+        )
+    }
+
+    fn terminate_invocation() -> Envelope {
+        Envelope::new(
+            processor_header(),
+            Command::TerminateInvocation(InvocationTermination::kill(fid())),
+        )
+    }
+
+    fn invoke(request_id: IngressRequestId) -> Envelope {
+        Envelope::new(
+            ingress_header(),
+            Command::Invoke(service_invocation(request_id)),
+        )
+    }
+
+    fn idempotent_invoke() -> Envelope {
+        let mut service_invocation = service_invocation(REQUEST_ID);
+        service_invocation.idempotency = Some(Idempotency {
+            key: "request-1".into(),
+            retention: Duration::from_secs(60 * 60 * 24),
+        });
+        Envelope::new(ingress_header(), Command::Invoke(service_invocation))
+    }
+
+    fn truncate_outbox() -> Envelope {
+        Envelope::new(ingress_header(), Command::TruncateOutbox(42))
+    }
+
+    fn invoker_effect() -> Envelope {
+        Envelope::new(
+            processor_header(),
+            Command::InvokerEffect(Effect {
+                full_invocation_id: fid(),
+                kind: EffectKind::Failed(InvocationError::internal("boom")),
+            }),
+        )
+    }
+
+    fn timer() -> Envelope {
+        Envelope::new(
+            processor_header(),
+            Command::Timer(TimerValue::new_sleep(
+                fid(),
+                MillisSinceEpoch::new(1_000),
+                3,
+            )),
+        )
+    }
+
+    fn invocation_response() -> Envelope {
+        Envelope::new(
+            processor_header(),
+            Command::InvocationResponse(InvocationResponse {
+                id: MaybeFullInvocationId::Full(fid()),
+                entry_index: 2,
+                result: ResponseResult::Success(Bytes::from_static(b"Hello Bob")),
+            }),
+        )
+    }
+
+    fn built_in_invoker_effect(request_id: IngressRequestId) -> Envelope {
+        Envelope::new(
+            processor_header(),
+            Command::BuiltInInvokerEffect(BuiltinServiceEffects::new(
+                fid(),
+                vec![
+                    BuiltinServiceEffect::SetState {
+                        key: Cow::Borrowed("name"),
+                        value: Bytes::from_static(b"Bob"),
+                    },
+                    BuiltinServiceEffect::OutboxMessage(OutboxMessage::ServiceInvocation(
+                        service_invocation(request_id),
+                    )),
+                    BuiltinServiceEffect::DelayedInvoke {
+                        target_fid: fid(),
+                        target_method: "greet".to_owned(),
+                        argument: Bytes::from_static(b"Bob"),
+                        source: InvocationSource::Internal,
+                        response_sink: Some(ServiceInvocationResponseSink::ingress(
+                            ingress_node(),
+                            request_id,
+                        )),
+                        time: MillisSinceEpoch::new(1_000),
+                        timer_index: 4,
+                    },
+                    BuiltinServiceEffect::IngressResponse(IngressResponse {
+                        target_node: ingress_node(),
+                        request_id,
+                        invocation_id: InvocationId::from(fid()),
+                        response: ResponseResult::Success(Bytes::from_static(b"Hello Bob")),
+                    }),
+                    BuiltinServiceEffect::End(None),
+                ],
+            )),
+        )
+    }
+
+    fn schedule_timer(request_id: IngressRequestId) -> Envelope {
+        Envelope::new(
+            ingress_header(),
+            Command::ScheduleTimer(TimerValue::new_invoke(
+                fid(),
+                MillisSinceEpoch::new(1_000),
+                0,
+                service_invocation(request_id),
+            )),
+        )
+    }
+
+    fn attach_invocation() -> Envelope {
+        Envelope::new(
+            ingress_header(),
+            Command::AttachInvocation(AttachInvocationRequest {
+                invocation_id: InvocationId::from(fid()),
+                block_on_inflight: true,
+                response_sink: ServiceInvocationResponseSink::ingress(ingress_node(), REQUEST_ID),
+            }),
+        )
+    }
+
+    /// Compares the envelopes including the values of timers, which are not part of the
+    /// equality of [`TimerValue`].
+    fn assert_envelope_eq(expected: &Envelope, actual: &Envelope) {
+        assert_eq!(expected, actual);
+        match (&expected.command, &actual.command) {
+            (Command::Timer(expected), Command::Timer(actual))
+            | (Command::ScheduleTimer(expected), Command::ScheduleTimer(actual)) => {
+                assert_eq!(expected.value(), actual.value());
+            }
+            _ => {}
+        }
+    }
+
+    // These bytes must never change: logs written by released versions contain them.
+    const ANNOUNCE_LEADER_V2: &[u8] = &[2, 1, 0, 3, 1, 251, 57, 5, 5, 0, 4, 0, 251, 57, 5, 2, 4, 6];
+    const PATCH_STATE_V2: &[u8] = &[
+        2, 2, 2, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53,
+        54, 120, 20, 96, 4, 220, 4, 1, 2, 118, 49, 1, 4, 110, 97, 109, 101, 3, 66, 111, 98,
+    ];
+    const TERMINATE_INVOCATION_V2: &[u8] = &[
+        2, 3, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 1, 7, 103, 114, 101, 101, 116, 101,
+        114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87,
+        109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 0,
+    ];
+    const INVOKE_V2: &[u8] = &[
+        2, 4, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 7, 103, 114, 101, 101, 116,
+        101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88,
+        98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 5, 103, 114, 101,
+        101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const IDEMPOTENT_INVOKE_V2: &[u8] = &[
+        2, 4, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 7, 103, 114, 101, 101, 116,
+        101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88,
+        98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 5, 103, 114, 101,
+        101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 9, 114, 101, 113, 117, 101, 115, 116, 45, 49, 252,
+        128, 81, 1, 0, 0,
+    ];
+    const TRUNCATE_OUTBOX_V2: &[u8] = &[
+        2, 5, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 42,
+    ];
+    const INVOKER_EFFECT_V2: &[u8] = &[
+        2, 6, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114,
+        3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109,
+        88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 4, 0, 12, 4, 98, 111, 111, 109,
+        0,
+    ];
+    const TIMER_V2: &[u8] = &[
+        2, 7, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 251, 232, 3, 22, 48, 117, 105, 75,
+        88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 3, 0, 7, 103,
+        114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4,
+    ];
+    const INVOCATION_RESPONSE_V2: &[u8] = &[
+        2, 8, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 1, 7, 103, 114, 101, 101, 116, 101,
+        114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87,
+        109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 2, 0, 9, 72, 101, 108,
+        108, 111, 32, 66, 111, 98,
+    ];
+    const BUILT_IN_INVOKER_EFFECT_V2: &[u8] = &[
+        2, 9, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114,
+        3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109,
+        88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 5, 3, 4, 110, 97, 109, 101, 3,
+        66, 111, 98, 5, 0, 7, 103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120,
+        20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48,
+        48, 121, 120, 52, 49, 114, 5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 7, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 7,
+        103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22,
+        48, 117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49,
+        114, 5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 2, 1, 2, 1, 2, 7, 251, 232, 3, 4, 8, 1, 2,
+        7, 38, 105, 110, 118, 95, 49, 52, 122, 102, 119, 102, 112, 90, 113, 48, 109, 103, 48, 117,
+        105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 0, 9,
+        72, 101, 108, 108, 111, 32, 66, 111, 98, 7, 0,
+    ];
+    const SCHEDULE_TIMER_V2: &[u8] = &[
+        2, 10, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 251, 232, 3, 22, 48, 117, 105,
+        75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 0, 1, 7,
+        103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 7,
+        103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22,
+        48, 117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49,
+        114, 5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 7, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const ATTACH_INVOCATION_V2: &[u8] = &[
+        2, 11, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 38, 105, 110, 118, 95, 49, 52,
+        122, 102, 119, 102, 112, 90, 113, 48, 109, 103, 48, 117, 105, 75, 88, 98, 87, 109, 88, 112,
+        71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 1, 2, 1, 2, 7,
+    ];
+
+    const ANNOUNCE_LEADER_V1: &[u8] = &[1, 1, 0, 3, 1, 251, 57, 5, 5, 0, 4, 0, 251, 57, 5, 2, 4, 6];
+    const PATCH_STATE_V1: &[u8] = &[
+        1, 2, 2, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53,
+        54, 120, 20, 96, 4, 220, 4, 1, 2, 118, 49, 1, 4, 110, 97, 109, 101, 3, 66, 111, 98,
+    ];
+    const TERMINATE_INVOCATION_V1: &[u8] = &[
+        1, 3, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 1, 7, 103, 114, 101, 101, 116, 101,
+        114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87,
+        109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 0,
+    ];
+    const INVOKE_V1: &[u8] = &[
+        1, 4, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 7, 103, 114, 101, 101, 116,
+        101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88,
+        98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 5, 103, 114, 101,
+        101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    const TRUNCATE_OUTBOX_V1: &[u8] = &[
+        1, 5, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 42,
+    ];
+    const INVOKER_EFFECT_V1: &[u8] = &[
+        1, 6, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114,
+        3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109,
+        88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 4, 0, 12, 4, 98, 111, 111, 109,
+        0,
+    ];
+    const TIMER_V1: &[u8] = &[
+        1, 7, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 251, 232, 3, 22, 48, 117, 105, 75,
+        88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 3, 0, 7, 103,
+        114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4,
+    ];
+    const INVOCATION_RESPONSE_V1: &[u8] = &[
+        1, 8, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 1, 7, 103, 114, 101, 101, 116, 101,
+        114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87,
+        109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 2, 0, 9, 72, 101, 108,
+        108, 111, 32, 66, 111, 98,
+    ];
+    const BUILT_IN_INVOKER_EFFECT_V1: &[u8] = &[
+        1, 9, 0, 3, 1, 251, 57, 5, 1, 0, 4, 0, 251, 57, 5, 2, 7, 103, 114, 101, 101, 116, 101, 114,
+        3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109,
+        88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 5, 3, 4, 110, 97, 109, 101, 3,
+        66, 111, 98, 5, 0, 7, 103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120,
+        20, 96, 4, 220, 4, 22, 48, 117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48,
+        48, 121, 120, 52, 49, 114, 5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 7, 103,
+        114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48,
+        117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114,
+        5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 2, 1, 2, 1, 2, 251, 232, 3, 4, 8, 1, 2, 7, 103,
+        114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22, 48,
+        117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114,
+        0, 9, 72, 101, 108, 108, 111, 32, 66, 111, 98, 7, 0,
+    ];
+    const SCHEDULE_TIMER_V1: &[u8] = &[
+        1, 10, 1, 1, 2, 1, 1, 3, 107, 101, 121, 7, 0, 251, 57, 5, 1, 251, 232, 3, 22, 48, 117, 105,
+        75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49, 114, 0, 1, 7,
+        103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 7,
+        103, 114, 101, 101, 116, 101, 114, 3, 98, 111, 98, 253, 53, 54, 120, 20, 96, 4, 220, 4, 22,
+        48, 117, 105, 75, 88, 98, 87, 109, 88, 112, 71, 87, 50, 87, 86, 48, 48, 121, 120, 52, 49,
+        114, 5, 103, 114, 101, 101, 116, 3, 66, 111, 98, 0, 1, 2, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+
+    fn golden_envelopes_v2() -> Vec<(Envelope, &'static [u8])> {
+        vec![
+            (announce_leader(), ANNOUNCE_LEADER_V2),
+            (patch_state(), PATCH_STATE_V2),
+            (terminate_invocation(), TERMINATE_INVOCATION_V2),
+            (invoke(REQUEST_ID), INVOKE_V2),
+            (idempotent_invoke(), IDEMPOTENT_INVOKE_V2),
+            (truncate_outbox(), TRUNCATE_OUTBOX_V2),
+            (invoker_effect(), INVOKER_EFFECT_V2),
+            (timer(), TIMER_V2),
+            (invocation_response(), INVOCATION_RESPONSE_V2),
+            (
+                built_in_invoker_effect(REQUEST_ID),
+                BUILT_IN_INVOKER_EFFECT_V2,
+            ),
+            (schedule_timer(REQUEST_ID), SCHEDULE_TIMER_V2),
+            (attach_invocation(), ATTACH_INVOCATION_V2),
+        ]
+    }
+
+    /// Version 1 envelopes together with the envelopes they decode to. Ingress response sinks
+    /// and responses of version 1 did not carry a request id, and invocations did not carry an
+    /// idempotency key.
+    fn golden_envelopes_v1() -> Vec<(Envelope, &'static [u8])> {
+        let request_id = v1::LEGACY_INGRESS_REQUEST_ID;
+        vec![
+            (announce_leader(), ANNOUNCE_LEADER_V1),
+            (patch_state(), PATCH_STATE_V1),
+            (terminate_invocation(), TERMINATE_INVOCATION_V1),
+            (invoke(request_id), INVOKE_V1),
+            (truncate_outbox(), TRUNCATE_OUTBOX_V1),
+            (invoker_effect(), INVOKER_EFFECT_V1),
+            (timer(), TIMER_V1),
+            (invocation_response(), INVOCATION_RESPONSE_V1),
+            (
+                built_in_invoker_effect(request_id),
+                BUILT_IN_INVOKER_EFFECT_V1,
+            ),
+            (schedule_timer(request_id), SCHEDULE_TIMER_V1),
+        ]
+    }
+
+    #[test]
+    fn encoding_matches_golden_bytes() {
+        for (envelope, golden) in golden_envelopes_v2() {
+            assert_eq!(
+                golden,
+                &encode(&envelope).unwrap()[..],
+                "{}",
+                envelope.command.name()
+            );
+        }
+    }
+
+    #[test]
+    fn golden_bytes_decode() {
+        for (envelope, golden) in golden_envelopes_v2() {
+            assert_envelope_eq(&envelope, &decode(golden).unwrap());
+        }
+    }
+
+    #[test]
+    fn v1_golden_bytes_decode() {
+        for (envelope, golden) in golden_envelopes_v1() {
+            assert_envelope_eq(&envelope, &decode(golden).unwrap());
+        }
+    }
+
+    #[test]
+    fn v1_invoke_decodes_without_idempotency() {
+        let Command::Invoke(service_invocation) = decode(INVOKE_V1).unwrap().command else {
+            panic!("expected an invoke command");
+        };
+        assert_eq!(None, service_invocation.idempotency);
+
+        let Command::Invoke(service_invocation) = decode(IDEMPOTENT_INVOKE_V2).unwrap().command
+        else {
+            panic!("expected an invoke command");
+        };
+        assert_eq!(
+            Some("request-1".to_owned()),
+            service_invocation
+                .idempotency
+                .map(|idempotency| idempotency.key.to_string())
+        );
+    }
+
+    #[test]
+    fn decode_errors_name_the_command() {
+        let mut truncated = TERMINATE_INVOCATION_V2.to_vec();
+        truncated.pop();
+        let err = decode(&truncated).unwrap_err();
+        assert!(matches!(
+            err,
+            DecodeError::Command {
+                command: "TerminateInvocation",
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("TerminateInvocation"));
+
+        let mut trailing = TRUNCATE_OUTBOX_V2.to_vec();
+        trailing.push(0);
+        assert!(matches!(
+            decode(&trailing),
+            Err(DecodeError::TrailingBytes {
+                command: "TruncateOutbox",
+                count: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_unknown_format_version_and_tag() {
+        let mut unknown_version = TRUNCATE_OUTBOX_V2.to_vec();
+        unknown_version[0] = 3;
+        assert!(matches!(
+            decode(&unknown_version),
+            Err(DecodeError::UnsupportedFormatVersion(3))
+        ));
+
+        let mut unknown_tag = TRUNCATE_OUTBOX_V2.to_vec();
+        unknown_tag[1] = 0;
+        assert!(matches!(
+            decode(&unknown_tag),
+            Err(DecodeError::UnknownCommand(0))
+        ));
+
+        // AttachInvocation has been introduced with version 2
+        let mut attach_invocation_v1 = ATTACH_INVOCATION_V2.to_vec();
+        attach_invocation_v1[0] = 1;
+        assert!(matches!(
+            decode(&attach_invocation_v1),
+            Err(DecodeError::UnknownCommand(11))
+        ));
+
+        assert!(matches!(decode(&[2]), Err(DecodeError::Truncated)));
+    }
+}
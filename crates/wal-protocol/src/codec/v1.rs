@@ -0,0 +1,332 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decoding of format version 1.
+//!
+//! Version 1 differs from version 2 in the commands which contain a [`ServiceInvocation`],
+//! a [`ServiceInvocationResponseSink`] or an [`IngressResponse`]:
+//!
+//! * the ingress response sink only carried the node id of the ingress,
+//! * ingress responses were addressed by the full invocation id instead of the request id,
+//! * service invocations did not carry an idempotency key.
+//!
+//! The types of this module mirror the version 1 layout of these commands and are converted
+//! into the current types after decoding. All other commands are decoded like in version 2.
+//! Version 1 does not know the `AttachInvocation` command.
+
+use bytes::Bytes;
+use bytestring::ByteString;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use restate_storage_api::invocation_status_table::NotificationTarget;
+use restate_storage_api::outbox_table;
+use restate_storage_api::timer_table::{self, TimerKey};
+use restate_types::errors::InvocationError;
+use restate_types::identifiers::{
+    EntryIndex, FullInvocationId, IngressRequestId, InvocationId, ServiceId,
+};
+use restate_types::ingress::IngressResponse;
+use restate_types::invocation::{
+    InvocationResponse, InvocationTermination, ResponseResult, ServiceInvocation,
+    ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source as InvocationSource,
+};
+use restate_types::journal::enriched::EnrichedRawEntry;
+use restate_types::time::MillisSinceEpoch;
+use restate_types::GenerationalNodeId;
+
+use super::{decode_value, DecodeError};
+use crate::effects::{BuiltinServiceEffect, BuiltinServiceEffects};
+use crate::timer::TimerValue;
+use crate::Command;
+
+/// Request id assigned to the ingress response sinks of version 1, which did not carry one. The
+/// ingress which awaited such a response has been restarted by the upgrade, so the response
+/// cannot be matched to a pending request anyway.
+pub(super) const LEGACY_INGRESS_REQUEST_ID: IngressRequestId = IngressRequestId::MAX;
+
+/// Tag of the last command known to version 1.
+const LAST_COMMAND_TAG: u8 = 10;
+
+pub(super) fn command_name(tag: u8) -> Result<&'static str, DecodeError> {
+    if tag > LAST_COMMAND_TAG {
+        return Err(DecodeError::UnknownCommand(tag));
+    }
+
+    super::command_name(tag)
+}
+
+pub(super) fn decode_command(tag: u8, bytes: &[u8]) -> Result<Command, DecodeError> {
+    match tag {
+        4 => Ok(Command::Invoke(
+            decode_value::<V1ServiceInvocation>("Invoke", bytes)?.into(),
+        )),
+        7 => Ok(Command::Timer(
+            decode_value::<V1TimerValue>("Timer", bytes)?.into(),
+        )),
+        9 => Ok(Command::BuiltInInvokerEffect(
+            decode_value::<V1BuiltinServiceEffects>("BuiltInInvokerEffect", bytes)?.into(),
+        )),
+        10 => Ok(Command::ScheduleTimer(
+            decode_value::<V1TimerValue>("ScheduleTimer", bytes)?.into(),
+        )),
+        tag if tag > LAST_COMMAND_TAG => Err(DecodeError::UnknownCommand(tag)),
+        tag => super::decode_command(tag, bytes),
+    }
+}
+
+#[derive(Deserialize)]
+struct V1ServiceInvocation {
+    fid: FullInvocationId,
+    method_name: ByteString,
+    argument: Bytes,
+    source: InvocationSource,
+    response_sink: Option<V1ServiceInvocationResponseSink>,
+    span_context: ServiceInvocationSpanContext,
+}
+
+impl From<V1ServiceInvocation> for ServiceInvocation {
+    fn from(value: V1ServiceInvocation) -> Self {
+        ServiceInvocation {
+            fid: value.fid,
+            method_name: value.method_name,
+            argument: value.argument,
+            source: value.source,
+            response_sink: value.response_sink.map(Into::into),
+            span_context: value.span_context,
+            idempotency: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+enum V1ServiceInvocationResponseSink {
+    PartitionProcessor {
+        caller: FullInvocationId,
+        entry_index: EntryIndex,
+    },
+    NewInvocation {
+        target: FullInvocationId,
+        method: String,
+        caller_context: Bytes,
+    },
+    Ingress(GenerationalNodeId),
+}
+
+impl From<V1ServiceInvocationResponseSink> for ServiceInvocationResponseSink {
+    fn from(value: V1ServiceInvocationResponseSink) -> Self {
+        match value {
+            V1ServiceInvocationResponseSink::PartitionProcessor {
+                caller,
+                entry_index,
+            } => ServiceInvocationResponseSink::PartitionProcessor {
+                caller,
+                entry_index,
+            },
+            V1ServiceInvocationResponseSink::NewInvocation {
+                target,
+                method,
+                caller_context,
+            } => ServiceInvocationResponseSink::NewInvocation {
+                target,
+                method,
+                caller_context,
+            },
+            V1ServiceInvocationResponseSink::Ingress(node_id) => {
+                ServiceInvocationResponseSink::ingress(node_id, LEGACY_INGRESS_REQUEST_ID)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct V1TimerValue {
+    timer_key: TimerKey,
+    value: V1Timer,
+}
+
+impl From<V1TimerValue> for TimerValue {
+    fn from(value: V1TimerValue) -> Self {
+        TimerValue::new(value.timer_key, value.value.into())
+    }
+}
+
+#[derive(Deserialize)]
+enum V1Timer {
+    CompleteSleepEntry(ServiceId),
+    Invoke(ServiceId, Box<V1ServiceInvocation>),
+}
+
+impl From<V1Timer> for timer_table::Timer {
+    fn from(value: V1Timer) -> Self {
+        match value {
+            V1Timer::CompleteSleepEntry(service_id) => {
+                timer_table::Timer::CompleteSleepEntry(service_id)
+            }
+            V1Timer::Invoke(service_id, service_invocation) => {
+                timer_table::Timer::Invoke(service_id, (*service_invocation).into())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct V1BuiltinServiceEffects {
+    full_invocation_id: FullInvocationId,
+    effects: Vec<V1BuiltinServiceEffect>,
+}
+
+impl From<V1BuiltinServiceEffects> for BuiltinServiceEffects {
+    fn from(value: V1BuiltinServiceEffects) -> Self {
+        BuiltinServiceEffects::new(
+            value.full_invocation_id,
+            value.effects.into_iter().map(Into::into).collect(),
+        )
+    }
+}
+
+#[derive(Deserialize)]
+enum V1BuiltinServiceEffect {
+    CreateJournal {
+        invocation_id: InvocationId,
+        span_context: ServiceInvocationSpanContext,
+        completion_notification_target: NotificationTarget,
+        kill_notification_target: NotificationTarget,
+    },
+    StoreEntry {
+        invocation_id: InvocationId,
+        entry_index: EntryIndex,
+        journal_entry: EnrichedRawEntry,
+    },
+    DropJournal {
+        invocation_id: InvocationId,
+        journal_length: EntryIndex,
+    },
+    SetState {
+        key: Cow<'static, str>,
+        value: Bytes,
+    },
+    ClearState(Cow<'static, str>),
+    OutboxMessage(V1OutboxMessage),
+    DelayedInvoke {
+        target_fid: FullInvocationId,
+        target_method: String,
+        argument: Bytes,
+        source: InvocationSource,
+        response_sink: Option<V1ServiceInvocationResponseSink>,
+        time: MillisSinceEpoch,
+        timer_index: EntryIndex,
+    },
+    End(Option<InvocationError>),
+    IngressResponse(V1IngressResponse),
+}
+
+impl From<V1BuiltinServiceEffect> for BuiltinServiceEffect {
+    fn from(value: V1BuiltinServiceEffect) -> Self {
+        match value {
+            V1BuiltinServiceEffect::CreateJournal {
+                invocation_id,
+                span_context,
+                completion_notification_target,
+                kill_notification_target,
+            } => BuiltinServiceEffect::CreateJournal {
+                invocation_id,
+                span_context,
+                completion_notification_target,
+                kill_notification_target,
+            },
+            V1BuiltinServiceEffect::StoreEntry {
+                invocation_id,
+                entry_index,
+                journal_entry,
+            } => BuiltinServiceEffect::StoreEntry {
+                invocation_id,
+                entry_index,
+                journal_entry,
+            },
+            V1BuiltinServiceEffect::DropJournal {
+                invocation_id,
+                journal_length,
+            } => BuiltinServiceEffect::DropJournal {
+                invocation_id,
+                journal_length,
+            },
+            V1BuiltinServiceEffect::SetState { key, value } => {
+                BuiltinServiceEffect::SetState { key, value }
+            }
+            V1BuiltinServiceEffect::ClearState(key) => BuiltinServiceEffect::ClearState(key),
+            V1BuiltinServiceEffect::OutboxMessage(message) => {
+                BuiltinServiceEffect::OutboxMessage(message.into())
+            }
+            V1BuiltinServiceEffect::DelayedInvoke {
+                target_fid,
+                target_method,
+                argument,
+                source,
+                response_sink,
+                time,
+                timer_index,
+            } => BuiltinServiceEffect::DelayedInvoke {
+                target_fid,
+                target_method,
+                argument,
+                source,
+                response_sink: response_sink.map(Into::into),
+                time,
+                timer_index,
+            },
+            V1BuiltinServiceEffect::End(error) => BuiltinServiceEffect::End(error),
+            V1BuiltinServiceEffect::IngressResponse(response) => {
+                BuiltinServiceEffect::IngressResponse(response.into())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+enum V1OutboxMessage {
+    ServiceInvocation(Box<V1ServiceInvocation>),
+    ServiceResponse(InvocationResponse),
+    InvocationTermination(InvocationTermination),
+}
+
+impl From<V1OutboxMessage> for outbox_table::OutboxMessage {
+    fn from(value: V1OutboxMessage) -> Self {
+        match value {
+            V1OutboxMessage::ServiceInvocation(service_invocation) => {
+                outbox_table::OutboxMessage::ServiceInvocation((*service_invocation).into())
+            }
+            V1OutboxMessage::ServiceResponse(response) => {
+                outbox_table::OutboxMessage::ServiceResponse(response)
+            }
+            V1OutboxMessage::InvocationTermination(termination) => {
+                outbox_table::OutboxMessage::InvocationTermination(termination)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct V1IngressResponse {
+    target_node: GenerationalNodeId,
+    full_invocation_id: FullInvocationId,
+    response: ResponseResult,
+}
+
+impl From<V1IngressResponse> for IngressResponse {
+    fn from(value: V1IngressResponse) -> Self {
+        IngressResponse {
+            target_node: value.target_node,
+            request_id: LEGACY_INGRESS_REQUEST_ID,
+            invocation_id: InvocationId::from(value.full_invocation_id),
+            response: value.response,
+        }
+    }
+}
@@ -22,3 +22,13 @@ pub struct AnnounceLeader {
     /// This is used because the header only contains the plain id.
     node_id: GenerationalNodeId,
 }
+
+impl AnnounceLeader {
+    pub fn new(node_id: GenerationalNodeId) -> Self {
+        Self { node_id }
+    }
+
+    pub fn node_id(&self) -> GenerationalNodeId {
+        self.node_id
+    }
+}
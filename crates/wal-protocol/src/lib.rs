@@ -10,7 +10,9 @@
 
 use assert2::let_assert;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
-use restate_types::invocation::{InvocationResponse, InvocationTermination, ServiceInvocation};
+use restate_types::invocation::{
+    AttachInvocationRequest, InvocationResponse, InvocationTermination, ServiceInvocation,
+};
 use restate_types::message::MessageIndex;
 use restate_types::state_mut::ExternalStateMutation;
 use restate_types::Version;
@@ -20,6 +22,8 @@ use crate::effects::BuiltinServiceEffects;
 use crate::timer::TimerValue;
 use restate_types::{GenerationalNodeId, PlainNodeId};
 
+#[cfg(feature = "serde")]
+pub mod codec;
 pub mod control;
 pub mod effects;
 pub mod timer;
@@ -125,8 +129,12 @@ pub enum Command {
     TerminateInvocation(InvocationTermination),
     /// Start an invocation on this partition
     Invoke(ServiceInvocation),
+    /// Send the result of an existing invocation to an additional response sink
+    AttachInvocation(AttachInvocationRequest),
     /// Outbox can be truncated up to this index
     TruncateOutbox(MessageIndex),
+    /// Register a timer, e.g. to execute an invocation sent by the ingress at a later point in time
+    ScheduleTimer(TimerValue),
 
     // -- Partition processor events for PP
     /// Invoker is reporting effect(s) from an ongoing invocation.
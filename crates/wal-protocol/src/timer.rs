@@ -66,6 +66,22 @@ impl TimerValue {
         }
     }
 
+    pub fn new_clean_invocation_status(
+        full_invocation_id: FullInvocationId,
+        expiry_time: MillisSinceEpoch,
+    ) -> Self {
+        let timer_key = TimerKeyWrapper(TimerKey {
+            invocation_uuid: full_invocation_id.invocation_uuid,
+            timestamp: expiry_time.as_u64(),
+            journal_index: 0,
+        });
+
+        Self {
+            timer_key,
+            value: Timer::CleanInvocationStatus(full_invocation_id.service_id),
+        }
+    }
+
     pub fn into_inner(self) -> (TimerKey, Timer) {
         (self.timer_key.0, self.value)
     }
@@ -13,6 +13,7 @@ use restate_types::identifiers::SubscriptionId;
 use restate_types::invocation::InvocationTermination;
 use restate_types::state_mut::ExternalStateMutation;
 use std::future::Future;
+use std::time::SystemTime;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -40,6 +41,38 @@ pub trait SubscriptionController {
     ) -> impl Future<Output = Result<(), Error>> + Send;
 }
 
+/// Status of the consumer of a subscription.
+#[derive(Debug, Clone)]
+pub struct SubscriptionStatusReport {
+    pub id: SubscriptionId,
+    /// False if the consumer failed and is waiting to be restarted.
+    pub running: bool,
+    /// Partitions currently assigned to the consumer.
+    pub partitions: Vec<SubscriptionPartitionStatus>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubscriptionPartitionStatus {
+    pub topic: String,
+    pub partition: i32,
+    /// Offset committed by the consumer, if any.
+    pub committed_offset: Option<u64>,
+    /// Number of messages the consumer is behind the end of the partition, if known.
+    pub lag: Option<u64>,
+}
+
+/// Struct to access the status of the subscriptions run by this worker
+pub trait SubscriptionStatusHandle {
+    type Iterator: Iterator<Item = SubscriptionStatusReport> + Send;
+
+    /// This method returns a snapshot of the status of the subscriptions currently run by this worker.
+    ///
+    /// The data returned by this method is eventually consistent.
+    fn read_status(&self) -> impl Future<Output = Self::Iterator> + Send;
+}
+
 pub trait Handle {
     /// Send a command to terminate an invocation. This command is best-effort.
     fn terminate_invocation(
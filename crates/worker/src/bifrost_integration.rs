@@ -0,0 +1,157 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! This module contains the glue code for writing the commands of the partition processors to
+//! their Bifrost logs.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
+
+use restate_bifrost::{Bifrost, Error as BifrostError};
+use restate_core::cancellation_watcher;
+use restate_types::identifiers::PartitionId;
+use restate_types::logs::{LogId, Payload};
+use restate_types::message::PartitionTarget;
+use restate_types::retries::RetryPolicy;
+use restate_wal_protocol::{codec, Envelope};
+
+pub(crate) type ProposalSender = mpsc::Sender<PartitionTarget<Envelope>>;
+
+/// The log of a partition has the same id as the partition.
+pub(crate) fn log_id(partition_id: PartitionId) -> LogId {
+    LogId::from(partition_id)
+}
+
+/// Appends the commands proposed for the partitions to the Bifrost logs of the partitions. The
+/// partition processors apply the commands once they read them from their logs.
+pub(crate) struct LogAppender {
+    bifrost: Bifrost,
+    max_batch_size: usize,
+    proposal_rx: mpsc::Receiver<PartitionTarget<Envelope>>,
+    // used to create the ProposalSenders
+    proposal_tx: ProposalSender,
+}
+
+impl LogAppender {
+    pub(crate) fn new(bifrost: Bifrost, channel_size: usize) -> Self {
+        let (proposal_tx, proposal_rx) = mpsc::channel(channel_size);
+        Self {
+            bifrost,
+            max_batch_size: channel_size,
+            proposal_rx,
+            proposal_tx,
+        }
+    }
+
+    pub(crate) fn create_proposal_sender(&self) -> ProposalSender {
+        self.proposal_tx.clone()
+    }
+
+    pub(crate) async fn run(self) -> anyhow::Result<()> {
+        let LogAppender {
+            mut bifrost,
+            max_batch_size,
+            mut proposal_rx,
+            ..
+        } = self;
+
+        debug!("Running the log appender");
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancellation_watcher() => {
+                    break;
+                }
+                Some(proposal) = proposal_rx.recv() => {
+                    // group all pending proposals by partition, keeping the order of proposals
+                    // within a partition
+                    let mut batches: BTreeMap<PartitionId, Vec<Payload>> = BTreeMap::new();
+                    let mut next_proposal = Some(proposal);
+                    let mut batch_size = 0;
+                    while let Some((partition_id, envelope)) = next_proposal.take() {
+                        batches
+                            .entry(partition_id)
+                            .or_default()
+                            .push(codec::encode(&envelope)?);
+                        batch_size += 1;
+                        if batch_size < max_batch_size {
+                            next_proposal = proposal_rx.try_recv().ok();
+                        }
+                    }
+
+                    for (partition_id, payloads) in batches {
+                        Self::append(&mut bifrost, log_id(partition_id), payloads).await;
+                    }
+                }
+            }
+        }
+
+        debug!("Shutting log appender down.");
+        Ok(())
+    }
+
+    /// Appends the payloads to the log, retrying until the append succeeds or the node shuts
+    /// down. Failing appends are retried with backoff rather than failing the appender which is
+    /// shared by all partitions.
+    async fn append(bifrost: &mut Bifrost, log_id: LogId, payloads: Vec<Payload>) {
+        let mut retry_iter = RetryPolicy::exponential(
+            Duration::from_millis(10),
+            2.0,
+            usize::MAX,
+            Some(Duration::from_secs(5)),
+        )
+        .into_iter();
+
+        loop {
+            let version = bifrost.version();
+            let result = match bifrost.append_batch(log_id, payloads.clone()).await {
+                Ok(lsns) => {
+                    trace!(%log_id, ?lsns, "Appended proposals");
+                    return;
+                }
+                Err(BifrostError::LogSealed(..)) => {
+                    // Sealed logs accept appends again once they are continued in a new segment
+                    debug!(%log_id, "Log is sealed, retrying append once it is continued");
+                    tokio::select! {
+                        _ = cancellation_watcher() => {
+                            debug!(%log_id, "Dropping proposals for sealed log on shutdown");
+                            return;
+                        }
+                        result = bifrost.wait_for_version_after(version) => result,
+                    }
+                }
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(()) => {}
+                Err(BifrostError::Shutdown) => {
+                    debug!(%log_id, "Dropping proposals on shutdown");
+                    return;
+                }
+                Err(err) => {
+                    let delay = retry_iter.next().expect("unlimited retries");
+                    warn!(%log_id, "Failed appending proposals, retrying in {delay:?}: {err}");
+                    tokio::select! {
+                        _ = cancellation_watcher() => {
+                            debug!(%log_id, "Dropping proposals on shutdown");
+                            return;
+                        }
+                        _ = tokio::time::sleep(delay) => {}
+                    }
+                }
+            }
+        }
+    }
+}
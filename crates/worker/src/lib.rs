@@ -10,12 +10,13 @@
 
 extern crate core;
 
+use crate::bifrost_integration::{LogAppender, ProposalSender};
 use crate::invoker_integration::EntryEnricher;
 use crate::partition::storage::invoker::InvokerStorageReader;
 use crate::services::Services;
 use codederror::CodedError;
 use partition::shuffle;
-use restate_consensus::Consensus;
+use restate_bifrost::Bifrost;
 use restate_core::{cancellation_watcher, task_center, TaskKind};
 use restate_ingress_dispatcher::{
     IngressDispatcherInputSender, Service as IngressDispatcherService,
@@ -31,14 +32,15 @@ use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_storage_query_postgres::service::PostgresQueryService;
 use restate_storage_rocksdb::{RocksDBStorage, RocksDBWriter};
-use restate_types::identifiers::{PartitionKey, PeerId};
-use restate_types::message::PartitionTarget;
+use restate_types::identifiers::{PartitionId, PartitionKey, PeerId};
+use std::collections::HashSet;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tracing::debug;
 use util::IdentitySender;
 
+mod bifrost_integration;
 mod invoker_integration;
 mod metric_definitions;
 mod network_integration;
@@ -85,9 +87,10 @@ use restate_types::Version;
 use restate_wal_protocol::Envelope;
 pub use services::WorkerCommandSender;
 
-type PartitionProcessorCommand = Envelope;
-type ConsensusCommand = restate_consensus::Command<PartitionProcessorCommand>;
-type ConsensusMsg = PartitionTarget<PartitionProcessorCommand>;
+/// Sends the ids of the partitions which this worker should lead. The partition processors of
+/// all other partitions become followers.
+pub type PartitionLeadershipSender = mpsc::Sender<HashSet<PartitionId>>;
+
 type PartitionProcessor = partition::PartitionProcessor<
     ProtobufRawEntryCodec,
     InvokerChannelServiceHandle,
@@ -164,9 +167,14 @@ impl Options {
         &self.storage_rocksdb.path
     }
 
-    pub fn build(self, networking: Networking, schemas: Schemas) -> Result<Worker, BuildError> {
+    pub fn build(
+        self,
+        networking: Networking,
+        bifrost: Bifrost,
+        schemas: Schemas,
+    ) -> Result<Worker, BuildError> {
         metric_definitions::describe_metrics();
-        Worker::new(self, networking, schemas)
+        Worker::new(self, networking, bifrost, schemas)
     }
 }
 
@@ -193,7 +201,10 @@ impl Error {
 }
 
 pub struct Worker {
-    consensus: Consensus<PartitionProcessorCommand>,
+    log_appender: LogAppender,
+    leadership_tx: PartitionLeadershipSender,
+    leadership_rx: mpsc::Receiver<HashSet<PartitionId>>,
+    leaders_tx: watch::Sender<HashSet<PartitionId>>,
     processors: Vec<PartitionProcessor>,
     networking: Networking,
     network: network_integration::Network,
@@ -219,6 +230,7 @@ impl Worker {
     pub fn new(
         opts: Options,
         networking: Networking,
+        bifrost: Bifrost,
         schemas: Schemas,
     ) -> Result<Self, BuildError> {
         let Options {
@@ -233,8 +245,6 @@ impl Worker {
             ..
         } = opts;
 
-        let (raft_in_tx, raft_in_rx) = mpsc::channel(channel_size);
-
         let ingress_dispatcher_service = IngressDispatcherService::new(channel_size);
 
         // ingress_grpc
@@ -256,17 +266,18 @@ impl Worker {
         let partition_table = FixedPartitionTable::new(Version::MIN, opts.partitions);
         let partitioner = partition_table.partitioner();
 
+        let log_appender = LogAppender::new(bifrost.clone(), channel_size);
+        let (leadership_tx, leadership_rx) = mpsc::channel(1);
+        let (leaders_tx, leaders_rx) = watch::channel(HashSet::new());
+
         let network = network_integration::Network::new(
-            raft_in_tx,
+            log_appender.create_proposal_sender(),
             ingress_dispatcher_service.create_ingress_dispatcher_input_sender(),
             Arc::new(partition_table),
             channel_size,
         );
         let network_ingress_sender = network.create_ingress_sender();
 
-        let mut consensus =
-            Consensus::new(raft_in_rx, network.create_consensus_sender(), channel_size);
-
         let network_handle = network.create_network_handle();
 
         let (rocksdb_storage, rocksdb_writer) = storage_rocksdb.build()?;
@@ -282,13 +293,14 @@ impl Worker {
         let storage_query_context = storage_query_datafusion.build(
             rocksdb_storage.clone(),
             invoker.status_reader(),
+            ingress_kafka.status_reader(),
             schemas.clone(),
         )?;
         let storage_query_postgres = storage_query_postgres.build(storage_query_context.clone());
 
-        let (command_senders, processors): (Vec<_>, Vec<_>) = partitioner
+        let processors = partitioner
             .map(|(idx, partition_range)| {
-                let proposal_sender = consensus.create_proposal_sender();
+                let proposal_sender = log_appender.create_proposal_sender();
                 let invoker_sender = invoker.handle();
 
                 Self::create_partition_processor(
@@ -296,6 +308,8 @@ impl Worker {
                     partition_range,
                     timers.clone(),
                     channel_size,
+                    bifrost.clone(),
+                    leaders_rx.clone(),
                     proposal_sender,
                     invoker_sender,
                     network_handle.clone(),
@@ -306,18 +320,19 @@ impl Worker {
                     ingress_dispatcher_service.create_ingress_dispatcher_input_sender(),
                 )
             })
-            .unzip();
-
-        consensus.register_state_machines(command_senders);
+            .collect();
 
         let services = Services::new(
-            consensus.create_proposal_sender(),
+            log_appender.create_proposal_sender(),
             subscription_controller_handle,
             channel_size,
         );
 
         Ok(Self {
-            consensus,
+            log_appender,
+            leadership_tx,
+            leadership_rx,
+            leaders_tx,
             processors,
             networking,
             network,
@@ -340,7 +355,9 @@ impl Worker {
         partition_key_range: RangeInclusive<PartitionKey>,
         timer_service_options: restate_timer::Options,
         channel_size: usize,
-        proposal_sender: mpsc::Sender<ConsensusMsg>,
+        bifrost: Bifrost,
+        leaders_rx: watch::Receiver<HashSet<PartitionId>>,
+        proposal_sender: ProposalSender,
         invoker_sender: InvokerChannelServiceHandle,
         network_handle: UnboundedNetworkHandle<shuffle::ShuffleInput, Envelope>,
         ack_sender: PartitionProcessorSender<partition::types::AckResponse>,
@@ -348,15 +365,15 @@ impl Worker {
         schemas: Schemas,
         partition_processor_options: partition::Options,
         ingress_tx: IngressDispatcherInputSender,
-    ) -> ((PeerId, mpsc::Sender<ConsensusCommand>), PartitionProcessor) {
-        let (command_tx, command_rx) = mpsc::channel(channel_size);
-        let processor = PartitionProcessor::new(
+    ) -> PartitionProcessor {
+        PartitionProcessor::new(
             peer_id,
             peer_id,
             partition_key_range,
             timer_service_options,
             channel_size,
-            command_rx,
+            bifrost,
+            leaders_rx,
             IdentitySender::new(peer_id, proposal_sender),
             invoker_sender,
             network_handle,
@@ -365,15 +382,17 @@ impl Worker {
             schemas,
             partition_processor_options,
             ingress_tx,
-        );
-
-        ((peer_id, command_tx), processor)
+        )
     }
 
     pub fn worker_command_tx(&self) -> WorkerCommandSender {
         self.services.worker_command_tx()
     }
 
+    pub fn partition_leadership_tx(&self) -> PartitionLeadershipSender {
+        self.leadership_tx.clone()
+    }
+
     pub fn subscription_controller_handle(&self) -> SubscriptionControllerHandle {
         self.services.subscription_controller_handler()
     }
@@ -444,12 +463,20 @@ impl Worker {
             self.ingress_kafka.run(),
         )?;
 
-        // Consensus Service
+        // Log appender
         tc.spawn_child(
             TaskKind::SystemService,
-            "consensus",
+            "log-appender",
             None,
-            self.consensus.run(),
+            self.log_appender.run(),
+        )?;
+
+        // Partition leadership
+        tc.spawn_child(
+            TaskKind::SystemService,
+            "partition-leadership",
+            None,
+            Self::distribute_leadership(self.leadership_rx, self.leaders_tx),
         )?;
 
         // Create partition processors
@@ -483,4 +510,29 @@ impl Worker {
 
         Ok(())
     }
+
+    /// Forwards the partitions which this worker should lead to all partition processors.
+    async fn distribute_leadership(
+        mut leadership_rx: mpsc::Receiver<HashSet<PartitionId>>,
+        leaders_tx: watch::Sender<HashSet<PartitionId>>,
+    ) -> anyhow::Result<()> {
+        loop {
+            tokio::select! {
+                _ = cancellation_watcher() => {
+                    break;
+                }
+                Some(leaders) = leadership_rx.recv() => {
+                    leaders_tx.send_if_modified(|current_leaders| {
+                        if *current_leaders != leaders {
+                            *current_leaders = leaders;
+                            true
+                        } else {
+                            false
+                        }
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 }
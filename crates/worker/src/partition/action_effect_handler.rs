@@ -12,6 +12,7 @@ use super::leadership::ActionEffect;
 use crate::partition::ConsensusWriter;
 use restate_core::metadata;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
+use restate_wal_protocol::control::AnnounceLeader;
 use restate_wal_protocol::effects::BuiltinServiceEffects;
 use restate_wal_protocol::{AckMode, Command, Destination, Envelope, Header, Source};
 use std::ops::RangeInclusive;
@@ -39,6 +40,20 @@ impl ActionEffectHandler {
         }
     }
 
+    /// Announces this node as the leader of the partition. Once applied, all proposals of
+    /// previous leader epochs are fenced off.
+    pub(super) async fn announce_leader(&self) {
+        let header = self.create_header(*self.partition_key_range.start());
+        // Err only if the consensus module is shutting down
+        let _ = self
+            .consensus_writer
+            .send(Envelope::new(
+                header,
+                Command::AnnounceLeader(AnnounceLeader::new(metadata().my_node_id())),
+            ))
+            .await;
+    }
+
     pub(super) async fn handle(&self, actuator_output: ActionEffect) {
         match actuator_output {
             ActionEffect::Invoker(invoker_output) => {
@@ -112,7 +127,7 @@ impl ActionEffectHandler {
                 partition_id: self.partition_id,
                 partition_key: Some(partition_key),
                 leader_epoch: self.leader_epoch,
-                // todo: Add support for deduplicating self proposals
+                // self proposals are fenced by the leader epoch
                 sequence_number: None,
                 node_id: metadata().my_node_id().as_plain(),
             },
@@ -107,12 +107,14 @@ where
         match action {
             Action::Invoke {
                 full_invocation_id,
+                method,
                 invoke_input_journal,
             } => {
                 invoker_tx
                     .invoke(
                         partition_leader_epoch,
                         full_invocation_id,
+                        method,
                         invoke_input_journal,
                     )
                     .await?
@@ -163,7 +165,7 @@ where
                         full_invocation_id,
                         method.deref(),
                         span_context,
-                        response_sink,
+                        response_sink.into_iter().collect(),
                         argument,
                     )
                     .await;
@@ -256,6 +256,7 @@ where
             .await
             .map_err(Error::Invoker)?;
 
+        let mut invoked_services = Vec::new();
         let mut built_in_invoked_services = Vec::new();
 
         {
@@ -268,20 +269,32 @@ where
                 if !non_deterministic::ServiceInvoker::is_supported(
                     &full_invocation_id.service_id.service_name,
                 ) {
-                    invoker_handle
-                        .invoke(
-                            partition_leader_epoch,
-                            full_invocation_id,
-                            InvokeInputJournal::NoCachedJournal,
-                        )
-                        .await
-                        .map_err(Error::Invoker)?;
+                    invoked_services.push(full_invocation_id);
                 } else {
                     built_in_invoked_services.push(full_invocation_id);
                 }
             }
         }
 
+        for full_invocation_id in invoked_services {
+            // the invoker needs the method to account the invocation against its handler quota
+            let status = partition_storage
+                .get_invocation_status(&InvocationId::from(&full_invocation_id))
+                .await?;
+
+            let_assert!(InvocationStatus::Invoked(metadata) = status);
+
+            invoker_handle
+                .invoke(
+                    partition_leader_epoch,
+                    full_invocation_id,
+                    metadata.method,
+                    InvokeInputJournal::NoCachedJournal,
+                )
+                .await
+                .map_err(Error::Invoker)?;
+        }
+
         for full_invocation_id in built_in_invoked_services {
             let input_entry = partition_storage
                 .load_journal_entry(&InvocationId::from(&full_invocation_id), 0)
@@ -302,14 +315,14 @@ where
             let_assert!(InvocationStatus::Invoked(metadata) = status);
 
             let method = metadata.method;
-            let response_sink = metadata.response_sink;
+            let response_sinks = metadata.response_sinks.into_iter().collect();
             let argument = input_entry.serialized_entry().clone();
             built_in_service_invoker
                 .invoke(
                     full_invocation_id,
                     &method,
                     metadata.journal_metadata.span_context,
-                    response_sink,
+                    response_sinks,
                     argument,
                 )
                 .await;
@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::bifrost_integration;
 use crate::metric_definitions::{PARTITION_ACTUATOR_HANDLED, PARTITION_TIMER_DUE_HANDLED};
 use crate::partition::action_effect_handler::ActionEffectHandler;
 use crate::partition::leadership::{ActionEffect, LeadershipState, TaskResult};
@@ -16,19 +17,25 @@ use crate::partition::state_machine::{
 };
 use crate::partition::storage::{PartitionStorage, Transaction};
 use crate::util::IdentitySender;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use metrics::counter;
+use restate_bifrost::{Bifrost, LogReadStream, LogRecord, Record};
+use restate_core::{cancellation_watcher, metadata};
 use restate_network::Networking;
 use restate_schema_impl::Schemas;
 use restate_storage_rocksdb::RocksDBStorage;
-use restate_types::identifiers::{PartitionId, PartitionKey, PeerId};
+use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, PeerId};
+use restate_types::logs::{Lsn, SequenceNumber};
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
-use tracing::{debug, info, instrument};
+use tokio::sync::watch;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, info, instrument, warn};
 
 mod action_effect_handler;
 mod leadership;
@@ -40,14 +47,33 @@ pub mod storage;
 pub mod types;
 
 use crate::partition::types::AckResponse;
-pub use options::Options;
-use restate_wal_protocol::Envelope;
+pub use options::{CompletedInvocationRetention, Options};
+use restate_wal_protocol::{codec, Envelope};
 
-type ConsensusReader = mpsc::Receiver<restate_consensus::Command<Envelope>>;
 type ConsensusWriter = IdentitySender<Envelope>;
+/// Receives the partitions which this node should lead.
+type LeadershipReader = watch::Receiver<HashSet<PartitionId>>;
 use restate_ingress_dispatcher::IngressDispatcherInputSender;
 
-#[derive(Debug)]
+/// The partition log is trimmed beyond the applied LSN of the partition and there is no
+/// snapshot that covers the trimmed records.
+#[derive(Debug, thiserror::Error)]
+#[error("partition {partition_id} has applied the log up to {applied_lsn} but the log is trimmed up to {trim_point}; a snapshot containing the trimmed records is required to continue")]
+pub struct LogTrimmedError {
+    pub partition_id: PartitionId,
+    pub applied_lsn: Lsn,
+    pub trim_point: Lsn,
+}
+
+/// A record of the partition log.
+enum LogEntry {
+    Envelope(Lsn, Box<Envelope>),
+    /// The records up to the given LSN have been trimmed.
+    TrimGap(Lsn),
+    /// The log is sealed, the read stream continues once the log is extended.
+    Seal,
+}
+
 pub(super) struct PartitionProcessor<RawEntryCodec, InvokerInputSender, NetworkHandle> {
     peer_id: PeerId,
     pub partition_id: PartitionId,
@@ -56,7 +82,8 @@ pub(super) struct PartitionProcessor<RawEntryCodec, InvokerInputSender, NetworkH
     timer_service_options: restate_timer::Options,
     channel_size: usize,
 
-    consensus_reader: ConsensusReader,
+    bifrost: Bifrost,
+    leadership_reader: LeadershipReader,
     consensus_writer: ConsensusWriter,
 
     invoker_tx: InvokerInputSender,
@@ -90,7 +117,8 @@ where
         partition_key_range: RangeInclusive<PartitionKey>,
         timer_service_options: restate_timer::Options,
         channel_size: usize,
-        consensus_reader: ConsensusReader,
+        bifrost: Bifrost,
+        leadership_reader: LeadershipReader,
         consensus_writer: ConsensusWriter,
         invoker_tx: InvokerInputSender,
         network_handle: NetworkHandle,
@@ -106,7 +134,8 @@ where
             partition_key_range,
             timer_service_options,
             channel_size,
-            consensus_reader,
+            bifrost,
+            leadership_reader,
             consensus_writer,
             invoker_tx,
             network_handle,
@@ -127,7 +156,8 @@ where
             partition_key_range,
             timer_service_options,
             channel_size,
-            mut consensus_reader,
+            bifrost,
+            mut leadership_reader,
             invoker_tx,
             network_handle,
             consensus_writer,
@@ -157,74 +187,159 @@ where
             ingress_tx,
         );
 
-        let mut state_machine =
-            Self::create_state_machine::<RawEntryCodec>(&mut partition_storage).await?;
+        let snapshots_path = options.snapshots_path.as_ref().map(PathBuf::from);
+
+        // Continue reading the log after the last record whose effects have been committed
+        let mut applied_lsn = partition_storage.load_applied_lsn().await?;
+        if applied_lsn == Lsn::INVALID {
+            if let Some(snapshots_path) = &snapshots_path {
+                if let Some(snapshot_lsn) = partition_storage
+                    .bootstrap_from_snapshot(snapshots_path)
+                    .await?
+                {
+                    info!(restate.partition.id = %partition_id, %snapshot_lsn, "Bootstrapped partition from snapshot");
+                    applied_lsn = snapshot_lsn;
+                }
+            }
+        }
+
+        let mut state_machine = Self::create_state_machine::<RawEntryCodec>(
+            &mut partition_storage,
+            options.completed_invocation_retention(),
+        )
+        .await?;
+
+        debug!(restate.partition.id = %partition_id, %applied_lsn, "Reading partition log");
+        let mut log_reader = bifrost
+            .create_reader(bifrost_integration::log_id(partition_id), applied_lsn)
+            .with_prefetch(channel_size);
 
         let mut actuator_output_handler = None;
+        // Last leader epoch of this partition processor
+        let mut leader_epoch: Option<LeaderEpoch> = None;
+
+        // Make sure that the initial leadership is picked up
+        leadership_reader.mark_changed();
+
+        let mut snapshot_interval =
+            snapshots_path
+                .as_ref()
+                .and(options.snapshot_interval)
+                .map(|snapshot_interval| {
+                    let snapshot_interval: Duration = snapshot_interval.into();
+                    let mut interval = tokio::time::interval_at(
+                        tokio::time::Instant::now() + snapshot_interval,
+                        snapshot_interval,
+                    );
+                    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                    interval
+                });
 
         loop {
             tokio::select! {
-                mut next_command = consensus_reader.recv() => {
-                    if next_command.is_none() {
-                        break;
-                    }
+                _ = cancellation_watcher() => {
+                    break;
+                },
+                Ok(()) = leadership_reader.changed() => {
+                    let should_lead = leadership_reader.borrow_and_update().contains(&partition_id);
+
+                    if should_lead && !leadership_state.is_leader() {
+                        // Continue after the last announced epoch, which can stem from another node
+                        let last_leader_epoch = leader_epoch
+                            .max(partition_storage.load_last_leader_epoch().await?)
+                            .max(state_machine.announced_leader().map(|announced_leader| announced_leader.leader_epoch));
+                        let next_leader_epoch = last_leader_epoch.map_or(LeaderEpoch::INITIAL, LeaderEpoch::next);
+                        leader_epoch = Some(next_leader_epoch);
+                        debug!(restate.partition.peer = %peer_id, restate.partition.id = %partition_id, restate.partition.leader_epoch = %next_leader_epoch, "Become leader");
+
+                        (actuator_stream, leadership_state) = leadership_state.become_leader(
+                            next_leader_epoch,
+                            partition_key_range.clone(),
+                            &mut partition_storage,
+                            &schemas,
+                        )
+                        .await?;
 
-                    while let Some(command) = next_command.take() {
-                        match command {
-                            restate_consensus::Command::Apply(command) => {
-                                // Clear the effects to reuse the vector
-                                effects.clear();
-
-                                // Prepare transaction
-                                let transaction = partition_storage.create_transaction();
-
-                                // Prepare message collector
-                                let is_leader = leadership_state.is_leader();
-                                let message_collector = leadership_state.into_message_collector();
-
-                                let (application_result, command) = Self::apply_envelope(
-                                    &mut state_machine,
-                                    command,
-                                    &mut effects,
-                                    transaction,
-                                    message_collector,
-                                    is_leader,
-                                    &mut consensus_reader,
-                                    options.max_batch_duration.map(Into::into))
-                                .await?;
-
-                                next_command = command;
-
-                                // Commit actuator messages
-                                let message_collector = application_result.commit().await?;
-                                leadership_state = message_collector.send().await?;
+                        let handler = ActionEffectHandler::new(partition_id, next_leader_epoch, partition_key_range.clone(), consensus_writer.clone());
+                        handler.announce_leader().await;
+                        actuator_output_handler = Some(handler);
+                    } else if !should_lead && leadership_state.is_leader() {
+                        info!(restate.partition.peer = %peer_id, restate.partition.id = %partition_id, "Become follower");
+                        (actuator_stream, leadership_state) = leadership_state.become_follower().await?;
+                        actuator_output_handler = None;
+                    }
+                },
+                record = log_reader.next() => {
+                    let record = record.ok_or_else(|| anyhow::anyhow!("log read stream is closed"))??;
+                    let trim_gap = match Self::decode_record(record)? {
+                        LogEntry::Envelope(lsn, envelope) => {
+                            // Clear the effects to reuse the vector
+                            effects.clear();
+
+                            // Prepare transaction
+                            let transaction = partition_storage.create_transaction();
+
+                            // Prepare message collector
+                            let is_leader = leadership_state.is_leader();
+                            let message_collector = leadership_state.into_message_collector();
+
+                            let (application_result, trim_gap) = Self::apply_envelopes(
+                                &mut state_machine,
+                                lsn,
+                                *envelope,
+                                &mut effects,
+                                transaction,
+                                message_collector,
+                                is_leader,
+                                &mut log_reader,
+                                options.max_batch_duration.map(Into::into))
+                            .await?;
+
+                            // Commit actuator messages
+                            let message_collector = application_result.commit().await?;
+                            leadership_state = message_collector.send().await?;
+
+                            // Another node announced itself for our leader epoch first, hence our
+                            // proposals are fenced and we must not act as leader anymore
+                            if let Some(announced_leader) = state_machine.announced_leader() {
+                                if leadership_state.is_leader() && leader_epoch == Some(announced_leader.leader_epoch) && announced_leader.node_id != metadata().my_node_id() {
+                                    info!(restate.partition.peer = %peer_id, restate.partition.id = %partition_id, restate.partition.leader_epoch = %announced_leader.leader_epoch, leader = %announced_leader.node_id, "Lost leader election, become follower");
+                                    (actuator_stream, leadership_state) = leadership_state.become_follower().await?;
+                                    actuator_output_handler = None;
+                                }
                             }
-                            restate_consensus::Command::BecomeLeader(leader_epoch) => {
-                                debug!(restate.partition.peer = %peer_id, restate.partition.id = %partition_id, restate.partition.leader_epoch = %leader_epoch, "Become leader");
 
-                                (actuator_stream, leadership_state) = leadership_state.become_leader(
-                                    leader_epoch,
-                                    partition_key_range.clone(),
-                                    &mut partition_storage,
-                                    &schemas,
-
-                                )
-                                .await?;
-
-                                actuator_output_handler = Some(ActionEffectHandler::new(partition_id, leader_epoch, partition_key_range.clone(), consensus_writer.clone()));
-                            }
-                            restate_consensus::Command::BecomeFollower => {
-                                info!(restate.partition.peer = %peer_id, restate.partition.id = %partition_id, "Become follower");
-                                (actuator_stream, leadership_state) = leadership_state.become_follower().await?;
-                                actuator_output_handler = None;
-                            },
-                            restate_consensus::Command::ApplySnapshot => {
-                                unimplemented!("Not supported yet.");
-                            }
-                            restate_consensus::Command::CreateSnapshot => {
-                                unimplemented!("Not supported yet.");
-                            }
-                        }
+                            trim_gap
+                        },
+                        LogEntry::TrimGap(trim_point) => Some(trim_point),
+                        LogEntry::Seal => {
+                            debug!(restate.partition.id = %partition_id, "Partition log is sealed, waiting for it to be continued");
+                            None
+                        },
+                    };
+
+                    if let Some(trim_point) = trim_gap {
+                        (actuator_stream, leadership_state) = leadership_state.become_follower().await?;
+                        actuator_output_handler = None;
+                        (state_machine, log_reader) = Self::restore_after_trim_gap(
+                            partition_id,
+                            &mut partition_storage,
+                            snapshots_path.as_deref(),
+                            trim_point,
+                            &bifrost,
+                            channel_size,
+                            options.completed_invocation_retention(),
+                        )
+                        .await?;
+                        // Lead again if this node is supposed to
+                        leadership_reader.mark_changed();
+                    }
+                },
+                _ = Self::next_snapshot_tick(&mut snapshot_interval) => {
+                    let snapshots_path = snapshots_path.as_ref().expect("snapshots are only scheduled if a snapshots path is configured");
+                    match partition_storage.create_snapshot(snapshots_path).await {
+                        Ok(metadata) => debug!(restate.partition.id = %partition_id, applied_lsn = %metadata.applied_lsn, "Created partition snapshot"),
+                        Err(err) => warn!(restate.partition.id = %partition_id, "Failed creating partition snapshot: {err}"),
                     }
                 },
                 actuator_output = actuator_stream.next() => {
@@ -253,6 +368,7 @@ where
 
     async fn create_state_machine<Codec>(
         partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        completed_invocation_retention: CompletedInvocationRetention,
     ) -> Result<DeduplicatingStateMachine<Codec>, restate_storage_api::StorageError>
     where
         Codec: restate_types::journal::raw::RawEntryCodec + Default + Debug,
@@ -260,31 +376,97 @@ where
         let inbox_seq_number = partition_storage.load_inbox_seq_number().await?;
         let outbox_seq_number = partition_storage.load_outbox_seq_number().await?;
 
-        let state_machine = DeduplicatingStateMachine::new(inbox_seq_number, outbox_seq_number);
+        let state_machine = DeduplicatingStateMachine::new(inbox_seq_number, outbox_seq_number)
+            .with_completed_invocation_retention(completed_invocation_retention)
+            .with_announced_leader(partition_storage.load_announced_leader().await?);
 
         Ok(state_machine)
     }
 
+    async fn next_snapshot_tick(snapshot_interval: &mut Option<Interval>) {
+        match snapshot_interval {
+            Some(snapshot_interval) => {
+                snapshot_interval.tick().await;
+            }
+            None => futures::future::pending().await,
+        }
+    }
+
+    fn decode_record(record: LogRecord) -> anyhow::Result<LogEntry> {
+        match record.record {
+            Record::Data(payload) => Ok(LogEntry::Envelope(
+                record.offset,
+                Box::new(codec::decode(&payload)?),
+            )),
+            Record::TrimGap(trim_gap) => Ok(LogEntry::TrimGap(trim_gap.until)),
+            Record::Seal(_) => Ok(LogEntry::Seal),
+        }
+    }
+
+    /// Replaces the partition state with a snapshot that contains the records which were
+    /// trimmed from the log and continues reading the log after the snapshot. Fails with a
+    /// [`LogTrimmedError`] if there is no such snapshot. Must only be called as follower.
+    async fn restore_after_trim_gap<Codec>(
+        partition_id: PartitionId,
+        partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        snapshots_path: Option<&Path>,
+        trim_point: Lsn,
+        bifrost: &Bifrost,
+        channel_size: usize,
+        completed_invocation_retention: CompletedInvocationRetention,
+    ) -> anyhow::Result<(DeduplicatingStateMachine<Codec>, LogReadStream)>
+    where
+        Codec: restate_types::journal::raw::RawEntryCodec + Default + Debug,
+    {
+        let applied_lsn = partition_storage.load_applied_lsn().await?;
+        let snapshot_lsn = match snapshots_path {
+            Some(snapshots_path) => {
+                partition_storage
+                    .restore_from_snapshot(snapshots_path, trim_point)
+                    .await?
+            }
+            None => None,
+        };
+        let Some(snapshot_lsn) = snapshot_lsn else {
+            return Err(LogTrimmedError {
+                partition_id,
+                applied_lsn,
+                trim_point,
+            }
+            .into());
+        };
+        info!(restate.partition.id = %partition_id, %applied_lsn, %trim_point, %snapshot_lsn, "Partition log is trimmed, restored partition from snapshot");
+
+        let state_machine =
+            Self::create_state_machine(partition_storage, completed_invocation_retention).await?;
+        let log_reader = bifrost
+            .create_reader(bifrost_integration::log_id(partition_id), snapshot_lsn)
+            .with_prefetch(channel_size);
+        Ok((state_machine, log_reader))
+    }
+
+    /// Applies the given envelope and all envelopes that have already been read from the log,
+    /// within `max_batch_duration`, in a single transaction. The LSN of the last applied
+    /// envelope is stored as part of the transaction. Returns the end of a trim gap that ended
+    /// the batch, it needs to be handled once the transaction is committed.
     #[allow(clippy::too_many_arguments)]
-    pub async fn apply_envelope<
+    pub async fn apply_envelopes<
         TransactionType: restate_storage_api::Transaction + Send,
         Collector: ActionCollector,
     >(
         state_machine: &mut DeduplicatingStateMachine<RawEntryCodec>,
+        lsn: Lsn,
         envelope: Envelope,
         effects: &mut Effects,
         transaction: Transaction<TransactionType>,
         message_collector: Collector,
         is_leader: bool,
-        consensus_reader: &mut ConsensusReader,
+        log_reader: &mut LogReadStream,
         max_batch_duration: Option<Duration>,
-    ) -> Result<
-        (
-            InterpretationResult<Transaction<TransactionType>, Collector>,
-            Option<restate_consensus::Command<Envelope>>,
-        ),
-        state_machine::Error,
-    > {
+    ) -> anyhow::Result<(
+        InterpretationResult<Transaction<TransactionType>, Collector>,
+        Option<Lsn>,
+    )> {
         let max_batch_duration_start =
             max_batch_duration.map(|duration| (duration, Instant::now()));
 
@@ -292,26 +474,41 @@ where
         let mut application_result = state_machine
             .apply(envelope, effects, transaction, message_collector, is_leader)
             .await?;
+        let mut applied_lsn = lsn;
+        let mut trim_gap = None;
 
         while max_batch_duration_start
             .map(|(max_duration, start)| start.elapsed() < max_duration)
             .unwrap_or(true)
         {
-            if let Ok(command) = consensus_reader.try_recv() {
-                if let restate_consensus::Command::Apply(envelope) = command {
-                    let (transaction, message_collector) = application_result.into_inner();
-                    application_result = state_machine
-                        .apply(envelope, effects, transaction, message_collector, is_leader)
-                        .await?;
-                } else {
-                    return Ok((application_result, Some(command)));
-                }
-            } else {
+            // Only batch records which are available without waiting
+            let Some(Some(record)) = log_reader.next().now_or_never() else {
                 break;
-            }
+            };
+            let (lsn, envelope) = match Self::decode_record(record?)? {
+                LogEntry::Envelope(lsn, envelope) => (lsn, *envelope),
+                LogEntry::TrimGap(trim_point) => {
+                    trim_gap = Some(trim_point);
+                    break;
+                }
+                // the read stream waits for the log to be continued
+                LogEntry::Seal => break,
+            };
+
+            let (transaction, message_collector) = application_result.into_inner();
+            application_result = state_machine
+                .apply(envelope, effects, transaction, message_collector, is_leader)
+                .await?;
+            applied_lsn = lsn;
         }
 
-        Ok((application_result, None))
+        let (mut transaction, message_collector) = application_result.into_inner();
+        transaction.store_applied_lsn(applied_lsn).await;
+
+        Ok((
+            InterpretationResult::new(transaction, message_collector),
+            trim_gap,
+        ))
     }
 }
 
@@ -9,6 +9,7 @@
 // by the Apache License, Version 2.0.
 
 use serde_with::serde_as;
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Partition processor options
@@ -30,12 +31,103 @@ pub struct Options {
     #[serde_as(as = "serde_with::NoneAsEmptyString")]
     #[cfg_attr(feature = "options_schema", schemars(with = "Option<String>"))]
     pub max_batch_duration: Option<humantime::Duration>,
+
+    /// # Snapshots path
+    ///
+    /// Directory which contains the snapshots of the partitions, one subdirectory per partition
+    /// id. A partition processor whose partition store is empty bootstraps its partition from the
+    /// snapshot in this directory, if one exists, and continues reading its log after the LSN
+    /// of the snapshot.
+    pub snapshots_path: Option<String>,
+
+    /// # Snapshot interval
+    ///
+    /// Interval in which the partition processors write a snapshot of their partition to the
+    /// snapshots path. If unset, no snapshots are written.
+    #[serde_as(as = "serde_with::NoneAsEmptyString")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "Option<String>"))]
+    pub snapshot_interval: Option<humantime::Duration>,
+
+    /// # Completed invocation retention
+    ///
+    /// Duration for which the status, the result and the journal of completed invocations are
+    /// retained, so that they can be queried after the invocation has finished. A duration of
+    /// zero removes invocations as soon as they complete.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub completed_invocation_retention: humantime::Duration,
+
+    /// # Completed invocation retention overrides
+    ///
+    /// Overrides the completed invocation retention for individual services or methods. The keys
+    /// have the format `<service>` to apply to all methods of a service, or `<service>/<method>`
+    /// to apply to a single method.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "HashMap<serde_with::Same, serde_with::DisplayFromStr>")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "HashMap<String, String>"))]
+    pub completed_invocation_retention_overrides: HashMap<String, humantime::Duration>,
+}
+
+impl Options {
+    pub fn completed_invocation_retention(&self) -> CompletedInvocationRetention {
+        let mut retention = CompletedInvocationRetention {
+            default: self.completed_invocation_retention.into(),
+            ..CompletedInvocationRetention::default()
+        };
+
+        for (key, duration) in &self.completed_invocation_retention_overrides {
+            match key.split_once('/') {
+                Some((service, method)) => {
+                    retention
+                        .methods
+                        .entry(service.to_owned())
+                        .or_default()
+                        .insert(method.to_owned(), (*duration).into());
+                }
+                None => {
+                    retention
+                        .services
+                        .insert(key.to_owned(), (*duration).into());
+                }
+            }
+        }
+
+        retention
+    }
+}
+
+/// Retention of completed invocations, resolved from the [`Options`].
+#[derive(Debug, Clone, Default)]
+pub struct CompletedInvocationRetention {
+    default: Duration,
+    services: HashMap<String, Duration>,
+    // service name -> method name -> retention
+    methods: HashMap<String, HashMap<String, Duration>>,
+}
+
+impl CompletedInvocationRetention {
+    /// Returns for how long completed invocations of the given method are retained.
+    pub fn retention(&self, service_name: &str, method_name: &str) -> Duration {
+        self.methods
+            .get(service_name)
+            .and_then(|methods| methods.get(method_name))
+            .or_else(|| self.services.get(service_name))
+            .copied()
+            .unwrap_or(self.default)
+    }
 }
 
 impl Default for Options {
     fn default() -> Self {
         Self {
             max_batch_duration: Some(Duration::from_millis(50).into()),
+            snapshots_path: None,
+            snapshot_interval: None,
+            completed_invocation_retention: Duration::ZERO.into(),
+            completed_invocation_retention_overrides: HashMap::default(),
         }
     }
 }
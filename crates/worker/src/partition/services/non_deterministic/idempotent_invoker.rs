@@ -8,6 +8,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+//! The ingress deduplicates idempotent requests natively in the partition processor and no
+//! longer routes them through this service. It is kept to complete idempotent invocations which
+//! have been accepted by previous versions.
+
 use super::*;
 
 use crate::partition::types::create_response_message;
@@ -61,12 +65,17 @@ impl<'a, State: StateReader + Send + Sync> IdempotentInvokerBuiltInService
             return Ok(());
         }
 
-        // Store this sink
-        if let Some(response_sink) = self.response_sink.take() {
-            // In this case we just store callee id and sink, to make sure it will be invoked
+        // Store the sinks
+        let response_sinks = std::mem::take(&mut self.response_sinks);
+        if !response_sinks.is_empty() {
+            // In this case we just store callee id and sinks, to make sure they will be invoked
             #[allow(clippy::mutable_key_type)]
             let mut sinks = self.load_state(&SINKS).await?.unwrap_or_default();
-            sinks.push((self.full_invocation_id.clone(), response_sink.clone()));
+            sinks.extend(
+                response_sinks
+                    .iter()
+                    .map(|sink| (self.full_invocation_id.clone(), sink.clone())),
+            );
             self.set_state(&SINKS, &sinks)?;
         }
 
@@ -225,6 +234,7 @@ mod tests {
     use test_log::test;
 
     use restate_test_util::matchers::*;
+    use restate_types::identifiers::InvocationId;
     use restate_types::invocation::ServiceInvocation;
 
     use crate::partition::services::non_deterministic::tests::TestInvocationContext;
@@ -339,7 +349,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(expected_fid.clone()),
+                        invocation_id: eq(InvocationId::from(&expected_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             IdempotentInvokeResponse {
                                 response: some(pat!(
@@ -383,7 +393,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(expected_fid),
+                        invocation_id: eq(InvocationId::from(&expected_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             IdempotentInvokeResponse {
                                 response: some(pat!(
@@ -127,6 +127,7 @@ mod tests {
 
     use restate_schema_api::deployment::Deployment;
     use restate_test_util::matchers::*;
+    use restate_types::identifiers::InvocationId;
     use restate_types::invocation::ServiceInvocation;
 
     use crate::partition::services::non_deterministic::tests::TestInvocationContext;
@@ -214,7 +215,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(fid),
+                        invocation_id: eq(InvocationId::from(&fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             restate_pb::restate::InvokeResponse { id: anything() }
                         ))))
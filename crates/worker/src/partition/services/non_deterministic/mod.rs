@@ -79,7 +79,7 @@ impl<'a> ServiceInvoker<'a> {
         full_invocation_id: FullInvocationId,
         method: &str,
         span_context: ServiceInvocationSpanContext,
-        response_sink: Option<ServiceInvocationResponseSink>,
+        response_sinks: Vec<ServiceInvocationResponseSink>,
         argument: Bytes,
     ) {
         let mut out_effects = vec![];
@@ -89,7 +89,7 @@ impl<'a> ServiceInvoker<'a> {
             span_context: &span_context,
             state_reader: &mut self.storage,
             schemas: self.schemas,
-            response_sink: response_sink.as_ref(),
+            response_sinks: &response_sinks,
             effects_buffer: &mut out_effects,
             state_and_journal_transitions: &mut state_and_journal_transitions,
         };
@@ -182,7 +182,8 @@ struct InvocationContext<'a, S> {
     state_reader: &'a mut S,
     schemas: &'a Schemas,
     span_context: &'a ServiceInvocationSpanContext,
-    response_sink: Option<&'a ServiceInvocationResponseSink>,
+    // Callers waiting for the result of this invocation
+    response_sinks: &'a [ServiceInvocationResponseSink],
 
     effects_buffer: &'a mut Vec<BuiltinServiceEffect>,
     state_and_journal_transitions: &'a mut StateAndJournalTransitions,
@@ -373,11 +374,11 @@ impl<S: StateReader> InvocationContext<'_, S> {
     }
 
     fn reply_to_caller(&mut self, res: ResponseResult) {
-        if let Some(response_sink) = self.response_sink {
+        for response_sink in self.response_sinks {
             self.send_response(create_response_message(
                 self.full_invocation_id,
                 response_sink.clone(),
-                res,
+                res.clone(),
             ));
         }
     }
@@ -444,8 +445,9 @@ mod tests {
                 service_id,
                 state_reader: Default::default(),
                 schemas: Default::default(),
-                response_sink: Some(ServiceInvocationResponseSink::Ingress(
+                response_sink: Some(ServiceInvocationResponseSink::ingress(
                     GenerationalNodeId::new(1, 1),
+                    0,
                 )),
             }
         }
@@ -484,7 +486,7 @@ mod tests {
                 span_context: &ServiceInvocationSpanContext::empty(),
                 state_reader: &mut self.state_reader,
                 schemas: &self.schemas,
-                response_sink: self.response_sink.as_ref(),
+                response_sinks: self.response_sink.as_slice(),
                 effects_buffer: &mut out_effects,
                 state_and_journal_transitions: &mut state_and_journal_transitions,
             };
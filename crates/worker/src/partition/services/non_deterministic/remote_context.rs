@@ -287,6 +287,7 @@ impl<'a, State: StateReader> InvocationContext<'a, State> {
                             entry_index,
                         }),
                         span_context: span_context.clone(),
+                        idempotency: None,
                     }));
 
                     EnrichedRawEntry::new(
@@ -343,6 +344,7 @@ impl<'a, State: StateReader> InvocationContext<'a, State> {
                         source: Source::Service(self.generate_virtual_fid(virtual_journal_id)),
                         response_sink: None,
                         span_context: span_context.clone(),
+                        idempotency: None,
                     }))
                 }
                 EnrichedRawEntry::new(
@@ -921,7 +923,8 @@ impl<'a, State: StateReader + Send + Sync> RemoteContextBuiltInService
             self.reply_to_caller(response_serializer.serialize_success(RecvResponse {
                 response: Some(recv_response::Response::Messages(pending_stream)),
             }));
-        } else if let Some(service_invocation_response_sink) = self.response_sink {
+        } else if let Some(service_invocation_response_sink) = self.response_sinks.first() {
+            // There can be at most one sink pulling at the same time
             self.set_state(
                 &PENDING_RECV_SINK,
                 &(
@@ -973,12 +976,16 @@ impl<'a, State: StateReader + Send + Sync> RemoteContextBuiltInService
     ) -> Result<(), InvocationError> {
         match self.load_state(&STATUS).await? {
             Some(InvocationStatus::Executing { .. }) => {
-                if let Some(sink) = self.response_sink {
+                if !self.response_sinks.is_empty() {
                     let mut pending_sinks = self
                         .load_state(&PENDING_GET_RESULT_SINKS)
                         .await?
                         .unwrap_or_default();
-                    pending_sinks.push((self.full_invocation_id.clone(), sink.clone()));
+                    pending_sinks.extend(
+                        self.response_sinks
+                            .iter()
+                            .map(|sink| (self.full_invocation_id.clone(), sink.clone())),
+                    );
                     self.set_state(&PENDING_GET_RESULT_SINKS, &pending_sinks)?;
                 }
             }
@@ -1340,7 +1347,7 @@ mod tests {
                 contains(pat!(BuiltinServiceEffect::CreateJournal { .. })),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(fid),
+                        invocation_id: eq(InvocationId::from(&fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             StartResponse {
                                 invocation_status: some(pat!(
@@ -1436,7 +1443,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid_stream_1.clone()),
+                    invocation_id: eq(InvocationId::from(&recv_fid_stream_1)),
                 }
             )))))
         );
@@ -1465,7 +1472,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid_stream_1),
+                    invocation_id: eq(InvocationId::from(&recv_fid_stream_1)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(eq(recv_response::Response::InvalidStream(())))
@@ -1478,7 +1485,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(start_fid_stream_2),
+                    invocation_id: eq(InvocationId::from(&start_fid_stream_2)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         StartResponse {
                             invocation_status: some(pat!(
@@ -1536,7 +1543,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         StartResponse {
                             invocation_status: some(pat!(
@@ -1693,7 +1700,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&recv_fid)),
                 }
             )))))
         );
@@ -1716,7 +1723,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(get_result_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&get_result_fid)),
                 }
             )))))
         );
@@ -1758,7 +1765,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(send_fid),
+                        invocation_id: eq(InvocationId::from(&send_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             SendResponse {
                                 response: some(eq(send_response::Response::Ok(())))
@@ -1768,7 +1775,7 @@ mod tests {
                 )))),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(recv_fid),
+                        invocation_id: eq(InvocationId::from(&recv_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             RecvResponse {
                                 response: some(eq(recv_response::Response::Messages(Bytes::new())))
@@ -1778,7 +1785,7 @@ mod tests {
                 )))),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(get_result_fid),
+                        invocation_id: eq(InvocationId::from(&get_result_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             GetResultResponse {
                                 response: some(eq(get_result_response::Response::Success(output)))
@@ -1822,7 +1829,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         SendResponse {
                             response: some(eq(send_response::Response::InvocationCompleted(())))
@@ -1871,7 +1878,7 @@ mod tests {
             send_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         SendResponse {
                             response: some(eq(send_response::Response::Ok(())))
@@ -1898,7 +1905,7 @@ mod tests {
             recv_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(pat!(recv_response::Response::Messages(
@@ -2106,7 +2113,7 @@ mod tests {
             send_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         SendResponse {
                             response: some(eq(send_response::Response::Ok(())))
@@ -2133,7 +2140,7 @@ mod tests {
             recv_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(pat!(recv_response::Response::Messages(
@@ -2224,7 +2231,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&recv_fid)),
                 }
             )))))
         );
@@ -2252,7 +2259,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(send_fid),
+                        invocation_id: eq(InvocationId::from(&send_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             SendResponse {
                                 response: some(eq(send_response::Response::Ok(())))
@@ -2262,7 +2269,7 @@ mod tests {
                 )))),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(recv_fid),
+                        invocation_id: eq(InvocationId::from(&recv_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             RecvResponse {
                                 response: some(pat!(recv_response::Response::Messages(
@@ -2307,7 +2314,7 @@ mod tests {
                 send_effects,
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(fid),
+                        invocation_id: eq(InvocationId::from(&fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             SendResponse {
                                 response: some(eq(send_response::Response::Ok(())))
@@ -2335,7 +2342,7 @@ mod tests {
             recv_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(pat!(recv_response::Response::Messages(
@@ -2381,7 +2388,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(eq(recv_response::Response::InvocationCompleted(())))
@@ -2414,7 +2421,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         GetResultResponse {
                             response: some(eq(get_result_response::Response::None(())))
@@ -2454,7 +2461,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         GetResultResponse {
                             response: some(eq(get_result_response::Response::Success(
@@ -2494,7 +2501,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         GetResultResponse {
                             response: some(eq(get_result_response::Response::Failure(
@@ -2566,7 +2573,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&recv_fid)),
                 }
             )))))
         );
@@ -2586,7 +2593,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(get_result_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&get_result_fid)),
                 }
             )))))
         );
@@ -2614,13 +2621,13 @@ mod tests {
             all!(
                 not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(recv_fid),
+                        invocation_id: eq(InvocationId::from(&recv_fid)),
                         response: pat!(ResponseResult::Failure(_, _))
                     }
                 ))))),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(get_result_fid),
+                        invocation_id: eq(InvocationId::from(&get_result_fid)),
                         response: pat!(ResponseResult::Failure(_, _))
                     }
                 ))))
@@ -2652,7 +2659,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(recv_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&recv_fid)),
                 }
             )))))
         );
@@ -2674,7 +2681,7 @@ mod tests {
             effects,
             not(contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(get_result_fid.clone()),
+                    invocation_id: eq(InvocationId::from(&get_result_fid)),
                 }
             )))))
         );
@@ -2726,7 +2733,7 @@ mod tests {
             all!(
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(recv_fid),
+                        invocation_id: eq(InvocationId::from(&recv_fid)),
                         // No special handling for blocked recv and kill: Once receiving empty bytes,
                         // the client will go through GetResult and get the killed status.
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
@@ -2738,7 +2745,7 @@ mod tests {
                 )))),
                 contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                     IngressResponse {
-                        full_invocation_id: eq(get_result_fid),
+                        invocation_id: eq(InvocationId::from(&get_result_fid)),
                         response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                             GetResultResponse {
                                 response: some(eq(get_result_response::Response::Failure(
@@ -2768,7 +2775,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(get_result_fid),
+                    invocation_id: eq(InvocationId::from(&get_result_fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         GetResultResponse {
                             response: some(eq(get_result_response::Response::Failure(
@@ -2796,7 +2803,7 @@ mod tests {
             effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(start_fid),
+                    invocation_id: eq(InvocationId::from(&start_fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         StartResponse {
                             invocation_status: some(pat!(
@@ -2905,7 +2912,7 @@ mod tests {
             send_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         SendResponse {
                             response: some(eq(send_response::Response::Ok(())))
@@ -2934,7 +2941,7 @@ mod tests {
             recv_effects,
             contains(pat!(BuiltinServiceEffect::IngressResponse(pat!(
                 IngressResponse {
-                    full_invocation_id: eq(fid),
+                    invocation_id: eq(InvocationId::from(&fid)),
                     response: pat!(ResponseResult::Success(protobuf_decoded(pat!(
                         RecvResponse {
                             response: some(pat!(recv_response::Response::Messages(
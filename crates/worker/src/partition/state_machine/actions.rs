@@ -25,6 +25,7 @@ use restate_wal_protocol::timer::TimerValue;
 pub enum Action {
     Invoke {
         full_invocation_id: FullInvocationId,
+        method: ByteString,
         invoke_input_journal: InvokeInputJournal,
     },
     InvokeBuiltInService {
@@ -16,14 +16,16 @@ use crate::partition::state_machine::effects::Effects;
 use crate::partition::types::{
     create_response_message, InvokerEffect, InvokerEffectKind, OutboxMessageExt, ResponseMessage,
 };
+use crate::partition::CompletedInvocationRetention;
 use assert2::let_assert;
 use bytes::Bytes;
 use bytestring::ByteString;
 use futures::{Stream, StreamExt};
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
+use restate_storage_api::idempotency_table::IdempotencyMetadata;
 use restate_storage_api::inbox_table::{InboxEntry, SequenceNumberInvocation};
 use restate_storage_api::invocation_status_table::{
-    InvocationMetadata, InvocationStatus, NotificationTarget,
+    CompletedInvocation, InvocationMetadata, InvocationStatus, NotificationTarget,
 };
 use restate_storage_api::journal_table::JournalEntry;
 use restate_storage_api::outbox_table::OutboxMessage;
@@ -32,15 +34,17 @@ use restate_storage_api::timer_table::{Timer, TimerKey};
 use restate_storage_api::Result as StorageResult;
 use restate_types::errors::{
     InvocationError, InvocationErrorCode, CANCELED_INVOCATION_ERROR, KILLED_INVOCATION_ERROR,
+    NOT_FOUND_INVOCATION_ERROR, NOT_READY_INVOCATION_ERROR,
 };
 use restate_types::identifiers::{
-    EntryIndex, FullInvocationId, InvocationId, InvocationUuid, ServiceId, WithPartitionKey,
+    EntryIndex, FullInvocationId, IdempotencyId, InvocationId, InvocationUuid, ServiceId,
+    WithPartitionKey,
 };
 use restate_types::ingress::IngressResponse;
 use restate_types::invocation::{
-    InvocationResponse, InvocationTermination, MaybeFullInvocationId, ResponseResult,
-    ServiceInvocation, ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source,
-    SpanRelation, SpanRelationCause, TerminationFlavor,
+    AttachInvocationRequest, InvocationResponse, InvocationTermination, MaybeFullInvocationId,
+    ResponseResult, ServiceInvocation, ServiceInvocationResponseSink, ServiceInvocationSpanContext,
+    Source, SpanRelation, SpanRelationCause, TerminationFlavor,
 };
 use restate_types::journal::enriched::{
     AwakeableEnrichmentResult, EnrichedEntryHeader, EnrichedRawEntry, InvokeEnrichmentResult,
@@ -59,6 +63,7 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::pin::pin;
+use std::time::SystemTime;
 use tracing::{debug, instrument, trace};
 
 pub trait StateReader {
@@ -105,6 +110,11 @@ pub trait StateReader {
         invocation_id: &InvocationId,
         length: EntryIndex,
     ) -> impl Stream<Item = StorageResult<(EntryIndex, JournalEntry)>> + Send;
+
+    fn get_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> impl Future<Output = StorageResult<Option<IdempotencyMetadata>>> + Send;
 }
 
 pub(crate) struct CommandInterpreter<Codec> {
@@ -112,6 +122,8 @@ pub(crate) struct CommandInterpreter<Codec> {
     inbox_seq_number: MessageIndex,
     outbox_seq_number: MessageIndex,
 
+    completed_invocation_retention: CompletedInvocationRetention,
+
     _codec: PhantomData<Codec>,
 }
 
@@ -120,6 +132,10 @@ impl<Codec> Debug for CommandInterpreter<Codec> {
         f.debug_struct("EffectCollector")
             .field("inbox_seq_number", &self.inbox_seq_number)
             .field("outbox_seq_number", &self.outbox_seq_number)
+            .field(
+                "completed_invocation_retention",
+                &self.completed_invocation_retention,
+            )
             .finish()
     }
 }
@@ -129,9 +145,18 @@ impl<Codec> CommandInterpreter<Codec> {
         Self {
             inbox_seq_number,
             outbox_seq_number,
+            completed_invocation_retention: CompletedInvocationRetention::default(),
             _codec: PhantomData,
         }
     }
+
+    pub(crate) fn with_completed_invocation_retention(
+        mut self,
+        completed_invocation_retention: CompletedInvocationRetention,
+    ) -> Self {
+        self.completed_invocation_retention = completed_invocation_retention;
+        self
+    }
 }
 
 impl<Codec> CommandInterpreter<Codec>
@@ -167,6 +192,11 @@ where
                         effects,
                     )
                     .await;
+                } else if self
+                    .try_deduplicate_idempotent_invocation(&service_invocation, state, effects)
+                    .await?
+                {
+                    // the response sink has been attached to the original invocation
                 } else if let ServiceStatus::Unlocked = service_status {
                     effects.invoke_service(service_invocation);
                 } else {
@@ -196,6 +226,22 @@ where
                 Ok((None, SpanRelation::None))
             }
             Command::Timer(timer) => self.on_timer(timer, state, effects).await,
+            Command::ScheduleTimer(timer) => {
+                let fid = FullInvocationId {
+                    service_id: timer.value().service_id().clone(),
+                    invocation_uuid: timer.key().invocation_uuid,
+                };
+                let span_context = match timer.value() {
+                    Timer::Invoke(_, service_invocation) => service_invocation.span_context.clone(),
+                    Timer::CompleteSleepEntry(_) | Timer::CleanInvocationStatus(_) => {
+                        ServiceInvocationSpanContext::empty()
+                    }
+                };
+                let span_relation = span_context.as_parent();
+
+                effects.register_timer(timer, span_context);
+                Ok((Some(fid), span_relation))
+            }
             Command::TerminateInvocation(invocation_termination) => {
                 self.try_terminate_invocation(invocation_termination, state, effects)
                     .await
@@ -208,13 +254,69 @@ where
                 self.handle_external_state_mutation(mutation, state, effects)
                     .await
             }
+            Command::AttachInvocation(attach_invocation_request) => {
+                self.try_attach_invocation(attach_invocation_request, state, effects)
+                    .await
+            }
             Command::AnnounceLeader(_) => {
-                // no-op :-)
+                // no-op, the announced leader is recorded and fenced by the deduplicating state
+                // machine
                 Ok((None, SpanRelation::None))
             }
         }
     }
 
+    /// Deduplicates an idempotent invocation against the invocation which has been registered
+    /// under the same idempotency id. Returns `false` if the invocation is new and needs to be
+    /// invoked, in which case its idempotency id is registered.
+    async fn try_deduplicate_idempotent_invocation<State: StateReader>(
+        &mut self,
+        service_invocation: &ServiceInvocation,
+        state: &mut State,
+        effects: &mut Effects,
+    ) -> Result<bool, Error> {
+        let Some(idempotency) = &service_invocation.idempotency else {
+            return Ok(false);
+        };
+
+        let idempotency_id = IdempotencyId::combine(
+            service_invocation.fid.service_id.clone(),
+            service_invocation.method_name.clone(),
+            idempotency.key.clone(),
+        );
+
+        let Some(IdempotencyMetadata { invocation_id }) =
+            state.get_idempotency_metadata(&idempotency_id).await?
+        else {
+            effects
+                .store_idempotency_id(idempotency_id, InvocationId::from(&service_invocation.fid));
+            return Ok(false);
+        };
+
+        match state.get_invocation_status(&invocation_id).await? {
+            status @ (InvocationStatus::Invoked(_) | InvocationStatus::Suspended { .. }) => {
+                if let Some(response_sink) = service_invocation.response_sink.clone() {
+                    effects.attach_response_sink(invocation_id, status, response_sink);
+                }
+                Ok(true)
+            }
+            InvocationStatus::Completed(completed) => {
+                if let Some(response_sink) = service_invocation.response_sink.clone() {
+                    self.send_attach_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        completed.result,
+                    );
+                }
+                Ok(true)
+            }
+            // The original invocation is still waiting in the inbox. The retry is enqueued as well
+            // and completed with the result of the original once it is popped.
+            InvocationStatus::Virtual { .. } | InvocationStatus::Free => Ok(false),
+        }
+    }
+
     fn enqueue_into_inbox(&mut self, effects: &mut Effects, inbox_entry: InboxEntry) {
         effects.enqueue_into_inbox(self.inbox_seq_number, inbox_entry);
         self.inbox_seq_number += 1;
@@ -238,6 +340,104 @@ where
         Ok((None, SpanRelation::None))
     }
 
+    async fn try_attach_invocation<State: StateReader>(
+        &mut self,
+        AttachInvocationRequest {
+            invocation_id,
+            block_on_inflight,
+            response_sink,
+        }: AttachInvocationRequest,
+        state: &mut State,
+        effects: &mut Effects,
+    ) -> Result<(Option<FullInvocationId>, SpanRelation), Error> {
+        let status = state.get_invocation_status(&invocation_id).await?;
+
+        match status {
+            InvocationStatus::Invoked(ref metadata)
+            | InvocationStatus::Suspended { ref metadata, .. } => {
+                let related_span = metadata.journal_metadata.span_context.as_parent();
+                let fid =
+                    FullInvocationId::combine(metadata.service_id.clone(), invocation_id.clone());
+
+                if block_on_inflight {
+                    effects.attach_response_sink(invocation_id, status, response_sink);
+                } else {
+                    self.send_attach_failure_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        &NOT_READY_INVOCATION_ERROR,
+                    );
+                }
+
+                Ok((Some(fid), related_span))
+            }
+            InvocationStatus::Completed(completed) => {
+                let related_span = completed.journal_metadata.span_context.as_parent();
+                let fid = FullInvocationId::combine(completed.service_id, invocation_id.clone());
+
+                self.send_attach_response(effects, invocation_id, response_sink, completed.result);
+
+                Ok((Some(fid), related_span))
+            }
+            InvocationStatus::Virtual { .. } | InvocationStatus::Free => {
+                self.send_attach_failure_response(
+                    effects,
+                    invocation_id,
+                    response_sink,
+                    &NOT_FOUND_INVOCATION_ERROR,
+                );
+
+                Ok((None, SpanRelation::None))
+            }
+        }
+    }
+
+    fn send_attach_failure_response(
+        &mut self,
+        effects: &mut Effects,
+        invocation_id: InvocationId,
+        response_sink: ServiceInvocationResponseSink,
+        error: &InvocationError,
+    ) {
+        self.send_attach_response(
+            effects,
+            invocation_id,
+            response_sink,
+            ResponseResult::from(error),
+        );
+    }
+
+    fn send_attach_response(
+        &mut self,
+        effects: &mut Effects,
+        invocation_id: InvocationId,
+        response_sink: ServiceInvocationResponseSink,
+        response: ResponseResult,
+    ) {
+        match response_sink {
+            ServiceInvocationResponseSink::Ingress {
+                node_id,
+                request_id,
+            } => self.ingress_response(
+                IngressResponse {
+                    target_node: node_id,
+                    request_id,
+                    invocation_id,
+                    response,
+                },
+                effects,
+            ),
+            response_sink => {
+                debug!(
+                    restate.invocation.id = %invocation_id,
+                    "Dropping attach response for unsupported response sink {:?}",
+                    response_sink
+                );
+            }
+        }
+    }
+
     async fn try_built_in_invoker_effect<State: StateReader>(
         &mut self,
         effects: &mut Effects,
@@ -369,6 +569,7 @@ where
             BuiltinServiceEffect::End(None) => {
                 self.end_invocation(
                     effects,
+                    state,
                     full_invocation_id.clone(),
                     invocation_metadata.clone(),
                 )
@@ -377,6 +578,7 @@ where
             BuiltinServiceEffect::End(Some(e)) => {
                 self.fail_invocation(
                     effects,
+                    state,
                     full_invocation_id.clone(),
                     invocation_metadata.clone(),
                     e,
@@ -594,6 +796,15 @@ where
 
         self.try_send_failure_response(effects, &fid, service_invocation.response_sink, &error);
 
+        // the idempotency id has been registered when the invocation was enqueued
+        if let Some(idempotency) = service_invocation.idempotency {
+            effects.delete_idempotency_id(IdempotencyId::combine(
+                fid.service_id.clone(),
+                service_invocation.method_name.clone(),
+                idempotency.key,
+            ));
+        }
+
         self.notify_invocation_result(
             &fid,
             service_invocation.method_name,
@@ -625,6 +836,7 @@ where
 
         self.fail_invocation(
             effects,
+            state,
             full_invocation_id.clone(),
             metadata,
             KILLED_INVOCATION_ERROR,
@@ -839,6 +1051,36 @@ where
                     SpanRelation::None,
                 ))
             }
+            Timer::CleanInvocationStatus(service_id) => {
+                let full_invocation_id = FullInvocationId {
+                    service_id,
+                    invocation_uuid,
+                };
+                let invocation_id = InvocationId::from(&full_invocation_id);
+
+                if let InvocationStatus::Completed(completed) =
+                    state.get_invocation_status(&invocation_id).await?
+                {
+                    if let Some(idempotency_key) = completed.idempotency_key {
+                        effects.delete_idempotency_id(IdempotencyId::combine(
+                            completed.service_id,
+                            completed.method,
+                            idempotency_key,
+                        ));
+                    }
+                    effects.drop_journal_and_free_invocation(
+                        invocation_id,
+                        completed.journal_metadata.length,
+                    );
+                } else {
+                    debug!(
+                        restate.invocation.id = %invocation_id,
+                        "Ignoring clean invocation status timer for invocation that is not completed."
+                    );
+                }
+
+                Ok((Some(full_invocation_id), SpanRelation::None))
+            }
         }
     }
 
@@ -932,11 +1174,11 @@ where
                 }
             }
             InvokerEffectKind::End => {
-                self.end_invocation(effects, full_invocation_id, invocation_metadata)
+                self.end_invocation(effects, state, full_invocation_id, invocation_metadata)
                     .await?;
             }
             InvokerEffectKind::Failed(e) => {
-                self.fail_invocation(effects, full_invocation_id, invocation_metadata, e)
+                self.fail_invocation(effects, state, full_invocation_id, invocation_metadata, e)
                     .await?;
             }
         }
@@ -944,16 +1186,17 @@ where
         Ok((related_sid, span_relation))
     }
 
-    async fn end_invocation(
+    async fn end_invocation<State: StateReader>(
         &mut self,
         effects: &mut Effects,
+        state: &mut State,
         full_invocation_id: FullInvocationId,
         invocation_metadata: InvocationMetadata,
     ) -> Result<(), Error> {
         self.notify_invocation_result(
             &full_invocation_id,
-            invocation_metadata.method,
-            invocation_metadata.journal_metadata.span_context,
+            invocation_metadata.method.clone(),
+            invocation_metadata.journal_metadata.span_context.clone(),
             invocation_metadata.timestamps.creation_time(),
             Ok(()),
             effects,
@@ -961,30 +1204,35 @@ where
 
         self.end_invocation_lifecycle(
             full_invocation_id,
-            invocation_metadata.journal_metadata.length,
+            invocation_metadata,
+            None,
+            state,
             effects,
         )
         .await
     }
 
-    async fn fail_invocation(
+    async fn fail_invocation<State: StateReader>(
         &mut self,
         effects: &mut Effects,
+        state: &mut State,
         full_invocation_id: FullInvocationId,
         invocation_metadata: InvocationMetadata,
         error: InvocationError,
     ) -> Result<(), Error> {
-        self.try_send_failure_response(
-            effects,
-            &full_invocation_id,
-            invocation_metadata.response_sink,
-            &error,
-        );
+        for response_sink in &invocation_metadata.response_sinks {
+            self.try_send_failure_response(
+                effects,
+                &full_invocation_id,
+                Some(response_sink.clone()),
+                &error,
+            );
+        }
 
         self.notify_invocation_result(
             &full_invocation_id,
-            invocation_metadata.method,
-            invocation_metadata.journal_metadata.span_context,
+            invocation_metadata.method.clone(),
+            invocation_metadata.journal_metadata.span_context.clone(),
             invocation_metadata.timestamps.creation_time(),
             Err((error.code(), error.to_string())),
             effects,
@@ -992,7 +1240,9 @@ where
 
         self.end_invocation_lifecycle(
             full_invocation_id,
-            invocation_metadata.journal_metadata.length,
+            invocation_metadata,
+            Some(ResponseResult::from(error)),
+            state,
             effects,
         )
         .await
@@ -1041,20 +1291,23 @@ where
                 );
             }
             EnrichedEntryHeader::OutputStream { .. } => {
-                if let Some(ref response_sink) = invocation_metadata.response_sink {
+                if !invocation_metadata.response_sinks.is_empty() {
                     let_assert!(
                         Entry::OutputStream(OutputStreamEntry { result }) =
                             journal_entry.deserialize_entry_ref::<Codec>()?
                     );
-
-                    self.send_response(
-                        create_response_message(
-                            &full_invocation_id,
-                            response_sink.clone(),
-                            result.into(),
-                        ),
-                        effects,
-                    );
+                    let result = ResponseResult::from(result);
+
+                    for response_sink in &invocation_metadata.response_sinks {
+                        self.send_response(
+                            create_response_message(
+                                &full_invocation_id,
+                                response_sink.clone(),
+                                result.clone(),
+                            ),
+                            effects,
+                        );
+                    }
                 }
             }
             EnrichedEntryHeader::GetState { is_completed, .. } => {
@@ -1451,17 +1704,106 @@ where
         );
     }
 
-    async fn end_invocation_lifecycle(
+    /// Ends the lifecycle of the invocation. If completed invocations of the invoked method are
+    /// retained, the invocation is stored as completed together with its `result`, which is
+    /// read from the output stream entry of the journal if not provided.
+    async fn end_invocation_lifecycle<State: StateReader>(
         &mut self,
         full_invocation_id: FullInvocationId,
-        journal_length: EntryIndex,
+        invocation_metadata: InvocationMetadata,
+        result: Option<ResponseResult>,
+        state: &mut State,
         effects: &mut Effects,
     ) -> Result<(), Error> {
-        effects.drop_journal_and_pop_inbox(full_invocation_id, journal_length);
+        let mut retention = self.completed_invocation_retention.retention(
+            &full_invocation_id.service_id.service_name,
+            &invocation_metadata.method,
+        );
+        // idempotent invocations are retained at least as long as requested by their caller
+        if let Some(idempotency) = &invocation_metadata.idempotency {
+            retention = retention.max(idempotency.retention);
+        }
+
+        if retention.is_zero() {
+            if let Some(idempotency) = invocation_metadata.idempotency {
+                effects.delete_idempotency_id(IdempotencyId::combine(
+                    full_invocation_id.service_id.clone(),
+                    invocation_metadata.method,
+                    idempotency.key,
+                ));
+            }
+            effects.drop_journal_and_pop_inbox(
+                full_invocation_id,
+                invocation_metadata.journal_metadata.length,
+            );
+            return Ok(());
+        }
+
+        let result = match result {
+            Some(result) => result,
+            None => {
+                Self::read_output_stream_result(
+                    &InvocationId::from(&full_invocation_id),
+                    invocation_metadata.journal_metadata.length,
+                    state,
+                )
+                .await?
+            }
+        };
+
+        // Like the other timestamps of the invocation status, the expiry is based on the local
+        // clock and is therefore not agreed between the replicas of the partition. A leftover
+        // cleanup timer of another replica is ignored once the invocation has been freed.
+        let expiry = MillisSinceEpoch::from(SystemTime::now() + retention);
+        effects.register_timer(
+            TimerValue::new_clean_invocation_status(full_invocation_id.clone(), expiry),
+            invocation_metadata.journal_metadata.span_context.clone(),
+        );
+
+        let mut timestamps = invocation_metadata.timestamps;
+        timestamps.update();
+        effects.store_completed_invocation_and_pop_inbox(
+            full_invocation_id.clone(),
+            CompletedInvocation {
+                service_id: full_invocation_id.service_id,
+                method: invocation_metadata.method,
+                journal_metadata: invocation_metadata.journal_metadata,
+                result,
+                timestamps,
+                source: invocation_metadata.source,
+                expiry,
+                idempotency_key: invocation_metadata.idempotency.map(|i| i.key),
+            },
+        );
 
         Ok(())
     }
 
+    /// Reads the result of the invocation from the last output stream entry of its journal.
+    async fn read_output_stream_result<State: StateReader>(
+        invocation_id: &InvocationId,
+        journal_length: EntryIndex,
+        state: &mut State,
+    ) -> Result<ResponseResult, Error> {
+        let mut result = None;
+        let mut journal = pin!(state.get_journal(invocation_id, journal_length));
+
+        while let Some(journal_entry) = journal.next().await {
+            if let (_, JournalEntry::Entry(journal_entry)) = journal_entry? {
+                if let EnrichedEntryHeader::OutputStream { .. } = journal_entry.header() {
+                    let_assert!(
+                        Entry::OutputStream(OutputStreamEntry { result: output }) =
+                            journal_entry.deserialize_entry_ref::<Codec>()?
+                    );
+                    result = Some(ResponseResult::from(output));
+                }
+            }
+        }
+
+        // Invocations can end without output, e.g. background invocations of void methods
+        Ok(result.unwrap_or_else(|| ResponseResult::Success(Bytes::new())))
+    }
+
     fn send_response(&mut self, response: ResponseMessage, effects: &mut Effects) {
         match response {
             ResponseMessage::Outbox(outbox) => self.outbox_message(outbox, effects),
@@ -1508,6 +1850,7 @@ where
             source,
             response_sink,
             span_context,
+            idempotency: None,
         }
     }
 }
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use bytestring::ByteString;
 use futures::stream;
 use googletest::matcher::Matcher;
-use googletest::{all, any, assert_that, pat, unordered_elements_are};
+use googletest::{all, any, assert_that, elements_are, pat, unordered_elements_are};
 use prost::Message;
 use test_log::test;
 
@@ -19,8 +19,11 @@ use restate_storage_api::{Result as StorageResult, StorageError};
 use restate_test_util::matchers::*;
 use restate_test_util::{assert_eq, let_assert};
 use restate_types::errors::UserErrorCode;
+use restate_types::invocation::Idempotency;
 use restate_types::journal::EntryResult;
 use restate_types::journal::{CompleteAwakeableEntry, Entry};
+use restate_types::GenerationalNodeId;
+use std::time::Duration;
 
 use crate::partition::state_machine::command_interpreter::StateReader;
 use crate::partition::state_machine::effects::Effect;
@@ -31,6 +34,7 @@ struct StateReaderMock {
     inboxes: HashMap<ServiceId, Vec<SequenceNumberInboxEntry>>,
     invocations: HashMap<InvocationId, InvocationStatus>,
     journals: HashMap<InvocationId, Vec<JournalEntry>>,
+    idempotency_ids: HashMap<IdempotencyId, IdempotencyMetadata>,
 }
 
 impl StateReaderMock {
@@ -259,6 +263,13 @@ impl StateReader for StateReaderMock {
                 }),
         )
     }
+
+    async fn get_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> StorageResult<Option<IdempotencyMetadata>> {
+        Ok(self.idempotency_ids.get(idempotency_id).cloned())
+    }
 }
 
 #[test(tokio::test)]
@@ -408,6 +419,180 @@ async fn send_response_using_invocation_id() {
     );
 }
 
+#[test(tokio::test)]
+async fn attach_to_invoked_invocation() -> Result<(), Error> {
+    let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(0, 0);
+    let mut effects = Effects::default();
+    let mut state_mock = StateReaderMock::default();
+
+    let fid = FullInvocationId::mock_random();
+    let invocation_id = InvocationId::from(&fid);
+    let response_sink = ServiceInvocationResponseSink::ingress(GenerationalNodeId::new(1, 1), 42);
+    state_mock.register_invoked_status_and_locked(fid.clone(), vec![]);
+
+    command_interpreter
+        .on_apply(
+            Command::AttachInvocation(AttachInvocationRequest {
+                invocation_id: invocation_id.clone(),
+                block_on_inflight: true,
+                response_sink: response_sink.clone(),
+            }),
+            &mut effects,
+            &mut state_mock,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        contains(pat!(Effect::AttachResponseSink {
+            invocation_id: eq(invocation_id),
+            response_sink: eq(response_sink)
+        }))
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn attach_idempotent_retry_to_invoked_invocation() -> Result<(), Error> {
+    let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(0, 0);
+    let mut effects = Effects::default();
+    let mut state_mock = StateReaderMock::default();
+
+    let service_id = ServiceId::new("MyService", "MyKey");
+    let idempotency_id = IdempotencyId::combine(service_id.clone(), "MyMethod", "my-key");
+    let fid = idempotency_id.full_invocation_id();
+    let invocation_id = InvocationId::from(&fid);
+    let response_sink = ServiceInvocationResponseSink::ingress(GenerationalNodeId::new(1, 1), 42);
+    state_mock.register_invoked_status_and_locked(fid.clone(), vec![]);
+    state_mock.idempotency_ids.insert(
+        idempotency_id,
+        IdempotencyMetadata {
+            invocation_id: invocation_id.clone(),
+        },
+    );
+
+    command_interpreter
+        .on_apply(
+            Command::Invoke(ServiceInvocation {
+                fid,
+                method_name: ByteString::from("MyMethod"),
+                response_sink: Some(response_sink.clone()),
+                idempotency: Some(Idempotency {
+                    key: ByteString::from("my-key"),
+                    retention: Duration::from_secs(60),
+                }),
+                ..ServiceInvocation::mock()
+            }),
+            &mut effects,
+            &mut state_mock,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        elements_are![pat!(Effect::AttachResponseSink {
+            invocation_id: eq(invocation_id),
+            response_sink: eq(response_sink)
+        })]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn kill_inboxed_idempotent_invocation() -> Result<(), Error> {
+    let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(0, 0);
+    let mut effects = Effects::default();
+    let mut state_mock = StateReaderMock::default();
+
+    let service_id = ServiceId::new("MyService", "MyKey");
+    let idempotency_id = IdempotencyId::combine(service_id.clone(), "MyMethod", "my-key");
+    let fid = idempotency_id.full_invocation_id();
+    state_mock.lock_service(service_id.clone());
+    state_mock.enqueue_into_inbox(
+        service_id.clone(),
+        SequenceNumberInboxEntry {
+            inbox_sequence_number: 0,
+            inbox_entry: InboxEntry::Invocation(ServiceInvocation {
+                fid: fid.clone(),
+                method_name: ByteString::from("MyMethod"),
+                idempotency: Some(Idempotency {
+                    key: ByteString::from("my-key"),
+                    retention: Duration::from_secs(60),
+                }),
+                ..ServiceInvocation::mock()
+            }),
+        },
+    );
+    state_mock.idempotency_ids.insert(
+        idempotency_id.clone(),
+        IdempotencyMetadata {
+            invocation_id: InvocationId::from(&fid),
+        },
+    );
+
+    command_interpreter
+        .on_apply(
+            Command::TerminateInvocation(InvocationTermination::kill(MaybeFullInvocationId::from(
+                fid,
+            ))),
+            &mut effects,
+            &mut state_mock,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        all!(
+            contains(pat!(Effect::DeleteInboxEntry {
+                service_id: eq(service_id),
+                sequence_number: eq(0)
+            })),
+            contains(pat!(Effect::DeleteIdempotencyId(eq(idempotency_id))))
+        )
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn attach_to_unknown_invocation() -> Result<(), Error> {
+    let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(0, 0);
+    let mut effects = Effects::default();
+    let mut state_mock = StateReaderMock::default();
+
+    let node_id = GenerationalNodeId::new(1, 1);
+    let invocation_id = InvocationId::from(&FullInvocationId::mock_random());
+
+    command_interpreter
+        .on_apply(
+            Command::AttachInvocation(AttachInvocationRequest {
+                invocation_id: invocation_id.clone(),
+                block_on_inflight: true,
+                response_sink: ServiceInvocationResponseSink::ingress(node_id, 42),
+            }),
+            &mut effects,
+            &mut state_mock,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        elements_are![pat!(Effect::IngressResponse(pat!(IngressResponse {
+            target_node: eq(node_id),
+            request_id: eq(42),
+            invocation_id: eq(invocation_id),
+            response: pat!(ResponseResult::Failure(
+                eq(UserErrorCode::NotFound),
+                anything()
+            ))
+        })))]
+    );
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn kill_inboxed_invocation() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(0, 0);
@@ -15,22 +15,55 @@ use crate::partition::state_machine::{
 };
 use crate::partition::storage::Transaction;
 use crate::partition::types::{AckResponse, IngressAckResponse, ShuffleAckResponse};
-use restate_storage_api::deduplication_table::SequenceNumberSource;
+use crate::partition::CompletedInvocationRetention;
+use restate_storage_api::deduplication_table::{EpochSequenceNumber, SequenceNumberSource};
+use restate_types::identifiers::LeaderEpoch;
 use restate_types::journal::raw::RawEntryCodec;
 use restate_types::message::{AckKind, MessageIndex};
-use restate_wal_protocol::{AckMode, Envelope, Source};
+use restate_types::{GenerationalNodeId, PlainNodeId};
+use restate_wal_protocol::{AckMode, Command, Envelope, Source};
+use tracing::debug;
+
+/// Leader of the partition which has been announced last. Proposals of this partition are only
+/// accepted from the announced leader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnnouncedLeader {
+    pub leader_epoch: LeaderEpoch,
+    pub node_id: GenerationalNodeId,
+}
 
 #[derive(Debug)]
 pub struct DeduplicatingStateMachine<Codec> {
     inner: StateMachine<Codec>,
+    announced_leader: Option<AnnouncedLeader>,
 }
 
 impl<Codec> DeduplicatingStateMachine<Codec> {
     pub fn new(inbox_seq_number: MessageIndex, outbox_seq_number: MessageIndex) -> Self {
         DeduplicatingStateMachine {
             inner: StateMachine::new(inbox_seq_number, outbox_seq_number),
+            announced_leader: None,
         }
     }
+
+    pub fn with_announced_leader(mut self, announced_leader: Option<AnnouncedLeader>) -> Self {
+        self.announced_leader = announced_leader;
+        self
+    }
+
+    pub fn announced_leader(&self) -> Option<AnnouncedLeader> {
+        self.announced_leader
+    }
+
+    pub fn with_completed_invocation_retention(
+        mut self,
+        completed_invocation_retention: CompletedInvocationRetention,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .with_completed_invocation_retention(completed_invocation_retention);
+        self
+    }
 }
 
 impl<Codec> DeduplicatingStateMachine<Codec>
@@ -48,62 +81,64 @@ where
         mut message_collector: Collector,
         is_leader: bool,
     ) -> Result<InterpretationResult<Transaction<TransactionType>, Collector>, Error> {
-        match envelope.header.ack_mode {
-            AckMode::Ack => {
-                let ack_response =
-                    create_ack_response(&envelope.header.source, |sequence_number| {
-                        AckKind::Acknowledge(sequence_number)
+        if let Some(producer) = ProducerSequenceNumber::from_envelope(&envelope) {
+            let last_known = transaction
+                .load_dedup_seq_number(producer.source.clone())
+                .await?;
+
+            match producer.check(last_known, self.announced_leader) {
+                DedupDecision::FencedLeader(announced_leader) => {
+                    debug!(
+                        epoch = producer.sequence_number.epoch,
+                        proposer = ?producer.proposer,
+                        announced_leader_epoch = %announced_leader.leader_epoch,
+                        announced_leader_node = %announced_leader.node_id,
+                        "Ignoring command '{}' from a leader which has not been announced",
+                        envelope.command.name()
+                    );
+                    return Ok(InterpretationResult::new(transaction, message_collector));
+                }
+                DedupDecision::Fenced(last_known) => {
+                    debug!(
+                        source = ?producer.source,
+                        epoch = producer.sequence_number.epoch,
+                        last_known_epoch = last_known.epoch,
+                        "Ignoring command '{}' from a fenced producer epoch",
+                        envelope.command.name()
+                    );
+                    return Ok(InterpretationResult::new(transaction, message_collector));
+                }
+                DedupDecision::Duplicate(last_known) => {
+                    let ack_response = create_ack_response(&envelope.header.source, |seq_number| {
+                        AckKind::Duplicate {
+                            seq_number,
+                            last_known_seq_number: last_known.sequence_number,
+                        }
                     });
-                message_collector.collect(Action::SendAckResponse(ack_response));
-            }
-            AckMode::Dedup => {
-                let (source, seq_number) = match &envelope.header.source {
-                    Source::Processor {
-                        partition_id,
-                        sequence_number,
-                        ..
-                    } => (
-                        SequenceNumberSource::Partition(*partition_id),
-                        sequence_number.expect("sequence number must be present"),
-                    ),
-                    Source::Ingress {
-                        dedup_key,
-                        sequence_number,
-                        ..
-                    } => (
-                        SequenceNumberSource::Ingress(
-                            dedup_key.clone().expect("dedup key must be present").into(),
-                        ),
-                        *sequence_number,
-                    ),
-                    Source::ControlPlane { .. } => {
-                        unimplemented!("control plane should not require deduping")
-                    }
-                };
-
-                if let Some(last_known_seq_number) =
-                    transaction.load_dedup_seq_number(source.clone()).await?
-                {
-                    if seq_number <= last_known_seq_number {
-                        let ack_response =
-                            create_ack_response(&envelope.header.source, |seq_number| {
-                                AckKind::Duplicate {
-                                    seq_number,
-                                    last_known_seq_number,
-                                }
-                            });
-                        message_collector.collect(Action::SendAckResponse(ack_response));
-                        return Ok(InterpretationResult::new(transaction, message_collector));
+                    message_collector.collect(Action::SendAckResponse(ack_response));
+                    return Ok(InterpretationResult::new(transaction, message_collector));
+                }
+                DedupDecision::Accept => {
+                    if let Some(Proposer::Announcement(node_id)) = producer.proposer {
+                        let announced_leader = AnnouncedLeader {
+                            leader_epoch: LeaderEpoch::from(producer.sequence_number.epoch),
+                            node_id,
+                        };
+                        transaction.store_announced_leader(announced_leader).await;
+                        self.announced_leader = Some(announced_leader);
                     }
+                    transaction
+                        .store_dedup_seq_number(producer.source, producer.sequence_number)
+                        .await;
                 }
-
-                transaction.store_dedup_seq_number(source, seq_number).await;
-                let ack_response = create_ack_response(&envelope.header.source, |seq_number| {
-                    AckKind::Acknowledge(seq_number)
-                });
-                message_collector.collect(Action::SendAckResponse(ack_response));
             }
-            AckMode::None => {}
+        }
+
+        if matches!(envelope.header.ack_mode, AckMode::Ack | AckMode::Dedup) {
+            let ack_response = create_ack_response(&envelope.header.source, |sequence_number| {
+                AckKind::Acknowledge(sequence_number)
+            });
+            message_collector.collect(Action::SendAckResponse(ack_response));
         }
 
         self.inner
@@ -118,6 +153,178 @@ where
     }
 }
 
+/// How the sequence numbers of a producer relate across its epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SequenceNumberScope {
+    /// The producer has no sequence numbers, only messages of its latest epoch are accepted.
+    None,
+    /// The sequence numbers continue across epochs, e.g. the outbox index of a partition.
+    AcrossEpochs,
+    /// The sequence numbers restart with every epoch, e.g. the message index of an ingress node.
+    PerEpoch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DedupDecision {
+    Accept,
+    Duplicate(EpochSequenceNumber),
+    Fenced(EpochSequenceNumber),
+    /// The proposal does not stem from the announced leader of the partition.
+    FencedLeader(AnnouncedLeader),
+}
+
+/// Leader of this partition which proposed a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Proposer {
+    /// A new leader announces itself with its generational node id.
+    Announcement(GenerationalNodeId),
+    /// All other proposals only carry the plain node id of the leader.
+    Node(PlainNodeId),
+}
+
+/// Sequence number of a message, which is used to drop replayed messages and messages from
+/// fenced producers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProducerSequenceNumber {
+    source: SequenceNumberSource,
+    sequence_number: EpochSequenceNumber,
+    scope: SequenceNumberScope,
+    /// Set for the proposals of the leader of this partition.
+    proposer: Option<Proposer>,
+}
+
+impl ProducerSequenceNumber {
+    fn from_envelope(envelope: &Envelope) -> Option<Self> {
+        let header = &envelope.header;
+        match (&header.source, header.ack_mode) {
+            // Proposals of the leader of this partition carry no sequence number. They are
+            // fenced by the leader epoch and the announced leader.
+            (
+                Source::Processor {
+                    leader_epoch,
+                    sequence_number: None,
+                    node_id,
+                    ..
+                },
+                _,
+            ) => Some(Self {
+                source: SequenceNumberSource::SelfProposal,
+                sequence_number: EpochSequenceNumber::new(u64::from(*leader_epoch), 0),
+                scope: SequenceNumberScope::None,
+                proposer: Some(match &envelope.command {
+                    Command::AnnounceLeader(announce_leader) => {
+                        Proposer::Announcement(announce_leader.node_id())
+                    }
+                    _ => Proposer::Node(*node_id),
+                }),
+            }),
+            (
+                Source::Processor {
+                    partition_id,
+                    leader_epoch,
+                    sequence_number: Some(sequence_number),
+                    ..
+                },
+                AckMode::Dedup,
+            ) => Some(Self {
+                source: SequenceNumberSource::Partition(*partition_id),
+                sequence_number: EpochSequenceNumber::new(
+                    u64::from(*leader_epoch),
+                    *sequence_number,
+                ),
+                scope: SequenceNumberScope::AcrossEpochs,
+                proposer: None,
+            }),
+            (
+                Source::Ingress {
+                    dedup_key: Some(dedup_key),
+                    sequence_number,
+                    ..
+                },
+                AckMode::Dedup,
+            ) => Some(Self {
+                source: SequenceNumberSource::Ingress(dedup_key.clone().into()),
+                sequence_number: EpochSequenceNumber::new(0, *sequence_number),
+                scope: SequenceNumberScope::AcrossEpochs,
+                proposer: None,
+            }),
+            // Without a dedup key, the sequence numbers are scoped to the generation of the node
+            (
+                Source::Ingress {
+                    node_id,
+                    dedup_key: None,
+                    sequence_number,
+                    ..
+                },
+                AckMode::Dedup,
+            ) => Some(Self {
+                source: SequenceNumberSource::IngressNode(node_id.as_plain()),
+                sequence_number: EpochSequenceNumber::new(
+                    u64::from(node_id.generation()),
+                    *sequence_number,
+                ),
+                scope: SequenceNumberScope::PerEpoch,
+                proposer: None,
+            }),
+            (Source::ControlPlane { .. }, AckMode::Dedup) => {
+                unimplemented!("control plane should not require deduping")
+            }
+            _ => None,
+        }
+    }
+
+    fn check(
+        &self,
+        last_known: Option<EpochSequenceNumber>,
+        announced_leader: Option<AnnouncedLeader>,
+    ) -> DedupDecision {
+        if let (Some(proposer), Some(announced_leader)) = (self.proposer, announced_leader) {
+            let epoch = self.sequence_number.epoch;
+            let announced_epoch = u64::from(announced_leader.leader_epoch);
+            let is_fenced = match proposer {
+                // Leaders which have been elected concurrently can announce the same epoch, only
+                // the first announcement of an epoch wins.
+                Proposer::Announcement(node_id) => {
+                    epoch < announced_epoch
+                        || (epoch == announced_epoch && node_id != announced_leader.node_id)
+                }
+                Proposer::Node(node_id) => {
+                    epoch != announced_epoch || node_id != announced_leader.node_id.as_plain()
+                }
+            };
+
+            if is_fenced {
+                return DedupDecision::FencedLeader(announced_leader);
+            }
+        }
+
+        let Some(last_known) = last_known else {
+            return DedupDecision::Accept;
+        };
+
+        if self.sequence_number.epoch < last_known.epoch {
+            return DedupDecision::Fenced(last_known);
+        }
+
+        let is_duplicate = match self.scope {
+            SequenceNumberScope::None => false,
+            SequenceNumberScope::AcrossEpochs => {
+                self.sequence_number.sequence_number <= last_known.sequence_number
+            }
+            SequenceNumberScope::PerEpoch => {
+                self.sequence_number.epoch == last_known.epoch
+                    && self.sequence_number.sequence_number <= last_known.sequence_number
+            }
+        };
+
+        if is_duplicate {
+            DedupDecision::Duplicate(last_known)
+        } else {
+            DedupDecision::Accept
+        }
+    }
+}
+
 fn create_ack_response(
     source: &Source,
     ack_kind: impl FnOnce(MessageIndex) -> AckKind,
@@ -144,3 +351,197 @@ fn create_ack_response(
         Source::ControlPlane { .. } => unimplemented!("control plane should not require acks"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_types::Version;
+    use restate_wal_protocol::control::AnnounceLeader;
+    use restate_wal_protocol::{Destination, Header};
+
+    fn envelope(source: Source, ack_mode: AckMode, command: Command) -> Envelope {
+        Envelope::new(
+            Header {
+                source,
+                dest: Destination::Processor { partition_key: 0 },
+                ack_mode,
+            },
+            command,
+        )
+    }
+
+    fn ingress(generation: u32, sequence_number: MessageIndex) -> ProducerSequenceNumber {
+        ProducerSequenceNumber::from_envelope(&envelope(
+            Source::Ingress {
+                node_id: GenerationalNodeId::new(1, generation),
+                nodes_config_version: Version::MIN,
+                dedup_key: None,
+                sequence_number,
+            },
+            AckMode::Dedup,
+            Command::TruncateOutbox(0),
+        ))
+        .expect("ingress messages are deduplicated")
+    }
+
+    fn processor(
+        leader_epoch: LeaderEpoch,
+        sequence_number: Option<MessageIndex>,
+    ) -> ProducerSequenceNumber {
+        ProducerSequenceNumber::from_envelope(&envelope(
+            Source::Processor {
+                partition_id: 1,
+                partition_key: None,
+                leader_epoch,
+                sequence_number,
+                node_id: PlainNodeId::from(1),
+            },
+            if sequence_number.is_some() {
+                AckMode::Dedup
+            } else {
+                AckMode::None
+            },
+            Command::TruncateOutbox(0),
+        ))
+        .expect("processor messages are deduplicated")
+    }
+
+    fn self_proposal(
+        leader_epoch: LeaderEpoch,
+        node_id: GenerationalNodeId,
+        command: Command,
+    ) -> ProducerSequenceNumber {
+        ProducerSequenceNumber::from_envelope(&envelope(
+            Source::Processor {
+                partition_id: 1,
+                partition_key: None,
+                leader_epoch,
+                sequence_number: None,
+                node_id: node_id.as_plain(),
+            },
+            AckMode::None,
+            command,
+        ))
+        .expect("self proposals are deduplicated")
+    }
+
+    fn announce_leader(
+        leader_epoch: LeaderEpoch,
+        node_id: GenerationalNodeId,
+    ) -> ProducerSequenceNumber {
+        self_proposal(
+            leader_epoch,
+            node_id,
+            Command::AnnounceLeader(AnnounceLeader::new(node_id)),
+        )
+    }
+
+    #[test]
+    fn ingress_node_sequence_numbers_restart_with_new_generation() {
+        let first = ingress(1, 10);
+        assert_eq!(
+            SequenceNumberSource::IngressNode(PlainNodeId::from(1)),
+            first.source
+        );
+        assert_eq!(DedupDecision::Accept, first.check(None, None));
+
+        let last_known = first.sequence_number;
+        assert_eq!(
+            DedupDecision::Duplicate(last_known),
+            ingress(1, 10).check(Some(last_known), None)
+        );
+        assert_eq!(
+            DedupDecision::Accept,
+            ingress(1, 11).check(Some(last_known), None)
+        );
+        assert_eq!(
+            DedupDecision::Accept,
+            ingress(2, 0).check(Some(last_known), None)
+        );
+
+        let restarted = ingress(2, 0).sequence_number;
+        assert_eq!(
+            DedupDecision::Fenced(restarted),
+            ingress(1, 11).check(Some(restarted), None)
+        );
+    }
+
+    #[test]
+    fn self_proposals_of_previous_leaders_are_fenced() {
+        let epoch = LeaderEpoch::INITIAL.next();
+        let last_known = processor(epoch, None).sequence_number;
+
+        assert_eq!(
+            DedupDecision::Accept,
+            processor(epoch, None).check(Some(last_known), None)
+        );
+        assert_eq!(
+            DedupDecision::Fenced(last_known),
+            processor(LeaderEpoch::INITIAL, None).check(Some(last_known), None)
+        );
+    }
+
+    #[test]
+    fn partition_sequence_numbers_continue_across_epochs() {
+        let last_known = processor(LeaderEpoch::INITIAL, Some(5)).sequence_number;
+        let next_epoch = LeaderEpoch::INITIAL.next();
+
+        assert_eq!(
+            DedupDecision::Duplicate(last_known),
+            processor(next_epoch, Some(5)).check(Some(last_known), None)
+        );
+        assert_eq!(
+            DedupDecision::Accept,
+            processor(next_epoch, Some(6)).check(Some(last_known), None)
+        );
+    }
+
+    #[test]
+    fn concurrent_leaders_of_the_same_epoch_are_fenced() {
+        let epoch = LeaderEpoch::INITIAL.next();
+        let first = GenerationalNodeId::new(1, 1);
+        let second = GenerationalNodeId::new(1, 2);
+        let other_node = GenerationalNodeId::new(2, 1);
+
+        let announcement = announce_leader(epoch, first);
+        assert_eq!(DedupDecision::Accept, announcement.check(None, None));
+        let last_known = Some(announcement.sequence_number);
+        let announced_leader = AnnouncedLeader {
+            leader_epoch: epoch,
+            node_id: first,
+        };
+
+        // only the first announcement of an epoch wins
+        assert_eq!(
+            DedupDecision::FencedLeader(announced_leader),
+            announce_leader(epoch, second).check(last_known, Some(announced_leader))
+        );
+        assert_eq!(
+            DedupDecision::FencedLeader(announced_leader),
+            announce_leader(epoch, other_node).check(last_known, Some(announced_leader))
+        );
+        assert_eq!(
+            DedupDecision::FencedLeader(announced_leader),
+            self_proposal(epoch, other_node, Command::TruncateOutbox(1))
+                .check(last_known, Some(announced_leader))
+        );
+        assert_eq!(
+            DedupDecision::Accept,
+            self_proposal(epoch, first, Command::TruncateOutbox(1))
+                .check(last_known, Some(announced_leader))
+        );
+
+        // a leader of a later epoch takes over once it has been announced
+        let next_epoch = epoch.next();
+        assert_eq!(
+            DedupDecision::FencedLeader(announced_leader),
+            self_proposal(next_epoch, second, Command::TruncateOutbox(1))
+                .check(last_known, Some(announced_leader))
+        );
+        assert_eq!(
+            DedupDecision::Accept,
+            announce_leader(next_epoch, second).check(last_known, Some(announced_leader))
+        );
+    }
+}
@@ -17,6 +17,7 @@ use crate::partition::{CommitError, Committable};
 use bytes::Bytes;
 use futures::{Stream, TryStreamExt};
 use restate_invoker_api::InvokeInputJournal;
+use restate_storage_api::idempotency_table::IdempotencyMetadata;
 use restate_storage_api::inbox_table::{InboxEntry, SequenceNumberInboxEntry};
 use restate_storage_api::invocation_status_table::{
     InvocationMetadata, InvocationStatus, JournalMetadata, StatusTimestamps,
@@ -25,8 +26,11 @@ use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::service_status_table::ServiceStatus;
 use restate_storage_api::timer_table::{Timer, TimerKey};
 use restate_storage_api::Result as StorageResult;
-use restate_types::identifiers::{EntryIndex, FullInvocationId, InvocationId, ServiceId};
-use restate_types::invocation::ServiceInvocation;
+use restate_types::identifiers::{
+    EntryIndex, FullInvocationId, IdempotencyId, InvocationId, ServiceId,
+};
+use restate_types::ingress::IngressResponse;
+use restate_types::invocation::{ServiceInvocation, ServiceInvocationResponseSink};
 use restate_types::journal::enriched::{EnrichedEntryHeader, EnrichedRawEntry};
 use restate_types::journal::raw::{PlainRawEntry, RawEntryCodec};
 use restate_types::journal::{Completion, CompletionResult, EntryType};
@@ -53,6 +57,22 @@ pub trait StateStorage {
         invocation_status: InvocationStatus,
     ) -> impl Future<Output = StorageResult<()>> + Send;
 
+    fn load_invocation_status(
+        &mut self,
+        invocation_id: &InvocationId,
+    ) -> impl Future<Output = StorageResult<InvocationStatus>> + Send;
+
+    fn store_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+        metadata: IdempotencyMetadata,
+    ) -> impl Future<Output = StorageResult<()>> + Send;
+
+    fn delete_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> impl Future<Output = StorageResult<()>> + Send;
+
     fn drop_journal(
         &mut self,
         invocation_id: &InvocationId,
@@ -225,12 +245,14 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
             } => {
                 metadata.timestamps.update();
                 let service_id = metadata.service_id.clone();
+                let method = metadata.method.clone();
                 state_storage
                     .store_invocation_status(&invocation_id, InvocationStatus::Invoked(metadata))
                     .await?;
 
                 collector.collect(Action::Invoke {
                     full_invocation_id: FullInvocationId::combine(service_id, invocation_id),
+                    method,
                     invoke_input_journal: InvokeInputJournal::NoCachedJournal,
                 });
             }
@@ -328,6 +350,22 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                     .store_invocation_status(&invocation_id, InvocationStatus::Invoked(metadata))
                     .await?;
             }
+            Effect::AttachResponseSink {
+                invocation_id,
+                mut previous_invocation_status,
+                response_sink,
+            } => {
+                previous_invocation_status
+                    .get_invocation_metadata_mut()
+                    .expect("response sinks can only be attached to in-flight invocations")
+                    .response_sinks
+                    .insert(response_sink);
+                previous_invocation_status.update_timestamps();
+
+                state_storage
+                    .store_invocation_status(&invocation_id, previous_invocation_status)
+                    .await?;
+            }
             Effect::AppendJournalEntry {
                 invocation_id,
                 previous_invocation_status,
@@ -431,6 +469,32 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                 Self::pop_from_inbox(state_storage, collector, &full_invocation_id.service_id)
                     .await?;
             }
+            Effect::StoreCompletedInvocationAndPopInbox {
+                full_invocation_id,
+                completed_invocation,
+            } => {
+                // the journal is retained together with the completed invocation
+                state_storage
+                    .store_invocation_status(
+                        &InvocationId::from(&full_invocation_id),
+                        InvocationStatus::Completed(completed_invocation),
+                    )
+                    .await?;
+
+                Self::pop_from_inbox(state_storage, collector, &full_invocation_id.service_id)
+                    .await?;
+            }
+            Effect::DropJournalAndFreeInvocation {
+                invocation_id,
+                journal_length,
+            } => {
+                state_storage
+                    .drop_journal(&invocation_id, journal_length)
+                    .await?;
+                state_storage
+                    .store_invocation_status(&invocation_id, InvocationStatus::Free)
+                    .await?;
+            }
             Effect::TraceInvocationResult { .. } | Effect::TraceBackgroundInvoke { .. } => {
                 // these effects are only needed for span creation
             }
@@ -449,6 +513,22 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
             Effect::IngressResponse(ingress_response) => {
                 collector.collect(Action::IngressResponse(ingress_response));
             }
+            Effect::StoreIdempotencyId {
+                idempotency_id,
+                invocation_id,
+            } => {
+                state_storage
+                    .store_idempotency_metadata(
+                        &idempotency_id,
+                        IdempotencyMetadata { invocation_id },
+                    )
+                    .await?;
+            }
+            Effect::DeleteIdempotencyId(idempotency_id) => {
+                state_storage
+                    .delete_idempotency_metadata(&idempotency_id)
+                    .await?;
+            }
         }
 
         Ok(())
@@ -467,6 +547,17 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
         while let Some(inbox_entry) = state_storage.pop_inbox(service_id).await? {
             match inbox_entry.inbox_entry {
                 InboxEntry::Invocation(service_invocation) => {
+                    if service_invocation.idempotency.is_some()
+                        && Self::try_complete_idempotent_invocation(
+                            state_storage,
+                            collector,
+                            &service_invocation,
+                        )
+                        .await?
+                    {
+                        continue;
+                    }
+
                     Self::invoke_service(state_storage, collector, service_invocation).await?;
                     return Ok(());
                 }
@@ -483,6 +574,43 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
         Ok(())
     }
 
+    /// Completes an inboxed retry of an idempotent invocation with the result of the original
+    /// invocation, which has completed while the retry was waiting in the inbox. Returns `false`
+    /// if the invocation needs to be invoked.
+    async fn try_complete_idempotent_invocation<S: StateStorage, C: ActionCollector>(
+        state_storage: &mut S,
+        collector: &mut C,
+        service_invocation: &ServiceInvocation,
+    ) -> Result<bool, Error> {
+        let invocation_id = InvocationId::from(&service_invocation.fid);
+        let InvocationStatus::Completed(completed) =
+            state_storage.load_invocation_status(&invocation_id).await?
+        else {
+            return Ok(false);
+        };
+
+        match &service_invocation.response_sink {
+            Some(ServiceInvocationResponseSink::Ingress {
+                node_id,
+                request_id,
+            }) => collector.collect(Action::IngressResponse(IngressResponse {
+                target_node: *node_id,
+                request_id: *request_id,
+                invocation_id,
+                response: completed.result,
+            })),
+            response_sink => {
+                debug!(
+                    restate.invocation.id = %invocation_id,
+                    "Dropping response of completed idempotent invocation for response sink {:?}",
+                    response_sink
+                );
+            }
+        }
+
+        Ok(true)
+    }
+
     async fn mutate_state<S: StateStorage>(
         state_storage: &mut S,
         state_mutation: ExternalStateMutation,
@@ -549,9 +677,14 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                     journal_metadata.clone(),
                     None,
                     service_invocation.method_name.clone(),
-                    service_invocation.response_sink.clone(),
+                    service_invocation
+                        .response_sink
+                        .clone()
+                        .into_iter()
+                        .collect(),
                     StatusTimestamps::now(),
                     service_invocation.source,
+                    service_invocation.idempotency,
                 )),
             )
             .await?;
@@ -579,6 +712,7 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
 
             collector.collect(Action::Invoke {
                 full_invocation_id: service_invocation.fid.clone(),
+                method: service_invocation.method_name.clone(),
                 invoke_input_journal: InvokeInputJournal::CachedJournal(
                     restate_invoker_api::JournalMetadata::new(
                         journal_metadata.length,
@@ -14,18 +14,18 @@ use opentelemetry_api::trace::SpanId;
 use restate_storage_api::inbox_table::InboxEntry;
 use restate_storage_api::invocation_status_table::InvocationMetadata;
 use restate_storage_api::invocation_status_table::{
-    InvocationStatus, JournalMetadata, NotificationTarget,
+    CompletedInvocation, InvocationStatus, JournalMetadata, NotificationTarget,
 };
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::timer_table::{Timer, TimerKey};
 use restate_types::errors::InvocationErrorCode;
 use restate_types::identifiers::{
-    DeploymentId, EntryIndex, FullInvocationId, InvocationId, ServiceId,
+    DeploymentId, EntryIndex, FullInvocationId, IdempotencyId, InvocationId, ServiceId,
 };
 use restate_types::ingress::IngressResponse;
 use restate_types::invocation::{
-    InvocationResponse, ResponseResult, ServiceInvocation, ServiceInvocationSpanContext,
-    SpanRelation,
+    InvocationResponse, ResponseResult, ServiceInvocation, ServiceInvocationResponseSink,
+    ServiceInvocationSpanContext, SpanRelation,
 };
 use restate_types::journal::enriched::EnrichedRawEntry;
 use restate_types::journal::{Completion, CompletionResult};
@@ -71,11 +71,26 @@ pub(crate) enum Effect {
         full_invocation_id: FullInvocationId,
         journal_length: EntryIndex,
     },
+    StoreCompletedInvocationAndPopInbox {
+        full_invocation_id: FullInvocationId,
+        completed_invocation: CompletedInvocation,
+    },
+    DropJournalAndFreeInvocation {
+        invocation_id: InvocationId,
+        journal_length: EntryIndex,
+    },
     DeleteInboxEntry {
         service_id: ServiceId,
         sequence_number: MessageIndex,
     },
 
+    // Idempotency
+    StoreIdempotencyId {
+        idempotency_id: IdempotencyId,
+        invocation_id: InvocationId,
+    },
+    DeleteIdempotencyId(IdempotencyId),
+
     // State
     SetState {
         service_id: ServiceId,
@@ -122,6 +137,11 @@ pub(crate) enum Effect {
         invocation_id: InvocationId,
         completion: Completion,
     },
+    AttachResponseSink {
+        invocation_id: InvocationId,
+        previous_invocation_status: InvocationStatus,
+        response_sink: ServiceInvocationResponseSink,
+    },
     ForwardCompletion {
         // TODO this can be invocation_id once the invoker uses only InvocationId
         full_invocation_id: FullInvocationId,
@@ -307,23 +327,39 @@ impl Effect {
             ),
             Effect::IngressResponse(IngressResponse {
                 response: ResponseResult::Success(_),
-                full_invocation_id,
+                invocation_id,
                 ..
             }) => debug_if_leader!(
                 is_leader,
-                restate.invocation.id = %full_invocation_id,
+                restate.invocation.id = %invocation_id,
                 "Effect: Send response to ingress: Success"),
             Effect::IngressResponse(IngressResponse {
                 response: ResponseResult::Failure(error_code, error_msg),
-                full_invocation_id,
+                invocation_id,
                 ..
             }) => debug_if_leader!(
                 is_leader,
-                restate.invocation.id = %full_invocation_id,
+                restate.invocation.id = %invocation_id,
                 "Effect: Send response to ingress: Failure(code: {}, msg: {})",
                 error_code,
                 error_msg,
             ),
+            Effect::StoreIdempotencyId {
+                idempotency_id,
+                invocation_id,
+            } => debug_if_leader!(
+                is_leader,
+                restate.invocation.id = %invocation_id,
+                "Effect: Store idempotency key {} of method {}",
+                idempotency_id.idempotency_key,
+                idempotency_id.method_name
+            ),
+            Effect::DeleteIdempotencyId(idempotency_id) => debug_if_leader!(
+                is_leader,
+                "Effect: Delete idempotency key {} of method {}",
+                idempotency_id.idempotency_key,
+                idempotency_id.method_name
+            ),
             Effect::DeleteInboxEntry {
                 service_id,
                 sequence_number,
@@ -352,6 +388,28 @@ impl Effect {
                     "Effect: Drop journal and pop from inbox"
                 );
             }
+            Effect::StoreCompletedInvocationAndPopInbox {
+                completed_invocation,
+                ..
+            } => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.journal.length = completed_invocation.journal_metadata.length,
+                    "Effect: Store completed invocation until {} and pop from inbox",
+                    completed_invocation.expiry
+                );
+            }
+            Effect::DropJournalAndFreeInvocation {
+                invocation_id,
+                journal_length,
+            } => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.invocation.id = %invocation_id,
+                    restate.journal.length = journal_length,
+                    "Effect: Drop journal and free completed invocation"
+                );
+            }
             Effect::SetState {
                 service_id,
                 invocation_id,
@@ -453,6 +511,16 @@ impl Effect {
                         "Effect: Register background invoke timer"
                     )
                 }
+                Timer::CleanInvocationStatus(service_id) => {
+                    debug_if_leader!(
+                        is_leader,
+                        rpc.service = %service_id.service_name,
+                        restate.invocation.id = %timer_value.invocation_id(),
+                        restate.timer.key = %TimerKeyDisplay(timer_value.key()),
+                        restate.timer.wake_up_time = %timer_value.wake_up_time(),
+                        "Effect: Register clean invocation status timer"
+                    )
+                }
             },
             Effect::DeleteTimer(timer_key) => {
                 let timer_key_display = TimerKeyDisplay(timer_key);
@@ -479,6 +547,16 @@ impl Effect {
                 "Effect: Write journal entry {:?} to storage",
                 journal_entry.header().as_entry_type()
             ),
+            Effect::AttachResponseSink {
+                invocation_id,
+                response_sink,
+                ..
+            } => debug_if_leader!(
+                is_leader,
+                restate.invocation.id = %invocation_id,
+                "Effect: Attach response sink {:?} to invocation",
+                response_sink
+            ),
             Effect::StoreCompletion {
                 completion:
                     Completion {
@@ -717,6 +795,22 @@ impl Effects {
         self.effects.push(Effect::IngressResponse(ingress_response));
     }
 
+    pub(crate) fn store_idempotency_id(
+        &mut self,
+        idempotency_id: IdempotencyId,
+        invocation_id: InvocationId,
+    ) {
+        self.effects.push(Effect::StoreIdempotencyId {
+            idempotency_id,
+            invocation_id,
+        });
+    }
+
+    pub(crate) fn delete_idempotency_id(&mut self, idempotency_id: IdempotencyId) {
+        self.effects
+            .push(Effect::DeleteIdempotencyId(idempotency_id));
+    }
+
     pub(crate) fn set_state(
         &mut self,
         service_id: ServiceId,
@@ -805,6 +899,19 @@ impl Effects {
         })
     }
 
+    pub(crate) fn attach_response_sink(
+        &mut self,
+        invocation_id: InvocationId,
+        previous_invocation_status: InvocationStatus,
+        response_sink: ServiceInvocationResponseSink,
+    ) {
+        self.effects.push(Effect::AttachResponseSink {
+            invocation_id,
+            previous_invocation_status,
+            response_sink,
+        })
+    }
+
     pub(crate) fn truncate_outbox(&mut self, outbox_sequence_number: MessageIndex) {
         self.effects
             .push(Effect::TruncateOutbox(outbox_sequence_number));
@@ -889,6 +996,29 @@ impl Effects {
         });
     }
 
+    pub(crate) fn store_completed_invocation_and_pop_inbox(
+        &mut self,
+        full_invocation_id: FullInvocationId,
+        completed_invocation: CompletedInvocation,
+    ) {
+        self.effects
+            .push(Effect::StoreCompletedInvocationAndPopInbox {
+                full_invocation_id,
+                completed_invocation,
+            });
+    }
+
+    pub(crate) fn drop_journal_and_free_invocation(
+        &mut self,
+        invocation_id: InvocationId,
+        journal_length: EntryIndex,
+    ) {
+        self.effects.push(Effect::DropJournalAndFreeInvocation {
+            invocation_id,
+            journal_length,
+        });
+    }
+
     pub(crate) fn trace_background_invoke(
         &mut self,
         full_invocation_id: FullInvocationId,
@@ -10,6 +10,7 @@
 
 use crate::metric_definitions::PARTITION_APPLY_COMMAND;
 use crate::partition::storage::Transaction;
+use crate::partition::CompletedInvocationRetention;
 use command_interpreter::CommandInterpreter;
 use metrics::counter;
 use restate_types::message::MessageIndex;
@@ -22,7 +23,7 @@ mod effects;
 
 pub use actions::Action;
 pub use command_interpreter::StateReader;
-pub use dedup::DeduplicatingStateMachine;
+pub use dedup::{AnnouncedLeader, DeduplicatingStateMachine};
 pub use effect_interpreter::StateStorage;
 pub use effect_interpreter::{ActionCollector, InterpretationResult};
 pub use effects::Effects;
@@ -44,6 +45,16 @@ impl<Codec> StateMachine<Codec> {
     pub fn new(inbox_seq_number: MessageIndex, outbox_seq_number: MessageIndex) -> Self {
         Self(CommandInterpreter::new(inbox_seq_number, outbox_seq_number))
     }
+
+    pub fn with_completed_invocation_retention(
+        self,
+        completed_invocation_retention: CompletedInvocationRetention,
+    ) -> Self {
+        Self(
+            self.0
+                .with_completed_invocation_retention(completed_invocation_retention),
+        )
+    }
 }
 
 impl<Codec: RawEntryCodec> StateMachine<Codec> {
@@ -759,6 +770,7 @@ mod tests {
                 source: Source::Ingress,
                 response_sink: None,
                 span_context: Default::default(),
+                idempotency: None,
             }))
             .await;
 
@@ -10,12 +10,14 @@
 
 use crate::metric_definitions::{PARTITION_STORAGE_TX_COMMITTED, PARTITION_STORAGE_TX_CREATED};
 use crate::partition::shuffle::{OutboxReader, OutboxReaderError};
+use crate::partition::state_machine::AnnouncedLeader;
 use crate::partition::{CommitError, Committable};
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures::{Stream, StreamExt, TryStreamExt};
 use metrics::counter;
-use restate_storage_api::deduplication_table::SequenceNumberSource;
+use restate_storage_api::deduplication_table::{EpochSequenceNumber, SequenceNumberSource};
 use restate_storage_api::fsm_table::ReadOnlyFsmTable;
+use restate_storage_api::idempotency_table::IdempotencyMetadata;
 use restate_storage_api::inbox_table::{
     InboxEntry, SequenceNumberInboxEntry, SequenceNumberInvocation,
 };
@@ -29,18 +31,23 @@ use restate_storage_api::state_table::ReadOnlyStateTable;
 use restate_storage_api::timer_table::{Timer, TimerKey, TimerTable};
 use restate_storage_api::Result as StorageResult;
 use restate_storage_api::StorageError;
+use restate_storage_rocksdb::snapshot::PartitionSnapshotMetadata;
+use restate_storage_rocksdb::RocksDBStorage;
 use restate_timer::TimerReader;
 use restate_types::identifiers::{
-    EntryIndex, FullInvocationId, InvocationId, PartitionId, PartitionKey, ServiceId,
-    WithPartitionKey,
+    EntryIndex, FullInvocationId, IdempotencyId, InvocationId, LeaderEpoch, PartitionId,
+    PartitionKey, ServiceId, WithPartitionKey,
 };
 use restate_types::invocation::MaybeFullInvocationId;
 use restate_types::journal::enriched::EnrichedRawEntry;
 use restate_types::journal::CompletionResult;
+use restate_types::logs::{Lsn, SequenceNumber};
 use restate_types::message::MessageIndex;
+use restate_types::GenerationalNodeId;
 use restate_wal_protocol::timer::{TimerKeyWrapper, TimerValue};
 use std::future::Future;
 use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
 pub mod invoker;
 
@@ -81,6 +88,137 @@ where
             self.storage.transaction(),
         )
     }
+
+    /// Loads the highest leader epoch which has been announced for this partition.
+    pub(super) async fn load_last_leader_epoch(
+        &mut self,
+    ) -> Result<Option<LeaderEpoch>, StorageError> {
+        let last_self_proposal = self
+            .create_transaction()
+            .load_dedup_seq_number(SequenceNumberSource::SelfProposal)
+            .await?;
+        Ok(
+            last_self_proposal
+                .map(|last_self_proposal| LeaderEpoch::from(last_self_proposal.epoch)),
+        )
+    }
+}
+
+impl PartitionStorage<RocksDBStorage> {
+    /// Replaces the snapshot of this partition in `snapshots_path` with a snapshot of the current
+    /// state of the partition.
+    pub(super) async fn create_snapshot(
+        &mut self,
+        snapshots_path: &Path,
+    ) -> Result<PartitionSnapshotMetadata, StorageError> {
+        let applied_lsn = self.load_applied_lsn().await?;
+        let storage = self.storage.clone();
+        let partition_id = self.partition_id;
+        let partition_key_range = self.partition_key_range.clone();
+        let snapshot_path = snapshot_path(snapshots_path, partition_id);
+        let tmp_snapshot_path = snapshots_path.join(format!("{partition_id}.tmp"));
+
+        tokio::task::spawn_blocking(move || {
+            if tmp_snapshot_path.exists() {
+                std::fs::remove_dir_all(&tmp_snapshot_path)?;
+            }
+            let metadata = storage.export_partition_snapshot(
+                partition_id,
+                partition_key_range,
+                applied_lsn,
+                &tmp_snapshot_path,
+            )?;
+            if snapshot_path.exists() {
+                std::fs::remove_dir_all(&snapshot_path)?;
+            }
+            std::fs::rename(&tmp_snapshot_path, &snapshot_path)?;
+
+            Ok::<_, anyhow::Error>(metadata)
+        })
+        .await
+        .map_err(|err| StorageError::Generic(err.into()))?
+        .map_err(StorageError::Generic)
+    }
+
+    /// Bootstraps this partition from its snapshot in `snapshots_path`, if there is one. Returns
+    /// the applied LSN of the snapshot.
+    pub(super) async fn bootstrap_from_snapshot(
+        &mut self,
+        snapshots_path: &Path,
+    ) -> Result<Option<Lsn>, StorageError> {
+        let snapshot_path = snapshot_path(snapshots_path, self.partition_id);
+        if !snapshot_path.exists() {
+            return Ok(None);
+        }
+
+        let storage = self.storage.clone();
+        let partition_id = self.partition_id;
+        let partition_key_range = self.partition_key_range.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let metadata = RocksDBStorage::read_partition_snapshot_metadata(&snapshot_path)?;
+            if metadata.partition_id != partition_id
+                || metadata.key_range != partition_key_range
+            {
+                anyhow::bail!(
+                    "snapshot at '{}' belongs to partition {} with key range {:?}, expected partition {} with key range {:?}",
+                    snapshot_path.display(),
+                    metadata.partition_id,
+                    metadata.key_range,
+                    partition_id,
+                    partition_key_range
+                );
+            }
+
+            Ok(storage.import_partition_snapshot(&snapshot_path)?.applied_lsn)
+        })
+        .await
+        .map_err(|err| StorageError::Generic(err.into()))?
+        .map(Some)
+        .map_err(StorageError::Generic)
+    }
+
+    /// Replaces the state of this partition with its snapshot in `snapshots_path`, if there is
+    /// one which contains the effects of all log records up to `min_applied_lsn`. Returns the
+    /// applied LSN of the snapshot.
+    pub(super) async fn restore_from_snapshot(
+        &mut self,
+        snapshots_path: &Path,
+        min_applied_lsn: Lsn,
+    ) -> Result<Option<Lsn>, StorageError> {
+        let snapshot_path = snapshot_path(snapshots_path, self.partition_id);
+        if !snapshot_path.exists() {
+            return Ok(None);
+        }
+
+        let storage = self.storage.clone();
+        let partition_id = self.partition_id;
+        let partition_key_range = self.partition_key_range.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let metadata = RocksDBStorage::read_partition_snapshot_metadata(&snapshot_path)?;
+            if metadata.partition_id != partition_id
+                || metadata.key_range != partition_key_range
+                || metadata.applied_lsn < min_applied_lsn
+            {
+                return Ok(None);
+            }
+
+            storage.clear_partition(partition_id, partition_key_range)?;
+            Ok::<_, anyhow::Error>(Some(
+                storage
+                    .import_partition_snapshot(&snapshot_path)?
+                    .applied_lsn,
+            ))
+        })
+        .await
+        .map_err(|err| StorageError::Generic(err.into()))?
+        .map_err(StorageError::Generic)
+    }
+}
+
+fn snapshot_path(snapshots_path: &Path, partition_id: PartitionId) -> PathBuf {
+    snapshots_path.join(partition_id.to_string())
 }
 
 async fn load_seq_number<F: ReadOnlyFsmTable + Send>(
@@ -128,6 +266,54 @@ where
         )
     }
 
+    pub async fn load_applied_lsn(&mut self) -> Result<Lsn, StorageError> {
+        let applied_lsn = self
+            .storage
+            .get(self.partition_id, fsm_variable::APPLIED_LSN)
+            .await?;
+
+        let Some(applied_lsn) = applied_lsn else {
+            return Ok(Lsn::INVALID);
+        };
+
+        let applied_lsn: [u8; 8] = applied_lsn.as_ref().try_into().map_err(|_| {
+            StorageError::Generic(anyhow::anyhow!(
+                "applied lsn of partition {} is corrupted: expected 8 bytes, got {}",
+                self.partition_id,
+                applied_lsn.len()
+            ))
+        })?;
+        Ok(Lsn::from(u64::from_be_bytes(applied_lsn)))
+    }
+
+    pub async fn load_announced_leader(&mut self) -> Result<Option<AnnouncedLeader>, StorageError> {
+        let announced_leader = self
+            .storage
+            .get(self.partition_id, fsm_variable::ANNOUNCED_LEADER)
+            .await?;
+
+        let Some(mut announced_leader) = announced_leader else {
+            return Ok(None);
+        };
+
+        // leader epoch (u64) followed by the plain node id (u32) and generation (u32)
+        if announced_leader.len() != 16 {
+            return Err(StorageError::Generic(anyhow::anyhow!(
+                "announced leader of partition {} is corrupted: expected 16 bytes, got {}",
+                self.partition_id,
+                announced_leader.len()
+            )));
+        }
+
+        let leader_epoch = LeaderEpoch::from(announced_leader.get_u64());
+        let node_id =
+            GenerationalNodeId::new(announced_leader.get_u32(), announced_leader.get_u32());
+        Ok(Some(AnnouncedLeader {
+            leader_epoch,
+            node_id,
+        }))
+    }
+
     pub fn scan_invoked_invocations(
         &mut self,
     ) -> impl Stream<Item = Result<FullInvocationId, StorageError>> + Send + '_ {
@@ -231,10 +417,33 @@ where
         Ok(())
     }
 
+    /// Stores the LSN of the last log record whose effects are part of this transaction.
+    pub(super) async fn store_applied_lsn(&mut self, applied_lsn: Lsn) {
+        let bytes = Bytes::copy_from_slice(&u64::from(applied_lsn).to_be_bytes());
+        self.inner
+            .put(self.partition_id, fsm_variable::APPLIED_LSN, &bytes)
+            .await;
+    }
+
+    /// Stores the leader which has been announced last.
+    pub(super) async fn store_announced_leader(&mut self, announced_leader: AnnouncedLeader) {
+        let mut bytes = BytesMut::with_capacity(16);
+        bytes.put_u64(u64::from(announced_leader.leader_epoch));
+        bytes.put_u32(announced_leader.node_id.id());
+        bytes.put_u32(announced_leader.node_id.generation());
+        self.inner
+            .put(
+                self.partition_id,
+                fsm_variable::ANNOUNCED_LEADER,
+                &bytes.freeze(),
+            )
+            .await;
+    }
+
     pub(super) async fn load_dedup_seq_number(
         &mut self,
         source: SequenceNumberSource,
-    ) -> Result<Option<MessageIndex>, StorageError> {
+    ) -> Result<Option<EpochSequenceNumber>, StorageError> {
         self.inner
             .get_sequence_number(self.partition_id, source)
             .await
@@ -243,7 +452,7 @@ where
     pub(super) async fn store_dedup_seq_number(
         &mut self,
         source: SequenceNumberSource,
-        dedup_seq_number: MessageIndex,
+        dedup_seq_number: EpochSequenceNumber,
     ) {
         self.inner
             .put_sequence_number(self.partition_id, source, dedup_seq_number)
@@ -268,6 +477,14 @@ where
         self.inner.get_invocation_status(invocation_id).await
     }
 
+    async fn get_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> StorageResult<Option<IdempotencyMetadata>> {
+        self.assert_partition_key(idempotency_id);
+        self.inner.get_idempotency_metadata(idempotency_id).await
+    }
+
     fn get_inboxed_invocation(
         &mut self,
         maybe_fid: impl Into<MaybeFullInvocationId>,
@@ -356,6 +573,35 @@ where
         Ok(())
     }
 
+    async fn load_invocation_status(
+        &mut self,
+        invocation_id: &InvocationId,
+    ) -> StorageResult<InvocationStatus> {
+        self.assert_partition_key(invocation_id);
+        self.inner.get_invocation_status(invocation_id).await
+    }
+
+    async fn store_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+        metadata: IdempotencyMetadata,
+    ) -> StorageResult<()> {
+        self.assert_partition_key(idempotency_id);
+        self.inner
+            .put_idempotency_metadata(idempotency_id, metadata)
+            .await;
+        Ok(())
+    }
+
+    async fn delete_idempotency_metadata(
+        &mut self,
+        idempotency_id: &IdempotencyId,
+    ) -> StorageResult<()> {
+        self.assert_partition_key(idempotency_id);
+        self.inner.delete_idempotency_metadata(idempotency_id).await;
+        Ok(())
+    }
+
     async fn drop_journal(
         &mut self,
         invocation_id: &InvocationId,
@@ -559,6 +805,8 @@ where
 mod fsm_variable {
     pub(crate) const INBOX_SEQ_NUMBER: u64 = 0;
     pub(crate) const OUTBOX_SEQ_NUMBER: u64 = 1;
+    pub(crate) const APPLIED_LSN: u64 = 2;
+    pub(crate) const ANNOUNCED_LEADER: u64 = 3;
 }
 
 impl<TransactionType> Committable for Transaction<TransactionType>
@@ -79,13 +79,15 @@ pub fn create_response_message(
             entry_index,
             result,
         })),
-        ServiceInvocationResponseSink::Ingress(ingress_dispatcher_id) => {
-            ResponseMessage::Ingress(IngressResponse {
-                target_node: ingress_dispatcher_id,
-                full_invocation_id: callee.clone(),
-                response: result,
-            })
-        }
+        ServiceInvocationResponseSink::Ingress {
+            node_id,
+            request_id,
+        } => ResponseMessage::Ingress(IngressResponse {
+            target_node: node_id,
+            request_id,
+            invocation_id: InvocationId::from(callee),
+            response: result,
+        }),
         ServiceInvocationResponseSink::NewInvocation {
             target,
             method,
@@ -10,20 +10,17 @@
 
 //! todo: This service can probably be removed once the admin service can directly write into target partitions
 
+use crate::bifrost_integration::ProposalSender;
 use crate::subscription_integration::SubscriptionControllerHandle;
 use restate_core::{cancellation_watcher, metadata};
 use restate_types::identifiers::{PartitionId, PartitionKey, WithPartitionKey};
 use restate_types::invocation::InvocationTermination;
-use restate_types::message::PartitionTarget;
 use restate_types::partition_table::{FindPartition, PartitionTableError};
 use restate_types::state_mut::ExternalStateMutation;
 use restate_wal_protocol::{AckMode, Command, Destination, Envelope, Header, Source};
 use tokio::sync::mpsc;
 use tracing::debug;
 
-// todo: Should become the log writer
-type ConsensusWriter = mpsc::Sender<PartitionTarget<Envelope>>;
-
 /// Commands that can be sent to a worker.
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum WorkerCommand {
@@ -66,8 +63,8 @@ impl restate_worker_api::Handle for WorkerCommandSender {
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("consensus closed")]
-    ConsensusClosed,
+    #[error("log appender closed")]
+    LogAppenderClosed,
     #[error(transparent)]
     PartitionNotFound(#[from] PartitionTableError),
 }
@@ -75,7 +72,7 @@ pub enum Error {
 pub(crate) struct Services {
     command_rx: mpsc::Receiver<WorkerCommand>,
 
-    consensus_writer: ConsensusWriter,
+    proposal_sender: ProposalSender,
 
     command_tx: WorkerCommandSender,
     subscription_controller_handle: SubscriptionControllerHandle,
@@ -83,7 +80,7 @@ pub(crate) struct Services {
 
 impl Services {
     pub(crate) fn new(
-        consensus_writer: ConsensusWriter,
+        proposal_sender: ProposalSender,
         subscription_controller_handle: SubscriptionControllerHandle,
         channel_size: usize,
     ) -> Self {
@@ -93,7 +90,7 @@ impl Services {
             command_rx,
             command_tx: WorkerCommandSender::new(command_tx),
             subscription_controller_handle,
-            consensus_writer,
+            proposal_sender,
         }
     }
 
@@ -108,7 +105,7 @@ impl Services {
     pub(crate) async fn run(self) -> anyhow::Result<()> {
         let Self {
             mut command_rx,
-            consensus_writer,
+            proposal_sender,
             ..
         } = self;
 
@@ -130,7 +127,7 @@ impl Services {
                             let partition_id = Self::find_partition_id(partition_key)?;
                             let header = create_header(partition_key);
                             let envelope = Envelope::new(header, Command::PatchState(mutation));
-                            consensus_writer.send((partition_id, envelope)).await.map_err(|_| Error::ConsensusClosed)?
+                            proposal_sender.send((partition_id, envelope)).await.map_err(|_| Error::LogAppenderClosed)?
                         },
                         WorkerCommand::TerminateInvocation(invocation_termination) => {
                             let partition_key = invocation_termination.maybe_fid.partition_key();
@@ -138,7 +135,7 @@ impl Services {
 
                             let header = create_header(partition_key);
                             let envelope = Envelope::new(header, Command::TerminateInvocation(invocation_termination));
-                            consensus_writer.send((partition_id, envelope)).await.map_err(|_| Error::ConsensusClosed)?
+                            proposal_sender.send((partition_id, envelope)).await.map_err(|_| Error::LogAppenderClosed)?
                         }
                     }
                 }